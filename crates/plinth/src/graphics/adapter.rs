@@ -0,0 +1,161 @@
+use std::path::PathBuf;
+
+use pollster::block_on;
+use tracing::warn;
+
+/// Controls which backend and GPU the graphics context renders with.
+///
+/// Changing these at runtime with
+/// [`GraphicsContext::set_adapter_settings`](super::GraphicsContext::set_adapter_settings)
+/// recreates the device, migrating window surfaces and textures to it. This
+/// is mostly useful on laptops with both integrated and discrete GPUs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AdapterSettings {
+    /// The backends to choose an adapter from. `None` uses the platform
+    /// default.
+    pub backends: Option<wgpu::Backends>,
+    pub power_preference: wgpu::PowerPreference,
+    /// Select a specific adapter by name, as reported by
+    /// [`GraphicsContext::available_adapters`](super::GraphicsContext::available_adapters).
+    /// Falls back to `power_preference` if no adapter with this name exists.
+    pub adapter_name: Option<String>,
+    /// A directory to persist compiled pipelines in, so that later launches
+    /// on the same adapter and driver skip most shader compilation. `None`
+    /// compiles pipelines from scratch every launch.
+    ///
+    /// Only supported with Vulkan. Elsewhere the driver usually keeps a cache
+    /// of its own.
+    pub pipeline_cache_dir: Option<PathBuf>,
+}
+
+impl AdapterSettings {
+    pub(crate) fn backends(&self) -> wgpu::Backends {
+        self.backends.unwrap_or(if cfg!(target_os = "windows") {
+            wgpu::Backends::DX12
+        } else if cfg!(target_os = "macos") {
+            wgpu::Backends::METAL
+        } else {
+            // The renderer samples one texture with two samplers, which GLES
+            // doesn't support.
+            wgpu::Backends::VULKAN
+        })
+    }
+}
+
+impl Default for AdapterSettings {
+    fn default() -> Self {
+        Self {
+            backends: None,
+            power_preference: wgpu::PowerPreference::LowPower,
+            adapter_name: None,
+            pipeline_cache_dir: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AdapterError {
+    /// No adapter matched the settings or was able to present to the
+    /// application's windows.
+    NotFound,
+    CreateSurface(wgpu::CreateSurfaceError),
+    RequestDevice(wgpu::RequestDeviceError),
+}
+
+impl std::fmt::Display for AdapterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdapterError::NotFound => write!(f, "no suitable graphics adapter found"),
+            AdapterError::CreateSurface(error) => write!(f, "failed to create surface: {error}"),
+            AdapterError::RequestDevice(error) => write!(f, "failed to create device: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for AdapterError {}
+
+impl From<wgpu::CreateSurfaceError> for AdapterError {
+    fn from(error: wgpu::CreateSurfaceError) -> Self {
+        AdapterError::CreateSurface(error)
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for AdapterError {
+    fn from(error: wgpu::RequestDeviceError) -> Self {
+        AdapterError::RequestDevice(error)
+    }
+}
+
+pub(crate) fn select_adapter(
+    instance: &wgpu::Instance,
+    settings: &AdapterSettings,
+    compatible_surface: Option<&wgpu::Surface>,
+) -> Option<wgpu::Adapter> {
+    if let Some(name) = &settings.adapter_name {
+        let adapters = block_on(instance.enumerate_adapters(settings.backends()));
+
+        let adapter = adapters.into_iter().find(|adapter| {
+            adapter.get_info().name == *name
+                && compatible_surface.is_none_or(|surface| adapter.is_surface_supported(surface))
+        });
+
+        if adapter.is_some() {
+            return adapter;
+        }
+
+        warn!(
+            adapter_name = %name,
+            "Requested adapter not found, falling back to power preference"
+        );
+    }
+
+    block_on(async {
+        instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: settings.power_preference,
+                force_fallback_adapter: false,
+                compatible_surface,
+                apply_limit_buckets: false,
+            })
+            .await
+    })
+    .ok()
+}
+
+/// Selects a software adapter, such as WARP on Windows or lavapipe on Linux.
+pub(crate) fn select_fallback_adapter(
+    instance: &wgpu::Instance,
+    settings: &AdapterSettings,
+) -> Option<wgpu::Adapter> {
+    block_on(async {
+        instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: settings.power_preference,
+                force_fallback_adapter: true,
+                compatible_surface: None,
+                apply_limit_buckets: false,
+            })
+            .await
+    })
+    .ok()
+}
+
+pub(crate) fn request_device(
+    adapter: &wgpu::Adapter,
+) -> Result<(wgpu::Device, wgpu::Queue), wgpu::RequestDeviceError> {
+    block_on(async {
+        adapter
+            .request_device(&wgpu::DeviceDescriptor {
+                label: Some("Device"),
+                // Timestamps are only used for frame statistics and pipeline
+                // caches only speed up startup, so both are optional.
+                required_features: adapter.features()
+                    & (wgpu::Features::TIMESTAMP_QUERY | wgpu::Features::PIPELINE_CACHE),
+                required_limits: wgpu::Limits::default(),
+                memory_hints: wgpu::MemoryHints::MemoryUsage,
+                trace: wgpu::Trace::Off,
+                experimental_features: wgpu::ExperimentalFeatures::disabled(),
+            })
+            .await
+    })
+}
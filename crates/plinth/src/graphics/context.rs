@@ -1,25 +1,53 @@
 use std::path::Path;
 use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 
-use pollster::block_on;
 use smallvec::SmallVec;
 use tracing::debug;
+use tracing::error;
 use tracing::info;
 use tracing::instrument;
 use tracing::warn;
 use winit::window::Window;
 use winit::window::WindowId;
 
+use crate::graphics::CacheStats;
 use crate::graphics::Canvas;
+use crate::graphics::ImageFetcher;
+use crate::graphics::ImageHandle;
+use crate::graphics::ImageSource;
 use crate::graphics::Texture;
 use crate::graphics::TextureLoadError;
+use crate::graphics::adapter::AdapterError;
+use crate::graphics::adapter::AdapterSettings;
+use crate::graphics::adapter::request_device;
+use crate::graphics::adapter::select_adapter;
+use crate::graphics::adapter::select_fallback_adapter;
 use crate::graphics::draw::CanvasStorage;
+use crate::graphics::draw::CanvasView;
+use crate::graphics::draw::ClipRect;
+use crate::graphics::draw::Damage;
 use crate::graphics::draw::DrawCommand;
 use crate::graphics::glyph_cache::GlyphCache;
+use crate::graphics::material::MaterialError;
+use crate::graphics::material::MaterialId;
+use crate::graphics::offscreen::OFFSCREEN_FORMAT;
+use crate::graphics::offscreen::OffscreenRenderer;
+use crate::graphics::offscreen::OffscreenTarget;
+use crate::graphics::offscreen::RenderedImage;
+use crate::graphics::pipeline::DiskPipelineCache;
+use crate::graphics::pipeline::RenderPipeline;
 use crate::graphics::pipeline::RenderPipelineCache;
 use crate::graphics::shader_data::DrawUniforms;
+use crate::graphics::stats::FrameProfiler;
+use crate::graphics::stats::RenderStats;
+use crate::graphics::surface::BindGroupCache;
+use crate::graphics::surface::Frame;
+use crate::graphics::surface::PresentSettings;
 use crate::graphics::surface::RenderError;
 use crate::graphics::surface::Surface;
+use crate::graphics::surface::SurfaceFrame;
 use crate::graphics::texture::TextureManager;
 
 pub struct GraphicsContext {
@@ -28,86 +56,80 @@ pub struct GraphicsContext {
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
 
+    settings: AdapterSettings,
     windows: Vec<Surface>,
+    offscreen: Option<OffscreenTarget>,
+    texture_renderer: Option<OffscreenRenderer>,
     textures: TextureManager,
     glyph_cache: GlyphCache,
 
     render_pipelines: Arc<RenderPipelineCache>,
+    profiler: FrameProfiler,
+
+    /// Set by the device's lost callback, which may run on any thread.
+    device_lost: Arc<AtomicBool>,
 }
 
 impl GraphicsContext {
     #[instrument(skip(window))]
-    pub fn new(window: Arc<dyn Window>) -> Self {
+    pub fn new(
+        window: Arc<dyn Window>,
+        settings: AdapterSettings,
+        present: PresentSettings,
+    ) -> Self {
         debug!("Creating graphics context");
 
-        let mut flags = wgpu::InstanceFlags::empty();
+        let instance = create_instance(settings.backends());
 
-        if cfg!(debug_assertions) {
-            info!("Creating graphics context with debug and validation layers enabled");
-            flags |= wgpu::InstanceFlags::DEBUG;
-            flags |= wgpu::InstanceFlags::VALIDATION;
-        }
+        let surface = instance.create_surface(window.clone()).unwrap();
+        let adapter = select_adapter(&instance, &settings, Some(&surface)).unwrap();
 
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::DX12,
-            display: None,
-            flags,
-            memory_budget_thresholds: wgpu::MemoryBudgetThresholds::default(),
-            backend_options: wgpu::BackendOptions {
-                dx12: wgpu::Dx12BackendOptions {
-                    shader_compiler: wgpu::Dx12Compiler::Fxc,
-                    presentation_system: wgpu::Dx12SwapchainKind::DxgiFromHwnd,
-                    latency_waitable_object: wgpu::Dx12UseFrameLatencyWaitableObject::Wait,
-                    force_shader_model: wgpu::ForceShaderModelToken::default(),
-                    agility_sdk: None,
-                },
-                ..Default::default()
-            },
-        });
+        let mut this = Self::with_adapter(instance, adapter, settings);
 
-        let surface = instance.create_surface(window.clone()).unwrap();
+        this.windows.push(Surface::new(
+            window,
+            surface,
+            &this.device,
+            &this.adapter,
+            &this.render_pipelines,
+            present,
+        ));
 
-        let adapter = block_on(async {
-            instance
-                .request_adapter(&wgpu::RequestAdapterOptions {
-                    power_preference: wgpu::PowerPreference::LowPower,
-                    force_fallback_adapter: false,
-                    compatible_surface: Some(&surface),
-                    apply_limit_buckets: false,
-                })
-                .await
-        })
-        .unwrap();
+        this
+    }
 
-        let adapter_info = adapter.get_info();
-        info!(
-            "Adapter: {} (driver: {} {})",
-            adapter_info.name, adapter_info.backend, adapter_info.driver,
-        );
+    /// Creates a graphics context that is not attached to any window.
+    ///
+    /// Canvases can be drawn with [`Self::render_to_image`]. Falls back to a
+    /// software adapter on machines without a GPU, such as CI runners, and
+    /// returns `None` if there is neither.
+    #[instrument]
+    pub fn headless() -> Option<Self> {
+        debug!("Creating headless graphics context");
 
-        let (device, queue) = block_on(async {
-            adapter
-                .request_device(&wgpu::DeviceDescriptor {
-                    label: Some("Device"),
-                    required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::default(),
-                    memory_hints: wgpu::MemoryHints::MemoryUsage,
-                    trace: wgpu::Trace::Off,
-                    experimental_features: wgpu::ExperimentalFeatures::disabled(),
-                })
-                .await
-        })
-        .unwrap();
+        let settings = AdapterSettings::default();
+        let instance = create_instance(settings.backends());
+        let adapter = select_adapter(&instance, &settings, None)
+            .or_else(|| select_fallback_adapter(&instance, &settings))?;
 
-        let render_pipelines = Arc::new(RenderPipelineCache::new(device.clone()));
+        Some(Self::with_adapter(instance, adapter, settings))
+    }
 
-        let windows = vec![Surface::new(
-            window,
-            surface,
-            &device,
-            &adapter,
-            &render_pipelines,
-        )];
+    fn with_adapter(
+        instance: wgpu::Instance,
+        adapter: wgpu::Adapter,
+        settings: AdapterSettings,
+    ) -> Self {
+        log_adapter_info(&adapter);
+
+        let (device, queue) = request_device(&adapter).unwrap();
+        let device_lost = watch_for_device_loss(&device);
+
+        let render_pipelines = Arc::new(RenderPipelineCache::new(
+            device.clone(),
+            open_disk_cache(&device, &adapter, &settings),
+        ));
+        let profiler = FrameProfiler::new(&device, &queue);
 
         let textures = TextureManager::new(queue.clone(), device.clone());
         let glyph_cache = GlyphCache::new();
@@ -118,16 +140,132 @@ impl GraphicsContext {
             device,
             queue,
 
-            windows,
+            settings,
+            windows: Vec::new(),
+            offscreen: None,
+            texture_renderer: None,
             textures,
             glyph_cache,
 
             render_pipelines,
+            profiler,
+            device_lost,
+        }
+    }
+
+    /// Lists the adapters available on the given backends, for presenting a
+    /// choice of GPU to the user.
+    pub fn available_adapters(backends: wgpu::Backends) -> Vec<wgpu::AdapterInfo> {
+        let instance = create_instance(backends);
+
+        pollster::block_on(instance.enumerate_adapters(backends))
+            .iter()
+            .map(wgpu::Adapter::get_info)
+            .collect()
+    }
+
+    pub fn adapter_settings(&self) -> &AdapterSettings {
+        &self.settings
+    }
+
+    /// Recreates the device with a different backend or adapter.
+    ///
+    /// Window surfaces are recreated on the new device, and texture contents
+    /// are copied over so that existing texture handles remain valid. On
+    /// failure the current device is kept.
+    #[instrument(skip(self))]
+    pub fn set_adapter_settings(&mut self, settings: AdapterSettings) -> Result<(), AdapterError> {
+        self.replace_device(settings, |textures, device, queue| {
+            textures.migrate(device, queue);
+        })
+    }
+
+    /// Whether the device has been lost, e.g. to a driver reset or because
+    /// the GPU was removed. Nothing is rendered until
+    /// [`Self::recover_lost_device`] succeeds.
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Acquire)
+    }
+
+    /// Replaces a lost device with a new one using the current adapter
+    /// settings, which may select a different GPU if the old one was removed.
+    ///
+    /// Window surfaces, pipelines and materials are recreated, and textures
+    /// are restored from their sources since the lost device's memory can't
+    /// be read. Render targets are not ready until they are drawn into again.
+    /// On failure the lost device is kept, and recovery may be retried later.
+    #[instrument(skip(self))]
+    pub fn recover_lost_device(&mut self) -> Result<(), AdapterError> {
+        self.replace_device(self.settings.clone(), |textures, device, queue| {
+            textures.recreate(device, queue);
+        })
+    }
+
+    fn replace_device(
+        &mut self,
+        settings: AdapterSettings,
+        move_textures: impl FnOnce(&TextureManager, wgpu::Device, wgpu::Queue),
+    ) -> Result<(), AdapterError> {
+        let instance = create_instance(settings.backends());
+
+        let mut surfaces = Vec::with_capacity(self.windows.len());
+        for surface in &self.windows {
+            let window = surface.window().clone();
+            surfaces.push((
+                window.clone(),
+                instance.create_surface(window)?,
+                surface.present_settings(),
+            ));
         }
+
+        let adapter = select_adapter(&instance, &settings, surfaces.first().map(|(_, s, _)| s))
+            .ok_or(AdapterError::NotFound)?;
+        let (device, queue) = request_device(&adapter)?;
+
+        log_adapter_info(&adapter);
+
+        move_textures(&self.textures, device.clone(), queue.clone());
+
+        // Some platforms allow only one swap chain per window, so the old
+        // surfaces must be released before the new ones are configured.
+        self.windows.clear();
+        self.offscreen = None;
+        self.texture_renderer = None;
+
+        let render_pipelines = Arc::new(RenderPipelineCache::new(
+            device.clone(),
+            open_disk_cache(&device, &adapter, &settings),
+        ));
+        render_pipelines.copy_materials_from(&self.render_pipelines);
+        self.render_pipelines = render_pipelines;
+
+        self.windows = surfaces
+            .into_iter()
+            .map(|(window, surface, present)| {
+                Surface::new(
+                    window,
+                    surface,
+                    &device,
+                    &adapter,
+                    &self.render_pipelines,
+                    present,
+                )
+            })
+            .collect();
+
+        self.profiler = FrameProfiler::new(&device, &queue);
+        self.device_lost = watch_for_device_loss(&device);
+        self.instance = instance;
+        self.adapter = adapter;
+        self.device = device;
+        self.queue = queue;
+        self.settings = settings;
+
+        Ok(())
     }
 
     #[instrument(skip(self))]
-    pub fn init_surface(&mut self, window: Arc<dyn Window>) {
+    pub fn init_surface(&mut self, window: Arc<dyn Window>, present: PresentSettings) {
         let surface = self.instance.create_surface(window.clone()).unwrap();
         self.windows.push(Surface::new(
             window,
@@ -135,9 +273,51 @@ impl GraphicsContext {
             &self.device,
             &self.adapter,
             &self.render_pipelines,
+            present,
         ));
     }
 
+    /// Changes how a window's frames are presented. Modes the window doesn't
+    /// support fall back to the closest supported mode.
+    #[instrument(skip(self))]
+    pub fn set_present_settings(&mut self, window_id: WindowId, present: PresentSettings) {
+        if let Some(surface) = self.windows.iter_mut().find(|w| w.window_id() == window_id) {
+            surface.set_present_settings(&self.device, present);
+        } else {
+            warn!("Window not found, skipping present settings.");
+        }
+    }
+
+    /// Replaces a window's surface after the platform invalidated it.
+    fn recreate_surface(&mut self, window_id: WindowId) {
+        let Some(index) = self.windows.iter().position(|w| w.window_id() == window_id) else {
+            return;
+        };
+
+        let old = self.windows.remove(index);
+        let window = old.window().clone();
+        let present = old.present_settings();
+
+        // The old surface must be released before a new one is created for
+        // the same window.
+        drop(old);
+
+        match self.instance.create_surface(window.clone()) {
+            Ok(surface) => self.windows.insert(
+                index,
+                Surface::new(
+                    window,
+                    surface,
+                    &self.device,
+                    &self.adapter,
+                    &self.render_pipelines,
+                    present,
+                ),
+            ),
+            Err(error) => error!(%error, "Unable to recreate lost surface"),
+        }
+    }
+
     #[instrument(skip(self))]
     pub fn destroy_surface(&mut self, window_id: WindowId) {
         if let Some(index) = self.windows.iter().position(|w| w.window_id() == window_id) {
@@ -147,11 +327,103 @@ impl GraphicsContext {
         }
     }
 
+    /// Loads an image file. Loading the same file again while its texture is
+    /// in use returns the same texture.
     #[instrument(skip(self, path), fields(path = %path.as_ref().display()))]
     pub fn load_image(&self, path: impl AsRef<Path>) -> Result<Texture, TextureLoadError> {
         self.textures.load(path)
     }
 
+    /// Loads an image from a file, from memory, or from a URL. See
+    /// [`ImageHandle`] for how loading progresses.
+    #[instrument(skip_all)]
+    pub fn load_image_source(&self, source: &ImageSource) -> ImageHandle {
+        self.textures.load_source(source)
+    }
+
+    /// Sets the fetcher used to load images from URLs with
+    /// [`Self::load_image_source`].
+    pub fn set_image_fetcher(&self, fetcher: Arc<dyn ImageFetcher>) {
+        self.textures.set_fetcher(fetcher);
+    }
+
+    /// Sets the function that is called from another thread whenever an image
+    /// has been fetched from a URL and is ready to be decoded on the next
+    /// frame.
+    pub(crate) fn set_fetch_waker(&self, waker: Arc<dyn Fn() + Send + Sync>) {
+        self.textures.set_fetch_waker(waker);
+    }
+
+    /// Registers a custom material for use with
+    /// [`Canvas::draw_custom`](super::Canvas::draw_custom).
+    ///
+    /// `source` is WGSL that defines a function with the signature below. It
+    /// may also define helper functions and constants of its own.
+    ///
+    /// ```wgsl
+    /// struct MaterialInput {
+    ///     // (0, 0) at the top-left of the primitive, (1, 1) at the bottom-right.
+    ///     uv: vec2f,
+    ///     // Fragment position in canvas coordinates.
+    ///     position: vec2f,
+    ///     // Primitive size in canvas coordinates.
+    ///     size: vec2f,
+    ///     // The `params` passed to `draw_custom`.
+    ///     params: array<vec4f, 3>,
+    /// }
+    ///
+    /// fn material(input: MaterialInput) -> vec4f
+    /// ```
+    #[instrument(skip_all)]
+    pub fn register_material(&self, source: &str) -> Result<MaterialId, MaterialError> {
+        self.render_pipelines.register_material(source)
+    }
+
+    /// Creates a texture that canvases can be rendered into with
+    /// [`Self::render_to_texture`], and that can then be drawn like any other
+    /// texture.
+    #[instrument(skip(self))]
+    pub fn create_render_target(&self, width: u16, height: u16) -> Texture {
+        self.textures.create_render_target(width, height)
+    }
+
+    /// Limits the bytes of loaded images uploaded per frame. Uploads past
+    /// the limit are deferred to later frames.
+    pub(crate) fn set_upload_budget(&self, max_bytes: Option<usize>) {
+        self.textures.set_upload_budget(max_bytes);
+    }
+
+    /// Limits the number of new glyphs rasterized per frame. Text that needs
+    /// more is drawn with placeholders that are refined over later frames.
+    pub(crate) fn set_glyph_budget(&self, max_glyphs: Option<u32>) {
+        self.glyph_cache.set_frame_budget(max_glyphs);
+    }
+
+    /// Limits the number of glyphs kept in the glyph atlas, evicting those
+    /// drawn longest ago.
+    pub(crate) fn set_glyph_capacity(&self, max_glyphs: Option<usize>) {
+        self.glyph_cache.set_capacity(max_glyphs);
+    }
+
+    /// The number of glyphs kept in the glyph atlas.
+    pub(crate) fn num_cached_glyphs(&self) -> usize {
+        self.glyph_cache.num_glyphs()
+    }
+
+    /// The number of bytes written to textures since the last call, and the
+    /// number of images whose uploads were deferred.
+    pub(crate) fn take_upload_stats(&self) -> (usize, usize) {
+        (
+            self.textures.take_uploaded_bytes(),
+            self.textures.num_deferred_uploads(),
+        )
+    }
+
+    /// How many glyphs drawn since the last call were already rasterized.
+    pub(crate) fn take_glyph_stats(&self) -> CacheStats {
+        self.glyph_cache.take_stats()
+    }
+
     #[instrument(skip(self))]
     pub fn create_canvas(&mut self) -> Canvas {
         Canvas::new(
@@ -161,15 +433,27 @@ impl GraphicsContext {
         )
     }
 
+    /// Renders each window's canvas and presents it.
+    ///
+    /// Returns statistics about the work done since the previous call,
+    /// including canvases drawn with [`Self::render_to_texture`].
     #[instrument(skip(self, targets))]
     pub fn render(
         &mut self,
         targets: SmallVec<[(WindowId, &Canvas); 2]>,
-    ) -> Result<(), RenderError> {
+    ) -> Result<RenderStats, RenderError> {
+        if self.is_device_lost() {
+            return Err(RenderError::DeviceLost);
+        }
+
         let mut command_buffers = SmallVec::<[_; 2]>::new();
         let mut presents = SmallVec::<[_; 2]>::new();
+        let mut lost_surfaces = SmallVec::<[_; 2]>::new();
 
         self.textures.flush();
+        // Every canvas has been drawn by now, so this is where the frame's
+        // glyph budget is replenished.
+        self.glyph_cache.end_frame();
 
         for (window_id, canvas) in targets {
             let canvas = canvas.storage();
@@ -181,17 +465,45 @@ impl GraphicsContext {
 
             window.resize_if_necessary(&self.device);
 
-            let (target, command_buffer) =
-                write_commands(&self.device, &self.queue, &self.textures, window, canvas)?;
+            let (target, command_buffer) = match write_commands(
+                &self.device,
+                &self.queue,
+                &self.textures,
+                &self.render_pipelines,
+                &mut self.profiler,
+                window,
+                canvas,
+            ) {
+                Ok(result) => result,
+                Err(RenderError::SurfaceLost) => {
+                    lost_surfaces.push(window_id);
+                    continue;
+                }
+                Err(error) => return Err(error),
+            };
 
             command_buffers.push(command_buffer);
             presents.push((window_id, target));
         }
 
+        if let Some(timer) = &self.profiler.timer {
+            let mut encoder = self
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Resolve Timestamps"),
+                });
+            timer.resolve(&mut encoder);
+            command_buffers.push(encoder.finish());
+        }
+
         tracing::info_span!("submit").in_scope(|| {
             self.queue.submit(command_buffers);
         });
 
+        if let Some(timer) = &mut self.profiler.timer {
+            timer.map_results();
+        }
+
         tracing::info_span!("present").in_scope(|| {
             for (window_id, target) in presents {
                 let Some(window) = self.windows.iter_mut().find(|w| w.window_id() == window_id)
@@ -205,6 +517,11 @@ impl GraphicsContext {
             }
         });
 
+        for window_id in lost_surfaces {
+            warn!(?window_id, "Surface lost, recreating it");
+            self.recreate_surface(window_id);
+        }
+
         self.textures.end_frame();
 
         #[cfg(feature = "profile")]
@@ -212,7 +529,135 @@ impl GraphicsContext {
             tracing_tracy::client::frame_mark();
         }
 
-        Ok(())
+        Ok(self.profiler.take_stats())
+    }
+
+    /// Renders a canvas into an offscreen texture and reads the pixels back.
+    ///
+    /// This blocks until the GPU has finished rendering. The offscreen texture
+    /// is kept between calls and only recreated when the size changes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` or `height` is zero.
+    #[instrument(skip(self, canvas))]
+    pub fn render_to_image(
+        &mut self,
+        canvas: &Canvas,
+        width: u32,
+        height: u32,
+    ) -> Result<RenderedImage, RenderError> {
+        assert!(width > 0 && height > 0, "Cannot render to an empty image");
+
+        self.textures.flush();
+
+        if self
+            .offscreen
+            .as_ref()
+            .is_none_or(|target| target.size() != (width, height))
+        {
+            self.offscreen = Some(OffscreenTarget::new(
+                &self.device,
+                &self.render_pipelines,
+                width,
+                height,
+            ));
+        }
+
+        let offscreen = self.offscreen.as_mut().unwrap();
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        let (view, frame, render_pipeline, bind_groups) =
+            offscreen.next_frame(self.textures.storage_version());
+
+        encode_canvas(
+            &self.device,
+            &self.queue,
+            &self.textures,
+            &self.render_pipelines,
+            &mut self.profiler,
+            &mut encoder,
+            RenderTarget {
+                view: &view,
+                size: [width, height],
+                frame,
+                render_pipeline,
+                bind_groups,
+                damage: Damage::Full,
+            },
+            canvas.storage(),
+        );
+
+        offscreen.copy_to_readback(&mut encoder);
+
+        tracing::info_span!("submit").in_scope(|| {
+            self.queue.submit([encoder.finish()]);
+        });
+
+        let image = offscreen
+            .read_pixels(&self.device)
+            .ok_or(RenderError::Unknown);
+
+        self.textures.end_frame();
+
+        image
+    }
+
+    /// Renders a canvas into a texture created with
+    /// [`Self::create_render_target`].
+    ///
+    /// The work is submitted immediately, so windows rendered afterwards see
+    /// the result. The canvas must not draw the target texture itself.
+    #[instrument(skip_all)]
+    pub fn render_to_texture(&mut self, canvas: &Canvas, target: &Texture) {
+        debug_assert_eq!(target.format(), OFFSCREEN_FORMAT);
+
+        self.textures.flush();
+
+        let Some(view) = self.textures.view(target.storage_id()) else {
+            warn!("Render target not found, skipping render.");
+            return;
+        };
+
+        let renderer = self
+            .texture_renderer
+            .get_or_insert_with(|| OffscreenRenderer::new(&self.render_pipelines));
+
+        let (frame, render_pipeline, bind_groups) =
+            renderer.next_frame(self.textures.storage_version());
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        let [width, height] = target.size();
+
+        encode_canvas(
+            &self.device,
+            &self.queue,
+            &self.textures,
+            &self.render_pipelines,
+            &mut self.profiler,
+            &mut encoder,
+            RenderTarget {
+                view: &view,
+                size: [width.into(), height.into()],
+                frame,
+                render_pipeline,
+                bind_groups,
+                damage: Damage::Full,
+            },
+            canvas.storage(),
+        );
+
+        tracing::info_span!("submit").in_scope(|| {
+            self.queue.submit([encoder.finish()]);
+        });
+
+        self.textures.mark_rendered(target);
     }
 }
 
@@ -222,6 +667,68 @@ impl Drop for GraphicsContext {
     }
 }
 
+fn create_instance(backends: wgpu::Backends) -> wgpu::Instance {
+    let mut flags = wgpu::InstanceFlags::empty();
+
+    if cfg!(debug_assertions) {
+        info!("Creating graphics context with debug and validation layers enabled");
+        flags |= wgpu::InstanceFlags::DEBUG;
+        flags |= wgpu::InstanceFlags::VALIDATION;
+    }
+
+    wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends,
+        display: None,
+        flags,
+        memory_budget_thresholds: wgpu::MemoryBudgetThresholds::default(),
+        backend_options: wgpu::BackendOptions {
+            dx12: wgpu::Dx12BackendOptions {
+                shader_compiler: wgpu::Dx12Compiler::Fxc,
+                presentation_system: wgpu::Dx12SwapchainKind::DxgiFromHwnd,
+                latency_waitable_object: wgpu::Dx12UseFrameLatencyWaitableObject::Wait,
+                force_shader_model: wgpu::ForceShaderModelToken::default(),
+                agility_sdk: None,
+            },
+            ..Default::default()
+        },
+    })
+}
+
+/// Returns a flag that is set if the device is lost for any reason other than
+/// being destroyed on purpose.
+fn watch_for_device_loss(device: &wgpu::Device) -> Arc<AtomicBool> {
+    let lost = Arc::new(AtomicBool::new(false));
+
+    device.set_device_lost_callback({
+        let lost = lost.clone();
+        move |reason, message| {
+            if reason != wgpu::DeviceLostReason::Destroyed {
+                error!(?reason, %message, "Graphics device lost");
+                lost.store(true, Ordering::Release);
+            }
+        }
+    });
+
+    lost
+}
+
+fn open_disk_cache(
+    device: &wgpu::Device,
+    adapter: &wgpu::Adapter,
+    settings: &AdapterSettings,
+) -> Option<DiskPipelineCache> {
+    let dir = settings.pipeline_cache_dir.as_deref()?;
+    DiskPipelineCache::open(device, adapter, dir)
+}
+
+fn log_adapter_info(adapter: &wgpu::Adapter) {
+    let adapter_info = adapter.get_info();
+    info!(
+        "Adapter: {} (driver: {} {})",
+        adapter_info.name, adapter_info.backend, adapter_info.driver,
+    );
+}
+
 #[instrument(
         skip_all,
         fields(
@@ -234,35 +741,132 @@ fn write_commands(
     device: &wgpu::Device,
     queue: &wgpu::Queue,
     textures: &TextureManager,
+    pipelines: &RenderPipelineCache,
+    profiler: &mut FrameProfiler,
     surface: &mut Surface,
     canvas: &CanvasStorage,
 ) -> Result<(wgpu::SurfaceTexture, wgpu::CommandBuffer), RenderError> {
-    let (target, frame, render_pipeline, bind_groups) =
-        surface.next_frame(device, textures.storage_version())?;
+    let SurfaceFrame {
+        output,
+        retained,
+        needs_full_redraw,
+        frame,
+        render_pipeline,
+        bind_groups,
+    } = surface.next_frame(
+        device,
+        textures.storage_version(),
+        textures.content_version(),
+        canvas.frame_number(),
+    )?;
 
     let mut encoder =
         device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
 
-    let load_op = if let Some(clear_color) = canvas.clear_color() {
-        wgpu::LoadOp::Clear(wgpu::Color {
-            r: clear_color.r.into(),
-            g: clear_color.g.into(),
-            b: clear_color.b.into(),
-            a: clear_color.a.into(),
-        })
-    } else {
-        wgpu::LoadOp::Load
+    let size = [output.texture.width(), output.texture.height()];
+
+    // Without a retained frame, the swap chain image's contents are unknown
+    // and everything must be redrawn.
+    let (target, damage) = match &retained {
+        Some(retained) if needs_full_redraw => (retained, Damage::Full),
+        Some(retained) => (retained, canvas.damage()),
+        None => (&output.texture, Damage::Full),
+    };
+
+    let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+
+    encode_canvas(
+        device,
+        queue,
+        textures,
+        pipelines,
+        profiler,
+        &mut encoder,
+        RenderTarget {
+            view: &view,
+            size,
+            frame,
+            render_pipeline,
+            bind_groups,
+            damage,
+        },
+        canvas,
+    );
+
+    if let Some(retained) = &retained {
+        encoder.copy_texture_to_texture(
+            retained.as_image_copy(),
+            output.texture.as_image_copy(),
+            retained.size(),
+        );
+    }
+
+    Ok((output, encoder.finish()))
+}
+
+struct RenderTarget<'a> {
+    view: &'a wgpu::TextureView,
+    size: [u32; 2],
+    frame: &'a mut Frame,
+    render_pipeline: &'a RenderPipeline,
+    bind_groups: &'a mut BindGroupCache,
+    /// The part of the target to redraw. Anything outside of it is kept.
+    damage: Damage,
+}
+
+#[expect(clippy::too_many_arguments)]
+fn encode_canvas(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    textures: &TextureManager,
+    pipelines: &RenderPipelineCache,
+    profiler: &mut FrameProfiler,
+    encoder: &mut wgpu::CommandEncoder,
+    target: RenderTarget,
+    canvas: &CanvasStorage,
+) {
+    let RenderTarget {
+        view,
+        size,
+        frame,
+        render_pipeline,
+        bind_groups,
+        damage,
+    } = target;
+
+    let scissor = match damage {
+        Damage::None => return,
+        Damage::Full => None,
+        Damage::Rect(rect) => match scissor_rect(rect, canvas.view(), size) {
+            Some(scissor) => Some(scissor),
+            None => return,
+        },
+    };
+
+    // Partial redraws keep the target's contents and clear the damaged region
+    // by drawing the canvas's clear primitive instead.
+    let (load_op, skip_vertices) = match (scissor, canvas.clear_color()) {
+        (None, Some(clear_color)) => (
+            wgpu::LoadOp::Clear(wgpu::Color {
+                r: clear_color.r.into(),
+                g: clear_color.g.into(),
+                b: clear_color.b.into(),
+                a: clear_color.a.into(),
+            }),
+            canvas.clear_vertices(),
+        ),
+        _ => (wgpu::LoadOp::Load, 0),
     };
 
-    let view = target
-        .texture
-        .create_view(&wgpu::TextureViewDescriptor::default());
+    profiler.stats.primitives += canvas.primitives().len();
 
     tracing::info_span!("render_pass").in_scope(|| {
+        let timestamp_writes = profiler.pass_writes();
+
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Render Pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &view,
+                view,
                 depth_slice: None,
                 resolve_target: None,
                 ops: wgpu::Operations {
@@ -272,33 +876,60 @@ fn write_commands(
             })],
             depth_stencil_attachment: None,
             occlusion_query_set: None,
-            timestamp_writes: None,
+            timestamp_writes,
             multiview_mask: None,
         });
 
         render_pass.set_pipeline(&render_pipeline.pipeline);
 
-        frame.draw_buffer.upload_and_bind(
+        if let Some([x, y, width, height]) = scissor {
+            render_pass.set_scissor_rect(x, y, width, height);
+        }
+
+        profiler.stats.buffer_upload_bytes += frame.draw_buffer.upload_and_bind(
             device,
             queue,
             &render_pipeline.draw_data_layout,
             &mut render_pass,
             DrawUniforms {
-                viewport_size: [target.texture.width(), target.texture.height()],
+                viewport_size: size,
+                origin: canvas.view().origin,
+                scale: canvas.view().scale,
+                corner_radius: canvas.view().corner_radius * canvas.view().scale,
             },
             canvas.primitives(),
             canvas.clips(),
         );
 
         let mut vertex_offset = 0;
+        let mut current_material = None;
 
         for command in canvas.commands() {
             match command {
                 DrawCommand::Draw {
                     color_storage_id,
                     alpha_storage_id,
+                    material,
                     num_vertices,
                 } => {
+                    if *material != current_material {
+                        let pipeline = match material {
+                            Some(material) => {
+                                pipelines.get_material(render_pipeline.format, *material)
+                            }
+                            None => Some(render_pipeline.pipeline.clone()),
+                        };
+
+                        let Some(pipeline) = pipeline else {
+                            warn!(?material, "Unknown material, skipping draw.");
+                            vertex_offset += *num_vertices;
+                            continue;
+                        };
+
+                        render_pass.set_pipeline(&pipeline);
+                        current_material = *material;
+                    }
+
                     let color_texture_view = textures.view(*color_storage_id).unwrap();
                     let alpha_texture_view = textures.view(*alpha_storage_id).unwrap();
 
@@ -310,13 +941,198 @@ fn write_commands(
                         });
 
                     render_pipeline.bind_texture(&mut render_pass, bind_group);
+                    profiler.stats.texture_binds += 1;
 
-                    render_pass.draw(vertex_offset..vertex_offset + *num_vertices, 0..1);
-                    vertex_offset += *num_vertices;
+                    let start = vertex_offset.max(skip_vertices);
+                    let end = vertex_offset + *num_vertices;
+                    if start < end {
+                        render_pass.draw(start..end, 0..1);
+                        profiler.stats.draw_calls += 1;
+                    }
+                    vertex_offset = end;
+                }
+                DrawCommand::PushDebugGroup { label } => {
+                    render_pass.push_debug_group(canvas.debug_label(*label));
+                }
+                DrawCommand::PopDebugGroup => {
+                    render_pass.pop_debug_group();
+                }
+                DrawCommand::InsertDebugMarker { label } => {
+                    render_pass.insert_debug_marker(canvas.debug_label(*label));
                 }
             }
         }
+
+        // Unbalanced groups fail validation, so close any the canvas left open.
+        for _ in 0..canvas.open_debug_groups() {
+            render_pass.pop_debug_group();
+        }
     });
+}
+
+/// Converts a damaged region to a scissor rect in target pixels, or `None`
+/// if it lies outside of the target.
+fn scissor_rect(rect: ClipRect, view: CanvasView, size: [u32; 2]) -> Option<[u32; 4]> {
+    // Grown by a pixel to include anti-aliased edges.
+    let to_pixels = |value: f32, origin: f32| (value - origin) * view.scale;
+    let x0 = (to_pixels(rect.point[0], view.origin[0]) - 1.0)
+        .floor()
+        .max(0.0);
+    let y0 = (to_pixels(rect.point[1], view.origin[1]) - 1.0)
+        .floor()
+        .max(0.0);
+    let x1 = (to_pixels(rect.point[0] + rect.size[0], view.origin[0]) + 1.0)
+        .ceil()
+        .min(size[0] as f32);
+    let y1 = (to_pixels(rect.point[1] + rect.size[1], view.origin[1]) + 1.0)
+        .ceil()
+        .min(size[1] as f32);
+
+    if x1 <= x0 || y1 <= y0 {
+        return None;
+    }
+
+    Some([x0 as u32, y0 as u32, (x1 - x0) as u32, (y1 - y0) as u32])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::Color;
+    use crate::graphics::Paint;
+    use crate::graphics::Primitive;
+
+    /// Tests return early without an adapter, as they can't be skipped at
+    /// runtime.
+    fn headless() -> Option<GraphicsContext> {
+        let graphics = GraphicsContext::headless();
+        if graphics.is_none() {
+            eprintln!("No graphics adapter available, skipping");
+        }
+        graphics
+    }
+
+    #[test]
+    fn headless_render_reads_back_pixels() {
+        let Some(mut graphics) = headless() else {
+            return;
+        };
+
+        let mut canvas = graphics.create_canvas();
+        canvas.reset(Color::BLACK);
+        canvas.draw(Primitive::with_paint(
+            0.0,
+            0.0,
+            8.0,
+            16.0,
+            Paint::solid(Color::RED),
+        ));
+
+        let image = graphics.render_to_image(&canvas, 16, 16).unwrap();
+
+        assert_eq!((image.width, image.height), (16, 16));
+        assert_eq!(image.pixels.len(), 16 * 16 * 4);
+        assert_eq!(image.pixel(2, 8), [255, 0, 0, 255]);
+        assert_eq!(image.pixel(13, 8), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn fractional_edges_are_antialiased() {
+        let Some(mut graphics) = headless() else {
+            return;
+        };
+
+        let mut canvas = graphics.create_canvas();
+        canvas.reset(Color::BLACK);
+        canvas.draw(Primitive::with_paint(
+            0.0,
+            0.0,
+            8.5,
+            16.0,
+            Paint::solid(Color::RED),
+        ));
+
+        let image = graphics.render_to_image(&canvas, 16, 16).unwrap();
+
+        let [edge_red, ..] = image.pixel(8, 8);
+        assert!(edge_red > 0 && edge_red < 255, "edge pixel red: {edge_red}");
+        assert_eq!(image.pixel(7, 8), [255, 0, 0, 255]);
+        assert_eq!(image.pixel(9, 8), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn custom_material_replaces_paint() {
+        let Some(mut graphics) = headless() else {
+            return;
+        };
+
+        let material = graphics
+            .register_material(
+                "fn material(input: MaterialInput) -> vec4f {
+                    return input.params[0];
+                }",
+            )
+            .unwrap();
+
+        let mut canvas = graphics.create_canvas();
+        canvas.reset(Color::BLACK);
+        canvas.draw_custom(
+            material,
+            Primitive::with_paint(0.0, 0.0, 8.0, 16.0, Paint::solid(Color::RED)),
+            [[0.0, 1.0, 0.0, 1.0], [0.0; 4], [0.0; 4]],
+        );
+
+        let image = graphics.render_to_image(&canvas, 16, 16).unwrap();
 
-    Ok((target, encoder.finish()))
+        assert_eq!(image.pixel(2, 8), [0, 255, 0, 255]);
+        assert_eq!(image.pixel(13, 8), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn render_target_can_be_sampled() {
+        let Some(mut graphics) = headless() else {
+            return;
+        };
+
+        let target = graphics.create_render_target(8, 8);
+
+        // Drawn at twice the scale, the rect covers the left half of the target.
+        let mut content = graphics.create_canvas();
+        content.reset(Color::BLACK);
+        content.set_view([0.0, 0.0], 2.0);
+        content.draw(Primitive::with_paint(
+            0.0,
+            0.0,
+            2.0,
+            4.0,
+            Paint::solid(Color::GREEN),
+        ));
+
+        graphics.render_to_texture(&content, &target);
+
+        let mut canvas = graphics.create_canvas();
+        canvas.reset(Color::BLACK);
+        canvas.draw(Primitive::with_paint(
+            0.0,
+            0.0,
+            16.0,
+            16.0,
+            Paint::textured(target, Color::WHITE),
+        ));
+
+        let image = graphics.render_to_image(&canvas, 16, 16).unwrap();
+
+        assert_eq!(image.pixel(2, 8), [0, 255, 0, 255]);
+        assert_eq!(image.pixel(13, 8), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn invalid_material_is_rejected() {
+        let Some(graphics) = headless() else {
+            return;
+        };
+
+        let result = graphics.register_material("fn material() {}");
+        assert!(matches!(result, Err(MaterialError::InvalidShader(_))));
+    }
 }
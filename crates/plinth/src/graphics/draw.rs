@@ -3,6 +3,8 @@ use std::path::Path;
 use crate::graphics::TextureLoadError;
 use crate::graphics::color::Color;
 use crate::graphics::glyph_cache::GlyphCache;
+use crate::graphics::material::MaterialId;
+use crate::graphics::paint::BorderColors;
 use crate::graphics::paint::GradientPaint;
 use crate::graphics::paint::Paint;
 use crate::graphics::shader_data::GpuPaint;
@@ -16,17 +18,26 @@ use super::shader_data::GpuClip;
 
 const VERTICES_PER_PRIMITIVE: u32 = 6;
 
+/// Half the size of the primitive used to clear damaged regions, large enough
+/// to cover any target.
+const CLEAR_EXTENT: f32 = 1_000_000.0;
+
 #[derive(Debug)]
 pub struct Primitive {
     pub point: [f32; 2],
     pub size: [f32; 2],
     pub paint: Paint,
     pub border: GradientPaint,
+    /// A color for each side of the border, drawn instead of `border`.
+    pub border_colors: Option<BorderColors>,
     /// Border widths in the order `[left, top, right, bottom]`.
     pub border_width: [f32; 4],
     pub corner_radii: [f32; 4],
     pub use_nearest_sampling: bool,
     pub clip: ClipRect,
+    /// Clockwise rotation in radians about the primitive's center. The clip
+    /// is not rotated.
+    pub rotation: f32,
 }
 
 impl Primitive {
@@ -37,10 +48,12 @@ impl Primitive {
             size: [width, height],
             paint,
             border: GradientPaint::vertical_gradient(Color::BLACK, Color::BLACK),
+            border_colors: None,
             border_width: [0.0, 0.0, 0.0, 0.0],
             corner_radii: [0.0; 4],
             use_nearest_sampling: false,
             clip: ClipRect::default(),
+            rotation: 0.0,
         }
     }
 }
@@ -61,6 +74,19 @@ impl Default for ClipRect {
 }
 
 impl ClipRect {
+    /// The smallest rect containing both rects.
+    pub(crate) fn union(&self, other: &ClipRect) -> ClipRect {
+        let x1 = self.point[0].min(other.point[0]);
+        let y1 = self.point[1].min(other.point[1]);
+        let x2 = (self.point[0] + self.size[0]).max(other.point[0] + other.size[0]);
+        let y2 = (self.point[1] + self.size[1]).max(other.point[1] + other.size[1]);
+
+        ClipRect {
+            point: [x1, y1],
+            size: [x2 - x1, y2 - y1],
+        }
+    }
+
     pub(crate) fn next(&self, next: &ClipRect) -> ClipRect {
         let x1 = self.point[0].max(next.point[0]);
         let y1 = self.point[1].max(next.point[1]);
@@ -100,12 +126,22 @@ impl Canvas {
         }
     }
 
+    /// Creates an empty canvas that shares this canvas's texture and glyph
+    /// caches.
+    pub(crate) fn new_sibling(&self) -> Self {
+        Self::new(
+            CanvasStorage::default(),
+            self.glyph_cache.clone(),
+            self.texture_manager.clone(),
+        )
+    }
+
     pub(crate) fn storage(&self) -> &CanvasStorage {
         &self.storage
     }
 
     pub fn is_empty(&self) -> bool {
-        self.storage.primitives.is_empty()
+        self.storage.primitives.len() as u32 * VERTICES_PER_PRIMITIVE <= self.storage.clear_vertices
     }
 
     #[must_use]
@@ -113,6 +149,11 @@ impl Canvas {
         self.storage.has_unready_textures
     }
 
+    /// Whether anything was drawn differently than on the previous frame.
+    pub(crate) fn has_damage(&self) -> bool {
+        self.storage.damage() != Damage::None
+    }
+
     pub fn reset(&mut self, clear_color: impl Into<Option<Color>>) {
         let white_pixel = self.texture_manager.white_pixel();
         let opaque_pixel = self.texture_manager.opaque_pixel();
@@ -122,12 +163,63 @@ impl Canvas {
             white_pixel.storage_id(),
             opaque_pixel.storage_id(),
         );
+
+        // Partial redraws keep the rest of the previous frame, so the damaged
+        // region must be cleared by drawing over it. Full redraws clear the
+        // target instead and skip this primitive.
+        if let Some(color) = self.storage.clear_color.filter(|c| c.a >= 1.0) {
+            self.storage.push(
+                &self.texture_manager,
+                Primitive::with_paint(
+                    -CLEAR_EXTENT,
+                    -CLEAR_EXTENT,
+                    CLEAR_EXTENT * 2.0,
+                    CLEAR_EXTENT * 2.0,
+                    Paint::solid(color),
+                ),
+            );
+            self.storage.clear_vertices = VERTICES_PER_PRIMITIVE;
+        }
+    }
+
+    /// Sets how canvas coordinates map onto the render target: the point
+    /// `origin` is drawn at the target's top-left corner, and one canvas unit
+    /// covers `scale` pixels.
+    ///
+    /// This is reset to the identity mapping by [`Self::reset`].
+    pub fn set_view(&mut self, origin: [f32; 2], scale: f32) {
+        debug_assert!(scale > 0.0, "Canvas scale must be positive");
+        self.storage.view = CanvasView {
+            origin,
+            scale,
+            ..self.storage.view
+        };
+    }
+
+    /// Rounds the corners of the render target by `radius` canvas units.
+    /// Anything drawn outside of the rounded corners is clipped away.
+    ///
+    /// This is reset to square corners by [`Self::reset`].
+    pub(crate) fn set_corner_radius(&mut self, radius: f32) {
+        self.storage.view.corner_radius = radius.max(0.0);
+    }
+
+    /// How many pixels of the render target one canvas unit covers, see
+    /// [`Self::set_view`].
+    pub(crate) fn scale(&self) -> f32 {
+        self.storage.view.scale
     }
 
     pub fn load_texture(&mut self, path: impl AsRef<Path>) -> Result<Texture, TextureLoadError> {
         self.texture_manager.load(path)
     }
 
+    /// Creates a texture that canvases can be rendered into with
+    /// [`GraphicsContext::render_to_texture`](super::GraphicsContext::render_to_texture).
+    pub fn create_render_target(&self, width: u16, height: u16) -> Texture {
+        self.texture_manager.create_render_target(width, height)
+    }
+
     pub fn draw_text_layout(
         &mut self,
         layout: &parley::Layout<Color>,
@@ -141,32 +233,304 @@ impl Canvas {
             origin,
             clip,
         );
+
+        for line in layout.lines() {
+            for item in line.items() {
+                let parley::PositionedLayoutItem::GlyphRun(glyph_run) = item else {
+                    continue;
+                };
+
+                let style = glyph_run.style();
+                let metrics = glyph_run.run().metrics();
+
+                if let Some(underline) = &style.underline {
+                    let offset = underline.offset.unwrap_or(metrics.underline_offset);
+                    let size = underline.size.unwrap_or(metrics.underline_size);
+                    self.draw_run_line(&glyph_run, origin, offset, size, underline.brush, clip);
+                }
+
+                if let Some(strikethrough) = &style.strikethrough {
+                    let offset = strikethrough.offset.unwrap_or(metrics.strikethrough_offset);
+                    let size = strikethrough.size.unwrap_or(metrics.strikethrough_size);
+                    let brush = strikethrough.brush;
+                    self.draw_run_line(&glyph_run, origin, offset, size, brush, clip);
+                }
+            }
+        }
+    }
+
+    /// Draws a line along the top of each run in `layout`, in the text's
+    /// color. Unlike underlines and strikethroughs, overlines can't be part
+    /// of a parley layout's styles.
+    pub fn draw_text_overlines(
+        &mut self,
+        layout: &parley::Layout<Color>,
+        origin: [f32; 2],
+        clip: ClipRect,
+    ) {
+        for line in layout.lines() {
+            for item in line.items() {
+                let parley::PositionedLayoutItem::GlyphRun(glyph_run) = item else {
+                    continue;
+                };
+
+                let metrics = glyph_run.run().metrics();
+                let color = glyph_run.style().brush;
+                let (offset, size) = (metrics.ascent, metrics.underline_size);
+                self.draw_run_line(&glyph_run, origin, offset, size, color, clip);
+            }
+        }
+    }
+
+    /// Draws a decoration line across a glyph run, with its top edge `offset`
+    /// above the baseline.
+    fn draw_run_line(
+        &mut self,
+        glyph_run: &parley::GlyphRun<'_, Color>,
+        origin: [f32; 2],
+        offset: f32,
+        size: f32,
+        color: Color,
+        clip: ClipRect,
+    ) {
+        // Snapped like glyphs are, so that thin lines stay crisp.
+        let y = (origin[1] + glyph_run.baseline() - offset).round();
+
+        self.draw(Primitive {
+            point: [origin[0] + glyph_run.offset(), y],
+            size: [glyph_run.advance(), size.max(1.0).round()],
+            clip,
+            paint: Paint::solid(color),
+            border: GradientPaint::default(),
+            border_colors: None,
+            border_width: [0.0; 4],
+            corner_radii: [0.0; 4],
+            use_nearest_sampling: false,
+            rotation: 0.0,
+        });
+    }
+
+    /// Draws `layout` with its first baseline following `path`, for labels
+    /// along arcs, gauges and routes.
+    ///
+    /// `path` is a polyline in canvas coordinates; curves should be flattened
+    /// into enough points to look smooth at the text's size. Text starts
+    /// `start` canvas units along the path, and each glyph is rotated to the
+    /// direction of the path under its center. Glyphs that would fall past
+    /// either end of the path are not drawn.
+    pub fn draw_text_on_path(
+        &mut self,
+        layout: &parley::Layout<Color>,
+        path: &[[f32; 2]],
+        start: f32,
+        clip: ClipRect,
+    ) {
+        self.glyph_cache.draw_on_path(
+            &mut self.storage,
+            &self.texture_manager,
+            layout,
+            path,
+            start,
+            clip,
+        );
     }
 
     pub fn draw(&mut self, primitive: Primitive) {
         self.storage.push(&self.texture_manager, primitive);
     }
+
+    /// Draws a primitive with a custom material instead of its paint.
+    ///
+    /// `params` are passed through to the material as `input.params`. The
+    /// primitive's clip, corner radii and border are applied as usual.
+    pub fn draw_custom(
+        &mut self,
+        material: MaterialId,
+        primitive: Primitive,
+        params: [[f32; 4]; 3],
+    ) {
+        self.storage
+            .push_custom(&self.texture_manager, material, primitive, params);
+    }
+
+    /// Opens a labeled group around subsequent draws. Groups show up in GPU
+    /// captures such as RenderDoc or PIX, so that draws can be traced back to
+    /// what issued them.
+    ///
+    /// Groups may be nested. Any left open when the canvas is rendered are
+    /// closed automatically.
+    pub fn push_debug_group(&mut self, label: &str) {
+        let label = self.storage.push_debug_label(label);
+        self.storage
+            .commands
+            .push(DrawCommand::PushDebugGroup { label });
+        self.storage.open_debug_groups += 1;
+    }
+
+    /// Closes the most recently opened debug group. Does nothing if no group
+    /// is open.
+    pub fn pop_debug_group(&mut self) {
+        if self.storage.open_debug_groups > 0 {
+            self.storage.commands.push(DrawCommand::PopDebugGroup);
+            self.storage.open_debug_groups -= 1;
+        }
+    }
+
+    /// Inserts a labeled marker between draws in GPU captures.
+    pub fn insert_debug_marker(&mut self, label: &str) {
+        let label = self.storage.push_debug_label(label);
+        self.storage
+            .commands
+            .push(DrawCommand::InsertDebugMarker { label });
+    }
+
+    /// Draws everything `other` drew since it was last reset, moved by
+    /// `offset` and clipped to `clip`, so that output recorded once can be
+    /// reused. Anything `other` drew to clear itself is skipped.
+    ///
+    /// The textures `other` drew with must still be alive.
+    pub(crate) fn draw_canvas(&mut self, other: &Canvas, offset: [f32; 2], clip: ClipRect) {
+        let source = &other.storage;
+        let first = (source.clear_vertices / VERTICES_PER_PRIMITIVE) as usize;
+        let mut index = 0;
+
+        for command in &source.commands {
+            match *command {
+                DrawCommand::Draw {
+                    color_storage_id,
+                    alpha_storage_id,
+                    material,
+                    num_vertices,
+                } => {
+                    let end = index + (num_vertices / VERTICES_PER_PRIMITIVE) as usize;
+
+                    for primitive in source.primitives[index..end]
+                        .iter()
+                        .skip(first.saturating_sub(index))
+                    {
+                        let source_clip = source.clips[primitive.clip_idx as usize];
+                        let clip_idx = self.storage.clip_index(clip.next(&ClipRect {
+                            point: [
+                                source_clip.point[0] + offset[0],
+                                source_clip.point[1] + offset[1],
+                            ],
+                            size: source_clip.extent,
+                        }));
+
+                        self.storage.primitives.push(GpuPrimitive {
+                            point: [
+                                primitive.point[0] + offset[0],
+                                primitive.point[1] + offset[1],
+                            ],
+                            clip_idx,
+                            ..*primitive
+                        });
+                        self.storage
+                            .push_draw(color_storage_id, alpha_storage_id, material);
+                    }
+
+                    index = end;
+                }
+                DrawCommand::PushDebugGroup { label } => {
+                    self.push_debug_group(source.debug_label(label));
+                }
+                DrawCommand::PopDebugGroup => self.pop_debug_group(),
+                DrawCommand::InsertDebugMarker { label } => {
+                    self.insert_debug_marker(source.debug_label(label));
+                }
+            }
+        }
+
+        if source.has_unready_textures {
+            self.storage.mark_unready();
+        }
+    }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub(crate) enum DrawCommand {
     Draw {
         color_storage_id: StorageId,
         alpha_storage_id: StorageId,
+        material: Option<MaterialId>,
         num_vertices: u32,
     },
+    PushDebugGroup {
+        label: DebugLabel,
+    },
+    PopDebugGroup,
+    InsertDebugMarker {
+        label: DebugLabel,
+    },
+}
+
+/// A range of [`CanvasStorage`]'s label buffer, so that labels don't need an
+/// allocation each.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct DebugLabel {
+    start: u32,
+    end: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct CanvasView {
+    pub origin: [f32; 2],
+    pub scale: f32,
+    /// The radius of the render target's corners, in canvas units.
+    pub corner_radius: f32,
+}
+
+impl Default for CanvasView {
+    fn default() -> Self {
+        Self {
+            origin: [0.0, 0.0],
+            scale: 1.0,
+            corner_radius: 0.0,
+        }
+    }
+}
+
+/// The part of a canvas that changed since it was last drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Damage {
+    None,
+    Full,
+    /// A region in canvas coordinates.
+    Rect(ClipRect),
+}
+
+/// What a canvas drew on its previous frame, for computing damage.
+#[derive(Default)]
+struct PreviousFrame {
+    clear_color: Option<Color>,
+    view: CanvasView,
+    commands: Vec<DrawCommand>,
+    primitives: Vec<GpuPrimitive>,
+    clips: Vec<GpuClip>,
 }
 
 #[derive(Default)]
 pub(crate) struct CanvasStorage {
     clear_color: Option<Color>,
+    view: CanvasView,
+    /// The vertices at the start of the first draw that clear the canvas, or 0
+    /// if it isn't cleared with an opaque color.
+    clear_vertices: u32,
     commands: Vec<DrawCommand>,
     primitives: Vec<GpuPrimitive>,
     clips: Vec<GpuClip>,
 
     last_clip_alloc: Option<(ClipRect, u32)>,
 
+    debug_labels: String,
+    open_debug_groups: u32,
+
     has_unready_textures: bool,
+
+    /// Counts resets, so that renderers can tell whether they drew the
+    /// previous frame that damage is computed against.
+    frame_number: u64,
+    previous: PreviousFrame,
 }
 
 impl CanvasStorage {
@@ -174,6 +538,70 @@ impl CanvasStorage {
         self.clear_color
     }
 
+    pub(crate) fn view(&self) -> CanvasView {
+        self.view
+    }
+
+    /// Records that something couldn't be drawn yet, so that the canvas is
+    /// drawn again on the next frame.
+    pub(crate) fn mark_unready(&mut self) {
+        self.has_unready_textures = true;
+    }
+
+    pub(crate) fn clear_vertices(&self) -> u32 {
+        self.clear_vertices
+    }
+
+    pub(crate) fn frame_number(&self) -> u64 {
+        self.frame_number
+    }
+
+    /// Compares this frame's draws with the previous frame's to find the
+    /// region that needs to be redrawn.
+    ///
+    /// Only draw data is compared. Changes to the contents of textures must be
+    /// tracked separately.
+    pub(crate) fn damage(&self) -> Damage {
+        let previous = &self.previous;
+
+        if self.clear_color != previous.clear_color
+            || self.view != previous.view
+            || self.commands != previous.commands
+            || self.primitives.len() != previous.primitives.len()
+        {
+            return Damage::Full;
+        }
+
+        let mut damage: Option<ClipRect> = None;
+        let mut add = |rect: ClipRect| {
+            if rect.size[0] > 0.0 && rect.size[1] > 0.0 {
+                damage = Some(damage.map_or(rect, |damage| damage.union(&rect)));
+            }
+        };
+
+        for (current, prev) in self.primitives.iter().zip(&previous.primitives) {
+            let current_clip = self.clips[current.clip_idx as usize];
+            let prev_clip = previous.clips[prev.clip_idx as usize];
+
+            if bytemuck::bytes_of(current) != bytemuck::bytes_of(prev)
+                || bytemuck::bytes_of(&current_clip) != bytemuck::bytes_of(&prev_clip)
+            {
+                add(primitive_bounds(current, &current_clip));
+                add(primitive_bounds(prev, &prev_clip));
+            }
+        }
+
+        // Translucent clears can't be drawn over the previous frame, and
+        // uncleared canvases depend on what was drawn before them.
+        let is_opaque = self.clear_color.is_some_and(|c| c.a >= 1.0);
+
+        match damage {
+            None => Damage::None,
+            Some(rect) if is_opaque => Damage::Rect(rect),
+            Some(_) => Damage::Full,
+        }
+    }
+
     pub(crate) fn commands(&self) -> &[DrawCommand] {
         &self.commands
     }
@@ -186,13 +614,41 @@ impl CanvasStorage {
         &self.clips
     }
 
+    pub(crate) fn debug_label(&self, label: DebugLabel) -> &str {
+        &self.debug_labels[label.start as usize..label.end as usize]
+    }
+
+    /// The number of debug groups that were pushed but never popped.
+    pub(crate) fn open_debug_groups(&self) -> u32 {
+        self.open_debug_groups
+    }
+
+    fn push_debug_label(&mut self, label: &str) -> DebugLabel {
+        let start = self.debug_labels.len() as u32;
+        self.debug_labels.push_str(label);
+        DebugLabel {
+            start,
+            end: self.debug_labels.len() as u32,
+        }
+    }
+
     pub(crate) fn reset(
         &mut self,
         clear_color: impl Into<Option<Color>>,
         white: StorageId,
         opaque: StorageId,
     ) {
+        let previous = &mut self.previous;
+        previous.clear_color = self.clear_color;
+        previous.view = self.view;
+        std::mem::swap(&mut previous.commands, &mut self.commands);
+        std::mem::swap(&mut previous.primitives, &mut self.primitives);
+        std::mem::swap(&mut previous.clips, &mut self.clips);
+        self.frame_number += 1;
+
         self.clear_color = clear_color.into();
+        self.view = CanvasView::default();
+        self.clear_vertices = 0;
         self.has_unready_textures = false;
 
         self.clips.clear();
@@ -202,11 +658,15 @@ impl CanvasStorage {
         });
         self.last_clip_alloc = Some((ClipRect::default(), 0));
 
+        self.debug_labels.clear();
+        self.open_debug_groups = 0;
+
         self.commands.clear();
         self.primitives.clear();
         self.commands.push(DrawCommand::Draw {
             color_storage_id: white,
             alpha_storage_id: opaque,
+            material: None,
             num_vertices: 0,
         });
     }
@@ -217,10 +677,12 @@ impl CanvasStorage {
             size,
             paint,
             border,
+            border_colors,
             border_width,
             corner_radii,
             use_nearest_sampling,
             clip,
+            rotation,
         } = primitive;
 
         let mut flags = PrimitiveRenderFlags::empty();
@@ -271,7 +733,81 @@ impl CanvasStorage {
             }
         };
 
-        let clip_idx = match self.last_clip_alloc {
+        let (border_color, border_bottom_color) = gpu_border(&border, border_colors, &mut flags);
+
+        let clip_idx = self.clip_index(clip);
+
+        self.primitives.push(GpuPrimitive {
+            point,
+            extent: size,
+            background: background_paint,
+            border_color,
+            border_bottom_color,
+            border_width,
+            corner_radii,
+            control_flags: flags,
+            clip_idx,
+            rotation,
+            _padding: 0,
+        });
+
+        self.push_draw(color_texture.storage_id(), alpha_texture.storage_id(), None);
+    }
+
+    pub(crate) fn push_custom(
+        &mut self,
+        texture_manager: &TextureManager,
+        material: MaterialId,
+        primitive: Primitive,
+        params: [[f32; 4]; 3],
+    ) {
+        let Primitive {
+            point,
+            size,
+            paint: _,
+            border,
+            border_colors,
+            border_width,
+            corner_radii,
+            use_nearest_sampling: _,
+            clip,
+            rotation,
+        } = primitive;
+
+        let mut flags = PrimitiveRenderFlags::empty();
+        let (border_color, border_bottom_color) = gpu_border(&border, border_colors, &mut flags);
+
+        let clip_idx = self.clip_index(clip);
+
+        self.primitives.push(GpuPrimitive {
+            point,
+            extent: size,
+            background: GpuPaint {
+                a: params[0],
+                b: params[1],
+                c: params[2],
+            },
+            border_color,
+            border_bottom_color,
+            border_width,
+            corner_radii,
+            control_flags: flags,
+            clip_idx,
+            rotation,
+            _padding: 0,
+        });
+
+        // Materials still bind the texture group, so the placeholder textures
+        // are used to keep the pipeline layout satisfied.
+        self.push_draw(
+            texture_manager.white_pixel().storage_id(),
+            texture_manager.opaque_pixel().storage_id(),
+            Some(material),
+        );
+    }
+
+    fn clip_index(&mut self, clip: ClipRect) -> u32 {
+        match self.last_clip_alloc {
             Some((cached, idx)) if cached == clip => idx,
             _ => {
                 let idx = self.clips.len() as u32;
@@ -282,42 +818,144 @@ impl CanvasStorage {
                 self.last_clip_alloc = Some((clip, idx));
                 idx
             }
-        };
+        }
+    }
 
-        self.primitives.push(GpuPrimitive {
-            point,
-            extent: size,
-            background: background_paint,
-            border_color: GpuPaint::gradient(
-                border.color_a,
-                border.color_b,
-                border.start,
-                border.end,
-            ),
-            border_width,
-            corner_radii,
-            control_flags: flags,
-            clip_idx,
-            _padding1: 0,
-            _padding2: 0,
-        });
+    /// Extends the last draw command if it uses the same textures and
+    /// material, or starts a new one otherwise.
+    fn push_draw(
+        &mut self,
+        color_storage_id: StorageId,
+        alpha_storage_id: StorageId,
+        material: Option<MaterialId>,
+    ) {
+        match self.commands.last_mut() {
+            Some(DrawCommand::Draw {
+                color_storage_id: prev_color_storage_id,
+                alpha_storage_id: prev_alpha_storage_id,
+                material: prev_material,
+                num_vertices,
+            }) if color_storage_id == *prev_color_storage_id
+                && alpha_storage_id == *prev_alpha_storage_id
+                && material == *prev_material =>
+            {
+                *num_vertices += VERTICES_PER_PRIMITIVE;
+            }
+            _ => {
+                self.commands.push(DrawCommand::Draw {
+                    color_storage_id,
+                    alpha_storage_id,
+                    material,
+                    num_vertices: VERTICES_PER_PRIMITIVE,
+                });
+            }
+        }
+    }
+}
+
+/// Converts a primitive's border to its GPU representation, flagging whether
+/// it is a gradient or a color for each side.
+fn gpu_border(
+    border: &GradientPaint,
+    colors: Option<BorderColors>,
+    flags: &mut PrimitiveRenderFlags,
+) -> (GpuPaint, [f32; 4]) {
+    match colors {
+        Some(colors) => {
+            flags.set(PrimitiveRenderFlags::USE_SIDE_BORDER_COLORS, true);
+            (
+                GpuPaint::sides(colors.left, colors.top, colors.right),
+                colors.bottom.into(),
+            )
+        }
+        None => (
+            GpuPaint::gradient(border.color_a, border.color_b, border.start, border.end),
+            [0.0; 4],
+        ),
+    }
+}
 
-        let DrawCommand::Draw {
-            color_storage_id: prev_color_texture_id,
-            alpha_storage_id: prev_alpha_texture_id,
-            num_vertices,
-        } = self.commands.last_mut().unwrap();
+/// The region a primitive can draw to, in canvas coordinates.
+fn primitive_bounds(primitive: &GpuPrimitive, clip: &GpuClip) -> ClipRect {
+    let bounds = if primitive.rotation == 0.0 {
+        ClipRect {
+            point: primitive.point,
+            size: primitive.extent,
+        }
+    } else {
+        // The axis-aligned box around the rotated rect.
+        let (sin, cos) = primitive.rotation.sin_cos();
+        let [width, height] = primitive.extent;
+        let size = [
+            width * cos.abs() + height * sin.abs(),
+            width * sin.abs() + height * cos.abs(),
+        ];
 
-        if color_texture.storage_id() == *prev_color_texture_id
-            && alpha_texture.storage_id() == *prev_alpha_texture_id
-        {
-            *num_vertices += VERTICES_PER_PRIMITIVE;
-        } else {
-            self.commands.push(DrawCommand::Draw {
-                color_storage_id: color_texture.storage_id(),
-                alpha_storage_id: alpha_texture.storage_id(),
-                num_vertices: VERTICES_PER_PRIMITIVE,
+        ClipRect {
+            point: [
+                primitive.point[0] + (width - size[0]) * 0.5,
+                primitive.point[1] + (height - size[1]) * 0.5,
+            ],
+            size,
+        }
+    };
+
+    bounds.next(&ClipRect {
+        point: clip.point,
+        size: clip.extent,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw_rects(storage: &mut CanvasStorage, rects: &[ClipRect]) {
+        storage.reset(Color::BLACK, StorageId::default(), StorageId::default());
+
+        for rect in rects {
+            let clip_idx = storage.clip_index(ClipRect::default());
+            storage.primitives.push(GpuPrimitive {
+                point: rect.point,
+                extent: rect.size,
+                clip_idx,
+                ..Default::default()
             });
+            storage.push_draw(StorageId::default(), StorageId::default(), None);
         }
     }
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> ClipRect {
+        ClipRect {
+            point: [x, y],
+            size: [width, height],
+        }
+    }
+
+    #[test]
+    fn unchanged_frame_has_no_damage() {
+        let mut storage = CanvasStorage::default();
+        let rects = [rect(10.0, 10.0, 20.0, 20.0), rect(100.0, 100.0, 10.0, 10.0)];
+
+        draw_rects(&mut storage, &rects);
+        draw_rects(&mut storage, &rects);
+
+        assert_eq!(storage.damage(), Damage::None);
+    }
+
+    #[test]
+    fn moved_primitive_damages_old_and_new_rects() {
+        let mut storage = CanvasStorage::default();
+
+        draw_rects(
+            &mut storage,
+            &[rect(10.0, 10.0, 20.0, 20.0), rect(100.0, 100.0, 10.0, 10.0)],
+        );
+        draw_rects(
+            &mut storage,
+            &[rect(40.0, 10.0, 20.0, 20.0), rect(100.0, 100.0, 10.0, 10.0)],
+        );
+
+        assert_eq!(storage.damage(), Damage::Rect(rect(10.0, 10.0, 50.0, 20.0)));
+    }
 }
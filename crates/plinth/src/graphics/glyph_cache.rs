@@ -10,14 +10,17 @@ use parley::PositionedLayoutItem;
 use swash::FontRef;
 use swash::scale::Render;
 use swash::scale::ScaleContext;
+use swash::scale::Scaler;
 use swash::scale::Source;
 use swash::scale::StrikeWith;
 use swash::scale::image::Content;
 use swash::scale::image::Image;
+use swash::scale::outline::Outline;
 use swash::zeno::Format;
 use swash::zeno::Vector;
 use tracing::instrument;
 
+use crate::graphics::CacheStats;
 use crate::graphics::ClipRect;
 use crate::graphics::Color;
 use crate::graphics::Paint;
@@ -57,6 +60,52 @@ impl GlyphCache {
             .borrow_mut()
             .draw(canvas, textures, layout, origin, clip);
     }
+
+    /// Draws `layout` with its baseline following `path`. See
+    /// [`Canvas::draw_text_on_path`](crate::graphics::Canvas::draw_text_on_path).
+    pub fn draw_on_path(
+        &self,
+        canvas: &mut CanvasStorage,
+        textures: &TextureManager,
+        layout: &Layout<Color>,
+        path: &[[f32; 2]],
+        start: f32,
+        clip: ClipRect,
+    ) {
+        self.inner
+            .borrow_mut()
+            .draw_on_path(canvas, textures, layout, path, start, clip);
+    }
+
+    /// Limits the number of new glyphs rasterized per frame. Glyphs past the
+    /// limit are drawn as placeholders until a later frame rasterizes them.
+    pub fn set_frame_budget(&self, max_glyphs: Option<u32>) {
+        self.inner.borrow_mut().budget.max_glyphs = max_glyphs;
+    }
+
+    /// Limits the number of glyphs kept rasterized. Past the limit, the
+    /// glyphs that were drawn longest ago are evicted at the end of the
+    /// frame, though never those drawn during it.
+    pub fn set_capacity(&self, max_glyphs: Option<usize>) {
+        self.inner.borrow_mut().capacity = max_glyphs;
+    }
+
+    /// The number of glyphs currently rasterized.
+    pub fn num_glyphs(&self) -> usize {
+        self.inner.borrow().glyph_cache.len()
+    }
+
+    /// How many glyphs were found in the cache since the last call.
+    pub fn take_stats(&self) -> CacheStats {
+        std::mem::take(&mut self.inner.borrow_mut().stats)
+    }
+
+    pub fn end_frame(&self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.budget.rasterized = 0;
+        inner.evict();
+        inner.frame += 1;
+    }
 }
 
 struct GlyphCacheInner {
@@ -67,6 +116,33 @@ struct GlyphCacheInner {
 
     /// Scratch space for rendering glyphs.
     image_place: Image,
+    /// Scratch space for measuring glyphs that are drawn as placeholders.
+    outline: Outline,
+
+    budget: RasterBudget,
+    stats: CacheStats,
+
+    /// The most glyphs to keep in `glyph_cache`, if limited.
+    capacity: Option<usize>,
+    /// Counts calls to `end_frame`, to tell how recently glyphs were drawn.
+    frame: u64,
+}
+
+#[derive(Default)]
+struct RasterBudget {
+    max_glyphs: Option<u32>,
+    rasterized: u32,
+}
+
+impl RasterBudget {
+    fn try_take(&mut self) -> bool {
+        if self.max_glyphs.is_some_and(|max| self.rasterized >= max) {
+            return false;
+        }
+
+        self.rasterized += 1;
+        true
+    }
 }
 
 impl GlyphCacheInner {
@@ -77,7 +153,45 @@ impl GlyphCacheInner {
             scaler_cx,
             glyph_cache: HashMap::new(),
             image_place: Image::new(),
+            outline: Outline::new(),
+            budget: RasterBudget::default(),
+            stats: CacheStats::default(),
+            capacity: None,
+            frame: 0,
+        }
+    }
+
+    /// Drops the least recently drawn glyphs that exceed the capacity,
+    /// releasing their space in the atlas.
+    fn evict(&mut self) {
+        let Some(capacity) = self.capacity else {
+            return;
+        };
+
+        let Some(excess) = self.glyph_cache.len().checked_sub(capacity) else {
+            return;
+        };
+
+        if excess == 0 {
+            return;
         }
+
+        let mut last_used = self
+            .glyph_cache
+            .values()
+            .map(|entry| entry.last_used)
+            .collect::<Vec<_>>();
+        let (_, &mut cutoff, _) = last_used.select_nth_unstable(excess - 1);
+
+        let frame = self.frame;
+        let mut remaining = excess;
+        self.glyph_cache.retain(|_, entry| {
+            let is_evicted = remaining > 0 && entry.last_used <= cutoff && entry.last_used != frame;
+            remaining -= usize::from(is_evicted);
+            !is_evicted
+        });
+
+        self.stats.evictions += excess - remaining;
     }
 
     #[instrument(skip_all)]
@@ -96,6 +210,10 @@ impl GlyphCacheInner {
                         &mut self.scaler_cx,
                         &mut self.image_place,
                         &mut self.glyph_cache,
+                        &mut self.outline,
+                        &mut self.budget,
+                        &mut self.stats,
+                        self.frame,
                         canvas,
                         textures,
                         &glyphs,
@@ -107,8 +225,52 @@ impl GlyphCacheInner {
             }
         }
     }
+
+    #[instrument(skip_all)]
+    fn draw_on_path(
+        &mut self,
+        canvas: &mut CanvasStorage,
+        textures: &TextureManager,
+        layout: &Layout<Color>,
+        path: &[[f32; 2]],
+        start: f32,
+        clip: ClipRect,
+    ) {
+        let Some(first_line) = layout.lines().next() else {
+            return;
+        };
+
+        let first_baseline = first_line.metrics().baseline;
+        let mut walker = PathWalker::new(path);
+
+        for line in layout.lines() {
+            for item in line.items() {
+                match item {
+                    PositionedLayoutItem::GlyphRun(glyphs) => draw_glyph_run_on_path(
+                        &mut self.scaler_cx,
+                        &mut self.image_place,
+                        &mut self.glyph_cache,
+                        &mut self.outline,
+                        &mut self.budget,
+                        &mut self.stats,
+                        self.frame,
+                        canvas,
+                        textures,
+                        &glyphs,
+                        &mut walker,
+                        [start, -first_baseline],
+                        clip,
+                    ),
+                    PositionedLayoutItem::InlineBox(_) => {}
+                }
+            }
+        }
+    }
 }
 
+/// The opacity of placeholders relative to the text they stand in for.
+const PLACEHOLDER_ALPHA: f32 = 0.2;
+
 const SUBPIXEL_VARIANTS: f32 = 3.0;
 const SUBPIXEL_VARIANTS_U8: u8 = 3;
 
@@ -141,14 +303,28 @@ fn draw_glyph_run(
     scaler_cx: &mut ScaleContext,
     temp_glyph: &mut Image,
     glyph_cache: &mut HashMap<GlyphCacheKey, GlyphCacheEntry>,
+    outline: &mut Outline,
+    budget: &mut RasterBudget,
+    stats: &mut CacheStats,
+    frame: u64,
     canvas: &mut CanvasStorage,
     textures: &TextureManager,
     glyph_run: &GlyphRun<Color>,
     origin: [f32; 2],
     clip: ClipRect,
 ) {
-    let mut run_x = glyph_run.offset() + origin[0];
-    let run_y = (glyph_run.baseline() + origin[1]).round();
+    // Glyphs are rasterized and snapped in the pixels of the render target,
+    // which canvas units are scaled to, so that they stay sharp.
+    let view = canvas.view();
+    let to_canvas = |x: f32, y: f32| {
+        [
+            x / view.scale + view.origin[0],
+            y / view.scale + view.origin[1],
+        ]
+    };
+
+    let mut run_x = (glyph_run.offset() + origin[0] - view.origin[0]) * view.scale;
+    let run_y = ((glyph_run.baseline() + origin[1] - view.origin[1]) * view.scale).round();
     let style = glyph_run.style();
     let color = style.brush;
 
@@ -156,7 +332,7 @@ fn draw_glyph_run(
 
     // Resolve properties of the Run
     let font = run.font();
-    let font_size = run.font_size();
+    let font_size = run.font_size() * view.scale;
     let normalized_coords = run.normalized_coords();
 
     // Convert from parley::Font to swash::FontRef. Should always succeed since
@@ -171,9 +347,9 @@ fn draw_glyph_run(
         .build();
 
     for glyph in glyph_run.glyphs() {
-        let x = run_x + glyph.x;
-        let y = run_y - glyph.y;
-        run_x += glyph.advance;
+        let x = run_x + glyph.x * view.scale;
+        let y = run_y - glyph.y * view.scale;
+        run_x += glyph.advance * view.scale;
 
         // figure out which glyph offset variant to use
         let x_placement = SubpixelAlignment::new(x);
@@ -198,74 +374,373 @@ fn draw_glyph_run(
             size: font_size as u16,
         };
 
-        let entry = match glyph_cache.entry(key) {
-            Entry::Occupied(occupied_entry) => occupied_entry.into_mut(),
-            Entry::Vacant(vacant_entry) => {
-                temp_glyph.clear();
+        let offset = Vector::new(x_placement.offset, y_placement.offset);
+        let entry = match lookup_glyph(
+            &mut scaler,
+            temp_glyph,
+            glyph_cache,
+            budget,
+            stats,
+            textures,
+            key,
+            frame,
+            offset,
+        ) {
+            GlyphLookup::Found(entry) => entry,
+            GlyphLookup::OverBudget => {
+                // Outlines are much cheaper to produce than rasterized glyphs,
+                // and glyphs without one (e.g. bitmap emoji) are left blank.
+                if scaler.scale_outline_into(glyph_id, outline) {
+                    let bounds = outline.bounds();
+                    draw_placeholder(
+                        canvas,
+                        textures,
+                        to_canvas(snapped_x + bounds.min.x, snapped_y - bounds.max.y),
+                        [bounds.width() / view.scale, bounds.height() / view.scale],
+                        0.0,
+                        color,
+                        clip,
+                    );
+                }
 
-                let offset = Vector::new(x_placement.offset, y_placement.offset);
+                canvas.mark_unready();
+                continue;
+            }
+            GlyphLookup::Empty => continue,
+        };
 
-                let success = Render::new(&[
-                    Source::ColorOutline(0),
-                    Source::ColorBitmap(StrikeWith::BestFit),
-                    Source::Bitmap(StrikeWith::BestFit),
-                    Source::Outline,
-                ])
-                .format(Format::Alpha)
-                .offset(offset)
-                .render_into(&mut scaler, glyph_id, temp_glyph);
+        let glyph_point = to_canvas(
+            (snapped_x as i32 + entry.left) as f32,
+            (snapped_y as i32 - entry.top) as f32,
+        );
+        let glyph_size = [
+            entry.width as f32 / view.scale,
+            entry.height as f32 / view.scale,
+        ];
+
+        // Glyphs rasterized this frame can't be drawn until their upload has
+        // been flushed.
+        if !entry.texture.is_ready() {
+            draw_placeholder(canvas, textures, glyph_point, glyph_size, 0.0, color, clip);
+        }
 
-                assert!(success);
+        canvas.push(
+            textures,
+            Primitive {
+                point: glyph_point,
+                size: glyph_size,
+                paint: entry.paint(color),
+                border: GradientPaint::default(),
+                border_colors: None,
+                border_width: [0.0; 4],
+                corner_radii: [0.0; 4],
+                use_nearest_sampling: true,
+                rotation: 0.0,
+                clip,
+            },
+        );
+    }
+}
 
-                if temp_glyph.placement.width == 0 || temp_glyph.placement.height == 0 {
-                    continue;
+/// Draws a glyph run with each glyph rotated to follow the path. `origin`
+/// maps layout coordinates to a distance along the path and an offset from it.
+#[expect(clippy::too_many_arguments)]
+fn draw_glyph_run_on_path(
+    scaler_cx: &mut ScaleContext,
+    temp_glyph: &mut Image,
+    glyph_cache: &mut HashMap<GlyphCacheKey, GlyphCacheEntry>,
+    outline: &mut Outline,
+    budget: &mut RasterBudget,
+    stats: &mut CacheStats,
+    frame: u64,
+    canvas: &mut CanvasStorage,
+    textures: &TextureManager,
+    glyph_run: &GlyphRun<Color>,
+    walker: &mut PathWalker,
+    origin: [f32; 2],
+    clip: ClipRect,
+) {
+    let mut run_x = glyph_run.offset() + origin[0];
+    let run_y = glyph_run.baseline() + origin[1];
+    let color = glyph_run.style().brush;
+
+    // Rasterized at the resolution of the render target, then drawn at the
+    // glyph's size in canvas units.
+    let scale = canvas.view().scale;
+    let run = glyph_run.run();
+    let font = run.font();
+    let font_size = run.font_size() * scale;
+
+    let font_ref = FontRef::from_index(font.data.as_ref(), font.index as usize).unwrap();
+
+    let mut scaler = scaler_cx
+        .builder(font_ref)
+        .size(font_size)
+        .hint(true)
+        .normalized_coords(run.normalized_coords())
+        .build();
+
+    for glyph in glyph_run.glyphs() {
+        // Glyphs are placed by the middle of their advance so that they sit
+        // evenly on curves.
+        let half_advance = glyph.advance * 0.5;
+        let distance = run_x + glyph.x + half_advance;
+        let offset = run_y - glyph.y;
+        run_x += glyph.advance;
+
+        let Some((anchor, angle)) = walker.sample(distance) else {
+            continue;
+        };
+
+        // Rotated glyphs can't be aligned to the pixel grid, so only one
+        // variant of each is needed.
+        let glyph_id = glyph.id as u16;
+        let key = GlyphCacheKey {
+            font_id: font.data.id(),
+            glyph_id,
+            x_variant: 0,
+            y_variant: 0,
+            size: font_size as u16,
+        };
+
+        // Places a rect given relative to the glyph's origin on the baseline.
+        let (sin, cos) = angle.sin_cos();
+        let place = |min: [f32; 2], size: [f32; 2]| {
+            let x = min[0] + size[0] * 0.5 - half_advance;
+            let y = min[1] + size[1] * 0.5 + offset;
+            let center = [anchor[0] + x * cos - y * sin, anchor[1] + x * sin + y * cos];
+
+            [center[0] - size[0] * 0.5, center[1] - size[1] * 0.5]
+        };
+
+        let entry = match lookup_glyph(
+            &mut scaler,
+            temp_glyph,
+            glyph_cache,
+            budget,
+            stats,
+            textures,
+            key,
+            frame,
+            Vector::new(0.0, 0.0),
+        ) {
+            GlyphLookup::Found(entry) => entry,
+            GlyphLookup::OverBudget => {
+                if scaler.scale_outline_into(glyph_id, outline) {
+                    let bounds = outline.bounds();
+                    let size = [bounds.width() / scale, bounds.height() / scale];
+                    draw_placeholder(
+                        canvas,
+                        textures,
+                        place([bounds.min.x / scale, -bounds.max.y / scale], size),
+                        size,
+                        angle,
+                        color,
+                        clip,
+                    );
                 }
 
-                let format = match temp_glyph.content {
-                    Content::Color => TextureFormat::Rgba8UnormSrgb,
-                    Content::Mask => TextureFormat::R8Unorm,
-                    _ => unimplemented!(),
-                };
-
-                let texture = textures.load_from_memory(
-                    &temp_glyph.data,
-                    temp_glyph.placement.width as u16,
-                    format,
-                );
-
-                vacant_entry.insert(GlyphCacheEntry {
-                    texture,
-                    width: temp_glyph.placement.width as u8,
-                    height: temp_glyph.placement.height as u8,
-                    left: temp_glyph.placement.left,
-                    top: temp_glyph.placement.top,
-                })
+                canvas.mark_unready();
+                continue;
             }
+            GlyphLookup::Empty => continue,
         };
 
-        let glyph_x = (snapped_x as i32 + entry.left) as f32;
-        let glyph_y = (snapped_y as i32 - entry.top) as f32;
+        let glyph_size = [entry.width as f32 / scale, entry.height as f32 / scale];
+        let glyph_point = place(
+            [entry.left as f32 / scale, -entry.top as f32 / scale],
+            glyph_size,
+        );
+
+        if !entry.texture.is_ready() {
+            draw_placeholder(
+                canvas,
+                textures,
+                glyph_point,
+                glyph_size,
+                angle,
+                color,
+                clip,
+            );
+        }
 
         canvas.push(
             textures,
             Primitive {
-                point: [glyph_x, glyph_y],
-                size: [entry.width as f32, entry.height as f32],
-                paint: Paint::Sampled {
-                    color_tint: color,
-                    color_texture: None,
-                    alpha_texture: Some(entry.texture.clone()),
-                },
+                point: glyph_point,
+                size: glyph_size,
+                paint: entry.paint(color),
                 border: GradientPaint::default(),
+                border_colors: None,
                 border_width: [0.0; 4],
                 corner_radii: [0.0; 4],
-                use_nearest_sampling: true,
+                use_nearest_sampling: false,
                 clip,
+                rotation: angle,
             },
         );
     }
 }
 
+/// Finds points along a polyline by their distance from its start.
+///
+/// Lookups are fastest when distances increase from one call to the next.
+struct PathWalker<'a> {
+    points: &'a [[f32; 2]],
+    /// The index of the segment that the last lookup landed on.
+    segment: usize,
+    /// The distance along the path to the start of `segment`.
+    segment_start: f32,
+}
+
+impl<'a> PathWalker<'a> {
+    fn new(points: &'a [[f32; 2]]) -> Self {
+        Self {
+            points,
+            segment: 0,
+            segment_start: 0.0,
+        }
+    }
+
+    /// The point at `distance` along the path and the direction of the path
+    /// there in radians, or `None` if `distance` is past either end.
+    fn sample(&mut self, distance: f32) -> Option<([f32; 2], f32)> {
+        if distance < 0.0 {
+            return None;
+        }
+
+        if distance < self.segment_start {
+            self.segment = 0;
+            self.segment_start = 0.0;
+        }
+
+        while self.segment + 1 < self.points.len() {
+            let [x0, y0] = self.points[self.segment];
+            let [x1, y1] = self.points[self.segment + 1];
+            let (dx, dy) = (x1 - x0, y1 - y0);
+            let length = dx.hypot(dy);
+
+            let t = (distance - self.segment_start) / length;
+            if t <= 1.0 {
+                return Some(([x0 + dx * t, y0 + dy * t], dy.atan2(dx)));
+            }
+
+            self.segment += 1;
+            self.segment_start += length;
+        }
+
+        None
+    }
+}
+
+enum GlyphLookup<'a> {
+    Found(&'a GlyphCacheEntry),
+    /// The glyph isn't cached, and rasterizing it would exceed the frame's
+    /// budget.
+    OverBudget,
+    /// The glyph has no pixels to draw.
+    Empty,
+}
+
+/// Finds the cached image of a glyph, rasterizing it if needed.
+#[expect(clippy::too_many_arguments)]
+fn lookup_glyph<'a>(
+    scaler: &mut Scaler,
+    temp_glyph: &mut Image,
+    glyph_cache: &'a mut HashMap<GlyphCacheKey, GlyphCacheEntry>,
+    budget: &mut RasterBudget,
+    stats: &mut CacheStats,
+    textures: &TextureManager,
+    key: GlyphCacheKey,
+    frame: u64,
+    offset: Vector,
+) -> GlyphLookup<'a> {
+    let entry = glyph_cache.entry(key);
+    stats.record(matches!(entry, Entry::Occupied(_)));
+
+    let vacant_entry = match entry {
+        Entry::Occupied(occupied_entry) => {
+            let entry = occupied_entry.into_mut();
+            entry.last_used = frame;
+            return GlyphLookup::Found(entry);
+        }
+        Entry::Vacant(_) if !budget.try_take() => return GlyphLookup::OverBudget,
+        Entry::Vacant(vacant_entry) => vacant_entry,
+    };
+
+    temp_glyph.clear();
+
+    let success = Render::new(&[
+        Source::ColorOutline(0),
+        Source::ColorBitmap(StrikeWith::BestFit),
+        Source::Bitmap(StrikeWith::BestFit),
+        Source::Outline,
+    ])
+    .format(Format::Alpha)
+    .offset(offset)
+    .render_into(scaler, key.glyph_id, temp_glyph);
+
+    assert!(success);
+
+    if temp_glyph.placement.width == 0 || temp_glyph.placement.height == 0 {
+        return GlyphLookup::Empty;
+    }
+
+    // Color glyphs such as emoji go in the RGBA atlas and keep their own
+    // colors, while masks are tinted with the text color.
+    let (format, is_color) = match temp_glyph.content {
+        Content::Color => (TextureFormat::Rgba8UnormSrgb, true),
+        Content::Mask => (TextureFormat::R8Unorm, false),
+        _ => unimplemented!(),
+    };
+
+    let texture =
+        textures.load_from_memory(&temp_glyph.data, temp_glyph.placement.width as u16, format);
+
+    GlyphLookup::Found(vacant_entry.insert(GlyphCacheEntry {
+        texture,
+        width: temp_glyph.placement.width as u8,
+        height: temp_glyph.placement.height as u8,
+        left: temp_glyph.placement.left,
+        top: temp_glyph.placement.top,
+        is_color,
+        last_used: frame,
+    }))
+}
+
+fn draw_placeholder(
+    canvas: &mut CanvasStorage,
+    textures: &TextureManager,
+    point: [f32; 2],
+    size: [f32; 2],
+    rotation: f32,
+    color: Color,
+    clip: ClipRect,
+) {
+    if size[0] <= 0.0 || size[1] <= 0.0 {
+        return;
+    }
+
+    canvas.push(
+        textures,
+        Primitive {
+            point,
+            size,
+            paint: Paint::solid(Color {
+                a: color.a * PLACEHOLDER_ALPHA,
+                ..color
+            }),
+            border: GradientPaint::default(),
+            border_colors: None,
+            border_width: [0.0; 4],
+            corner_radii: [1.0; 4],
+            use_nearest_sampling: false,
+            rotation,
+            clip,
+        },
+    );
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 struct GlyphCacheKey {
     font_id: u64,
@@ -282,4 +757,71 @@ struct GlyphCacheEntry {
     height: u8,
     left: i32,
     top: i32,
+    is_color: bool,
+    /// The frame the glyph was last drawn in.
+    last_used: u64,
+}
+
+impl GlyphCacheEntry {
+    fn paint(&self, color: Color) -> Paint {
+        if self.is_color {
+            // Only the text's opacity applies to color glyphs.
+            Paint::textured(self.texture.clone(), Color::WHITE.mul_alpha(color.a))
+        } else {
+            Paint::Sampled {
+                color_tint: color,
+                color_texture: None,
+                alpha_texture: Some(self.texture.clone()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    const PATH: [[f32; 2]; 3] = [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0]];
+
+    #[track_caller]
+    fn assert_sample(walker: &mut PathWalker, distance: f32, point: [f32; 2], angle: f32) {
+        let (sampled_point, sampled_angle) = walker.sample(distance).unwrap();
+        assert!(
+            (sampled_point[0] - point[0]).abs() < 1e-4
+                && (sampled_point[1] - point[1]).abs() < 1e-4
+                && (sampled_angle - angle).abs() < 1e-4,
+            "at {distance}: expected {point:?} at {angle}, got {sampled_point:?} at {sampled_angle}"
+        );
+    }
+
+    #[test]
+    fn path_walker_follows_segments() {
+        let mut walker = PathWalker::new(&PATH);
+
+        assert_sample(&mut walker, 0.0, [0.0, 0.0], 0.0);
+        assert_sample(&mut walker, 5.0, [5.0, 0.0], 0.0);
+        // The corner belongs to the segment that ends there.
+        assert_sample(&mut walker, 10.0, [10.0, 0.0], 0.0);
+        assert_sample(&mut walker, 15.0, [10.0, 5.0], FRAC_PI_2);
+        assert_sample(&mut walker, 20.0, [10.0, 10.0], FRAC_PI_2);
+    }
+
+    #[test]
+    fn path_walker_rewinds_for_earlier_distances() {
+        let mut walker = PathWalker::new(&PATH);
+
+        assert_sample(&mut walker, 15.0, [10.0, 5.0], FRAC_PI_2);
+        assert_sample(&mut walker, 5.0, [5.0, 0.0], 0.0);
+    }
+
+    #[test]
+    fn path_walker_ends_at_path_ends() {
+        let mut walker = PathWalker::new(&PATH);
+
+        assert_eq!(walker.sample(-1.0), None);
+        assert_eq!(walker.sample(20.5), None);
+        assert_eq!(PathWalker::new(&[[0.0, 0.0]]).sample(0.0), None);
+    }
 }
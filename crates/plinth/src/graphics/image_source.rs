@@ -0,0 +1,137 @@
+use std::cell::RefCell;
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::rc::Weak;
+use std::sync::Arc;
+
+use super::Texture;
+use super::TextureLoadError;
+
+/// Where an image is loaded from.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ImageSource {
+    Path(PathBuf),
+    /// The contents of an image file, such as a PNG.
+    Bytes(Arc<[u8]>),
+    /// Fetched with the fetcher set by
+    /// [`GraphicsContext::set_image_fetcher`](super::GraphicsContext::set_image_fetcher).
+    Url(String),
+}
+
+impl ImageSource {
+    pub fn url(url: impl Into<String>) -> Self {
+        ImageSource::Url(url.into())
+    }
+}
+
+impl From<PathBuf> for ImageSource {
+    fn from(path: PathBuf) -> Self {
+        ImageSource::Path(path)
+    }
+}
+
+impl From<&Path> for ImageSource {
+    fn from(path: &Path) -> Self {
+        ImageSource::Path(path.to_owned())
+    }
+}
+
+impl From<Vec<u8>> for ImageSource {
+    fn from(bytes: Vec<u8>) -> Self {
+        ImageSource::Bytes(bytes.into())
+    }
+}
+
+impl From<Arc<[u8]>> for ImageSource {
+    fn from(bytes: Arc<[u8]>) -> Self {
+        ImageSource::Bytes(bytes)
+    }
+}
+
+/// Fetches the contents of images requested by URL, e.g. over HTTP.
+///
+/// Each fetch runs on a thread of its own, so implementations may block.
+pub trait ImageFetcher: Send + Sync {
+    fn fetch(&self, url: &str) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// An image that may still be loading.
+///
+/// Handles to the same URL share their state, so an image is only fetched
+/// once however many widgets display it. It is fetched again if requested
+/// after every handle to it has been dropped.
+#[derive(Clone)]
+pub struct ImageHandle {
+    state: Rc<RefCell<ImageState>>,
+}
+
+pub(crate) enum ImageState {
+    Loading,
+    Loaded(Texture),
+    Failed(Rc<TextureLoadError>),
+}
+
+impl ImageHandle {
+    pub(crate) fn loading() -> Self {
+        Self {
+            state: Rc::new(RefCell::new(ImageState::Loading)),
+        }
+    }
+
+    pub(crate) fn from_result(result: Result<Texture, TextureLoadError>) -> Self {
+        let state = match result {
+            Ok(texture) => ImageState::Loaded(texture),
+            Err(error) => ImageState::Failed(Rc::new(error)),
+        };
+
+        Self {
+            state: Rc::new(RefCell::new(state)),
+        }
+    }
+
+    pub(crate) fn upgrade(state: &Weak<RefCell<ImageState>>) -> Option<Self> {
+        state.upgrade().map(|state| Self { state })
+    }
+
+    pub(crate) fn set_state(&self, state: ImageState) {
+        *self.state.borrow_mut() = state;
+    }
+
+    pub(crate) fn downgrade(&self) -> Weak<RefCell<ImageState>> {
+        Rc::downgrade(&self.state)
+    }
+
+    /// The image's texture, or `None` if it is still loading or failed to
+    /// load. The texture itself may not be ready to draw for a few frames
+    /// while it is decoded.
+    pub fn texture(&self) -> Option<Texture> {
+        match &*self.state.borrow() {
+            ImageState::Loaded(texture) => Some(texture.clone()),
+            ImageState::Loading | ImageState::Failed(_) => None,
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        matches!(*self.state.borrow(), ImageState::Loading)
+    }
+
+    pub fn error(&self) -> Option<Rc<TextureLoadError>> {
+        match &*self.state.borrow() {
+            ImageState::Failed(error) => Some(error.clone()),
+            ImageState::Loading | ImageState::Loaded(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Debug for ImageHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut f = f.debug_struct("ImageHandle");
+        match &*self.state.borrow() {
+            ImageState::Loading => f.field("state", &"Loading"),
+            ImageState::Loaded(texture) => f.field("texture", texture),
+            ImageState::Failed(error) => f.field("error", error),
+        };
+        f.finish()
+    }
+}
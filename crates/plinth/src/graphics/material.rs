@@ -0,0 +1,44 @@
+use super::pipeline::SHADER_SOURCE;
+
+const MATERIAL_SOURCE: &str = include_str!("material.wgsl");
+
+/// Identifies a custom material registered with
+/// [`GraphicsContext::register_material`](super::GraphicsContext::register_material).
+///
+/// Ids remain valid for the lifetime of the graphics context, including
+/// across adapter changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MaterialId(pub(crate) u32);
+
+#[derive(Debug)]
+pub enum MaterialError {
+    /// The material's source failed to compile or did not define a valid
+    /// `material` function.
+    InvalidShader(String),
+}
+
+impl std::fmt::Display for MaterialError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MaterialError::InvalidShader(message) => write!(f, "invalid material: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for MaterialError {}
+
+/// Builds the full shader for a material from the snippet provided by the
+/// user.
+///
+/// The snippet is appended after the built-in shader so that it can call its
+/// helper functions, such as `sample_gradient`.
+pub(crate) fn material_shader_source(snippet: &str) -> String {
+    let mut source =
+        String::with_capacity(SHADER_SOURCE.len() + MATERIAL_SOURCE.len() + snippet.len() + 2);
+    source.push_str(SHADER_SOURCE);
+    source.push('\n');
+    source.push_str(MATERIAL_SOURCE);
+    source.push('\n');
+    source.push_str(snippet);
+    source
+}
@@ -0,0 +1,53 @@
+// Entry point for primitives drawn with a custom material. This is appended to
+// shader.wgsl, followed by the material's own source, which must define:
+//
+//     fn material(input: MaterialInput) -> vec4f
+//
+// Clipping, rounded corners, borders and anti-aliasing are applied to the
+// returned color the same way as for any other primitive.
+
+struct MaterialInput {
+    // Position within the rect, from (0, 0) at the top-left corner to (1, 1)
+    // at the bottom-right.
+    uv: vec2f,
+    // Position of the fragment in canvas coordinates.
+    position: vec2f,
+    // Size of the rect in canvas coordinates.
+    size: vec2f,
+    // The parameters passed to `Canvas::draw_custom`.
+    params: array<vec4f, 3>,
+}
+
+@fragment
+fn fs_material(
+    in: VertexOutput
+) -> @location(0) vec4f {
+    let point = canvas_point(in.frag_coord.xy);
+
+    if (!inside_clip(point, in.clip_point, in.clip_extent)) {
+        discard;
+    }
+
+    let rect = rects[in.rect_index];
+    let local_point = rotate_about(point, rect.point + rect.extent * 0.5, -rect.rotation);
+
+    let corner_radius = rect.corner_radii[corner_from_uv(in.uv)];
+    let edge_alpha = shape_coverage(rect, local_point, corner_radius);
+    if (edge_alpha <= 0.0) {
+        discard;
+    }
+
+    let uv = clamp(in.uv, vec2f(0.0), vec2f(1.0));
+
+    var input: MaterialInput;
+    input.uv = uv;
+    input.position = point;
+    input.size = rect.extent;
+    input.params = array<vec4f, 3>(rect.background.a, rect.background.b, rect.background.c);
+
+    var content_color = material(input);
+    content_color = apply_border(rect, local_point, uv, corner_radius, content_color);
+    content_color.a *= edge_alpha * target_coverage(in.frag_coord.xy);
+
+    return content_color;
+}
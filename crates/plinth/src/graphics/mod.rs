@@ -1,22 +1,42 @@
+pub use adapter::AdapterError;
+pub use adapter::AdapterSettings;
 pub use color::Color;
 pub use context::GraphicsContext;
 pub use draw::Canvas;
 pub use draw::ClipRect;
 pub use draw::Primitive;
+pub use image_source::ImageFetcher;
+pub use image_source::ImageHandle;
+pub use image_source::ImageSource;
+pub use material::MaterialError;
+pub use material::MaterialId;
+pub use offscreen::RenderedImage;
+pub use paint::BorderColors;
 pub use paint::GradientPaint;
 pub use paint::Paint;
+pub use stats::CacheStats;
+pub use stats::RenderStats;
+pub use surface::PresentMode;
+pub use surface::PresentSettings;
+pub use surface::RenderError;
 pub use text::*;
+pub use texture::AlphaMask;
 pub use texture::Texture;
 pub use texture::TextureId;
 pub use texture::TextureLoadError;
 
+mod adapter;
 mod color;
 mod context;
 mod draw;
 mod glyph_cache;
+mod image_source;
+mod material;
+mod offscreen;
 mod paint;
 mod pipeline;
 mod shader_data;
+mod stats;
 mod surface;
 mod text;
 mod texture;
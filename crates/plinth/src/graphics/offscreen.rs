@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use tracing::instrument;
+
+use crate::graphics::pipeline::RenderPipeline;
+use crate::graphics::pipeline::RenderPipelineCache;
+use crate::graphics::surface::BindGroupCache;
+use crate::graphics::surface::Frame;
+
+/// The format of offscreen render targets and of the pixels read back from
+/// them.
+pub(crate) const OFFSCREEN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+const BYTES_PER_PIXEL: u32 = 4;
+
+/// The pixels of a canvas rendered with
+/// [`GraphicsContext::render_to_image`](super::GraphicsContext::render_to_image).
+///
+/// Pixels are tightly packed 8-bit sRGB RGBA, row by row from the top-left.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderedImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RenderedImage {
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let offset = ((y * self.width + x) * BYTES_PER_PIXEL) as usize;
+        self.pixels[offset..offset + BYTES_PER_PIXEL as usize]
+            .try_into()
+            .unwrap()
+    }
+}
+
+/// The per-target state needed to encode canvases into textures of
+/// [`OFFSCREEN_FORMAT`].
+pub(crate) struct OffscreenRenderer {
+    render_pipeline: RenderPipeline,
+    frame: Frame,
+
+    bind_groups: BindGroupCache,
+    cached_storage_version: u64,
+}
+
+impl OffscreenRenderer {
+    pub fn new(pipeline_cache: &RenderPipelineCache) -> Self {
+        let render_pipeline = pipeline_cache.get(OFFSCREEN_FORMAT);
+        let frame = Frame::new(&render_pipeline);
+
+        Self {
+            render_pipeline,
+            frame,
+            bind_groups: HashMap::new(),
+            cached_storage_version: 0,
+        }
+    }
+
+    pub fn next_frame(
+        &mut self,
+        storage_version: u64,
+    ) -> (&mut Frame, &RenderPipeline, &mut BindGroupCache) {
+        if storage_version != self.cached_storage_version {
+            self.bind_groups.clear();
+            self.cached_storage_version = storage_version;
+        }
+
+        (
+            &mut self.frame,
+            &self.render_pipeline,
+            &mut self.bind_groups,
+        )
+    }
+}
+
+/// A texture that canvases can be rendered into without a window, along with
+/// a buffer to copy the result back to the CPU.
+pub(crate) struct OffscreenTarget {
+    texture: wgpu::Texture,
+    readback: wgpu::Buffer,
+    padded_bytes_per_row: u32,
+
+    renderer: OffscreenRenderer,
+}
+
+impl OffscreenTarget {
+    #[instrument(skip(device, pipeline_cache))]
+    pub fn new(
+        device: &wgpu::Device,
+        pipeline_cache: &RenderPipelineCache,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: OFFSCREEN_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        // Buffer copies require each row to start on an aligned offset, so the
+        // readback buffer may be wider than the image.
+        let padded_bytes_per_row =
+            (width * BYTES_PER_PIXEL).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Offscreen Readback"),
+            size: u64::from(padded_bytes_per_row) * u64::from(height),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            texture,
+            readback,
+            padded_bytes_per_row,
+            renderer: OffscreenRenderer::new(pipeline_cache),
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.texture.width(), self.texture.height())
+    }
+
+    pub fn next_frame(
+        &mut self,
+        storage_version: u64,
+    ) -> (
+        wgpu::TextureView,
+        &mut Frame,
+        &RenderPipeline,
+        &mut BindGroupCache,
+    ) {
+        let view = self
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let (frame, render_pipeline, bind_groups) = self.renderer.next_frame(storage_version);
+
+        (view, frame, render_pipeline, bind_groups)
+    }
+
+    pub fn copy_to_readback(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &self.readback,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            self.texture.size(),
+        );
+    }
+
+    /// Maps the readback buffer and copies its contents out, stripping row
+    /// padding. Blocks until the GPU has finished the copy.
+    #[instrument(skip_all)]
+    pub fn read_pixels(&self, device: &wgpu::Device) -> Option<RenderedImage> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        self.readback
+            .map_async(wgpu::MapMode::Read, .., move |result| {
+                let _ = sender.send(result);
+            });
+
+        device.poll(wgpu::PollType::wait_indefinitely()).ok()?;
+        receiver.recv().ok()?.ok()?;
+
+        let (width, height) = self.size();
+        let row_bytes = (width * BYTES_PER_PIXEL) as usize;
+
+        let pixels = self.readback.get_mapped_range(..).ok().map(|mapped| {
+            let mut pixels = Vec::with_capacity(row_bytes * height as usize);
+            for row in mapped.chunks_exact(self.padded_bytes_per_row as usize) {
+                pixels.extend_from_slice(&row[..row_bytes]);
+            }
+            pixels
+        });
+
+        self.readback.unmap();
+
+        Some(RenderedImage {
+            width,
+            height,
+            pixels: pixels?,
+        })
+    }
+}
@@ -3,11 +3,16 @@ use super::Texture;
 
 /// Defines how a primitive is painted - either with textures or a gradient.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Paint {
     /// Paint using sampled textures with a color tint.
     Sampled {
         color_tint: Color,
+        // Textures are loaded at runtime, so they can't be part of a
+        // serialized style.
+        #[cfg_attr(feature = "serde", serde(skip))]
         color_texture: Option<Texture>,
+        #[cfg_attr(feature = "serde", serde(skip))]
         alpha_texture: Option<Texture>,
     },
     /// Paint using a linear gradient between two colors.
@@ -81,9 +86,25 @@ impl Paint {
             end,
         }
     }
+
+    /// Return a copy of this paint with its alpha multiplied by `opacity`.
+    #[must_use]
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        match &mut self {
+            Paint::Sampled { color_tint, .. } => *color_tint = color_tint.mul_alpha(opacity),
+            Paint::Gradient {
+                color_a, color_b, ..
+            } => {
+                *color_a = color_a.mul_alpha(opacity);
+                *color_b = color_b.mul_alpha(opacity);
+            }
+        }
+        self
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GradientPaint {
     pub color_a: Color,
     pub color_b: Color,
@@ -131,4 +152,62 @@ impl GradientPaint {
             end,
         }
     }
+
+    /// Return a copy of this gradient with its alpha multiplied by `opacity`.
+    #[must_use]
+    pub fn with_opacity(self, opacity: f32) -> Self {
+        Self {
+            color_a: self.color_a.mul_alpha(opacity),
+            color_b: self.color_b.mul_alpha(opacity),
+            ..self
+        }
+    }
+}
+
+/// A color for each side of a border, for bevels and highlights that a
+/// gradient can't express. Sides meet along the diagonals of the corners.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BorderColors {
+    pub left: Color,
+    pub top: Color,
+    pub right: Color,
+    pub bottom: Color,
+}
+
+impl BorderColors {
+    pub fn uniform(color: Color) -> Self {
+        Self {
+            left: color,
+            top: color,
+            right: color,
+            bottom: color,
+        }
+    }
+
+    /// A raised bevel, lit from the top left.
+    pub fn outset(light: Color, dark: Color) -> Self {
+        Self {
+            left: light,
+            top: light,
+            right: dark,
+            bottom: dark,
+        }
+    }
+
+    /// A sunken bevel, lit from the top left.
+    pub fn inset(light: Color, dark: Color) -> Self {
+        Self::outset(dark, light)
+    }
+
+    /// Return a copy of these colors with their alpha multiplied by `opacity`.
+    #[must_use]
+    pub fn with_opacity(self, opacity: f32) -> Self {
+        Self {
+            left: self.left.mul_alpha(opacity),
+            top: self.top.mul_alpha(opacity),
+            right: self.right.mul_alpha(opacity),
+            bottom: self.bottom.mul_alpha(opacity),
+        }
+    }
 }
@@ -1,18 +1,32 @@
 use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Mutex;
 
+use pollster::block_on;
 use tracing::debug;
+use tracing::warn;
 
+use crate::graphics::material::MaterialError;
+use crate::graphics::material::MaterialId;
+use crate::graphics::material::material_shader_source;
 use crate::graphics::shader_data::DrawUniforms;
 use crate::graphics::shader_data::GpuPrimitive;
 
 use super::shader_data::GpuClip;
 
-const SHADER_SOURCE: &str = include_str!("shader.wgsl");
+pub(super) const SHADER_SOURCE: &str = include_str!("shader.wgsl");
+
+/// Substituted for a material that no longer compiles, to make the problem
+/// obvious without disturbing the rest of the UI.
+const FALLBACK_MATERIAL: &str = "fn material(input: MaterialInput) -> vec4f {
+    return vec4f(1.0, 0.0, 1.0, 1.0);
+}";
 
 #[derive(Clone)]
 pub(crate) struct RenderPipeline {
     pub device: wgpu::Device,
+    pub format: wgpu::TextureFormat,
     pub pipeline: wgpu::RenderPipeline,
     pub sampler_bind_group: wgpu::BindGroup,
     pub draw_data_layout: wgpu::BindGroupLayout,
@@ -127,7 +141,7 @@ impl DrawBuffer {
         draw_info: DrawUniforms,
         primitives: &[GpuPrimitive],
         clips: &[GpuClip],
-    ) {
+    ) -> u64 {
         let prim_size = std::mem::size_of_val(primitives) as u64;
         let clip_size = std::mem::size_of_val(clips) as u64;
 
@@ -181,6 +195,8 @@ impl DrawBuffer {
         queue.write_buffer(&self.clip_buffer, 0, bytemuck::cast_slice(clips));
 
         render_pass.set_bind_group(0, &self.bind_group, &[]);
+
+        size_of::<DrawUniforms>() as u64 + prim_size + clip_size
     }
 }
 
@@ -193,6 +209,9 @@ impl DrawBuffer {
 ///
 /// There is no mechanism to invalidate the cache, under the assumption that
 /// there are a fixed number of formats that can be used.
+///
+/// Custom materials are registered here as well, and get a pipeline per
+/// format on first use.
 pub(crate) struct RenderPipelineCache {
     device: wgpu::Device,
     shader: wgpu::ShaderModule,
@@ -209,10 +228,89 @@ pub(crate) struct RenderPipelineCache {
     texture_bind_group_layout: wgpu::BindGroupLayout,
 
     pipelines: Mutex<HashMap<wgpu::TextureFormat, RenderPipeline>>,
+
+    materials: Mutex<Vec<Material>>,
+    material_pipelines: Mutex<HashMap<(wgpu::TextureFormat, MaterialId), wgpu::RenderPipeline>>,
+
+    disk_cache: Option<DiskPipelineCache>,
+}
+
+struct Material {
+    /// The user's snippet, kept so that the material can be recompiled for a
+    /// new device.
+    snippet: String,
+    shader: wgpu::ShaderModule,
+}
+
+/// A [`wgpu::PipelineCache`] that is saved to a file named after the adapter,
+/// so that it is only reused with the same GPU and driver.
+pub(crate) struct DiskPipelineCache {
+    cache: wgpu::PipelineCache,
+    path: PathBuf,
+}
+
+impl DiskPipelineCache {
+    /// Opens the cache for `adapter` in `dir`, or returns `None` if the
+    /// device doesn't support pipeline caches.
+    pub fn open(device: &wgpu::Device, adapter: &wgpu::Adapter, dir: &Path) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::PIPELINE_CACHE) {
+            return None;
+        }
+
+        let path = dir.join(wgpu::util::pipeline_cache_key(&adapter.get_info())?);
+
+        let data = match std::fs::read(&path) {
+            Ok(data) => Some(data),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+            Err(error) => {
+                warn!(%error, path = %path.display(), "Failed to read pipeline cache");
+                None
+            }
+        };
+
+        debug!(
+            path = %path.display(),
+            bytes = data.as_ref().map_or(0, Vec::len),
+            "Opening pipeline cache"
+        );
+
+        // SAFETY: The data was written by `Self::save` for an adapter with
+        // the same key. wgpu validates its header against the driver and
+        // falls back to an empty cache if it is stale or corrupt.
+        let cache = unsafe {
+            device.create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                label: Some("Pipeline Cache"),
+                data: data.as_deref(),
+                fallback: true,
+            })
+        };
+
+        Some(Self { cache, path })
+    }
+
+    /// Writes the cache to disk. The file is replaced atomically so that a
+    /// crash mid-write can't leave a truncated cache for the next launch.
+    pub fn save(&self) {
+        let Some(data) = self.cache.get_data() else {
+            return;
+        };
+
+        let temp_path = self.path.with_extension("tmp");
+        let result = self
+            .path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&temp_path, &data))
+            .and_then(|()| std::fs::rename(&temp_path, &self.path));
+
+        if let Err(error) = result {
+            warn!(%error, path = %self.path.display(), "Failed to save pipeline cache");
+        }
+    }
 }
 
 impl RenderPipelineCache {
-    pub fn new(device: wgpu::Device) -> Self {
+    pub fn new(device: wgpu::Device, disk_cache: Option<DiskPipelineCache>) -> Self {
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: None,
             source: wgpu::ShaderSource::Wgsl(SHADER_SOURCE.into()),
@@ -223,7 +321,7 @@ impl RenderPipelineCache {
             entries: &[
                 wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
                         has_dynamic_offset: false,
@@ -355,6 +453,54 @@ impl RenderPipelineCache {
             draw_data_layout,
             texture_bind_group_layout,
             pipelines: Mutex::new(HashMap::new()),
+            materials: Mutex::new(Vec::new()),
+            material_pipelines: Mutex::new(HashMap::new()),
+            disk_cache,
+        }
+    }
+
+    /// Compiles a material from a WGSL snippet defining
+    /// `fn material(input: MaterialInput) -> vec4f`.
+    pub fn register_material(&self, snippet: &str) -> Result<MaterialId, MaterialError> {
+        let shader = self.compile_material(snippet)?;
+
+        let mut materials = self.materials.lock().unwrap();
+        let id = MaterialId(materials.len() as u32);
+        materials.push(Material {
+            snippet: snippet.to_owned(),
+            shader,
+        });
+
+        Ok(id)
+    }
+
+    /// Recompiles the materials registered with another cache so that their
+    /// ids remain valid with this one.
+    pub fn copy_materials_from(&self, other: &RenderPipelineCache) {
+        let other_materials = other.materials.lock().unwrap();
+        let mut materials = self.materials.lock().unwrap();
+
+        for material in other_materials.iter() {
+            // The snippet compiled on the previous device, so this only fails
+            // if the new device lacks a feature it depends on. Keeping the
+            // slot preserves the ids of later materials.
+            let shader = self
+                .compile_material(&material.snippet)
+                .unwrap_or_else(|error| {
+                    warn!(%error, "Failed to recompile material");
+                    self.device
+                        .create_shader_module(wgpu::ShaderModuleDescriptor {
+                            label: Some("Material"),
+                            source: wgpu::ShaderSource::Wgsl(
+                                material_shader_source(FALLBACK_MATERIAL).into(),
+                            ),
+                        })
+                });
+
+            materials.push(Material {
+                snippet: material.snippet.clone(),
+                shader,
+            });
         }
     }
 
@@ -367,8 +513,80 @@ impl RenderPipelineCache {
 
         debug!("Creating a new pipeline for {:?}", format);
 
-        let render_pipeline = self
+        let render_pipeline = self.create_pipeline(&self.shader, "fs_main", format);
+        self.save_disk_cache();
+
+        let pipeline = RenderPipeline {
+            device: self.device.clone(),
+            format,
+            pipeline: render_pipeline,
+            sampler_bind_group: self.sampler_bind_group.clone(),
+            draw_data_layout: self.draw_data_layout.clone(),
+            texture_bind_group_layout: self.texture_bind_group_layout.clone(),
+        };
+
+        pipelines.insert(format, pipeline.clone());
+
+        pipeline
+    }
+
+    /// Returns the pipeline for drawing a material to the given format, or
+    /// `None` if the material does not exist.
+    pub fn get_material(
+        &self,
+        format: wgpu::TextureFormat,
+        material: MaterialId,
+    ) -> Option<wgpu::RenderPipeline> {
+        let mut pipelines = self.material_pipelines.lock().unwrap();
+        if let Some(pipeline) = pipelines.get(&(format, material)) {
+            return Some(pipeline.clone());
+        }
+
+        let materials = self.materials.lock().unwrap();
+        let shader = &materials.get(material.0 as usize)?.shader;
+
+        debug!(
+            "Creating a new pipeline for {:?} with {:?}",
+            format, material
+        );
+        let pipeline = self.create_pipeline(shader, "fs_material", format);
+        self.save_disk_cache();
+        pipelines.insert((format, material), pipeline.clone());
+
+        Some(pipeline)
+    }
+
+    /// Pipelines are created rarely and the application may exit without
+    /// warning, so the disk cache is saved after each one.
+    fn save_disk_cache(&self) {
+        if let Some(disk_cache) = &self.disk_cache {
+            disk_cache.save();
+        }
+    }
+
+    fn compile_material(&self, snippet: &str) -> Result<wgpu::ShaderModule, MaterialError> {
+        let error_scope = self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let shader = self
             .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Material"),
+                source: wgpu::ShaderSource::Wgsl(material_shader_source(snippet).into()),
+            });
+
+        match block_on(error_scope.pop()) {
+            Some(error) => Err(MaterialError::InvalidShader(error.to_string())),
+            None => Ok(shader),
+        }
+    }
+
+    fn create_pipeline(
+        &self,
+        fragment_shader: &wgpu::ShaderModule,
+        fragment_entry_point: &str,
+        format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        self.device
             .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
                 label: Some("Render Pipeline"),
                 layout: Some(&self.layout),
@@ -394,8 +612,8 @@ impl RenderPipelineCache {
                     alpha_to_coverage_enabled: false,
                 },
                 fragment: Some(wgpu::FragmentState {
-                    module: &self.shader,
-                    entry_point: Some("fs_main"),
+                    module: fragment_shader,
+                    entry_point: Some(fragment_entry_point),
                     compilation_options: wgpu::PipelineCompilationOptions::default(),
                     targets: &[Some(wgpu::ColorTargetState {
                         format,
@@ -411,20 +629,8 @@ impl RenderPipelineCache {
                     })],
                 }),
                 multiview_mask: None,
-                cache: None,
-            });
-
-        let pipeline = RenderPipeline {
-            device: self.device.clone(),
-            pipeline: render_pipeline,
-            sampler_bind_group: self.sampler_bind_group.clone(),
-            draw_data_layout: self.draw_data_layout.clone(),
-            texture_bind_group_layout: self.texture_bind_group_layout.clone(),
-        };
-
-        pipelines.insert(format, pipeline.clone());
-
-        pipeline
+                cache: self.disk_cache.as_ref().map(|disk_cache| &disk_cache.cache),
+            })
     }
 }
 
@@ -434,8 +640,7 @@ mod tests {
 
     #[test]
     fn test_gpu_primitive_size() {
-        // Expected: 2*4 + 2*4 + 4*4 + 4*4 + 4*4 + 4 + 4 + 4 + 4 = 80 bytes
-        assert_eq!(std::mem::size_of::<GpuPrimitive>(), 160);
+        assert_eq!(std::mem::size_of::<GpuPrimitive>(), 176);
         assert_eq!(std::mem::align_of::<GpuPrimitive>(), 16);
     }
 }
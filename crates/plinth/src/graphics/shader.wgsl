@@ -1,5 +1,11 @@
 struct DrawInfo {
-    viewport_size: vec2<u32>
+    viewport_size: vec2<u32>,
+    // The canvas point drawn at the top-left of the target.
+    origin: vec2f,
+    // Target pixels per canvas unit.
+    scale: f32,
+    // The radius of the target's corners, in target pixels.
+    corner_radius: f32,
 }
 
 // Rectangle primitive with configurable paint (sampled texture or gradient)
@@ -7,15 +13,20 @@ struct Rect {
     point: vec2f,
     extent: vec2f,
     background: Paint,
-    border_color: GradientPaint,
+    // A gradient, or with USE_SIDE_BORDER_COLORS the colors of the left, top,
+    // and right sides.
+    border_color: Paint,
+    // The color of the bottom side with USE_SIDE_BORDER_COLORS.
+    border_bottom_color: vec4f,
     // left, top, right, bottom
     border_width: vec4f,
     // top-left, top-right, bottom-left, bottom-right
     corner_radii: vec4f,
     control_flags: Bitflags,
     clip_idx: u32,
-    _padding1: u32,
-    _padding2: u32,
+    // Clockwise, in radians, about the rect's center.
+    rotation: f32,
+    _padding: u32,
 }
 
 struct Clip {
@@ -48,15 +59,23 @@ fn vs_main(
 
     let vertex_index = in_vertex_index % 6;
     let vertex_corner = CORNER[vertex_index];
-    let vertex_position = rect.point + EXTENT_LOOKUP[vertex_corner] * rect.extent;
+
+    // The quad is grown past the rect so that pixels only partially covered
+    // by its edges are rasterized and can be blended by coverage.
+    let padding = AA_PADDING / draw_info.scale;
+    let quad_point = rect.point - vec2f(padding);
+    let quad_extent = rect.extent + vec2f(padding * 2.0);
+    let vertex_position = quad_point + EXTENT_LOOKUP[vertex_corner] * quad_extent;
+    let rect_center = rect.point + rect.extent * 0.5;
+    let rotated_position = rotate_about(vertex_position, rect_center, rect.rotation);
 
     let clip = clips[rect.clip_idx];
 
     var out: VertexOutput;
 
     out.rect_index = rect_index;
-    out.frag_coord = to_clip_coords(vertex_position);
-    out.uv = EXTENT_LOOKUP[vertex_corner];
+    out.frag_coord = to_clip_coords((rotated_position - draw_info.origin) * draw_info.scale);
+    out.uv = (vertex_position - rect.point) / max(rect.extent, vec2f(1e-6));
     out.clip_point = clip.point;
     out.clip_extent = clip.extent;
 
@@ -73,38 +92,38 @@ fn fs_main(
     in: VertexOutput
 ) -> @location(0) vec4f {
 
+    let point = canvas_point(in.frag_coord.xy);
+
     // Discard early to skip expensive texture sampling for fully-clipped fragments
-    if (!inside_clip(in.frag_coord.xy, in.clip_point, in.clip_extent)) {
+    if (!inside_clip(point, in.clip_point, in.clip_extent)) {
         discard;
     }
 
     let rect = rects[in.rect_index];
 
-    let rect_center = rect.point + rect.extent * 0.5;
-    let corner_radius = rect.corner_radii[corner_from_uv(in.uv)];
+    // Clips are axis-aligned, but the rect's shape and border are evaluated
+    // in its own unrotated space.
+    let local_point = rotate_about(point, rect.point + rect.extent * 0.5, -rect.rotation);
 
-    let shape_distance = distance_from_rect(
-        in.frag_coord.xy,
-        rect_center,
-        rect.extent * 0.5,
-        corner_radius
-    );
-
-    // Anti-aliased edge: smoothstep over ~1 pixel
-    let edge_alpha = 1.0 - smoothstep(-0.5, 0.5, shape_distance);
+    let corner_radius = rect.corner_radii[corner_from_uv(in.uv)];
+    let edge_alpha = shape_coverage(rect, local_point, corner_radius);
     if (edge_alpha <= 0.0) {
         discard;
     }
 
+    // Fragments in the padding around the rect must not sample texels from
+    // outside its region of the atlas.
+    let uv = clamp(in.uv, vec2f(0.0), vec2f(1.0));
+
     var content_color: vec4f;
     if (is_gradient_paint(rect.control_flags)) {
-        content_color = sample_gradient(as_gradient_paint(rect.background), in.uv);
+        content_color = sample_gradient(as_gradient_paint(rect.background), uv);
     } else {
         // Sampled texture mode
         let sampled = as_sampled_paint(rect.background);
 
-        let color_uv = sampled.color_uvwh.xy + sampled.color_uvwh.zw * in.uv;
-        let alpha_uv = sampled.alpha_uvwh.xy + sampled.alpha_uvwh.zw * in.uv;
+        let color_uv = sampled.color_uvwh.xy + sampled.color_uvwh.zw * uv;
+        let alpha_uv = sampled.alpha_uvwh.xy + sampled.alpha_uvwh.zw * uv;
 
         if (is_nearest_sampling(rect.control_flags)) {
             content_color = sampled.color_tint * textureSample(color_texture, nearest_sampler, color_uv);
@@ -115,40 +134,112 @@ fn fs_main(
         }
     }
 
+    content_color = apply_border(rect, local_point, uv, corner_radius, content_color);
+    content_color.a *= edge_alpha * target_coverage(in.frag_coord.xy);
+
+    return content_color;
+}
+
+/// Maps a fragment's position in the target back to canvas coordinates.
+fn canvas_point(frag_coord: vec2f) -> vec2f {
+    return draw_info.origin + frag_coord / draw_info.scale;
+}
+
+/// Rotates `point` clockwise by `angle` radians about `center`.
+fn rotate_about(point: vec2f, center: vec2f, angle: f32) -> vec2f {
+    if (angle == 0.0) {
+        return point;
+    }
+
+    let offset = point - center;
+    let c = cos(angle);
+    let s = sin(angle);
+    return center + vec2f(offset.x * c - offset.y * s, offset.x * s + offset.y * c);
+}
+
+/// The fraction of the pixel at `point` covered by the rect's rounded shape.
+fn shape_coverage(rect: Rect, point: vec2f, corner_radius: f32) -> f32 {
+    let rect_center = rect.point + rect.extent * 0.5;
+
+    let shape_distance = distance_from_rect(
+        point,
+        rect_center,
+        rect.extent * 0.5,
+        corner_radius
+    );
+
+    return coverage(shape_distance);
+}
+
+fn apply_border(rect: Rect, point: vec2f, uv: vec2f, corner_radius: f32, content_color: vec4f) -> vec4f {
     // Skip border calculation if no border
     let has_border = any(rect.border_width != vec4f(0.0));
-    if (has_border) {
-        let inner_point = rect.point + vec2f(rect.border_width.x, rect.border_width.y);
-        let inner_extent = rect.extent - vec2f(rect.border_width.x + rect.border_width.z, rect.border_width.y + rect.border_width.w);
-        let inner_center = inner_point + inner_extent * 0.5;
-
-        let inner_corner_radius = corner_radius - max(
-            max(rect.border_width.x, rect.border_width.y),
-            max(rect.border_width.z, rect.border_width.w)
-        );
-
-        let border_distance = distance_from_rect(
-            in.frag_coord.xy,
-            inner_center,
-            inner_extent * 0.5,
-            inner_corner_radius
-        );
-
-        // Only blend if we're near or inside the border region
-        // border_distance > 0 means we're in the border (outside inner rect)
-        // We need AA on the inner edge, so check > -0.5
-        if (border_distance > -0.5) {
-            let border_blend = smoothstep(-0.5, 0.5, border_distance);
-            let border_color = sample_gradient(rect.border_color, in.uv);
-            content_color = mix(content_color, border_color, border_blend);
-        }
+    if (!has_border) {
+        return content_color;
     }
 
-    content_color.a *= edge_alpha;
+    let inner_point = rect.point + vec2f(rect.border_width.x, rect.border_width.y);
+    let inner_extent = rect.extent - vec2f(rect.border_width.x + rect.border_width.z, rect.border_width.y + rect.border_width.w);
+    let inner_center = inner_point + inner_extent * 0.5;
 
-    return content_color;
+    let inner_corner_radius = max(corner_radius - max(
+        max(rect.border_width.x, rect.border_width.y),
+        max(rect.border_width.z, rect.border_width.w)
+    ), 0.0);
+
+    let border_distance = distance_from_rect(
+        point,
+        inner_center,
+        max(inner_extent, vec2f(0.0)) * 0.5,
+        inner_corner_radius
+    );
+
+    // The border covers whatever the outer edge covers and the inner edge
+    // does not, so borders thinner than a pixel fade instead of disappearing.
+    let inner_coverage = coverage(border_distance);
+    if (inner_coverage >= 1.0) {
+        return content_color;
+    }
+
+    var border_color: vec4f;
+    if (has_side_border_colors(rect.control_flags)) {
+        border_color = side_border_color(rect, point);
+    } else {
+        border_color = sample_gradient(as_gradient_paint(rect.border_color), uv);
+    }
+
+    return mix(border_color, content_color, inner_coverage);
 }
 
+/// The color of the border side nearest to `point` relative to its width, so
+/// that sides meet along the diagonals of the corners.
+fn side_border_color(rect: Rect, point: vec2f) -> vec4f {
+    // Sides without a width are never nearest.
+    let near = (point - rect.point) / max(rect.border_width.xy, vec2f(1e-6));
+    let far = (rect.point + rect.extent - point) / max(rect.border_width.zw, vec2f(1e-6));
+
+    var nearest = near.x;
+    var color = rect.border_color.a;
+    if (near.y < nearest) {
+        nearest = near.y;
+        color = rect.border_color.b;
+    }
+    if (far.x < nearest) {
+        nearest = far.x;
+        color = rect.border_color.c;
+    }
+    if (far.y < nearest) {
+        color = rect.border_bottom_color;
+    }
+
+    return color;
+}
+
+/// How far quads extend past their rect, in target pixels. Coverage falls off
+/// within half a pixel of an edge, so one pixel is enough to reach every
+/// partially covered pixel.
+const AA_PADDING: f32 = 1.0;
+
 const TOP_LEFT: u32 = 0u;
 const TOP_RIGHT: u32 = 1u;
 const BOTTOM_LEFT: u32 = 2u;
@@ -198,6 +289,24 @@ fn distance_from_rect(point: vec2f, rect_center: vec2f, rect_half_extent: vec2f,
     return length(max(q, vec2f(0.0, 0.0))) + min(max(q.x, q.y), 0.0) - corner_radius;
 }
 
+/// The fraction of a pixel covered by a shape, given the signed distance in
+/// canvas units from the pixel's center to the shape's edge. This is exact for
+/// straight edges and a close approximation for curves larger than a pixel.
+fn coverage(signed_distance: f32) -> f32 {
+    return clamp(0.5 - signed_distance * draw_info.scale, 0.0, 1.0);
+}
+
+/// The fraction of a target pixel inside the target's rounded corners.
+fn target_coverage(frag_coord: vec2f) -> f32 {
+    if (draw_info.corner_radius <= 0.0) {
+        return 1.0;
+    }
+
+    let half_extent = vec2f(draw_info.viewport_size) * 0.5;
+    let distance = distance_from_rect(frag_coord, half_extent, half_extent, draw_info.corner_radius);
+    return clamp(0.5 - distance, 0.0, 1.0);
+}
+
 fn inside_clip(point: vec2f, clip_point: vec2f, clip_extent: vec2f) -> bool {
     return point.x >= clip_point.x
         && point.y >= clip_point.y
@@ -207,6 +316,7 @@ fn inside_clip(point: vec2f, clip_point: vec2f, clip_extent: vec2f) -> bool {
 
 const USE_NEAREST_SAMPLING: u32 = 1;
 const USE_GRADIENT_PAINT: u32 = 2;
+const USE_SIDE_BORDER_COLORS: u32 = 4;
 
 struct Bitflags {
     value: u32
@@ -220,6 +330,10 @@ fn is_gradient_paint(flags: Bitflags) -> bool {
     return (flags.value & USE_GRADIENT_PAINT) != 0u;
 }
 
+fn has_side_border_colors(flags: Bitflags) -> bool {
+    return (flags.value & USE_SIDE_BORDER_COLORS) != 0u;
+}
+
 struct Paint {
     a: vec4f,
     b: vec4f,
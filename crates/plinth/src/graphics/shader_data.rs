@@ -7,6 +7,10 @@ use crate::graphics::Color;
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct DrawUniforms {
     pub viewport_size: [u32; 2],
+    pub origin: [f32; 2],
+    pub scale: f32,
+    /// The radius of the target's corners, in target pixels.
+    pub corner_radius: f32,
 }
 
 #[repr(C, align(16))]
@@ -15,15 +19,20 @@ pub(crate) struct GpuPrimitive {
     pub point: [f32; 2],
     pub extent: [f32; 2],
     pub background: GpuPaint,
+    /// A gradient, or with `USE_SIDE_BORDER_COLORS` the colors of the left,
+    /// top, and right sides.
     pub border_color: GpuPaint,
+    /// The color of the bottom side with `USE_SIDE_BORDER_COLORS`.
+    pub border_bottom_color: [f32; 4],
     // left, top, right, bottom
     pub border_width: [f32; 4],
     // top-left, top-right, bottom-left, bottom-right
     pub corner_radii: [f32; 4],
     pub control_flags: PrimitiveRenderFlags,
     pub clip_idx: u32,
-    pub _padding1: u32,
-    pub _padding2: u32,
+    /// Clockwise rotation in radians about the primitive's center.
+    pub rotation: f32,
+    pub _padding: u32,
 }
 
 /// A union type representing either a sampled texture paint or a gradient paint.
@@ -55,6 +64,15 @@ impl GpuPaint {
             c: [p1[0], p1[1], p2[0], p2[1]],
         }
     }
+
+    /// Create the left, top, and right side colors of a border.
+    pub fn sides(left: Color, top: Color, right: Color) -> Self {
+        Self {
+            a: left.into(),
+            b: top.into(),
+            c: right.into(),
+        }
+    }
 }
 
 bitflags::bitflags! {
@@ -63,6 +81,7 @@ bitflags::bitflags! {
     pub struct PrimitiveRenderFlags: u32 {
         const USE_NEAREST_SAMPLING = 1;
         const USE_GRADIENT_PAINT = 2;
+        const USE_SIDE_BORDER_COLORS = 4;
     }
 }
 
@@ -0,0 +1,263 @@
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+/// Statistics about the work submitted to render a frame, for building
+/// performance overlays.
+///
+/// Counts include render targets drawn with
+/// [`GraphicsContext::render_to_texture`](super::GraphicsContext::render_to_texture)
+/// since the previous frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RenderStats {
+    /// The number of primitives drawn, including those outside of damaged
+    /// regions that were culled by the scissor test.
+    pub primitives: usize,
+    pub draw_calls: u32,
+    /// The number of times a pair of textures was bound for drawing.
+    pub texture_binds: u32,
+    /// Bytes of primitives, clips and uniforms written to GPU buffers.
+    pub buffer_upload_bytes: u64,
+    /// Time the GPU spent in render passes.
+    ///
+    /// This is measured with timestamp queries that are read back without
+    /// blocking, so it describes a frame or two earlier. `None` if the adapter
+    /// doesn't support timestamp queries or no measurement has completed yet.
+    pub gpu_time: Option<Duration>,
+}
+
+/// How often a cache had what was asked of it, for checking that caches are
+/// effective.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    /// Entries dropped to keep the cache within its size limit.
+    pub evictions: usize,
+}
+
+impl CacheStats {
+    /// The fraction of lookups that were hits, or `None` if there were none.
+    pub fn hit_rate(&self) -> Option<f32> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f32 / lookups as f32)
+    }
+
+    pub(crate) fn record(&mut self, is_hit: bool) {
+        if is_hit {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+    }
+}
+
+impl std::ops::AddAssign for CacheStats {
+    fn add_assign(&mut self, other: Self) {
+        self.hits += other.hits;
+        self.misses += other.misses;
+        self.evictions += other.evictions;
+    }
+}
+
+/// The most render passes that are timed per frame. Passes past the limit
+/// are left out of the measurement.
+const MAX_TIMED_PASSES: u32 = 16;
+
+/// Measures render passes on the GPU with timestamp queries.
+///
+/// Timestamps from every pass since the last [`Self::resolve`] are summed
+/// into one measurement. Only one measurement is read back at a time, and
+/// passes encoded while it is in flight aren't timed.
+pub(crate) struct GpuTimer {
+    device: wgpu::Device,
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    /// Nanoseconds per timestamp tick.
+    period: f32,
+
+    /// The number of queries written since the last resolve.
+    num_queries: u32,
+    /// The number of queries in the readback buffer, if it is being mapped.
+    in_flight: Option<u32>,
+    /// Set by the map callback, along with whether mapping succeeded.
+    is_done: Arc<AtomicBool>,
+    is_mapped: Arc<AtomicBool>,
+
+    last_time: Option<Duration>,
+}
+
+impl GpuTimer {
+    /// Returns `None` if the device wasn't created with timestamp queries.
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+
+        let count = MAX_TIMED_PASSES * 2;
+        let size = u64::from(count) * size_of::<u64>() as u64;
+
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Pass Timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count,
+        });
+
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Resolve"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Readback"),
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Some(Self {
+            device: device.clone(),
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            period: queue.get_timestamp_period(),
+            num_queries: 0,
+            in_flight: None,
+            is_done: Arc::new(AtomicBool::new(false)),
+            is_mapped: Arc::new(AtomicBool::new(false)),
+            last_time: None,
+        })
+    }
+
+    /// The most recent measurement.
+    pub fn last_time(&self) -> Option<Duration> {
+        self.last_time
+    }
+
+    /// Reads back the measurement in flight if the GPU has finished with it.
+    fn collect(&mut self) {
+        let Some(num_queries) = self.in_flight else {
+            return;
+        };
+
+        let _ = self.device.poll(wgpu::PollType::Poll);
+
+        if !self.is_done.swap(false, Ordering::Acquire) {
+            return;
+        }
+
+        self.in_flight = None;
+
+        if !self.is_mapped.load(Ordering::Acquire) {
+            return;
+        }
+
+        if let Ok(data) = self.readback_buffer.get_mapped_range(..) {
+            let timestamps: &[u64] = bytemuck::cast_slice(&data);
+            let ticks: u64 = timestamps[..num_queries as usize]
+                .chunks_exact(2)
+                .map(|pair| pair[1].saturating_sub(pair[0]))
+                .sum();
+
+            self.last_time = Some(Duration::from_nanos(
+                (ticks as f64 * f64::from(self.period)) as u64,
+            ));
+        }
+
+        self.readback_buffer.unmap();
+    }
+
+    /// Timestamp writes for the next render pass, or `None` if the pass
+    /// won't be measured because the previous results are still being read
+    /// or the frame has too many passes.
+    pub fn pass_writes(&mut self) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        self.collect();
+
+        if self.in_flight.is_some() || self.num_queries + 2 > MAX_TIMED_PASSES * 2 {
+            return None;
+        }
+
+        let start = self.num_queries;
+        self.num_queries += 2;
+
+        Some(wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(start),
+            end_of_pass_write_index: Some(start + 1),
+        })
+    }
+
+    /// Copies the timestamps written since the last resolve to the readback
+    /// buffer. Must be followed by [`Self::map_results`] once `encoder` has
+    /// been submitted.
+    pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        if self.num_queries == 0 || self.in_flight.is_some() {
+            return;
+        }
+
+        encoder.resolve_query_set(
+            &self.query_set,
+            0..self.num_queries,
+            &self.resolve_buffer,
+            0,
+        );
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            u64::from(self.num_queries) * size_of::<u64>() as u64,
+        );
+    }
+
+    pub fn map_results(&mut self) {
+        if self.num_queries == 0 || self.in_flight.is_some() {
+            return;
+        }
+
+        let is_done = self.is_done.clone();
+        let is_mapped = self.is_mapped.clone();
+        self.readback_buffer
+            .map_async(wgpu::MapMode::Read, .., move |result| {
+                is_mapped.store(result.is_ok(), Ordering::Release);
+                is_done.store(true, Ordering::Release);
+            });
+
+        self.in_flight = Some(std::mem::take(&mut self.num_queries));
+    }
+}
+
+/// Accumulates statistics for the frame being rendered.
+#[derive(Default)]
+pub(crate) struct FrameProfiler {
+    pub stats: RenderStats,
+    pub timer: Option<GpuTimer>,
+}
+
+impl FrameProfiler {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        Self {
+            stats: RenderStats::default(),
+            timer: GpuTimer::new(device, queue),
+        }
+    }
+
+    /// Timestamp writes for the next render pass, if it can be timed.
+    pub fn pass_writes(&mut self) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        self.timer.as_mut()?.pass_writes()
+    }
+
+    /// Returns the frame's statistics and starts counting the next frame's.
+    pub fn take_stats(&mut self) -> RenderStats {
+        let gpu_time = self.timer.as_ref().and_then(GpuTimer::last_time);
+
+        RenderStats {
+            gpu_time,
+            ..std::mem::take(&mut self.stats)
+        }
+    }
+}
@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use tracing::instrument;
 use tracing::trace;
+use tracing::warn;
 use winit::window::Window;
 use winit::window::WindowId;
 
@@ -11,12 +12,90 @@ use crate::graphics::pipeline::RenderPipeline;
 use crate::graphics::pipeline::RenderPipelineCache;
 use crate::graphics::texture::StorageId;
 
-type BindGroupCache = HashMap<(StorageId, StorageId), wgpu::BindGroup>;
+pub(crate) type BindGroupCache = HashMap<(StorageId, StorageId), wgpu::BindGroup>;
+
+/// How a window's frames are synchronized with the display.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PresentMode {
+    /// Waits for vertical blank to avoid tearing, preferring the lowest
+    /// latency mode the platform supports.
+    #[default]
+    AutoVsync,
+    /// Presents as soon as possible, tearing if necessary.
+    AutoNoVsync,
+    /// Queues frames and presents one per vertical blank. Supported
+    /// everywhere.
+    Fifo,
+    /// Like `Fifo`, but a late frame is presented immediately and may tear.
+    FifoRelaxed,
+    /// Presents the newest frame on vertical blank, dropping older ones.
+    Mailbox,
+    /// Presents immediately without waiting for vertical blank.
+    Immediate,
+}
+
+/// Controls the trade-off between latency and tearing for a window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PresentSettings {
+    pub mode: PresentMode,
+    /// The number of frames that may be queued for presentation. Lower values
+    /// reduce input latency, higher values smooth out uneven frame times.
+    pub max_frame_latency: u32,
+    /// Blends the window with whatever is behind it wherever its frames are
+    /// not opaque, if the platform supports it.
+    pub transparent: bool,
+}
+
+impl Default for PresentSettings {
+    fn default() -> Self {
+        Self {
+            mode: PresentMode::AutoVsync,
+            max_frame_latency: 1,
+            transparent: false,
+        }
+    }
+}
+
+impl PresentMode {
+    /// Picks the supported mode closest to this one. `Fifo` is always
+    /// supported, so it is the final fallback.
+    fn select(self, supported: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+        use wgpu::PresentMode::*;
+
+        let preferences: &[wgpu::PresentMode] = match self {
+            PresentMode::AutoVsync => &[Mailbox, FifoRelaxed, Fifo],
+            PresentMode::AutoNoVsync => &[Immediate, Mailbox, Fifo],
+            PresentMode::Fifo => &[Fifo],
+            PresentMode::FifoRelaxed => &[FifoRelaxed, Fifo],
+            PresentMode::Mailbox => &[Mailbox, Fifo],
+            PresentMode::Immediate => &[Immediate, Fifo],
+        };
+
+        let mode = preferences
+            .iter()
+            .copied()
+            .find(|mode| supported.contains(mode))
+            .unwrap_or(Fifo);
+
+        if !matches!(self, PresentMode::AutoVsync | PresentMode::AutoNoVsync)
+            && mode != preferences[0]
+        {
+            warn!(requested = ?self, selected = ?mode, "Present mode not supported");
+        }
+
+        mode
+    }
+}
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RenderError {
     Occluded,
     TimedOut,
+    /// A window's surface was invalidated. It is recreated automatically.
+    SurfaceLost,
+    /// The device was lost. See
+    /// [`GraphicsContext::recover_lost_device`](super::GraphicsContext::recover_lost_device).
+    DeviceLost,
     Unknown,
 }
 
@@ -24,6 +103,9 @@ pub(crate) struct Surface {
     window: Arc<dyn Window>,
     config: wgpu::SurfaceConfiguration,
     handle: wgpu::Surface<'static>,
+    present: PresentSettings,
+    supported_present_modes: Vec<wgpu::PresentMode>,
+    supported_alpha_modes: Vec<wgpu::CompositeAlphaMode>,
 
     frame_counter: u64,
     render_pipeline: RenderPipeline,
@@ -31,6 +113,35 @@ pub(crate) struct Surface {
 
     bind_groups: BindGroupCache,
     cached_storage_version: u64,
+
+    /// A copy of the last frame that damaged regions are redrawn into before
+    /// it is copied to the swap chain. Swap chain images hold frames from an
+    /// unknown number of presents ago, so they can't be drawn into directly.
+    /// `None` if the surface can't be copied into.
+    retained: Option<RetainedFrame>,
+    supports_retained: bool,
+}
+
+struct RetainedFrame {
+    texture: wgpu::Texture,
+    /// The texture content version the frame was drawn with.
+    content_version: u64,
+    /// The canvas frame that was last drawn into the texture.
+    canvas_frame: u64,
+}
+
+/// A swap chain image and the state needed to draw into it.
+pub(crate) struct SurfaceFrame<'a> {
+    pub output: wgpu::SurfaceTexture,
+    /// The texture to draw into before copying it to `output`, if the
+    /// surface supports partial redraws.
+    pub retained: Option<wgpu::Texture>,
+    /// Set when the retained texture's contents are stale and it must be
+    /// redrawn in full.
+    pub needs_full_redraw: bool,
+    pub frame: &'a mut Frame,
+    pub render_pipeline: &'a RenderPipeline,
+    pub bind_groups: &'a mut BindGroupCache,
 }
 
 impl Surface {
@@ -41,6 +152,7 @@ impl Surface {
         device: &wgpu::Device,
         adapter: &wgpu::Adapter,
         pipeline_cache: &RenderPipelineCache,
+        present: PresentSettings,
     ) -> Self {
         let caps = surface.get_capabilities(adapter);
 
@@ -50,35 +162,24 @@ impl Surface {
             .copied()
             .expect("Surface incompatible with selected adapter!");
 
-        let present_mode = {
-            let mut mailbox = None;
-            let mut relaxed = None;
-            let mut fifo = None;
-
-            for mode in caps.present_modes.iter().copied() {
-                match mode {
-                    wgpu::PresentMode::Mailbox => mailbox = Some(mode),
-                    wgpu::PresentMode::FifoRelaxed => relaxed = Some(mode),
-                    wgpu::PresentMode::Fifo => fifo = Some(mode),
-                    _ => {}
-                }
-            }
+        let present_mode = present.mode.select(&caps.present_modes);
 
-            mailbox
-                .or(relaxed)
-                .or(fifo)
-                .unwrap_or(caps.present_modes[0])
-        };
+        let supports_retained = caps.usages.contains(wgpu::TextureUsages::COPY_DST);
+
+        let mut usage = wgpu::TextureUsages::RENDER_ATTACHMENT;
+        if supports_retained {
+            usage |= wgpu::TextureUsages::COPY_DST;
+        }
 
         let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            usage,
             format,
             color_space: wgpu::SurfaceColorSpace::Auto,
             width: window.surface_size().width,
             height: window.surface_size().height,
             present_mode,
-            desired_maximum_frame_latency: 1,
-            alpha_mode: caps.alpha_modes[0],
+            desired_maximum_frame_latency: present.max_frame_latency,
+            alpha_mode: select_alpha_mode(present.transparent, &caps.alpha_modes),
             view_formats: vec![],
         };
 
@@ -92,18 +193,44 @@ impl Surface {
             window,
             config,
             handle: surface,
+            present,
+            supported_present_modes: caps.present_modes,
+            supported_alpha_modes: caps.alpha_modes,
             frame_counter: 0,
             render_pipeline,
             frame,
             bind_groups: HashMap::new(),
             cached_storage_version: 0,
+            retained: None,
+            supports_retained,
         }
     }
 
+    pub fn window(&self) -> &Arc<dyn Window> {
+        &self.window
+    }
+
     pub fn window_id(&self) -> WindowId {
         self.window.id()
     }
 
+    pub fn present_settings(&self) -> PresentSettings {
+        self.present
+    }
+
+    pub fn set_present_settings(&mut self, device: &wgpu::Device, present: PresentSettings) {
+        if present == self.present {
+            return;
+        }
+
+        self.present = present;
+        self.config.present_mode = present.mode.select(&self.supported_present_modes);
+        self.config.desired_maximum_frame_latency = present.max_frame_latency;
+        self.config.alpha_mode =
+            select_alpha_mode(present.transparent, &self.supported_alpha_modes);
+        self.handle.configure(device, &self.config);
+    }
+
     #[instrument(skip(self, device))]
     pub fn resize_if_necessary(&mut self, device: &wgpu::Device) {
         let new_size = self.window.surface_size();
@@ -133,15 +260,9 @@ impl Surface {
         &mut self,
         device: &wgpu::Device,
         storage_version: u64,
-    ) -> Result<
-        (
-            wgpu::SurfaceTexture,
-            &mut Frame,
-            &RenderPipeline,
-            &mut BindGroupCache,
-        ),
-        RenderError,
-    > {
+        content_version: u64,
+        canvas_frame: u64,
+    ) -> Result<SurfaceFrame<'_>, RenderError> {
         let output = tracing::info_span!("get_current_texture").in_scope(|| {
             let mut attempts = 0;
 
@@ -163,9 +284,7 @@ impl Surface {
                     }
                     wgpu::CurrentSurfaceTexture::Timeout => break Err(RenderError::TimedOut),
                     wgpu::CurrentSurfaceTexture::Occluded => break Err(RenderError::Occluded),
-                    wgpu::CurrentSurfaceTexture::Lost => {
-                        unimplemented!("Surface lost handling not implemented yet")
-                    }
+                    wgpu::CurrentSurfaceTexture::Lost => break Err(RenderError::SurfaceLost),
                     wgpu::CurrentSurfaceTexture::Validation => break Err(RenderError::Unknown),
                 }
 
@@ -173,30 +292,104 @@ impl Surface {
             }
         })?;
 
+        let mut needs_full_redraw = storage_version != self.cached_storage_version;
+
         if storage_version != self.cached_storage_version {
             self.bind_groups.clear();
             self.cached_storage_version = storage_version;
         }
 
+        let retained = if self.supports_retained {
+            let size = output.texture.size();
+
+            if self
+                .retained
+                .as_ref()
+                .is_none_or(|retained| retained.texture.size() != size)
+            {
+                needs_full_redraw = true;
+                self.retained = Some(RetainedFrame {
+                    texture: create_retained_texture(device, self.config.format, size),
+                    content_version,
+                    canvas_frame,
+                });
+            }
+
+            let retained = self.retained.as_mut().unwrap();
+
+            // Damage is relative to the canvas's previous frame, which may
+            // have been skipped (e.g. while the window was occluded).
+            if retained.content_version != content_version
+                || retained.canvas_frame + 1 != canvas_frame
+            {
+                needs_full_redraw = true;
+                retained.content_version = content_version;
+            }
+
+            retained.canvas_frame = canvas_frame;
+
+            Some(retained.texture.clone())
+        } else {
+            None
+        };
+
         self.frame_counter += 1;
 
-        Ok((
+        Ok(SurfaceFrame {
             output,
-            &mut self.frame,
-            &self.render_pipeline,
-            &mut self.bind_groups,
-        ))
+            retained,
+            needs_full_redraw,
+            frame: &mut self.frame,
+            render_pipeline: &self.render_pipeline,
+            bind_groups: &mut self.bind_groups,
+        })
     }
 }
 
+fn create_retained_texture(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    size: wgpu::Extent3d,
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Retained Frame"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
 pub struct Frame {
     pub draw_buffer: DrawBuffer,
 }
 
 impl Frame {
-    fn new(render_pipeline: &RenderPipeline) -> Self {
+    pub fn new(render_pipeline: &RenderPipeline) -> Self {
         Self {
             draw_buffer: render_pipeline.create_draw_buffer(),
         }
     }
 }
+
+/// Picks a mode that blends the window with what's behind it if `transparent`
+/// is set, or the platform's preferred mode otherwise.
+fn select_alpha_mode(
+    transparent: bool,
+    supported: &[wgpu::CompositeAlphaMode],
+) -> wgpu::CompositeAlphaMode {
+    use wgpu::CompositeAlphaMode::*;
+
+    let preferred = transparent
+        .then(|| {
+            [PreMultiplied, PostMultiplied, Inherit]
+                .into_iter()
+                .find(|mode| supported.contains(mode))
+        })
+        .flatten();
+
+    preferred.unwrap_or(supported[0])
+}
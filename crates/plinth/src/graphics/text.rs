@@ -1,7 +1,9 @@
 use std::borrow::Cow;
+use std::path::Path;
 
 use parley::FontContext;
 use parley::LayoutContext;
+use smallvec::SmallVec;
 
 use crate::graphics::Color;
 
@@ -12,6 +14,12 @@ pub struct TextLayoutContext {
 }
 
 impl TextLayoutContext {
+    pub fn fonts(&mut self) -> Fonts<'_> {
+        Fonts {
+            context: &mut self.fonts,
+        }
+    }
+
     pub(crate) fn drive<'a>(
         &'a mut self,
         editor: &'a mut parley::PlainEditor<Color>,
@@ -20,7 +28,68 @@ impl TextLayoutContext {
     }
 }
 
+/// Registers fonts in addition to the ones installed on the system.
+pub struct Fonts<'a> {
+    context: &'a mut FontContext,
+}
+
+impl Fonts<'_> {
+    /// Registers the fonts in a font file or collection, returning the names
+    /// of their families.
+    pub fn load_bytes(&mut self, data: impl Into<Vec<u8>>) -> Result<Vec<String>, FontLoadError> {
+        let registered = self
+            .context
+            .collection
+            .register_fonts(data.into().into(), None);
+
+        if registered.is_empty() {
+            return Err(FontLoadError::NoFonts);
+        }
+
+        Ok(registered
+            .into_iter()
+            .filter_map(|(id, _)| self.context.collection.family_name(id).map(str::to_string))
+            .collect())
+    }
+
+    /// Like [`load_bytes`](Self::load_bytes), reading the fonts from a file.
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> Result<Vec<String>, FontLoadError> {
+        self.load_bytes(std::fs::read(path)?)
+    }
+
+    /// Makes a generic family such as [`FontFamily::SansSerif`] resolve to
+    /// the named `families`, in order of preference. Families that aren't
+    /// registered are skipped, and named families can't be aliased.
+    pub fn alias(&mut self, generic: FontFamily, families: &[&str]) {
+        let parley::FontFamilyName::Generic(generic) = generic.into() else {
+            return;
+        };
+
+        let collection = &mut self.context.collection;
+        let ids = families
+            .iter()
+            .filter_map(|name| collection.family_id(name))
+            .collect::<SmallVec<[_; 4]>>();
+
+        collection.set_generic_families(generic, ids.into_iter());
+    }
+}
+
+#[derive(Debug)]
+pub enum FontLoadError {
+    Io(std::io::Error),
+    /// The data isn't a font format that can be read.
+    NoFonts,
+}
+
+impl From<std::io::Error> for FontLoadError {
+    fn from(err: std::io::Error) -> Self {
+        FontLoadError::Io(err)
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextAlignment {
     Start,
     Center,
@@ -40,6 +109,7 @@ impl From<TextAlignment> for parley::Alignment {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FontStyle {
     Normal,
     Italic,
@@ -55,6 +125,7 @@ impl From<FontStyle> for parley::FontStyle {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Font {
     pub family: FontStack,
 }
@@ -68,13 +139,87 @@ impl Default for Font {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FontStack {
     Source(Cow<'static, str>),
     Single(FontFamily),
     List(Cow<'static, [FontFamily]>),
 }
 
+impl FontStack {
+    /// Passes the stack to `callback` as a parley family, followed by the
+    /// `fallbacks` for characters that its fonts don't cover.
+    pub(crate) fn with_parley_family(
+        &self,
+        fallbacks: &[FontFamily],
+        callback: impl FnOnce(parley::FontFamily<'_>),
+    ) {
+        match self {
+            FontStack::Source(_) => callback(self.to_parley_family(fallbacks)),
+            FontStack::Single(font_family) if fallbacks.is_empty() => {
+                callback(parley::FontFamily::Single(font_family.clone().into()));
+            }
+            FontStack::Single(font_family) => {
+                let families = std::iter::once(font_family)
+                    .chain(fallbacks)
+                    .cloned()
+                    .map(|f| f.into())
+                    .collect::<SmallVec<[parley::FontFamilyName; 4]>>();
+                callback(families.as_slice().into());
+            }
+            FontStack::List(cow) => {
+                let families = cow
+                    .iter()
+                    .chain(fallbacks)
+                    .cloned()
+                    .map(|f| f.into())
+                    .collect::<SmallVec<[parley::FontFamilyName; 4]>>();
+                callback(families.as_slice().into());
+            }
+        }
+    }
+
+    /// Like [`with_parley_family`](Self::with_parley_family), for styles
+    /// that are kept after the call.
+    pub(crate) fn to_parley_family(&self, fallbacks: &[FontFamily]) -> parley::FontFamily<'static> {
+        match self {
+            FontStack::Source(cow) if fallbacks.is_empty() => {
+                parley::FontFamily::Source(cow.clone())
+            }
+            FontStack::Source(cow) => {
+                let mut source = cow.to_string();
+                for family in fallbacks {
+                    source.push_str(", ");
+                    family.write_css(&mut source);
+                }
+                parley::FontFamily::Source(source.into())
+            }
+            FontStack::Single(font_family) if fallbacks.is_empty() => {
+                parley::FontFamily::Single(font_family.clone().into())
+            }
+            FontStack::Single(font_family) => {
+                let families = std::iter::once(font_family)
+                    .chain(fallbacks)
+                    .cloned()
+                    .map(|f| f.into())
+                    .collect::<Vec<_>>();
+                parley::FontFamily::List(families.into())
+            }
+            FontStack::List(cow) => {
+                let families = cow
+                    .iter()
+                    .chain(fallbacks)
+                    .cloned()
+                    .map(|f| f.into())
+                    .collect::<Vec<_>>();
+                parley::FontFamily::List(families.into())
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FontFamily {
     Named(Cow<'static, str>),
     Cursive,
@@ -92,6 +237,34 @@ pub enum FontFamily {
     UiSerif,
 }
 
+impl FontFamily {
+    /// Appends the family as it would appear in a CSS `font-family` list.
+    fn write_css(&self, css: &mut String) {
+        let generic = match self {
+            FontFamily::Named(name) => {
+                css.push('"');
+                css.push_str(name);
+                css.push('"');
+                return;
+            }
+            FontFamily::Cursive => "cursive",
+            FontFamily::Emoji => "emoji",
+            FontFamily::FangSong => "fangsong",
+            FontFamily::Fantasy => "fantasy",
+            FontFamily::Math => "math",
+            FontFamily::Monospace => "monospace",
+            FontFamily::SansSerif => "sans-serif",
+            FontFamily::Serif => "serif",
+            FontFamily::SystemUi => "system-ui",
+            FontFamily::UiMonospace => "ui-monospace",
+            FontFamily::UiRounded => "ui-rounded",
+            FontFamily::UiSansSerif => "ui-sans-serif",
+            FontFamily::UiSerif => "ui-serif",
+        };
+        css.push_str(generic);
+    }
+}
+
 impl From<FontFamily> for parley::FontFamilyName<'static> {
     fn from(value: FontFamily) -> Self {
         match value {
@@ -113,7 +286,7 @@ impl From<FontFamily> for parley::FontFamilyName<'static> {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct FontWeight(pub(crate) f32);
 
 impl FontWeight {
@@ -135,3 +308,72 @@ impl From<FontWeight> for parley::FontWeight {
         parley::FontWeight::new(value.0)
     }
 }
+
+/// Overrides of the widget's text style for one span of rich text. Properties
+/// that aren't set are taken from the widget's style.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpanStyle {
+    pub font: Option<Font>,
+    pub weight: Option<FontWeight>,
+    /// The font size in points.
+    pub size: Option<f32>,
+    pub color: Option<Color>,
+    pub underline: Option<bool>,
+}
+
+/// A run of text in a paragraph that mixes styles.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextSpan<'a> {
+    pub text: &'a str,
+    pub style: SpanStyle,
+    /// Whether the span can be clicked like a hyperlink, in widgets that
+    /// support it.
+    pub is_link: bool,
+}
+
+impl<'a> TextSpan<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            style: SpanStyle::default(),
+            is_link: false,
+        }
+    }
+
+    /// Makes the span clickable like a hyperlink.
+    pub fn link(mut self) -> Self {
+        self.is_link = true;
+        self
+    }
+
+    pub fn font(mut self, font: Font) -> Self {
+        self.style.font = Some(font);
+        self
+    }
+
+    pub fn weight(mut self, weight: FontWeight) -> Self {
+        self.style.weight = Some(weight);
+        self
+    }
+
+    pub fn size(mut self, size: f32) -> Self {
+        self.style.size = Some(size);
+        self
+    }
+
+    pub fn color(mut self, color: impl Into<Color>) -> Self {
+        self.style.color = Some(color.into());
+        self
+    }
+
+    pub fn underline(mut self, underline: bool) -> Self {
+        self.style.underline = Some(underline);
+        self
+    }
+}
+
+impl<'a> From<&'a str> for TextSpan<'a> {
+    fn from(text: &'a str) -> Self {
+        Self::new(text)
+    }
+}
@@ -1,9 +1,14 @@
 use std::cell::Cell;
 use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::fs::File;
 use std::io::Cursor;
 use std::path::Path;
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::rc::Weak;
+use std::sync::Arc;
 use std::sync::mpsc;
 
 use guillotiere::AllocId;
@@ -23,6 +28,11 @@ use tracing::instrument;
 use tracing::trace;
 use tracing::warn;
 
+use super::ImageFetcher;
+use super::ImageHandle;
+use super::ImageSource;
+use super::image_source::ImageState;
+
 new_key_type! {
     pub struct TextureId;
 
@@ -68,6 +78,10 @@ impl Default for StorageId {
 pub enum TextureLoadError {
     Decoding(Box<dyn std::error::Error>),
     Io(std::io::Error),
+    /// An image was requested by URL before an [`ImageFetcher`] was set.
+    NoFetcher,
+    /// The [`ImageFetcher`] failed to fetch the image.
+    Fetch(Box<dyn std::error::Error + Send + Sync>),
 }
 
 impl From<std::io::Error> for TextureLoadError {
@@ -129,6 +143,70 @@ impl Texture {
             .inspect(self.id, |usage| usage.is_ready)
             .unwrap()
     }
+
+    /// Where the texture is opaque, for images decoded from files once they
+    /// are ready.
+    #[must_use]
+    pub fn alpha_mask(&self) -> Option<Arc<AlphaMask>> {
+        self.manager
+            .inspect(self.id, |usage| usage.alpha_mask.clone())
+            .unwrap()
+    }
+}
+
+/// A low resolution record of where an image is opaque, so that hit tests
+/// can skip its transparent parts without reading the texture back.
+#[derive(Debug)]
+pub struct AlphaMask {
+    width: u16,
+    height: u16,
+    cells: Vec<bool>,
+}
+
+impl AlphaMask {
+    /// The most cells along each side of a mask. Larger images share a cell
+    /// between several pixels.
+    const MAX_CELLS: u32 = 128;
+
+    /// Builds a mask from RGBA8 pixels. A cell is opaque if any of its
+    /// pixels is, so that thin features stay hittable.
+    fn from_rgba(pixels: &[u8], width: u16, height: u16) -> Self {
+        let cell_width = u32::from(width).div_ceil(Self::MAX_CELLS).max(1);
+        let cell_height = u32::from(height).div_ceil(Self::MAX_CELLS).max(1);
+        let mask_width = u32::from(width).div_ceil(cell_width);
+        let mask_height = u32::from(height).div_ceil(cell_height);
+
+        let mut cells = vec![false; (mask_width * mask_height) as usize];
+        let row_bytes = usize::from(width) * 4;
+
+        for (y, row) in pixels.chunks_exact(row_bytes).enumerate() {
+            let cell_row = y as u32 / cell_height * mask_width;
+            for (x, pixel) in row.chunks_exact(4).enumerate() {
+                if pixel[3] != 0 {
+                    cells[(cell_row + x as u32 / cell_width) as usize] = true;
+                }
+            }
+        }
+
+        Self {
+            width: mask_width as u16,
+            height: mask_height as u16,
+            cells,
+        }
+    }
+
+    /// Whether the image is opaque at a point given as a fraction of its
+    /// width and height. Points outside the image are transparent.
+    #[must_use]
+    pub fn is_opaque_at(&self, u: f32, v: f32) -> bool {
+        if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+            return false;
+        }
+
+        let x = (u * f32::from(self.width)) as usize;
+        let y = (v * f32::from(self.height)) as usize;
+        self.cells[y * usize::from(self.width) + x]
+    }
 }
 
 impl Clone for Texture {
@@ -204,23 +282,112 @@ impl TextureManager {
         self.inner.storage_version.get()
     }
 
+    /// A counter that increases whenever texture contents may have changed,
+    /// so that retained frames know to redraw.
+    pub(crate) fn content_version(&self) -> u64 {
+        self.inner.content_version.get()
+    }
+
+    /// Records that a render target was drawn into.
+    pub(crate) fn mark_rendered(&self, target: &Texture) {
+        self.inner.mark_ready(target.id);
+    }
+
     #[instrument(skip(self, data))]
     pub fn load_from_memory(&self, data: &[u8], width: u16, format: TextureFormat) -> Texture {
         self.inner.from_memory(data, width, format)
     }
 
+    /// Loads an image file, decoding it in the background.
+    ///
+    /// Loading a file that is already loaded returns the existing texture
+    /// rather than decoding it again. The file is only read again once every
+    /// handle to its texture has been dropped.
     #[instrument(skip(self), fields(path = %path.as_ref().display()))]
     pub fn load(&self, path: impl AsRef<Path>) -> Result<Texture, TextureLoadError> {
         self.inner.load(path)
     }
 
+    /// Loads an image from a file, from memory, or from a URL.
+    ///
+    /// URLs are fetched in the background, and the handle reports the
+    /// image as loading until its contents have arrived. Images from the same
+    /// URL or file share a texture while any handle to them is alive.
+    #[instrument(skip_all)]
+    pub fn load_source(&self, source: &ImageSource) -> ImageHandle {
+        match source {
+            ImageSource::Path(path) => ImageHandle::from_result(self.inner.load(path)),
+            ImageSource::Bytes(bytes) => {
+                ImageHandle::from_result(self.inner.load_encoded(bytes.clone()))
+            }
+            ImageSource::Url(url) => self.inner.fetch(url),
+        }
+    }
+
+    /// Sets the fetcher used to load images from URLs.
+    pub fn set_fetcher(&self, fetcher: Arc<dyn ImageFetcher>) {
+        self.inner.fetcher.replace(Some(fetcher));
+    }
+
+    /// Sets the function that is called from another thread whenever an
+    /// image has been fetched, so that the event loop can wake up to decode it.
+    pub(crate) fn set_fetch_waker(&self, waker: Arc<dyn Fn() + Send + Sync>) {
+        self.inner.fetch_waker.replace(Some(waker));
+    }
+
+    /// Creates a texture that can be both rendered into and sampled. Render
+    /// targets get a storage of their own so that rendering can't overwrite
+    /// other textures.
+    #[instrument(skip(self))]
+    pub fn create_render_target(&self, width: u16, height: u16) -> Texture {
+        self.inner.render_target(width, height)
+    }
+
     pub fn flush(&self) {
         self.inner.flush();
     }
 
+    /// Limits the bytes of decoded images uploaded per frame. Uploads past
+    /// the limit are deferred to later frames, though at least one is made
+    /// every frame. Textures loaded from memory are always uploaded
+    /// immediately, but count towards the limit.
+    pub(crate) fn set_upload_budget(&self, max_bytes: Option<usize>) {
+        self.inner.upload_budget.set(max_bytes);
+    }
+
+    /// The number of bytes written to textures since the last call.
+    pub(crate) fn take_uploaded_bytes(&self) -> usize {
+        self.inner.uploaded_bytes.take()
+    }
+
+    /// The number of decoded images waiting for upload.
+    pub(crate) fn num_deferred_uploads(&self) -> usize {
+        self.inner.deferred_uploads.borrow().len()
+    }
+
     pub fn end_frame(&self) {
         self.inner.end_frame();
     }
+
+    /// Moves all texture storage to a new device.
+    ///
+    /// Every texture keeps its place in its atlas, so existing [`Texture`]
+    /// handles and glyph cache entries remain valid. This blocks until
+    /// pending loads have finished and the old atlases have been read back.
+    pub(crate) fn migrate(&self, device: wgpu::Device, queue: wgpu::Queue) {
+        self.inner.migrate(device, queue);
+    }
+
+    /// Recreates all texture storage on a new device after the old one was
+    /// lost, when its contents can no longer be read back.
+    ///
+    /// Textures are restored from their sources: small textures such as
+    /// glyphs from a copy kept in memory, and images by loading their files
+    /// again. Render targets can't be restored and are not ready until they
+    /// have been drawn into again. Existing [`Texture`] handles remain valid.
+    pub(crate) fn recreate(&self, device: wgpu::Device, queue: wgpu::Queue) {
+        self.inner.recreate(device, queue);
+    }
 }
 
 struct TextureManagerInner {
@@ -228,17 +395,54 @@ struct TextureManagerInner {
     opaque_pixel: Cell<TextureId>,
 
     texture_map: RefCell<SlotMap<TextureId, TextureUsage>>,
+    /// Textures loaded from files, so that loading a file again returns the
+    /// same texture while it is still in use.
+    file_cache: RefCell<HashMap<PathBuf, TextureId>>,
+    /// Images requested by URL, so that each is only fetched once while any
+    /// handle to it is alive.
+    url_cache: RefCell<HashMap<String, Weak<RefCell<ImageState>>>>,
+    fetcher: RefCell<Option<Arc<dyn ImageFetcher>>>,
+    fetch_waker: RefCell<Option<Arc<dyn Fn() + Send + Sync>>>,
+    fetch_sender: mpsc::Sender<FetchedImage>,
+    fetch_receiver: mpsc::Receiver<FetchedImage>,
     rgba_textures: RefCell<FormattedTextureManager>,
     srgba_textures: RefCell<FormattedTextureManager>,
     alpha_textures: RefCell<FormattedTextureManager>,
 
     storage_version: Cell<u64>,
+    content_version: Cell<u64>,
 
-    queue: wgpu::Queue,
-    device: wgpu::Device,
+    queue: RefCell<wgpu::Queue>,
+    device: RefCell<wgpu::Device>,
 
     ready_sender: mpsc::Sender<TextureId>,
     ready_receiver: mpsc::Receiver<TextureId>,
+
+    /// Images decoded by loader threads, uploaded on the main thread so that
+    /// the bytes uploaded per frame can be limited.
+    upload_sender: mpsc::Sender<PendingUpload>,
+    upload_receiver: mpsc::Receiver<PendingUpload>,
+    deferred_uploads: RefCell<VecDeque<PendingUpload>>,
+    upload_budget: Cell<Option<usize>>,
+    uploaded_bytes: Cell<usize>,
+}
+
+/// Pixel data waiting to be written into a texture's atlas.
+///
+/// Uploads refer to textures by id rather than by their atlas, so that images
+/// decoded while the device is replaced are written to the new device.
+struct PendingUpload {
+    texture_id: TextureId,
+    data: Vec<u8>,
+    alpha_mask: Option<AlphaMask>,
+    bytes_per_row: u32,
+    size: wgpu::Extent3d,
+}
+
+/// The contents of an image fetched from a URL, before decoding.
+struct FetchedImage {
+    url: String,
+    result: Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>,
 }
 
 impl TextureManagerInner {
@@ -246,32 +450,49 @@ impl TextureManagerInner {
         let rgba_textures = FormattedTextureManager {
             format: TextureFormat::Rgba8Unorm,
             storage: SlotMap::with_key(),
+            page_size: MIN_ATLAS_PAGE_SIZE,
         };
 
         let srgba_textures = FormattedTextureManager {
             format: TextureFormat::Rgba8UnormSrgb,
             storage: SlotMap::with_key(),
+            page_size: MIN_ATLAS_PAGE_SIZE,
         };
 
         let alpha_textures = FormattedTextureManager {
             format: TextureFormat::R8Unorm,
             storage: SlotMap::with_key(),
+            page_size: MIN_ATLAS_PAGE_SIZE,
         };
 
         let (ready_sender, ready_receiver) = mpsc::channel();
+        let (upload_sender, upload_receiver) = mpsc::channel();
+        let (fetch_sender, fetch_receiver) = mpsc::channel();
 
         let this = Rc::new(TextureManagerInner {
             white_pixel: Cell::new(TextureId::default()),
             opaque_pixel: Cell::new(TextureId::default()),
             texture_map: RefCell::new(SlotMap::with_key()),
+            file_cache: RefCell::new(HashMap::new()),
+            url_cache: RefCell::new(HashMap::new()),
+            fetcher: RefCell::new(None),
+            fetch_waker: RefCell::new(None),
+            fetch_sender,
+            fetch_receiver,
             rgba_textures: RefCell::new(rgba_textures),
             srgba_textures: RefCell::new(srgba_textures),
             alpha_textures: RefCell::new(alpha_textures),
             storage_version: Cell::new(0),
-            queue,
-            device,
+            content_version: Cell::new(0),
+            queue: RefCell::new(queue),
+            device: RefCell::new(device),
             ready_sender,
             ready_receiver,
+            upload_sender,
+            upload_receiver,
+            deferred_uploads: RefCell::new(VecDeque::new()),
+            upload_budget: Cell::new(None),
+            uploaded_bytes: Cell::new(0),
         });
 
         // Set up the white pixel and forget it so that its refcount is never 0.
@@ -289,6 +510,10 @@ impl TextureManagerInner {
         this
     }
 
+    fn mark_content_changed(&self) {
+        self.content_version.set(self.content_version.get() + 1);
+    }
+
     fn white_pixel(self: &Rc<Self>) -> Texture {
         self.get(self.white_pixel.get()).unwrap()
     }
@@ -342,6 +567,10 @@ impl TextureManagerInner {
 
                 let usage = texture_map.remove(id).unwrap();
 
+                if let TextureSource::File(path) = &usage.source {
+                    self.file_cache.borrow_mut().remove(path);
+                }
+
                 let storage = match usage.format {
                     TextureFormat::Rgba8Unorm => &self.rgba_textures,
                     TextureFormat::Rgba8UnormSrgb => &self.srgba_textures,
@@ -364,6 +593,10 @@ impl TextureManagerInner {
             if usage.refcount == 0 {
                 let usage = texture_map.remove(id).unwrap();
 
+                if let TextureSource::File(path) = &usage.source {
+                    self.file_cache.get_mut().remove(path);
+                }
+
                 let storage = match usage.format {
                     TextureFormat::Rgba8Unorm => &self.rgba_textures,
                     TextureFormat::Rgba8UnormSrgb => &self.srgba_textures,
@@ -408,8 +641,13 @@ impl TextureManagerInner {
         }
         .borrow_mut();
 
-        let (texture, usage, rectangle) =
-            manager.allocate(width, height, &self.device, &self.storage_version);
+        let (texture, usage, rectangle) = manager.allocate(
+            width,
+            height,
+            TextureSource::Memory(Rc::from(data)),
+            &self.device.borrow(),
+            &self.storage_version,
+        );
 
         let uvwh = usage.uvwh;
         let storage_id = usage.storage;
@@ -426,7 +664,7 @@ impl TextureManagerInner {
             "Loaded texture from memory"
         );
 
-        self.queue.write_texture(
+        self.queue.borrow().write_texture(
             wgpu::TexelCopyTextureInfo {
                 texture: &texture,
                 mip_level: 0,
@@ -450,6 +688,8 @@ impl TextureManagerInner {
             },
         );
 
+        self.uploaded_bytes
+            .set(self.uploaded_bytes.get() + data.len());
         self.ready_sender.send(texture_id).unwrap();
 
         Texture {
@@ -462,23 +702,91 @@ impl TextureManagerInner {
         }
     }
 
-    fn load(self: &Rc<Self>, path: impl AsRef<Path>) -> Result<Texture, TextureLoadError> {
-        let start_time = std::time::Instant::now();
+    fn render_target(self: &Rc<Self>, width: u16, height: u16) -> Texture {
+        let format = TextureFormat::Rgba8UnormSrgb;
+
+        let mut usage = self.srgba_textures.borrow_mut().allocate_render_target(
+            width,
+            height,
+            &self.device.borrow(),
+            &self.storage_version,
+        );
+
+        // There is nothing to upload, so the texture can be drawn right away.
+        usage.is_ready = true;
+        self.mark_content_changed();
 
+        let uvwh = usage.uvwh;
+        let storage_id = usage.storage;
+        let texture_id = self.texture_map.borrow_mut().insert(usage);
+
+        Texture {
+            id: texture_id,
+            storage_id,
+            format,
+            uvwh,
+            size: [width, height],
+            manager: self.clone(),
+        }
+    }
+
+    fn load(self: &Rc<Self>, path: impl AsRef<Path>) -> Result<Texture, TextureLoadError> {
+        // Different spellings of the same path share a texture. Paths that
+        // can't be resolved can't be opened either, and fail below.
         let path = path.as_ref();
+        let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
 
-        let file = File::open(path)?;
+        let cached = self.file_cache.borrow().get(&path).copied();
+        if let Some(texture) = cached.and_then(|id| self.get(id)) {
+            trace!(path = %path.display(), "Reusing texture loaded from file");
+            return Ok(texture);
+        }
+
+        let file = File::open(&path)?;
         let mapping = unsafe { memmap2::Mmap::map(&file) }?;
 
-        let ((width, height), color_type, bytes_per_pixel) = {
-            let reader = ImageReader::new(Cursor::new(&mapping)).with_guessed_format()?;
+        let texture = self.allocate_encoded(&mapping, TextureSource::File(path.clone()))?;
+        self.file_cache
+            .borrow_mut()
+            .insert(path.clone(), texture.id);
+
+        self.decode_in_background(
+            texture.id,
+            path.display(),
+            mapping,
+            texture.size,
+            bytes_per_pixel(texture.format) as u8,
+        );
+
+        Ok(texture)
+    }
+
+    /// Loads an image from the contents of an image file.
+    fn load_encoded(self: &Rc<Self>, data: Arc<[u8]>) -> Result<Texture, TextureLoadError> {
+        let texture = self.allocate_encoded(&data, TextureSource::Encoded(data.clone()))?;
+
+        self.decode_in_background(
+            texture.id,
+            "memory",
+            data,
+            texture.size,
+            bytes_per_pixel(texture.format) as u8,
+        );
+
+        Ok(texture)
+    }
+
+    /// Reads an image's header and allocates a texture for it. The texture is
+    /// not ready until its pixels have been decoded and uploaded.
+    fn allocate_encoded(
+        self: &Rc<Self>,
+        data: &[u8],
+        source: TextureSource,
+    ) -> Result<Texture, TextureLoadError> {
+        let ((width, height), color_type) = {
+            let reader = ImageReader::new(Cursor::new(data)).with_guessed_format()?;
             let decoder = reader.into_decoder()?;
-            let color_type = decoder.color_type();
-            (
-                decoder.dimensions(),
-                color_type,
-                color_type.bytes_per_pixel(),
-            )
+            (decoder.dimensions(), decoder.color_type())
         };
 
         let (format, mut manager) = match color_type {
@@ -497,49 +805,133 @@ impl TextureManagerInner {
             .try_into()
             .expect("Max texture dimension of 65535 exceeded.");
 
-        let (texture, usage, rectangle) =
-            manager.allocate(width, height, &self.device, &self.storage_version);
+        let (_, usage, _) = manager.allocate(
+            width,
+            height,
+            source,
+            &self.device.borrow(),
+            &self.storage_version,
+        );
 
         let uvwh = usage.uvwh;
         let storage_id = usage.storage;
         let texture_id = self.texture_map.borrow_mut().insert(usage);
 
-        let handle = Texture {
+        Ok(Texture {
             id: texture_id,
             storage_id,
             format,
             uvwh,
             size: [width, height],
             manager: self.clone(),
+        })
+    }
+
+    /// Starts fetching an image from a URL, unless it is already loaded or
+    /// being fetched.
+    fn fetch(self: &Rc<Self>, url: &str) -> ImageHandle {
+        if let Some(handle) = self
+            .url_cache
+            .borrow()
+            .get(url)
+            .and_then(ImageHandle::upgrade)
+        {
+            trace!(url, "Reusing image fetched from URL");
+            return handle;
+        }
+
+        let Some(fetcher) = self.fetcher.borrow().clone() else {
+            return ImageHandle::from_result(Err(TextureLoadError::NoFetcher));
         };
 
+        let handle = ImageHandle::loading();
+        self.url_cache
+            .borrow_mut()
+            .insert(url.to_owned(), handle.downgrade());
+
+        std::thread::spawn({
+            let span = debug_span!("Fetching image", url);
+            let url = url.to_owned();
+            let sender = self.fetch_sender.clone();
+            let waker = self.fetch_waker.borrow().clone();
+
+            move || {
+                let _enter = span.enter();
+                let result = fetcher.fetch(&url);
+                let _ = sender.send(FetchedImage { url, result });
+
+                if let Some(wake) = waker {
+                    wake();
+                }
+            }
+        });
+
+        handle
+    }
+
+    /// Starts decoding fetched images whose handles are still alive.
+    fn receive_fetched_images(self: &Rc<Self>) {
+        for fetched in self.fetch_receiver.try_iter() {
+            let Some(handle) = self
+                .url_cache
+                .borrow()
+                .get(&fetched.url)
+                .and_then(ImageHandle::upgrade)
+            else {
+                continue;
+            };
+
+            let state = match fetched.result {
+                Ok(data) => match self.load_encoded(data.into()) {
+                    Ok(texture) => ImageState::Loaded(texture),
+                    Err(error) => ImageState::Failed(Rc::new(error)),
+                },
+                Err(error) => {
+                    warn!(url = fetched.url, %error, "Unable to fetch image");
+                    ImageState::Failed(Rc::new(TextureLoadError::Fetch(error)))
+                }
+            };
+
+            handle.set_state(state);
+            self.mark_content_changed();
+        }
+
+        self.url_cache
+            .borrow_mut()
+            .retain(|_, state| state.strong_count() > 0);
+    }
+
+    /// Decodes an image on another thread and queues its pixels for upload
+    /// to the texture's region of its atlas.
+    fn decode_in_background(
+        &self,
+        texture_id: TextureId,
+        source: impl std::fmt::Display,
+        data: impl AsRef<[u8]> + Send + 'static,
+        [width, height]: [u16; 2],
+        bytes_per_pixel: u8,
+    ) {
+        let start_time = std::time::Instant::now();
+
         // todo: figure out a better way
         std::thread::spawn({
             let span = debug_span!(
-                "Loading texture from file",
-                path = %path.display(),
+                "Decoding texture",
+                %source,
                 texture_id = debug(texture_id),
                 width = width,
                 height = height,
-                file_size = mapping.len(),
+                file_size = data.as_ref().len(),
                 decoded_size = Empty,
             );
 
-            let queue = self.queue.clone();
-            let ready = self.ready_sender.clone();
-            let handle = handle.clone();
+            let uploads = self.upload_sender.clone();
 
             move || {
-                // Clone the handle and keep it around to ensure that the
-                // texture isn't released while we're loading it. We'll drop it
-                // at the end of this closure, which will allow the texture to
-                // be released if the caller drops their handle.
-                let _ = handle;
-
                 let _enter = span.enter();
 
                 let temp = {
-                    let reader = ImageReader::new(Cursor::new(&mapping))
+                    let reader = ImageReader::new(Cursor::new(data.as_ref()))
                         .with_guessed_format()
                         .unwrap();
 
@@ -552,53 +944,241 @@ impl TextureManagerInner {
                     temp
                 };
 
-                queue.write_texture(
-                    wgpu::TexelCopyTextureInfo {
-                        texture: &texture,
-                        mip_level: 0,
-                        origin: wgpu::Origin3d {
-                            x: rectangle.x_range().start.try_into().unwrap(),
-                            y: rectangle.y_range().start.try_into().unwrap(),
-                            z: 0,
-                        },
-                        aspect: wgpu::TextureAspect::All,
-                    },
-                    &temp,
-                    wgpu::TexelCopyBufferLayout {
-                        offset: 0,
-                        bytes_per_row: Some(u32::from(width) * u32::from(bytes_per_pixel)),
-                        rows_per_image: Some(height.into()),
-                    },
-                    wgpu::Extent3d {
+                let alpha_mask =
+                    (bytes_per_pixel == 4).then(|| AlphaMask::from_rgba(&temp, width, height));
+
+                // The receiver is gone if the texture manager was dropped
+                // while the image was decoding.
+                let _ = uploads.send(PendingUpload {
+                    texture_id,
+                    data: temp,
+                    alpha_mask,
+                    bytes_per_row: u32::from(width) * u32::from(bytes_per_pixel),
+                    size: wgpu::Extent3d {
                         width: width.into(),
                         height: height.into(),
                         depth_or_array_layers: 1,
                     },
-                );
-
-                ready.send(texture_id).unwrap();
+                });
 
                 debug!(
-                    x = rectangle.x_range().start,
-                    y = rectangle.y_range().start,
-                    uvwh = ?uvwh,
                     texture_id = ?texture_id,
                     load_time = ?start_time.elapsed(),
-                    format = ?format,
-                    "Loaded texture from file"
+                    "Decoded texture"
                 );
             }
         });
-
-        Ok(handle)
     }
 
     fn flush(self: &Rc<Self>) {
+        self.flush_with_budget(self.upload_budget.get());
+    }
+
+    fn flush_with_budget(self: &Rc<Self>, budget: Option<usize>) {
+        self.receive_fetched_images();
+
         while let Ok(texture_id) = self.ready_receiver.try_recv() {
-            if let Some(usage) = self.texture_map.borrow_mut().get_mut(texture_id) {
-                usage.is_ready = true;
+            self.mark_ready(texture_id);
+        }
+
+        let mut deferred = self.deferred_uploads.borrow_mut();
+        deferred.extend(self.upload_receiver.try_iter());
+
+        while let Some(upload) = deferred.pop_front() {
+            let uploaded = self.uploaded_bytes.get();
+
+            if uploaded > 0 && budget.is_some_and(|budget| uploaded + upload.data.len() > budget) {
+                deferred.push_front(upload);
+                break;
+            }
+
+            self.write_upload(upload);
+        }
+
+        if !deferred.is_empty() {
+            trace!(
+                deferred = deferred.len(),
+                "Deferred texture uploads past the frame budget"
+            );
+        }
+    }
+
+    fn write_upload(&self, upload: PendingUpload) {
+        // The texture's place in the atlas may have been given to another
+        // texture if it was released while its image was being decoded.
+        let Some((format, storage, origin)) = self
+            .texture_map
+            .borrow()
+            .get(upload.texture_id)
+            .map(|usage| (usage.format, usage.storage, usage.origin))
+        else {
+            return;
+        };
+
+        self.write_pixels(
+            format,
+            storage,
+            origin,
+            &upload.data,
+            upload.bytes_per_row,
+            upload.size,
+        );
+
+        self.uploaded_bytes
+            .set(self.uploaded_bytes.get() + upload.data.len());
+
+        if let Some(usage) = self.texture_map.borrow_mut().get_mut(upload.texture_id) {
+            usage.alpha_mask = upload.alpha_mask.map(Arc::new);
+        }
+
+        self.mark_ready(upload.texture_id);
+    }
+
+    /// Writes pixels into a region of an atlas.
+    fn write_pixels(
+        &self,
+        format: TextureFormat,
+        storage: RawStorageId,
+        origin: wgpu::Origin3d,
+        data: &[u8],
+        bytes_per_row: u32,
+        size: wgpu::Extent3d,
+    ) {
+        let Some(texture) = self
+            .formatted(format)
+            .borrow()
+            .storage
+            .get(storage)
+            .map(|s| s.texture.clone())
+        else {
+            return;
+        };
+
+        self.queue.borrow().write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin,
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(bytes_per_row),
+                rows_per_image: Some(size.height),
+            },
+            size,
+        );
+    }
+
+    fn formatted(&self, format: TextureFormat) -> &RefCell<FormattedTextureManager> {
+        match format {
+            TextureFormat::Rgba8Unorm => &self.rgba_textures,
+            TextureFormat::Rgba8UnormSrgb => &self.srgba_textures,
+            TextureFormat::R8Unorm => &self.alpha_textures,
+        }
+    }
+
+    fn mark_ready(&self, texture_id: TextureId) {
+        if let Some(usage) = self.texture_map.borrow_mut().get_mut(texture_id) {
+            usage.is_ready = true;
+            self.mark_content_changed();
+        }
+    }
+
+    #[instrument(skip_all)]
+    fn migrate(self: &Rc<Self>, device: wgpu::Device, queue: wgpu::Queue) {
+        // Uploads that were already received are written before the atlases
+        // are copied. Loads still decoding are written to the new device once
+        // they arrive.
+        self.flush_with_budget(None);
+
+        let old_device = self.device.replace(device.clone());
+        let old_queue = self.queue.replace(queue.clone());
+
+        for textures in [
+            &self.rgba_textures,
+            &self.srgba_textures,
+            &self.alpha_textures,
+        ] {
+            textures
+                .borrow_mut()
+                .migrate(&old_device, &old_queue, &device, &queue);
+        }
+
+        self.storage_version.set(self.storage_version.get() + 1);
+    }
+
+    #[instrument(skip_all)]
+    fn recreate(self: &Rc<Self>, device: wgpu::Device, queue: wgpu::Queue) {
+        self.device.replace(device.clone());
+        self.queue.replace(queue);
+
+        for textures in [
+            &self.rgba_textures,
+            &self.srgba_textures,
+            &self.alpha_textures,
+        ] {
+            textures.borrow_mut().recreate(&device);
+        }
+
+        let mut texture_map = self.texture_map.borrow_mut();
+        for (texture_id, usage) in texture_map.iter_mut() {
+            let [width, height] = usage.size;
+
+            match &usage.source {
+                TextureSource::Memory(data) => {
+                    self.write_pixels(
+                        usage.format,
+                        usage.storage,
+                        usage.origin,
+                        data,
+                        u32::from(width) * bytes_per_pixel(usage.format) as u32,
+                        wgpu::Extent3d {
+                            width: width.into(),
+                            height: height.into(),
+                            depth_or_array_layers: 1,
+                        },
+                    );
+                }
+                TextureSource::File(path) => {
+                    let mapping =
+                        File::open(path).and_then(|file| unsafe { memmap2::Mmap::map(&file) });
+
+                    match mapping {
+                        Ok(mapping) => {
+                            usage.is_ready = false;
+                            self.decode_in_background(
+                                texture_id,
+                                path.display(),
+                                mapping,
+                                usage.size,
+                                bytes_per_pixel(usage.format) as u8,
+                            );
+                        }
+                        // The texture is left blank rather than unready so
+                        // that canvases drawing it don't wait on it forever.
+                        Err(error) => {
+                            warn!(path = %path.display(), %error, "Unable to reload texture");
+                        }
+                    }
+                }
+                TextureSource::Encoded(data) => {
+                    usage.is_ready = false;
+                    self.decode_in_background(
+                        texture_id,
+                        "memory",
+                        data.clone(),
+                        usage.size,
+                        bytes_per_pixel(usage.format) as u8,
+                    );
+                }
+                TextureSource::RenderTarget => usage.is_ready = false,
             }
         }
+
+        self.storage_version.set(self.storage_version.get() + 1);
+        self.mark_content_changed();
     }
 
     fn end_frame(self: &Rc<Self>) {
@@ -622,6 +1202,12 @@ impl Drop for TextureManagerInner {
     }
 }
 
+/// The width and height of the first atlas page of each format. Each page
+/// added after it is twice as large, up to [`MAX_ATLAS_PAGE_SIZE`], so that
+/// apps that show little text don't hold on to large textures.
+const MIN_ATLAS_PAGE_SIZE: u16 = 512;
+const MAX_ATLAS_PAGE_SIZE: u16 = 4096;
+
 #[derive(Clone)]
 struct TextureUsage {
     storage: RawStorageId,
@@ -631,6 +1217,24 @@ struct TextureUsage {
     format: TextureFormat,
     uvwh: [f32; 4],
     size: [u16; 2],
+    /// The texture's position in its atlas.
+    origin: wgpu::Origin3d,
+    source: TextureSource,
+    alpha_mask: Option<Arc<AlphaMask>>,
+}
+
+/// Where a texture's contents come from, so that they can be restored if the
+/// device is lost.
+#[derive(Clone)]
+enum TextureSource {
+    /// A copy of the uploaded pixels. Only textures loaded from memory, which
+    /// are small, keep one.
+    Memory(Rc<[u8]>),
+    File(PathBuf),
+    /// The contents of an image file, decoded again when restored.
+    Encoded(Arc<[u8]>),
+    /// Drawn by the GPU, so the contents can't be restored.
+    RenderTarget,
 }
 
 #[derive(Clone)]
@@ -652,6 +1256,8 @@ impl Drop for TextureStorage {
 struct FormattedTextureManager {
     format: TextureFormat,
     storage: SlotMap<RawStorageId, TextureStorage>,
+    /// The size of the next atlas page to be allocated.
+    page_size: u16,
 }
 
 impl Drop for FormattedTextureManager {
@@ -684,6 +1290,10 @@ impl FormattedTextureManager {
 
             storage.refcount > 0
         });
+
+        if self.storage.is_empty() {
+            self.page_size = MIN_ATLAS_PAGE_SIZE;
+        }
     }
 
     /// Release a reference for the given texture.
@@ -696,11 +1306,60 @@ impl FormattedTextureManager {
         }
     }
 
-    #[instrument(skip(self, device, storage_version))]
+    fn migrate(
+        &mut self,
+        old_device: &wgpu::Device,
+        old_queue: &wgpu::Queue,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        for storage in self.storage.values_mut() {
+            let size = storage.texture.size();
+            let texture = create_atlas_texture(
+                device,
+                self.format,
+                size.width.try_into().unwrap(),
+                size.height.try_into().unwrap(),
+                storage.texture.usage(),
+            );
+
+            transfer_texture(
+                old_device,
+                old_queue,
+                &storage.texture,
+                queue,
+                &texture,
+                bytes_per_pixel(self.format) as u32,
+            );
+
+            storage.texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            std::mem::replace(&mut storage.texture, texture).destroy();
+        }
+    }
+
+    /// Replaces every atlas with an empty one of the same size on `device`.
+    fn recreate(&mut self, device: &wgpu::Device) {
+        for storage in self.storage.values_mut() {
+            let size = storage.texture.size();
+            storage.texture = create_atlas_texture(
+                device,
+                self.format,
+                size.width.try_into().unwrap(),
+                size.height.try_into().unwrap(),
+                storage.texture.usage(),
+            );
+            storage.texture_view = storage
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default());
+        }
+    }
+
+    #[instrument(skip(self, source, device, storage_version))]
     fn allocate(
         &mut self,
         width: u16,
         height: u16,
+        source: TextureSource,
         device: &wgpu::Device,
         storage_version: &Cell<u64>,
     ) -> (wgpu::Texture, TextureUsage, Box2D<i32>) {
@@ -720,29 +1379,17 @@ impl FormattedTextureManager {
             }
 
             // If we reach here, we need to allocate a new texture storage.
-            let atlas_width = 4096.max(width);
-            let atlas_height = 4096.max(height);
-
-            let label = match self.format {
-                TextureFormat::Rgba8UnormSrgb => "Atlas Texture (sRGB)",
-                TextureFormat::Rgba8Unorm => "Atlas Texture (RGBA)",
-                TextureFormat::R8Unorm => "Atlas Texture (Alpha)",
-            };
-
-            let texture = device.create_texture(&wgpu::TextureDescriptor {
-                label: Some(label),
-                size: wgpu::Extent3d {
-                    width: atlas_width.into(),
-                    height: atlas_height.into(),
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: self.format.into(),
-                usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
-                view_formats: &[],
-            });
+            let atlas_width = self.page_size.max(width);
+            let atlas_height = self.page_size.max(height);
+            self.page_size = self.page_size.saturating_mul(2).min(MAX_ATLAS_PAGE_SIZE);
+
+            let texture = create_atlas_texture(
+                device,
+                self.format,
+                atlas_width,
+                atlas_height,
+                wgpu::TextureUsages::empty(),
+            );
 
             let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
 
@@ -779,10 +1426,159 @@ impl FormattedTextureManager {
                 format: self.format,
                 uvwh: [u, v, w, h],
                 size: [width, height],
+                origin: wgpu::Origin3d {
+                    x: rectangle.min.x.try_into().unwrap(),
+                    y: rectangle.min.y.try_into().unwrap(),
+                    z: 0,
+                },
+                source,
+                alpha_mask: None,
             },
             rectangle,
         )
     }
+
+    /// Allocates a storage that holds a single render target filling all of
+    /// it.
+    #[instrument(skip(self, device, storage_version))]
+    fn allocate_render_target(
+        &mut self,
+        width: u16,
+        height: u16,
+        device: &wgpu::Device,
+        storage_version: &Cell<u64>,
+    ) -> TextureUsage {
+        let texture = create_atlas_texture(
+            device,
+            self.format,
+            width,
+            height,
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+        );
+
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut atlas = AtlasAllocator::new(size2(width.into(), height.into()));
+        // Filling the atlas keeps other textures from being placed in it.
+        let Allocation { id, .. } = atlas.allocate(size2(width.into(), height.into())).unwrap();
+
+        let storage_id = self.storage.insert(TextureStorage {
+            refcount: 1,
+            atlas,
+            texture,
+            texture_view,
+        });
+        storage_version.set(storage_version.get() + 1);
+
+        TextureUsage {
+            storage: storage_id,
+            is_ready: false,
+            refcount: 1,
+            atlas_id: id,
+            format: self.format,
+            // Nothing else shares the texture, so there is no bleed to inset
+            // against.
+            uvwh: [0.0, 0.0, 1.0, 1.0],
+            size: [width, height],
+            origin: wgpu::Origin3d::ZERO,
+            source: TextureSource::RenderTarget,
+            alpha_mask: None,
+        }
+    }
+}
+
+fn create_atlas_texture(
+    device: &wgpu::Device,
+    format: TextureFormat,
+    width: u16,
+    height: u16,
+    extra_usage: wgpu::TextureUsages,
+) -> wgpu::Texture {
+    let label = match format {
+        TextureFormat::Rgba8UnormSrgb => "Atlas Texture (sRGB)",
+        TextureFormat::Rgba8Unorm => "Atlas Texture (RGBA)",
+        TextureFormat::R8Unorm => "Atlas Texture (Alpha)",
+    };
+
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d {
+            width: width.into(),
+            height: height.into(),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: format.into(),
+        // COPY_SRC lets the atlas be read back when migrating to another
+        // device.
+        usage: wgpu::TextureUsages::COPY_DST
+            | wgpu::TextureUsages::COPY_SRC
+            | wgpu::TextureUsages::TEXTURE_BINDING
+            | extra_usage,
+        view_formats: &[],
+    })
+}
+
+/// Copies the contents of a texture on one device into a texture of the same
+/// size and format on another by reading it back through the CPU.
+#[instrument(skip_all)]
+fn transfer_texture(
+    src_device: &wgpu::Device,
+    src_queue: &wgpu::Queue,
+    src: &wgpu::Texture,
+    dst_queue: &wgpu::Queue,
+    dst: &wgpu::Texture,
+    bytes_per_pixel: u32,
+) {
+    let size = src.size();
+    let layout = wgpu::TexelCopyBufferLayout {
+        offset: 0,
+        bytes_per_row: Some(
+            (size.width * bytes_per_pixel).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT),
+        ),
+        rows_per_image: None,
+    };
+
+    let buffer = src_device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Atlas Readback"),
+        size: u64::from(layout.bytes_per_row.unwrap()) * u64::from(size.height),
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder =
+        src_device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    encoder.copy_texture_to_buffer(
+        src.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout,
+        },
+        size,
+    );
+    src_queue.submit([encoder.finish()]);
+
+    let (sender, receiver) = mpsc::channel();
+    buffer.map_async(wgpu::MapMode::Read, .., move |result| {
+        let _ = sender.send(result);
+    });
+
+    if src_device
+        .poll(wgpu::PollType::wait_indefinitely())
+        .is_err()
+        || !matches!(receiver.recv(), Ok(Ok(())))
+    {
+        warn!("Failed to read back texture atlas, contents will be lost");
+        return;
+    }
+
+    if let Ok(data) = buffer.get_mapped_range(..) {
+        dst_queue.write_texture(dst.as_image_copy(), &data, layout, size);
+    }
+
+    buffer.unmap();
 }
 
 fn bytes_per_pixel(format: TextureFormat) -> usize {
@@ -1,20 +1,56 @@
 mod app_context;
+mod budget;
 mod clipboard;
+mod clock;
 mod frame;
+#[cfg(feature = "gamepad")]
+mod gamepad;
 mod input;
+mod shortcut;
+mod stats;
+mod task;
+#[cfg(feature = "theme-files")]
+mod theme_watcher;
+mod timer;
 mod window;
 mod winit;
 
 pub use app_context::AppContext;
 pub use app_context::AppContextBuilder;
 pub use app_context::AppLifecycleHandler;
+pub use budget::FrameBudget;
 pub use clipboard::Clipboard;
+pub use clock::Clock;
+pub use clock::ManualClock;
+pub use clock::SystemClock;
 pub use frame::Context;
 pub use frame::FileDialog;
 pub use frame::FolderDialog;
 pub use input::ElementState;
+pub use input::ImeEvent;
 pub use input::Input;
 pub use input::KeyboardEvent;
 pub use input::MouseButtonState;
+pub use input::NavigationDirection;
+pub use input::NavigationEvent;
 pub use input::WindowSize;
+pub use shortcut::Shortcut;
+pub(crate) use shortcut::Shortcuts;
+pub use stats::FrameStats;
+pub use task::ProgressSender;
+pub use task::TaskHandle;
+pub use window::Appearance;
+pub use window::MonitorInfo;
+pub(crate) use window::WindowAction;
 pub use window::WindowConfig;
+pub use window::WindowEdge;
+pub use window::WindowIcon;
+pub use window::WindowMode;
+pub use window::WindowShape;
+
+// Exposed through `UiBuilder::set_cursor`.
+pub use ::winit::cursor::CursorIcon;
+// Exposed through `Shortcut`.
+pub use ::winit::keyboard::KeyCode;
+// Exposed through `Input::modifiers`.
+pub use ::winit::keyboard::ModifiersState;
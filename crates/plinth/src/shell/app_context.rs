@@ -1,21 +1,42 @@
 use std::collections::HashMap;
+#[cfg(feature = "theme-files")]
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
 
 use smallvec::SmallVec;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
 use winit::event_loop::ControlFlow;
 use winit::event_loop::EventLoop;
-use winit::platform::windows::EventLoopBuilderExtWindows;
 
+use crate::graphics::AdapterSettings;
 use crate::graphics::Color;
+use crate::graphics::Fonts;
 use crate::graphics::GraphicsContext;
+use crate::graphics::RenderError;
 use crate::graphics::TextLayoutContext;
 use crate::shell::Clipboard;
 use crate::shell::WindowConfig;
 use crate::ui::Theme;
+#[cfg(feature = "theme-files")]
+use crate::ui::ThemeLoadError;
 use crate::ui::UiBuilder;
 use crate::ui::text::TextLayoutStorage;
 
+use super::budget::FrameBudget;
+use super::clock::Clock;
+use super::clock::FrameClock;
 use super::frame::Context;
+#[cfg(feature = "gamepad")]
+use super::gamepad::Gamepads;
+use super::shortcut::Shortcuts;
+use super::stats::FrameStats;
+use super::task::TaskStatus;
+#[cfg(feature = "theme-files")]
+use super::theme_watcher::ThemeWatcher;
 use super::winit::DeferredCommand;
 use super::winit::WinitApp;
 use super::winit::WinitWindow;
@@ -23,6 +44,11 @@ use super::winit::WinitWindow;
 #[derive(Default)]
 pub struct AppContextBuilder {
     theme: Option<Theme>,
+    adapter_settings: Option<AdapterSettings>,
+    frame_clock: Option<FrameClock>,
+    frame_budget: FrameBudget,
+    #[cfg(feature = "theme-files")]
+    theme_file: Option<PathBuf>,
 }
 
 impl AppContextBuilder {
@@ -31,26 +57,84 @@ impl AppContextBuilder {
         self
     }
 
+    /// Applies a theme file on top of the theme, and again whenever the file
+    /// changes. See [`AppContext::watch_theme_file`].
+    #[cfg(feature = "theme-files")]
+    pub fn with_theme_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.theme_file = Some(path.into());
+        self
+    }
+
+    pub fn with_adapter_settings(mut self, settings: AdapterSettings) -> Self {
+        self.adapter_settings = Some(settings);
+        self
+    }
+
+    /// Replaces the system clock used to time frames, e.g. with a
+    /// [`ManualClock`](super::ManualClock) in tests.
+    pub fn with_clock(mut self, clock: impl Clock) -> Self {
+        self.frame_clock = Some(FrameClock::new(clock));
+        self
+    }
+
+    /// Sets soft limits on the work done per frame. See [`FrameBudget`].
+    pub fn with_frame_budget(mut self, budget: FrameBudget) -> Self {
+        self.frame_budget = budget;
+        self
+    }
+
+    /// Runs the application until all windows are closed.
+    ///
+    /// This must be called from the main thread. macOS requires the event
+    /// loop, windows, and Metal layers to be created there, and winit will
+    /// panic otherwise.
     pub fn run(self, handler: impl AppLifecycleHandler) {
-        let event_loop = EventLoop::builder().with_dpi_aware(true).build().unwrap();
+        #[allow(unused_mut)]
+        let mut builder = EventLoop::builder();
+
+        #[cfg(target_os = "windows")]
+        {
+            use winit::platform::windows::EventLoopBuilderExtWindows;
+            builder.with_dpi_aware(true);
+        }
+
+        let event_loop = builder.build().unwrap();
         event_loop.set_control_flow(ControlFlow::Wait);
 
         let theme = self.theme.unwrap_or_default();
 
-        let runtime = WinitApp {
+        #[allow(unused_mut)]
+        let mut runtime = WinitApp {
             runtime: AppContext {
                 clipboard: Clipboard::new(),
                 deferred_commands: Vec::new(),
+                frame_clock: self.frame_clock.unwrap_or_default(),
+                frame_budget: self.frame_budget,
+                frame_stats: FrameStats::default(),
+                adapter_settings: self.adapter_settings.unwrap_or_default(),
                 theme,
                 graphics: None,
                 text_system: TextLayoutContext::default(),
                 text_layouts: TextLayoutStorage::default(),
                 format_buffer: String::with_capacity(2048),
+                tasks: Vec::new(),
+                shortcuts: Shortcuts::default(),
+                #[cfg(feature = "theme-files")]
+                theme_watcher: None,
+                #[cfg(feature = "gamepad")]
+                gamepads: Gamepads::new(),
             },
             windows: HashMap::new(),
             user_handler: handler,
         };
 
+        #[cfg(feature = "theme-files")]
+        if let Some(path) = self.theme_file
+            && let Err(error) = runtime.runtime.watch_theme_file(&path)
+        {
+            warn!(path = %path.display(), %error, "Unable to load theme");
+        }
+
         event_loop.run_app(runtime).unwrap();
     }
 }
@@ -66,6 +150,10 @@ pub trait AppLifecycleHandler: 'static {
 pub struct AppContext {
     pub(super) clipboard: Clipboard,
     pub(super) deferred_commands: Vec<DeferredCommand>,
+    pub(super) frame_clock: FrameClock,
+    pub(super) frame_budget: FrameBudget,
+    pub(super) frame_stats: FrameStats,
+    pub(super) adapter_settings: AdapterSettings,
 
     pub(super) theme: Theme,
 
@@ -73,6 +161,16 @@ pub struct AppContext {
     pub(super) text_system: TextLayoutContext,
     pub(super) text_layouts: TextLayoutStorage,
     pub(super) format_buffer: String,
+
+    /// Tasks started with [`Context::spawn`] that are still running.
+    pub(super) tasks: Vec<Arc<TaskStatus>>,
+    pub(super) shortcuts: Shortcuts,
+
+    #[cfg(feature = "theme-files")]
+    pub(super) theme_watcher: Option<ThemeWatcher>,
+    /// `None` if gamepads can't be read on this platform.
+    #[cfg(feature = "gamepad")]
+    pub(super) gamepads: Option<Gamepads>,
 }
 
 impl AppContext {
@@ -87,38 +185,121 @@ impl AppContext {
         });
     }
 
+    /// The time at which the most recent frame began, relative to application
+    /// start.
+    pub fn frame_time(&self) -> Duration {
+        self.frame_clock.frame_time()
+    }
+
+    /// Switches the graphics backend or adapter. The change is applied after
+    /// the current event has been handled.
+    pub fn set_adapter_settings(&mut self, settings: AdapterSettings) {
+        self.deferred_commands
+            .push(DeferredCommand::SetAdapterSettings(settings));
+    }
+
+    pub fn frame_budget(&self) -> &FrameBudget {
+        &self.frame_budget
+    }
+
+    pub fn set_frame_budget(&mut self, budget: FrameBudget) {
+        self.frame_budget = budget;
+    }
+
+    /// Statistics about the most recently drawn frame.
+    pub fn frame_stats(&self) -> &FrameStats {
+        &self.frame_stats
+    }
+
     pub fn theme(&self) -> &Theme {
         &self.theme
     }
 
+    pub fn clipboard(&self) -> &Clipboard {
+        &self.clipboard
+    }
+
+    pub fn fonts(&mut self) -> Fonts<'_> {
+        // Cached text layouts are keyed by the theme revision, so moving it
+        // reshapes them with the new fonts.
+        self.theme.follow_revision(self.theme.revision());
+        self.text_system.fonts()
+    }
+
+    /// Applies a theme file on top of the theme, then reapplies it whenever
+    /// the file changes so that styles can be tweaked without restarting.
+    ///
+    /// Only one file is watched at a time. Changes that fail to load are
+    /// logged and leave the theme as it was.
+    #[cfg(feature = "theme-files")]
+    pub fn watch_theme_file(&mut self, path: impl Into<PathBuf>) -> Result<(), ThemeLoadError> {
+        let path = path.into();
+        self.theme.apply_file(&path)?;
+        self.theme_watcher = Some(ThemeWatcher::new(path));
+        Ok(())
+    }
+
     pub fn theme_mut(&mut self) -> &mut Theme {
         &mut self.theme
     }
 
+    pub fn set_theme(&mut self, mut theme: Theme) {
+        // Cached text layouts and style resolutions are keyed by revision,
+        // so the new theme must not reuse one of the old theme's.
+        theme.follow_revision(self.theme.revision());
+        self.theme = theme;
+    }
+
     pub(super) fn repaint<'a>(&mut self, windows: impl IntoIterator<Item = &'a mut WinitWindow>) {
         let graphics = self.graphics.as_mut().unwrap();
+        self.frame_clock.tick();
+
+        graphics.set_upload_budget(self.frame_budget.upload_limit());
+        graphics.set_glyph_budget(self.frame_budget.max_glyphs);
+        graphics.set_glyph_capacity(self.frame_budget.max_cached_glyphs);
 
         let windows = windows.into_iter();
         let mut outputs = SmallVec::with_capacity(windows.size_hint().0);
+        let mut rendered = SmallVec::<[_; 2]>::with_capacity(windows.size_hint().0);
+        let mut stats = FrameStats::default();
 
         for window in windows {
             // borrow input for this frame
             let mut input = std::mem::take(&mut window.input);
 
+            let time_delta = self.frame_clock.viewport_delta(&mut window.last_frame_time);
+            let window_theme = window.config.theme.clone();
+            let theme = window_theme.as_deref().unwrap_or(&self.theme);
+
+            window.ui_context.match_shortcuts(&input, &self.shortcuts);
             let ui_builder = window.ui_context.begin_frame(
-                &mut self.clipboard,
+                &self.clipboard,
                 &mut self.text_system,
                 &mut self.text_layouts,
                 &mut self.format_buffer,
-                &self.theme,
+                theme,
                 &input,
-                Duration::ZERO,
+                self.frame_clock.frame_time(),
+                time_delta,
+                window.refresh_interval,
+                window.config.shape,
             );
 
             let context = Context {
                 window: window.window.as_ref(),
                 graphics,
                 deferred_commands: &mut self.deferred_commands,
+                repaint: &mut window.repaint,
+                frame_stats: &self.frame_stats,
+                title: &mut window.config.title,
+                tasks: &mut self.tasks,
+                clipboard: &self.clipboard,
+                shortcuts: &mut self.shortcuts,
+                windowed_geometry: &mut window.windowed_geometry,
+                close_requested: std::mem::take(&mut window.close_requested),
+                close_intercepted: &mut window.config.intercept_close,
+                timers: &mut window.timers,
+                hit_targets: &window.hit_targets,
             };
 
             (window.handler)(context, ui_builder);
@@ -126,21 +307,100 @@ impl AppContext {
             input.prev_pointer = input.pointer;
             window.input = input;
             window.input.keyboard_events.clear();
+            window.input.ime_events.clear();
+            window.input.navigation_events.clear();
+            window.input.dropped_files.clear();
+            window.timers.end_frame();
+            window.input.scroll_delta = Default::default();
 
-            window.canvas.reset(Color::BLACK);
+            // Shaped windows are transparent around their shape, and
+            // transparent windows wherever nothing is drawn.
+            window.canvas.reset(match window.config.transparent {
+                false => Color::BLACK,
+                true => Color::TRANSPARENT,
+            });
+            // The UI is laid out in logical pixels and scaled as it is drawn,
+            // so that it is rasterized at the display's resolution.
+            window
+                .canvas
+                .set_view([0.0, 0.0], window.input.scale_factor);
+            // Children of the root aren't clipped to its rounded corners, so
+            // the whole canvas is.
+            window
+                .canvas
+                .set_corner_radius(window.config.shape.corner_radius(window.input.window_size));
             window.ui_context.finish(
+                theme,
                 &mut self.text_system,
                 &mut self.text_layouts,
                 &mut window.canvas,
             );
+            window
+                .ui_context
+                .update_hit_targets(&mut window.hit_targets);
 
-            if window.canvas.has_unready_textures() {
+            self.frame_budget.check_window(
+                window.window.id(),
+                window.ui_context.num_nodes(),
+                window.ui_context.text_layouts_built(),
+            );
+
+            stats.nodes += window.ui_context.num_nodes();
+            stats.layout_time += window.ui_context.layout_time();
+            stats.text_layouts += window.ui_context.text_layout_stats();
+            stats.styles += window.ui_context.style_cache_stats();
+
+            window.update_ime(window.ui_context.ime_cursor_area());
+            window.update_cursor(window.ui_context.cursor_icon());
+            if let Some(action) = window.ui_context.window_action() {
+                window.apply_action(action);
+            }
+
+            if let Some(delay) = window.ui_context.repaint_after() {
+                window.repaint.request_at(Instant::now() + delay);
+            }
+
+            if window.repaint.continuous
+                || window.canvas.has_unready_textures()
+                || window.ui_context.needs_redraw()
+            {
                 window.window.request_redraw();
             }
 
+            for (canvas, target) in window.ui_context.texture_layers() {
+                graphics.render_to_texture(canvas, target);
+            }
+
             outputs.push((window.window.id(), &window.canvas));
+            rendered.push(window.window.clone());
+        }
+
+        match graphics.render(outputs) {
+            Ok(render_stats) => stats.render = render_stats,
+            Err(RenderError::DeviceLost) => {
+                // On failure, recovery is attempted again the next time a
+                // window is redrawn.
+                match graphics.recover_lost_device() {
+                    Ok(()) => {
+                        info!("Recovered from lost graphics device");
+
+                        for window in rendered {
+                            window.request_redraw();
+                        }
+                    }
+                    Err(error) => error!(%error, "Unable to recover lost graphics device"),
+                }
+            }
+            Err(error) => warn!(?error, "Unable to render frame"),
         }
 
-        graphics.render(outputs).unwrap();
+        let (upload_bytes, deferred_uploads) = graphics.take_upload_stats();
+        self.frame_budget
+            .check_uploads(upload_bytes, deferred_uploads);
+
+        stats.texture_upload_bytes = upload_bytes;
+        stats.glyphs = graphics.take_glyph_stats();
+        stats.cached_glyphs = graphics.num_cached_glyphs();
+        self.frame_stats = stats;
     }
 }
@@ -0,0 +1,75 @@
+use tracing::debug;
+use tracing::warn;
+use winit::window::WindowId;
+
+/// Soft limits on the work done to draw a frame.
+///
+/// Exceeding a limit logs a warning so that regressions show up in logs and
+/// traces before they turn into visible jank. Limits are not enforced, with
+/// the exception of `max_glyphs` and `max_cached_glyphs`, and of
+/// `max_upload_bytes` when `defer_uploads` is set.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameBudget {
+    /// The most layout nodes a window should build per frame.
+    pub max_nodes: Option<usize>,
+    /// The most text layouts a window should shape per frame. Text that is
+    /// unchanged since the previous frame reuses its layout and isn't counted.
+    pub max_text_layouts: Option<usize>,
+    /// The most new glyphs to rasterize per frame, across all windows. Glyphs
+    /// past the limit are drawn as placeholders and rasterized over the
+    /// following frames. Unlike the other limits, this one is enforced.
+    pub max_glyphs: Option<u32>,
+    /// The most rasterized glyphs to keep in the glyph atlas, across all
+    /// windows. Past the limit, the glyphs drawn longest ago are evicted at
+    /// the end of each frame, so that apps that show text at many sizes don't
+    /// fill the atlas. Glyphs drawn in the frame are never evicted.
+    pub max_cached_glyphs: Option<usize>,
+    /// The most bytes that should be uploaded to textures per frame, across
+    /// all windows.
+    pub max_upload_bytes: Option<usize>,
+    /// Defers uploads of loaded images that would exceed `max_upload_bytes`
+    /// to later frames. Images that haven't been uploaded yet are not drawn.
+    pub defer_uploads: bool,
+}
+
+impl FrameBudget {
+    /// The upload limit to enforce, if any.
+    pub(crate) fn upload_limit(&self) -> Option<usize> {
+        self.max_upload_bytes.filter(|_| self.defer_uploads)
+    }
+
+    pub(crate) fn check_window(&self, window_id: WindowId, nodes: usize, text_layouts: usize) {
+        if let Some(max_nodes) = self.max_nodes
+            && nodes > max_nodes
+        {
+            warn!(?window_id, nodes, limit = max_nodes, "Node budget exceeded");
+        }
+
+        if let Some(max_text_layouts) = self.max_text_layouts
+            && text_layouts > max_text_layouts
+        {
+            warn!(
+                ?window_id,
+                text_layouts,
+                limit = max_text_layouts,
+                "Text layout budget exceeded"
+            );
+        }
+    }
+
+    pub(crate) fn check_uploads(&self, upload_bytes: usize, deferred: usize) {
+        if let Some(max_upload_bytes) = self.max_upload_bytes
+            && upload_bytes > max_upload_bytes
+        {
+            warn!(
+                upload_bytes,
+                limit = max_upload_bytes,
+                "Texture upload budget exceeded"
+            );
+        }
+
+        if deferred > 0 {
+            debug!(deferred, "Deferred texture uploads to a later frame");
+        }
+    }
+}
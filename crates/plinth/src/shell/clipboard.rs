@@ -1,18 +1,34 @@
+use std::cell::RefCell;
+
 use tracing::error;
 
+/// The platform clipboard, shared by the application's windows.
+///
+/// If the clipboard can't be opened, it acts as if it were always empty.
 pub struct Clipboard {
-    inner: arboard::Clipboard,
+    // The clipboard is shared between each window's UI and its context during
+    // a frame, so it is borrowed only while it is read or written.
+    inner: RefCell<Option<arboard::Clipboard>>,
 }
 
 impl Clipboard {
     pub(crate) fn new() -> Self {
+        let inner = match arboard::Clipboard::new() {
+            Ok(clipboard) => Some(clipboard),
+            Err(error) => {
+                error!(%error, "Unable to open the clipboard");
+                None
+            }
+        };
+
         Self {
-            inner: arboard::Clipboard::new().unwrap(),
+            inner: RefCell::new(inner),
         }
     }
 
-    pub fn get_text(&mut self) -> Option<String> {
-        match self.inner.get_text() {
+    pub fn get_text(&self) -> Option<String> {
+        let mut inner = self.inner.borrow_mut();
+        match inner.as_mut()?.get_text() {
             Ok(text) => Some(text),
             Err(error) => {
                 error!(%error, "Unable to get clipboard text");
@@ -21,8 +37,13 @@ impl Clipboard {
         }
     }
 
-    pub fn set_text(&mut self, text: &str) {
-        if let Err(error) = self.inner.set_text(text) {
+    pub fn set_text(&self, text: &str) {
+        let mut inner = self.inner.borrow_mut();
+        let Some(clipboard) = inner.as_mut() else {
+            return;
+        };
+
+        if let Err(error) = clipboard.set_text(text) {
             error!(%error, "Unable to set clipboard text");
         }
     }
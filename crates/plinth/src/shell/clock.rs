@@ -0,0 +1,157 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+use std::time::Instant;
+
+/// A source of the current time for the frame clock.
+///
+/// The runtime uses [`SystemClock`] by default. Tests can substitute a
+/// [`ManualClock`] to control exactly how much time passes between frames.
+pub trait Clock: 'static {
+    fn now(&self) -> Instant;
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only advances when told to.
+///
+/// Clones share the same underlying time, so a test can keep one handle while
+/// the runtime owns another.
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    now: Rc<Cell<Instant>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self {
+            now: Rc::new(Cell::new(Instant::now())),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+}
+
+/// Tracks the time of the current frame relative to application start.
+///
+/// The time is sampled once per repaint so that every viewport drawn in the
+/// same pass observes the same frame time.
+pub(crate) struct FrameClock {
+    clock: Box<dyn Clock>,
+    epoch: Instant,
+    frame_time: Duration,
+}
+
+impl FrameClock {
+    pub fn new(clock: impl Clock) -> Self {
+        let epoch = clock.now();
+        Self {
+            clock: Box::new(clock),
+            epoch,
+            frame_time: Duration::ZERO,
+        }
+    }
+
+    /// Samples the clock for a new frame.
+    pub fn tick(&mut self) {
+        self.frame_time = self.clock.now().saturating_duration_since(self.epoch);
+    }
+
+    /// The time at which the current frame began, relative to application
+    /// start.
+    pub fn frame_time(&self) -> Duration {
+        self.frame_time
+    }
+
+    /// Computes the time elapsed since a viewport's previous frame and
+    /// records the current frame time as its new previous frame.
+    ///
+    /// Returns zero for a viewport's first frame.
+    pub fn viewport_delta(&self, last_frame_time: &mut Option<Duration>) -> Duration {
+        let delta =
+            last_frame_time.map_or(Duration::ZERO, |last| self.frame_time.saturating_sub(last));
+        *last_frame_time = Some(self.frame_time);
+        delta
+    }
+}
+
+impl Default for FrameClock {
+    fn default() -> Self {
+        Self::new(SystemClock)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_viewport_frame_has_zero_delta() {
+        let clock = ManualClock::new();
+        let mut frame_clock = FrameClock::new(clock.clone());
+
+        clock.advance(Duration::from_millis(100));
+        frame_clock.tick();
+
+        let mut last = None;
+        assert_eq!(frame_clock.viewport_delta(&mut last), Duration::ZERO);
+        assert_eq!(last, Some(Duration::from_millis(100)));
+        assert_eq!(frame_clock.frame_time(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn viewports_track_deltas_independently() {
+        let clock = ManualClock::new();
+        let mut frame_clock = FrameClock::new(clock.clone());
+
+        let mut a = None;
+        let mut b = None;
+
+        frame_clock.tick();
+        frame_clock.viewport_delta(&mut a);
+
+        clock.advance(Duration::from_millis(16));
+        frame_clock.tick();
+        assert_eq!(
+            frame_clock.viewport_delta(&mut a),
+            Duration::from_millis(16)
+        );
+        frame_clock.viewport_delta(&mut b);
+
+        clock.advance(Duration::from_millis(16));
+        frame_clock.tick();
+        assert_eq!(
+            frame_clock.viewport_delta(&mut b),
+            Duration::from_millis(16)
+        );
+
+        clock.advance(Duration::from_millis(16));
+        frame_clock.tick();
+        assert_eq!(
+            frame_clock.viewport_delta(&mut a),
+            Duration::from_millis(32)
+        );
+        assert_eq!(frame_clock.frame_time(), Duration::from_millis(48));
+    }
+}
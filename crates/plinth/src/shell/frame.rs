@@ -1,13 +1,50 @@
+use std::borrow::Cow;
+use std::fmt;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
+use glamour::Point2;
+use glamour::Size2;
+use tracing::warn;
+use winit::dpi::PhysicalPosition;
+use winit::dpi::PhysicalSize;
+use winit::monitor::Fullscreen;
+use winit::window::WindowLevel;
+
+use crate::graphics::AdapterSettings;
 use crate::graphics::GraphicsContext;
+use crate::graphics::ImageFetcher;
+use crate::graphics::ImageHandle;
+use crate::graphics::ImageSource;
+use crate::graphics::MaterialError;
+use crate::graphics::MaterialId;
+use crate::graphics::PresentSettings;
 use crate::graphics::Texture;
 use crate::graphics::TextureLoadError;
+use crate::ui::Pixels;
+use crate::ui::Theme;
 use crate::ui::UiBuilder;
+use crate::ui::WidgetId;
+use crate::ui::context::HitTargets;
 
+use super::Appearance;
+use super::Clipboard;
+use super::FrameStats;
+use super::MonitorInfo;
+use super::ProgressSender;
+use super::Shortcut;
+use super::TaskHandle;
 use super::WindowConfig;
+use super::WindowIcon;
+use super::WindowMode;
+use super::shortcut::Shortcuts;
+use super::task::TaskStatus;
+use super::timer::Timers;
 use super::winit::DeferredCommand;
+use super::winit::RepaintSchedule;
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct FileDialog {
@@ -72,6 +109,17 @@ pub struct Context<'a> {
     pub(super) window: &'a dyn winit::window::Window,
     pub(super) graphics: &'a mut GraphicsContext,
     pub(super) deferred_commands: &'a mut Vec<DeferredCommand>,
+    pub(super) repaint: &'a mut RepaintSchedule,
+    pub(super) frame_stats: &'a FrameStats,
+    pub(super) title: &'a mut Cow<'static, str>,
+    pub(super) tasks: &'a mut Vec<Arc<TaskStatus>>,
+    pub(super) clipboard: &'a Clipboard,
+    pub(super) shortcuts: &'a mut Shortcuts,
+    pub(super) windowed_geometry: &'a mut Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>,
+    pub(super) close_requested: bool,
+    pub(super) close_intercepted: &'a mut bool,
+    pub(super) timers: &'a mut Timers,
+    pub(super) hit_targets: &'a HitTargets,
 }
 
 impl Context<'_> {
@@ -86,14 +134,365 @@ impl Context<'_> {
         });
     }
 
+    /// Switches the graphics backend or adapter. The change is applied after
+    /// the current frame has been presented.
+    pub fn set_adapter_settings(&mut self, settings: AdapterSettings) {
+        self.deferred_commands
+            .push(DeferredCommand::SetAdapterSettings(settings));
+    }
+
+    pub fn adapter_settings(&self) -> &AdapterSettings {
+        self.graphics.adapter_settings()
+    }
+
+    /// Replaces the app's theme. The change is applied after the current
+    /// frame, and all windows are redrawn with it.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.deferred_commands
+            .push(DeferredCommand::SetTheme(theme));
+    }
+
+    /// Styles this window with its own theme, or with the app's theme again
+    /// if `None`. The change is applied after the current frame.
+    pub fn set_window_theme(&mut self, theme: Option<Theme>) {
+        self.deferred_commands
+            .push(DeferredCommand::SetWindowTheme(self.window.id(), theme));
+    }
+
+    /// Statistics about the previous frame, e.g. for a performance overlay.
+    /// Drawing them requests no extra frames, so pair this with
+    /// [`Self::set_continuous_repaint`] to keep an overlay up to date.
+    pub fn frame_stats(&self) -> &FrameStats {
+        self.frame_stats
+    }
+
+    /// The topmost widget that `point` was over last frame, e.g. for custom
+    /// gestures and debugging tools. The same as [`UiBuilder::hit_test`].
+    pub fn hit_test(&self, point: Point2<Pixels>) -> Option<WidgetId> {
+        self.hit_targets.hit_test(point)
+    }
+
+    /// The platform clipboard, e.g. for copy and paste commands in a menu bar.
+    pub fn clipboard(&self) -> &Clipboard {
+        self.clipboard
+    }
+
+    /// Registers a custom material for use with
+    /// [`Canvas::draw_custom`](crate::graphics::Canvas::draw_custom). See
+    /// [`GraphicsContext::register_material`] for the expected source.
+    pub fn register_material(&self, source: &str) -> Result<MaterialId, MaterialError> {
+        self.graphics.register_material(source)
+    }
+
+    /// Changes how this window's frames are synchronized with the display,
+    /// e.g. to toggle vsync.
+    pub fn set_present_settings(&mut self, present: PresentSettings) {
+        self.graphics
+            .set_present_settings(self.window.id(), present);
+    }
+
+    /// Sets this window's title, e.g. `set_title(format_args!("{name} —
+    /// modified"))`. The title is compared without allocating and only passed
+    /// to the platform when it changes, so this can be called every frame.
+    pub fn set_title(&mut self, title: fmt::Arguments) {
+        let is_unchanged = match title.as_str() {
+            Some(title) => *self.title == title,
+            None => {
+                let mut matcher = PrefixMatcher {
+                    remaining: self.title.as_ref(),
+                };
+                fmt::write(&mut matcher, title).is_ok() && matcher.remaining.is_empty()
+            }
+        };
+
+        if !is_unchanged {
+            *self.title = Cow::Owned(title.to_string());
+            self.window.set_title(self.title.as_ref());
+        }
+    }
+
+    /// Keeps this window's contents out of screenshots and screen recordings,
+    /// e.g. while it shows a password. Has no effect on platforms that don't
+    /// support it.
+    pub fn set_content_protected(&mut self, protected: bool) {
+        self.window.set_content_protected(protected);
+    }
+
+    /// Sets the smallest size, in pixels, the user can resize this window to.
+    pub fn set_min_size(&mut self, size: Option<(u32, u32)>) {
+        self.window
+            .set_min_surface_size(size.map(|(w, h)| PhysicalSize::new(w, h).into()));
+    }
+
+    /// Sets the largest size, in pixels, the user can resize this window to.
+    pub fn set_max_size(&mut self, size: Option<(u32, u32)>) {
+        self.window
+            .set_max_surface_size(size.map(|(w, h)| PhysicalSize::new(w, h).into()));
+    }
+
+    /// Blurs what's behind the transparent parts of this window, if it was
+    /// created [transparent](WindowConfig::transparent). Only supported on
+    /// KDE Plasma and macOS; on Windows the backdrop can only be chosen with
+    /// [`WindowConfig::blur`].
+    pub fn set_blur(&mut self, blur: bool) {
+        self.window.set_blur(blur);
+    }
+
+    pub fn set_resizable(&mut self, resizable: bool) {
+        self.window.set_resizable(resizable);
+    }
+
+    /// Shows or hides the platform's title bar and borders.
+    pub fn set_decorations(&mut self, decorations: bool) {
+        self.window.set_decorations(decorations);
+    }
+
+    pub fn set_minimized(&mut self, minimized: bool) {
+        self.window.set_minimized(minimized);
+    }
+
+    /// Closes this window after the current frame, even if closing is
+    /// [intercepted](Self::set_close_intercepted).
+    pub fn close(&mut self) {
+        self.deferred_commands
+            .push(DeferredCommand::Close(self.window.id()));
+    }
+
+    /// Whether the user tried to close this window since the last frame,
+    /// while closing is intercepted. The request is only reported once; to
+    /// go ahead, call [`Self::close`].
+    pub fn is_close_requested(&self) -> bool {
+        self.close_requested
+    }
+
+    /// Leaves this window open when the user tries to close it, and reports
+    /// it through [`Self::is_close_requested`] instead. See
+    /// [`WindowConfig::intercept_close`].
+    pub fn set_close_intercepted(&mut self, intercepted: bool) {
+        *self.close_intercepted = intercepted;
+    }
+
+    pub fn set_maximized(&mut self, maximized: bool) {
+        self.window.set_maximized(maximized);
+    }
+
+    pub fn is_maximized(&self) -> bool {
+        self.window.is_maximized()
+    }
+
+    /// Moves the top-left corner of the window, including its frame, to
+    /// `position` on the screen. Ignored on Wayland.
+    pub fn set_outer_position(&mut self, position: Point2<Pixels>) {
+        self.window
+            .set_outer_position(PhysicalPosition::new(position.x as i32, position.y as i32).into());
+    }
+
+    pub fn set_icon(&mut self, icon: Option<&WindowIcon>) {
+        self.window
+            .set_window_icon(icon.and_then(WindowIcon::to_winit));
+    }
+
+    /// Keeps this window above other windows, or stops doing so.
+    pub fn set_always_on_top(&mut self, always_on_top: bool) {
+        self.window.set_window_level(match always_on_top {
+            true => WindowLevel::AlwaysOnTop,
+            false => WindowLevel::Normal,
+        });
+    }
+
+    /// Where the top-left corner of the window, including its frame, is on
+    /// the screen, or `None` if the platform doesn't say, as on Wayland.
+    ///
+    /// To convert between window and screen coordinates, see
+    /// [`Input::window_to_screen`](super::Input::window_to_screen).
+    pub fn outer_position(&self) -> Option<Point2<Pixels>> {
+        let position = self.window.outer_position().ok()?;
+        Some(Point2::new(position.x as f32, position.y as f32))
+    }
+
+    /// Makes this window fullscreen on its current monitor, or returns it to
+    /// the position and size it had before it went fullscreen.
+    pub fn set_window_mode(&mut self, mode: WindowMode) {
+        if mode == self.window_mode() {
+            return;
+        }
+
+        let monitor = self.window.current_monitor();
+        let fullscreen = match mode {
+            WindowMode::Windowed => None,
+            WindowMode::BorderlessFullscreen => Some(Fullscreen::Borderless(monitor)),
+            WindowMode::ExclusiveFullscreen => {
+                let Some((monitor, video_mode)) =
+                    monitor.and_then(|m| m.current_video_mode().map(|v| (m, v)))
+                else {
+                    warn!("Unable to find a video mode for exclusive fullscreen");
+                    return;
+                };
+                Some(Fullscreen::Exclusive(monitor, video_mode))
+            }
+        };
+
+        if self.window.fullscreen().is_none() {
+            *self.windowed_geometry = self
+                .window
+                .outer_position()
+                .ok()
+                .map(|position| (position, self.window.surface_size()));
+        }
+
+        self.window.set_fullscreen(fullscreen);
+
+        // Not every platform restores the window by itself.
+        if mode == WindowMode::Windowed
+            && let Some((position, size)) = self.windowed_geometry.take()
+        {
+            self.window.set_outer_position(position.into());
+            let _ = self.window.request_surface_size(size.into());
+        }
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        match self.window.fullscreen() {
+            None => WindowMode::Windowed,
+            Some(Fullscreen::Borderless(_)) => WindowMode::BorderlessFullscreen,
+            Some(Fullscreen::Exclusive(..)) => WindowMode::ExclusiveFullscreen,
+        }
+    }
+
+    /// The monitor that most of this window is on, or `None` if it can't be
+    /// determined.
+    pub fn current_monitor(&self) -> Option<MonitorInfo> {
+        let monitor = self.window.current_monitor()?;
+
+        Some(MonitorInfo {
+            name: monitor.name().map(|name| name.to_string()),
+            position: monitor
+                .position()
+                .map(|p| Point2::new(p.x as f32, p.y as f32)),
+            size: monitor.current_video_mode().map(|mode| {
+                let size = mode.size();
+                Size2::new(size.width as f32, size.height as f32)
+            }),
+            scale_factor: monitor.scale_factor() as f32,
+        })
+    }
+
+    /// The appearance the platform prefers for this window. Windows are
+    /// redrawn when it changes, so an app can follow it by comparing it to
+    /// the appearance of its theme each frame and calling
+    /// [`Self::set_theme`] when they differ.
+    ///
+    /// This is [`Appearance::Light`] on platforms that don't report a
+    /// preference.
+    pub fn system_appearance(&self) -> Appearance {
+        self.window
+            .theme()
+            .map(Appearance::from)
+            .unwrap_or_default()
+    }
+
+    /// This window's current title.
+    pub fn title(&self) -> &str {
+        self.title.as_ref()
+    }
+
     pub fn request_repaint(&self) {
         self.window.request_redraw();
     }
 
+    /// Draws this window again once `delay` has passed, even if there is no
+    /// input, e.g. for the next step of an animation.
+    pub fn request_repaint_after(&mut self, delay: Duration) {
+        self.repaint.request_at(Instant::now() + delay);
+    }
+
+    /// Draws this window every frame while enabled, instead of only in
+    /// response to input. Frames are paced by the window's present mode.
+    pub fn set_continuous_repaint(&mut self, continuous: bool) {
+        self.repaint.continuous = continuous;
+    }
+
+    pub fn is_continuous_repaint(&self) -> bool {
+        self.repaint.continuous
+    }
+
+    /// Draws this window again once `delay` has passed, and reports then
+    /// that the timer `id` fired through [`Self::timer_fired`]. Setting a
+    /// timer that is already running restarts it.
+    pub fn set_timeout(&mut self, delay: Duration, id: &'static str) {
+        self.timers.set(id, Instant::now() + delay, None);
+    }
+
+    /// Like [`Self::set_timeout`], but fires every `period` until cancelled.
+    pub fn set_interval(&mut self, period: Duration, id: &'static str) {
+        self.timers.set(id, Instant::now() + period, Some(period));
+    }
+
+    pub fn cancel_timer(&mut self, id: &str) {
+        self.timers.cancel(id);
+    }
+
+    /// Whether the timer `id` fired since this window was last drawn.
+    pub fn timer_fired(&self, id: &str) -> bool {
+        self.timers.has_fired(id)
+    }
+
+    /// Runs `task` on another thread. The task reports its progress through
+    /// the sender it is given, and all windows are redrawn when it does, so
+    /// that the returned handle can be read each frame, e.g. for a progress
+    /// bar.
+    pub fn spawn<P: Send + 'static>(
+        &mut self,
+        task: impl FnOnce(ProgressSender<P>) + Send + 'static,
+    ) -> TaskHandle<P> {
+        let (handle, status) = super::task::spawn(task);
+        self.tasks.push(status);
+        handle
+    }
+
+    /// Runs `command` when `shortcut` is pressed in any window, from the next
+    /// frame on. Check for it with [`UiBuilder::shortcut_pressed`].
+    ///
+    /// A command has one shortcut at a time, so registering it again replaces
+    /// its shortcut. While a text edit is focused, shortcuts without Ctrl,
+    /// Alt, or Meta are left to it. The key events of pressed shortcuts are
+    /// consumed, so widgets don't also react to them.
+    pub fn register_shortcut(&mut self, shortcut: Shortcut, command: &'static str) {
+        self.shortcuts.register(shortcut, command);
+    }
+
+    pub fn unregister_shortcut(&mut self, command: &str) {
+        self.shortcuts.unregister(command);
+    }
+
+    /// Runs `future` to completion on another thread. Windows are redrawn
+    /// when it completes, and its output can then be taken from the returned
+    /// handle with [`TaskHandle::take`].
+    ///
+    /// The future is driven by a minimal executor, so futures that need a
+    /// particular runtime, like tokio's, must enter it themselves.
+    pub fn spawn_async<T: Send + 'static>(
+        &mut self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> TaskHandle<T> {
+        self.spawn(move |sender| sender.send(pollster::block_on(future)))
+    }
+
     pub fn load_image(&self, path: impl AsRef<Path>) -> Result<Texture, TextureLoadError> {
         self.graphics.load_image(path)
     }
 
+    /// Loads an image from a file, from memory, or from a URL. The window is
+    /// redrawn as images fetched from URLs arrive.
+    pub fn load_image_source(&self, source: impl Into<ImageSource>) -> ImageHandle {
+        self.graphics.load_image_source(&source.into())
+    }
+
+    /// Sets the fetcher used to load images from URLs.
+    pub fn set_image_fetcher(&self, fetcher: Arc<dyn ImageFetcher>) {
+        self.graphics.set_image_fetcher(fetcher);
+    }
+
     pub fn pick_file(&self, dialog: FileDialog) -> Option<PathBuf> {
         dialog.builder(self.window).pick_file()
     }
@@ -110,3 +509,16 @@ impl Context<'_> {
         dialog.builder(self.window).pick_folders()
     }
 }
+
+/// Compares formatted output against a string without allocating. Fails as
+/// soon as the output diverges.
+struct PrefixMatcher<'a> {
+    remaining: &'a str,
+}
+
+impl fmt::Write for PrefixMatcher<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.remaining = self.remaining.strip_prefix(s).ok_or(fmt::Error)?;
+        Ok(())
+    }
+}
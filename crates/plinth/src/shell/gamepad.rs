@@ -0,0 +1,110 @@
+use std::time::Duration;
+use std::time::Instant;
+
+use gilrs::Axis;
+use gilrs::Button;
+use gilrs::EventType;
+use gilrs::Gilrs;
+use tracing::warn;
+
+use super::NavigationDirection;
+use super::NavigationEvent;
+
+/// How often gamepads are polled while one is connected. Fast enough that
+/// holding the stick doesn't feel laggy.
+const POLL_INTERVAL: Duration = Duration::from_millis(16);
+/// How often to check for newly connected gamepads while none are.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// How far a stick must be pushed to move focus. It must return inside this
+/// before it moves focus again.
+const STICK_THRESHOLD: f32 = 0.5;
+
+/// Turns gamepad input into [`NavigationEvent`]s: the d-pad and left stick
+/// move focus, South (A on Xbox pads) activates and East (B) cancels.
+///
+/// gilrs has no way to wake the event loop, so gamepads are polled.
+pub(super) struct Gamepads {
+    gilrs: Gilrs,
+    next_check: Instant,
+    /// The direction the left stick was last pushed in on each axis, so that
+    /// holding it moves focus only once.
+    stick: (Option<NavigationDirection>, Option<NavigationDirection>),
+}
+
+impl Gamepads {
+    pub fn new() -> Option<Self> {
+        match Gilrs::new() {
+            Ok(gilrs) => Some(Self {
+                gilrs,
+                next_check: Instant::now(),
+                stick: (None, None),
+            }),
+            Err(error) => {
+                warn!(%error, "Unable to read gamepads");
+                None
+            }
+        }
+    }
+
+    /// The next time gamepads should be polled.
+    pub fn next_check(&self) -> Instant {
+        self.next_check
+    }
+
+    /// Reads the gamepad events since the last poll into `events`.
+    pub fn poll(&mut self, now: Instant, events: &mut impl Extend<NavigationEvent>) {
+        if now < self.next_check {
+            return;
+        }
+
+        while let Some(event) = self.gilrs.next_event() {
+            match event.event {
+                EventType::ButtonPressed(button, _) | EventType::ButtonRepeated(button, _) => {
+                    let event = match button {
+                        Button::DPadUp => NavigationEvent::Move(NavigationDirection::Up),
+                        Button::DPadDown => NavigationEvent::Move(NavigationDirection::Down),
+                        Button::DPadLeft => NavigationEvent::Move(NavigationDirection::Left),
+                        Button::DPadRight => NavigationEvent::Move(NavigationDirection::Right),
+                        Button::South => NavigationEvent::Activate,
+                        Button::East => NavigationEvent::Cancel,
+                        _ => continue,
+                    };
+                    events.extend([event]);
+                }
+                EventType::AxisChanged(Axis::LeftStickX, value, _) => {
+                    let direction = (value.abs() >= STICK_THRESHOLD).then_some(if value < 0.0 {
+                        NavigationDirection::Left
+                    } else {
+                        NavigationDirection::Right
+                    });
+
+                    if direction.is_some() && direction != self.stick.0 {
+                        events.extend(direction.map(NavigationEvent::Move));
+                    }
+                    self.stick.0 = direction;
+                }
+                EventType::AxisChanged(Axis::LeftStickY, value, _) => {
+                    // gilrs reports up as positive.
+                    let direction = (value.abs() >= STICK_THRESHOLD).then_some(if value < 0.0 {
+                        NavigationDirection::Down
+                    } else {
+                        NavigationDirection::Up
+                    });
+
+                    if direction.is_some() && direction != self.stick.1 {
+                        events.extend(direction.map(NavigationEvent::Move));
+                    }
+                    self.stick.1 = direction;
+                }
+                _ => {}
+            }
+        }
+
+        let interval = if self.gilrs.gamepads().next().is_some() {
+            POLL_INTERVAL
+        } else {
+            IDLE_POLL_INTERVAL
+        };
+        self.next_check = now + interval;
+    }
+}
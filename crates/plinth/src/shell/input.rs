@@ -1,11 +1,15 @@
+use std::ops::Range;
+use std::path::PathBuf;
 use std::time::Duration;
 use std::time::Instant;
 
 use glamour::Contains;
 use glamour::Point2;
 use glamour::Size2;
+use glamour::Vector2;
 use keyboard_types::Location;
 use smallvec::SmallVec;
+use winit::keyboard::KeyCode;
 use winit::keyboard::PhysicalKey;
 use winit::keyboard::SmolStr;
 
@@ -38,23 +42,99 @@ impl MouseButtonState {
     }
 }
 
-#[derive(Clone, Debug, Default)]
+/// Positions and sizes within the window are in logical pixels, which the UI
+/// is laid out in, and are scaled by `scale_factor` when drawn.
+#[derive(Clone, Debug)]
 pub struct Input {
     pub pointer: Point2<Pixels>,
     pub prev_pointer: Point2<Pixels>,
     pub mouse_state: MouseButtonState,
+    /// How far the mouse wheel or touchpad scrolled since the last frame, in
+    /// pixels. Positive values scroll toward the start of the content, i.e.
+    /// up or left.
+    pub scroll_delta: Vector2<Pixels>,
     pub window_size: WindowSize,
+    /// Where the top-left corner of the window's content is on the screen in
+    /// physical pixels, or `None` if the platform doesn't say, as on Wayland.
+    pub window_position: Option<Point2<Pixels>>,
+    /// The display scale of the window, e.g. 1.5 at 150%: the number of
+    /// physical pixels to a logical pixel.
+    pub scale_factor: f32,
     pub keyboard_events: SmallVec<[KeyboardEvent; 4]>,
+    pub ime_events: SmallVec<[ImeEvent; 1]>,
+    /// Focus movement from a gamepad, sent to the focused window.
+    pub navigation_events: SmallVec<[NavigationEvent; 1]>,
+    /// The files being dragged over the window from another application.
+    pub hovered_files: Vec<PathBuf>,
+    /// The files dropped on the window this frame, at `pointer`.
+    pub dropped_files: Vec<PathBuf>,
     pub modifiers: winit::keyboard::ModifiersState,
 }
 
+impl Default for Input {
+    fn default() -> Self {
+        Self {
+            pointer: Point2::default(),
+            prev_pointer: Point2::default(),
+            mouse_state: MouseButtonState::default(),
+            scroll_delta: Vector2::default(),
+            window_size: WindowSize::default(),
+            window_position: None,
+            scale_factor: 1.0,
+            keyboard_events: SmallVec::new(),
+            ime_events: SmallVec::new(),
+            navigation_events: SmallVec::new(),
+            hovered_files: Vec::new(),
+            dropped_files: Vec::new(),
+            modifiers: winit::keyboard::ModifiersState::default(),
+        }
+    }
+}
+
 impl Input {
     pub fn focus_changed(&mut self) {
         *self = Self {
             window_size: self.window_size,
+            window_position: self.window_position,
+            scale_factor: self.scale_factor,
             ..Default::default()
         }
     }
+
+    /// Converts a point in the window to screen coordinates, if the window's
+    /// position is known.
+    pub fn window_to_screen(&self, point: Point2<Pixels>) -> Option<Point2<Pixels>> {
+        self.window_position.map(|origin| {
+            Point2::new(
+                origin.x + point.x * self.scale_factor,
+                origin.y + point.y * self.scale_factor,
+            )
+        })
+    }
+
+    /// Converts a point on the screen to window coordinates, if the window's
+    /// position is known.
+    pub fn screen_to_window(&self, point: Point2<Pixels>) -> Option<Point2<Pixels>> {
+        self.window_position.map(|origin| {
+            Point2::new(
+                (point.x - origin.x) / self.scale_factor,
+                (point.y - origin.y) / self.scale_factor,
+            )
+        })
+    }
+
+    /// Whether the keyboard shortcut for opening a context menu was pressed
+    /// this frame: either the Menu key or Shift+F10.
+    pub fn is_context_menu_key_pressed(&self) -> bool {
+        self.keyboard_events.iter().any(|event| {
+            event.state.is_pressed()
+                && match event.key {
+                    PhysicalKey::Code(KeyCode::ContextMenu) => true,
+                    PhysicalKey::Code(KeyCode::F10) => self.modifiers.shift_key(),
+                    _ => false,
+                }
+        })
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -66,6 +146,41 @@ pub struct KeyboardEvent {
     pub state: ElementState,
 }
 
+/// Text from an input method, which is only sent while a text edit is
+/// focused.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImeEvent {
+    /// Replaces the text being composed, which is cleared if `text` is empty.
+    /// `cursor` is the byte range of the input method's cursor in `text`, if
+    /// it shows one.
+    Preedit {
+        text: String,
+        cursor: Option<Range<usize>>,
+    },
+    /// Ends composition, entering `text` in place of the composed text.
+    Commit(String),
+}
+
+/// Moves focus between widgets without a pointer or keyboard, e.g. with a
+/// gamepad.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NavigationEvent {
+    /// Focuses the nearest widget in a direction that handles presses.
+    Move(NavigationDirection),
+    /// Activates the focused widget as if it were clicked.
+    Activate,
+    /// Clears focus.
+    Cancel,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NavigationDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ElementState {
     Pressed,
@@ -94,8 +209,31 @@ impl From<winit::event::ElementState> for ElementState {
 // Windows default value, good enough if we can't get the system settings.
 const DEFAULT_MAX_CLICK_INTERVAL: Duration = Duration::from_millis(500);
 const DEFAULT_MAX_CLICK_SLOP: f32 = 4.0;
+#[cfg(target_os = "windows")]
 const WINDOWS_STANDARD_DPI: f64 = 96.0;
 
+/// The cursor's position on the screen in physical pixels, or `None` if the
+/// platform can't report it without a window under the cursor.
+pub(crate) fn cursor_screen_position() -> Option<Point2<Pixels>> {
+    #[cfg(target_os = "windows")]
+    {
+        use windows_sys::Win32::Foundation::POINT;
+        use windows_sys::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+        let mut point = POINT { x: 0, y: 0 };
+        if unsafe { GetCursorPos(&mut point) } == 0 {
+            return None;
+        }
+
+        Some(Point2::new(point.x as f32, point.y as f32))
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        None
+    }
+}
+
 /// Tracks double-click state for mouse buttons.
 ///
 /// Winit doesn't currently provide double-click events, so we have to track
@@ -111,6 +249,9 @@ pub(crate) struct DoubleClickTracker {
 }
 
 impl DoubleClickTracker {
+    /// The slop is measured in logical pixels, like the pointer. Only Windows
+    /// reports it for a given scale.
+    #[cfg_attr(not(target_os = "windows"), expect(unused_variables))]
     pub fn load_parameters(scale_factor: f64) -> Self {
         let max_click_interval;
         let max_click_slop;
@@ -127,9 +268,9 @@ impl DoubleClickTracker {
             let get_slop = |metric| {
                 let value = unsafe { GetSystemMetricsForDpi(metric, dpi) };
                 if value == 0 {
-                    DEFAULT_MAX_CLICK_SLOP * scale_factor as f32
+                    DEFAULT_MAX_CLICK_SLOP
                 } else {
-                    value as f32
+                    value as f32 / scale_factor as f32
                 }
             };
 
@@ -146,10 +287,7 @@ impl DoubleClickTracker {
         #[cfg(not(target_os = "windows"))]
         {
             max_click_interval = DEFAULT_MAX_CLICK_INTERVAL;
-            max_click_slop = Size2::new(
-                DEFAULT_MAX_CLICK_SLOP * scale_factor as f32,
-                DEFAULT_MAX_CLICK_SLOP * scale_factor as f32,
-            );
+            max_click_slop = Size2::new(DEFAULT_MAX_CLICK_SLOP, DEFAULT_MAX_CLICK_SLOP);
         }
 
         Self {
@@ -0,0 +1,70 @@
+use winit::keyboard::KeyCode;
+use winit::keyboard::ModifiersState;
+use winit::keyboard::PhysicalKey;
+
+use super::KeyboardEvent;
+
+/// A key combination that runs a command, e.g. Ctrl+S.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    /// The modifiers that must be held, and no others.
+    pub modifiers: ModifiersState,
+    pub key: KeyCode,
+}
+
+impl Shortcut {
+    pub const fn new(modifiers: ModifiersState, key: KeyCode) -> Self {
+        Self { modifiers, key }
+    }
+
+    /// Whether the shortcut is pressed by `event` while `modifiers` are held.
+    pub fn matches(&self, event: &KeyboardEvent, modifiers: ModifiersState) -> bool {
+        event.state.is_pressed()
+            && event.key == PhysicalKey::Code(self.key)
+            && modifiers == self.modifiers
+    }
+
+    /// Whether the key combination would type text into a text edit, so that
+    /// it must be left alone while one is focused.
+    fn enters_text(&self) -> bool {
+        !(self.modifiers.control_key() || self.modifiers.alt_key() || self.modifiers.meta_key())
+    }
+}
+
+/// The shortcuts registered with [`Context::register_shortcut`](super::Context::register_shortcut).
+#[derive(Default)]
+pub(crate) struct Shortcuts {
+    commands: Vec<(Shortcut, &'static str)>,
+}
+
+impl Shortcuts {
+    /// Registers `shortcut` for `command`, replacing any shortcut the command
+    /// had before, so that registering the same shortcut every frame is
+    /// cheap.
+    pub fn register(&mut self, shortcut: Shortcut, command: &'static str) {
+        match self.commands.iter_mut().find(|(_, c)| *c == command) {
+            Some(entry) => entry.0 = shortcut,
+            None => self.commands.push((shortcut, command)),
+        }
+    }
+
+    pub fn unregister(&mut self, command: &str) {
+        self.commands.retain(|(_, c)| *c != command);
+    }
+
+    /// The commands whose shortcuts `event` pressed. While a text edit is
+    /// focused, only shortcuts that don't type text are matched.
+    pub fn matching<'a>(
+        &'a self,
+        event: &'a KeyboardEvent,
+        modifiers: ModifiersState,
+        is_text_focused: bool,
+    ) -> impl Iterator<Item = &'static str> + 'a {
+        self.commands
+            .iter()
+            .filter(move |(shortcut, _)| {
+                shortcut.matches(event, modifiers) && !(is_text_focused && shortcut.enters_text())
+            })
+            .map(|(_, command)| *command)
+    }
+}
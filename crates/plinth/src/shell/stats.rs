@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use crate::graphics::CacheStats;
+use crate::graphics::RenderStats;
+
+/// Statistics about the most recently drawn frame, for building performance
+/// overlays. Counts are summed across all windows drawn that frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameStats {
+    /// The number of layout nodes built.
+    pub nodes: usize,
+    /// Time spent computing layout on the CPU.
+    pub layout_time: Duration,
+    /// Text layouts reused from the previous frame, and those that had to be
+    /// shaped.
+    pub text_layouts: CacheStats,
+    /// Glyphs drawn from the glyph atlas, those that had to be rasterized,
+    /// and those evicted to stay within
+    /// [`FrameBudget::max_cached_glyphs`](super::FrameBudget::max_cached_glyphs).
+    pub glyphs: CacheStats,
+    /// The number of glyphs in the glyph atlas after the frame.
+    pub cached_glyphs: usize,
+    /// Text inputs whose text kept its styling from the previous frame, and
+    /// those that had to be restyled.
+    pub styles: CacheStats,
+    /// Bytes of images and glyphs written to textures.
+    pub texture_upload_bytes: usize,
+    /// Work submitted to the GPU.
+    pub render: RenderStats,
+}
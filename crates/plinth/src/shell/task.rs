@@ -0,0 +1,111 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+/// How often running tasks are checked for new progress. Progress bars don't
+/// need to be updated more often than this.
+pub(super) const POLL_INTERVAL: Duration = Duration::from_millis(33);
+
+/// A task running on another thread, started with
+/// [`Context::spawn`](super::Context::spawn).
+///
+/// Windows are redrawn whenever the task reports progress, so the latest value
+/// can be read while building the UI.
+pub struct TaskHandle<P> {
+    shared: Arc<TaskShared<P>>,
+}
+
+impl<P: Clone> TaskHandle<P> {
+    /// The value the task last reported, if it has reported any.
+    pub fn progress(&self) -> Option<P> {
+        self.shared.latest.lock().unwrap().clone()
+    }
+}
+
+impl<P> TaskHandle<P> {
+    /// Takes the value the task last reported, leaving none until it reports
+    /// another. For tasks started with
+    /// [`Context::spawn_async`](super::Context::spawn_async), this is the
+    /// output of the future once it completes.
+    pub fn take(&self) -> Option<P> {
+        self.shared.latest.lock().unwrap().take()
+    }
+
+    /// Whether the task has returned.
+    pub fn is_finished(&self) -> bool {
+        self.shared.status.is_finished()
+    }
+}
+
+/// Reports the progress of a task to its [`TaskHandle`]. The task is finished
+/// once this is dropped, which happens when the task returns unless it was
+/// moved elsewhere.
+pub struct ProgressSender<P> {
+    shared: Arc<TaskShared<P>>,
+}
+
+impl<P> ProgressSender<P> {
+    /// Replaces the task's progress. Values that the UI didn't get to see
+    /// before being replaced are skipped.
+    pub fn send(&self, value: P) {
+        *self.shared.latest.lock().unwrap() = Some(value);
+        self.shared.status.changed.store(true, Ordering::Release);
+    }
+}
+
+impl<P> Drop for ProgressSender<P> {
+    fn drop(&mut self) {
+        // Set in this order so that a task that is seen to be finished has
+        // its last change seen too.
+        self.shared.status.changed.store(true, Ordering::SeqCst);
+        self.shared.status.finished.store(true, Ordering::SeqCst);
+    }
+}
+
+struct TaskShared<P> {
+    latest: Mutex<Option<P>>,
+    status: Arc<TaskStatus>,
+}
+
+/// What the app needs to know about a task to redraw its windows, without
+/// knowing the task's progress type.
+#[derive(Default)]
+pub(super) struct TaskStatus {
+    changed: AtomicBool,
+    finished: AtomicBool,
+}
+
+impl TaskStatus {
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::SeqCst)
+    }
+
+    /// Whether the task reported progress or finished since this was last
+    /// called.
+    pub fn take_changed(&self) -> bool {
+        self.changed.swap(false, Ordering::SeqCst)
+    }
+}
+
+/// Runs `task` on a new thread, returning its handle and the status the app
+/// polls to redraw windows.
+pub(super) fn spawn<P: Send + 'static>(
+    task: impl FnOnce(ProgressSender<P>) + Send + 'static,
+) -> (TaskHandle<P>, Arc<TaskStatus>) {
+    let status = Arc::new(TaskStatus::default());
+    let shared = Arc::new(TaskShared {
+        latest: Mutex::new(None),
+        status: status.clone(),
+    });
+
+    std::thread::spawn({
+        let sender = ProgressSender {
+            shared: shared.clone(),
+        };
+        move || task(sender)
+    });
+
+    (TaskHandle { shared }, status)
+}
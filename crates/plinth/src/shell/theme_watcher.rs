@@ -0,0 +1,74 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+use tracing::info;
+use tracing::warn;
+
+use crate::ui::Theme;
+
+/// How often the theme file's modification time is checked.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Reapplies a theme file to the app's theme whenever the file changes.
+///
+/// The file is polled rather than watched with OS notifications, which keeps
+/// this dependency-free and copes with editors that replace files on save.
+pub(super) struct ThemeWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    next_check: Instant,
+}
+
+impl ThemeWatcher {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            modified: modified_time(&path),
+            path,
+            next_check: Instant::now() + POLL_INTERVAL,
+        }
+    }
+
+    /// The next time the file should be checked.
+    pub fn next_check(&self) -> Instant {
+        self.next_check
+    }
+
+    /// Reapplies the file to `theme` if it has changed since the last check.
+    /// Returns `true` if the theme was modified.
+    pub fn poll(&mut self, now: Instant, theme: &mut Theme) -> bool {
+        if now < self.next_check {
+            return false;
+        }
+
+        self.next_check = now + POLL_INTERVAL;
+
+        let modified = modified_time(&self.path);
+        if modified == self.modified {
+            return false;
+        }
+
+        self.modified = modified;
+
+        // Editors may truncate the file before writing it, so errors are
+        // reported but the last good theme is kept.
+        match theme.apply_file(&self.path) {
+            Ok(()) => {
+                info!(path = %self.path.display(), "Reloaded theme");
+                true
+            }
+            Err(error) => {
+                warn!(path = %self.path.display(), %error, "Unable to reload theme");
+                false
+            }
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
@@ -0,0 +1,149 @@
+use std::time::Duration;
+use std::time::Instant;
+
+use smallvec::SmallVec;
+
+struct Timer {
+    id: &'static str,
+    deadline: Instant,
+    /// How often the timer repeats, or `None` if it only fires once.
+    period: Option<Duration>,
+}
+
+/// A window's timers, set with [`Context::set_timeout`](super::Context::set_timeout)
+/// and [`Context::set_interval`](super::Context::set_interval).
+#[derive(Default)]
+pub(crate) struct Timers {
+    pending: Vec<Timer>,
+    /// The timers that fired since the window was last drawn.
+    fired: SmallVec<[&'static str; 2]>,
+}
+
+impl Timers {
+    /// Starts the timer `id`, replacing any timer with the same id.
+    pub fn set(&mut self, id: &'static str, deadline: Instant, period: Option<Duration>) {
+        self.cancel(id);
+        self.pending.push(Timer {
+            id,
+            deadline,
+            period,
+        });
+    }
+
+    pub fn cancel(&mut self, id: &str) {
+        self.pending.retain(|timer| timer.id != id);
+    }
+
+    pub fn has_fired(&self, id: &str) -> bool {
+        self.fired.contains(&id)
+    }
+
+    /// Fires the timers that are due. Returns `true` if any fired.
+    pub fn poll(&mut self, now: Instant) -> bool {
+        let count = self.fired.len();
+
+        self.pending.retain_mut(|timer| {
+            if timer.deadline > now {
+                return true;
+            }
+
+            if !self.fired.contains(&timer.id) {
+                self.fired.push(timer.id);
+            }
+
+            match timer.period {
+                // Late intervals fire once rather than catching up.
+                Some(period) => {
+                    timer.deadline += period;
+                    if timer.deadline <= now {
+                        timer.deadline = now + period;
+                    }
+                    true
+                }
+                None => false,
+            }
+        });
+
+        self.fired.len() > count
+    }
+
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.pending.iter().map(|timer| timer.deadline).min()
+    }
+
+    /// Forgets the timers that fired, once the window has been drawn.
+    pub fn end_frame(&mut self) {
+        self.fired.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn timers_fire_in_deadline_order() {
+        let start = Instant::now();
+        let mut timers = Timers::default();
+        timers.set("late", start + 2 * SECOND, None);
+        timers.set("early", start + SECOND, None);
+
+        assert!(!timers.poll(start));
+        assert!(timers.poll(start + SECOND));
+        assert!(timers.has_fired("early"));
+        assert!(!timers.has_fired("late"));
+
+        timers.end_frame();
+        assert!(timers.poll(start + 2 * SECOND));
+        assert!(!timers.has_fired("early"));
+        assert!(timers.has_fired("late"));
+    }
+
+    #[test]
+    fn cancelled_timers_never_fire() {
+        let start = Instant::now();
+        let mut timers = Timers::default();
+        timers.set("timeout", start + SECOND, None);
+        timers.cancel("timeout");
+
+        assert!(!timers.poll(start + SECOND));
+        assert!(!timers.has_fired("timeout"));
+        assert_eq!(timers.next_deadline(), None);
+    }
+
+    #[test]
+    fn intervals_repeat_without_catching_up() {
+        let start = Instant::now();
+        let mut timers = Timers::default();
+        timers.set("interval", start + SECOND, Some(SECOND));
+
+        assert!(timers.poll(start + SECOND));
+        timers.end_frame();
+        assert!(timers.poll(start + 2 * SECOND));
+        timers.end_frame();
+
+        // Missed periods fire once, and the next is a period from now.
+        assert!(timers.poll(start + 5 * SECOND + SECOND / 2));
+        assert_eq!(
+            timers.next_deadline(),
+            Some(start + 6 * SECOND + SECOND / 2)
+        );
+    }
+
+    #[test]
+    fn next_deadline_skips_fired_timers() {
+        let start = Instant::now();
+        let mut timers = Timers::default();
+        timers.set("timeout", start + SECOND, None);
+        timers.set("interval", start + 2 * SECOND, Some(3 * SECOND));
+        assert_eq!(timers.next_deadline(), Some(start + SECOND));
+
+        timers.poll(start + SECOND);
+        assert_eq!(timers.next_deadline(), Some(start + 2 * SECOND));
+
+        timers.poll(start + 2 * SECOND);
+        assert_eq!(timers.next_deadline(), Some(start + 5 * SECOND));
+    }
+}
@@ -1,8 +1,294 @@
 use std::borrow::Cow;
+use std::rc::Rc;
+
+use glamour::Point2;
+use glamour::Size2;
+use tracing::warn;
+use winit::window::ResizeDirection;
+
+use crate::graphics::PresentSettings;
+use crate::ui::Pixels;
+use crate::ui::Theme;
+
+use super::CursorIcon;
+use super::WindowSize;
 
 #[derive(Clone, Debug)]
 pub struct WindowConfig {
     pub title: Cow<'static, str>,
     pub width: u32,
     pub height: u32,
+    /// How the window's frames are synchronized with the display. Can be
+    /// changed later with [`Context::set_present_settings`](super::Context::set_present_settings).
+    pub present: PresentSettings,
+    /// Draws the window every frame instead of only in response to input.
+    /// Can be changed later with [`Context::set_continuous_repaint`](super::Context::set_continuous_repaint).
+    pub continuous_repaint: bool,
+    pub shape: WindowShape,
+    /// Keeps the window's contents out of screenshots and screen recordings
+    /// where the platform supports it, as on Windows and macOS. Can be
+    /// changed later with [`Context::set_content_protected`](super::Context::set_content_protected).
+    pub content_protected: bool,
+    /// The smallest size, in pixels, the user can resize the window to.
+    pub min_size: Option<(u32, u32)>,
+    /// The largest size, in pixels, the user can resize the window to.
+    pub max_size: Option<(u32, u32)>,
+    pub resizable: bool,
+    /// Shows the platform's title bar and borders. Shaped windows never have
+    /// them.
+    pub decorations: bool,
+    pub maximized: bool,
+    /// Where to place the top-left corner of the window, including its frame,
+    /// on the screen. The platform chooses if `None`.
+    pub position: Option<Point2<Pixels>>,
+    /// Lets what's behind the window show through wherever the UI draws
+    /// transparent colors. Shaped windows are always transparent. Can't be
+    /// changed after the window is created.
+    pub transparent: bool,
+    /// Blurs what's behind the transparent parts of the window, as on KDE
+    /// Plasma and macOS, or gives it an acrylic backdrop on Windows 11.
+    /// Implies `transparent`. Has no effect on other platforms.
+    pub blur: bool,
+    pub icon: Option<WindowIcon>,
+    /// Keeps the window above other windows.
+    pub always_on_top: bool,
+    /// Leaves the window open when the user tries to close it, and reports
+    /// it with [`Context::is_close_requested`](super::Context::is_close_requested)
+    /// instead, e.g. to ask about unsaved changes first. Can be changed later
+    /// with [`Context::set_close_intercepted`](super::Context::set_close_intercepted).
+    pub intercept_close: bool,
+    /// Styles the window with its own theme instead of the app's. Can be
+    /// changed later with [`Context::set_window_theme`](super::Context::set_window_theme).
+    pub theme: Option<Rc<Theme>>,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            title: Cow::Borrowed(""),
+            width: 800,
+            height: 600,
+            present: PresentSettings::default(),
+            continuous_repaint: false,
+            shape: WindowShape::Rectangle,
+            content_protected: false,
+            min_size: None,
+            max_size: None,
+            resizable: true,
+            decorations: true,
+            maximized: false,
+            position: None,
+            transparent: false,
+            blur: false,
+            icon: None,
+            always_on_top: false,
+            intercept_close: false,
+            theme: None,
+        }
+    }
+}
+
+/// An image for a window's title bar and task bar entry.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WindowIcon {
+    /// Unpremultiplied RGBA pixels, row by row.
+    pub rgba: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl WindowIcon {
+    pub(super) fn to_winit(&self) -> Option<winit::icon::Icon> {
+        match winit::icon::RgbaIcon::new(self.rgba.clone(), self.width, self.height) {
+            Ok(icon) => Some(icon.into()),
+            Err(error) => {
+                warn!(%error, "Unable to use window icon");
+                None
+            }
+        }
+    }
+}
+
+/// The outline of a window.
+///
+/// Windows of any shape other than [`WindowShape::Rectangle`] have no title
+/// bar or borders and are transparent outside of their shape, where they are
+/// also clipped. Pointer presses outside of the shape are ignored, and on
+/// Windows they pass through to whatever is below the window.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum WindowShape {
+    #[default]
+    Rectangle,
+    /// A rectangle with corners rounded to `radius` pixels.
+    RoundedRectangle { radius: f32 },
+    /// A circle, or a capsule if the window is not square.
+    Circle,
+}
+
+impl WindowShape {
+    /// The radius of the window's corners at the given size.
+    pub fn corner_radius(self, size: WindowSize) -> f32 {
+        let max_radius = size.width.min(size.height) / 2.0;
+
+        match self {
+            WindowShape::Rectangle => 0.0,
+            WindowShape::RoundedRectangle { radius } => radius.clamp(0.0, max_radius),
+            WindowShape::Circle => max_radius,
+        }
+    }
+
+    /// Whether `point` lies within a window of this shape and the given size.
+    pub fn contains(self, size: WindowSize, point: Point2<Pixels>) -> bool {
+        if point.x < 0.0 || point.y < 0.0 || point.x > size.width || point.y > size.height {
+            return false;
+        }
+
+        // Only the corners are cut away. Away from them, the distance from the
+        // point to the rectangle inset by the radius is zero.
+        let radius = self.corner_radius(size);
+        let dx = (radius - point.x)
+            .max(point.x - (size.width - radius))
+            .max(0.0);
+        let dy = (radius - point.y)
+            .max(point.y - (size.height - radius))
+            .max(0.0);
+
+        dx * dx + dy * dy <= radius * radius
+    }
+}
+
+/// Whether a window is shown in a frame or fills a monitor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum WindowMode {
+    #[default]
+    Windowed,
+    /// Fills the window's monitor without changing its video mode.
+    BorderlessFullscreen,
+    /// Takes over the window's monitor in its current video mode. Switching
+    /// apps may minimize the window.
+    ExclusiveFullscreen,
+}
+
+/// A display connected to the computer.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MonitorInfo {
+    pub name: Option<String>,
+    /// Where the top-left corner of the monitor is on the screen, if the
+    /// platform says.
+    pub position: Option<Point2<Pixels>>,
+    /// The monitor's resolution in its current video mode, if known.
+    pub size: Option<Size2<Pixels>>,
+    pub scale_factor: f32,
+}
+
+/// An edge or corner of a window, for resizing it from a UI-drawn border.
+/// See [`UiBuilder::window_resize_region`](crate::ui::UiBuilder::window_resize_region).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WindowEdge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl WindowEdge {
+    /// The cursor shown over a border that resizes from this edge.
+    pub fn cursor(self) -> CursorIcon {
+        match self {
+            WindowEdge::Top | WindowEdge::Bottom => CursorIcon::NsResize,
+            WindowEdge::Left | WindowEdge::Right => CursorIcon::EwResize,
+            WindowEdge::TopLeft | WindowEdge::BottomRight => CursorIcon::NwseResize,
+            WindowEdge::TopRight | WindowEdge::BottomLeft => CursorIcon::NeswResize,
+        }
+    }
+}
+
+impl From<WindowEdge> for ResizeDirection {
+    fn from(edge: WindowEdge) -> Self {
+        match edge {
+            WindowEdge::Top => ResizeDirection::North,
+            WindowEdge::Bottom => ResizeDirection::South,
+            WindowEdge::Left => ResizeDirection::West,
+            WindowEdge::Right => ResizeDirection::East,
+            WindowEdge::TopLeft => ResizeDirection::NorthWest,
+            WindowEdge::TopRight => ResizeDirection::NorthEast,
+            WindowEdge::BottomLeft => ResizeDirection::SouthWest,
+            WindowEdge::BottomRight => ResizeDirection::SouthEast,
+        }
+    }
+}
+
+/// Something the UI asked the platform to do with its window, which the
+/// shell carries out after the frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum WindowAction {
+    /// Moves the window with the pointer until the button is released.
+    Drag,
+    /// Resizes the window with the pointer until the button is released.
+    Resize(WindowEdge),
+    ToggleMaximized,
+}
+
+/// Whether the platform prefers light or dark interfaces.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Appearance {
+    #[default]
+    Light,
+    Dark,
+}
+
+impl From<winit::window::Theme> for Appearance {
+    fn from(theme: winit::window::Theme) -> Self {
+        match theme {
+            winit::window::Theme::Light => Appearance::Light,
+            winit::window::Theme::Dark => Appearance::Dark,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: WindowSize = WindowSize {
+        width: 200.0,
+        height: 100.0,
+    };
+
+    #[test]
+    fn rectangle_contains_its_corners() {
+        let shape = WindowShape::Rectangle;
+
+        assert!(shape.contains(SIZE, Point2::new(0.0, 0.0)));
+        assert!(shape.contains(SIZE, Point2::new(200.0, 100.0)));
+        assert!(!shape.contains(SIZE, Point2::new(-1.0, 50.0)));
+        assert!(!shape.contains(SIZE, Point2::new(100.0, 101.0)));
+    }
+
+    #[test]
+    fn rounded_rectangle_cuts_away_its_corners() {
+        let shape = WindowShape::RoundedRectangle { radius: 20.0 };
+
+        assert!(!shape.contains(SIZE, Point2::new(1.0, 1.0)));
+        assert!(!shape.contains(SIZE, Point2::new(199.0, 99.0)));
+        // On the arc, 45 degrees around from the top-left corner.
+        assert!(shape.contains(SIZE, Point2::new(6.0, 6.0)));
+        // Along the edges, away from the corners.
+        assert!(shape.contains(SIZE, Point2::new(0.0, 50.0)));
+        assert!(shape.contains(SIZE, Point2::new(100.0, 0.0)));
+    }
+
+    #[test]
+    fn circle_is_a_capsule_in_wide_windows() {
+        let shape = WindowShape::Circle;
+
+        assert!(shape.contains(SIZE, Point2::new(100.0, 0.0)));
+        assert!(shape.contains(SIZE, Point2::new(0.0, 50.0)));
+        assert!(!shape.contains(SIZE, Point2::new(10.0, 10.0)));
+        assert!(!shape.contains(SIZE, Point2::new(195.0, 90.0)));
+    }
 }
@@ -1,37 +1,205 @@
 use std::collections::HashMap;
+use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
+use glamour::Point2;
+use glamour::Rect;
+use tracing::warn;
 use winit::application::ApplicationHandler;
+use winit::dpi::LogicalPosition;
+use winit::dpi::LogicalSize;
+use winit::dpi::PhysicalPosition;
+use winit::dpi::PhysicalSize;
 use winit::event::ButtonSource;
+use winit::event::Ime;
+use winit::event::MouseScrollDelta;
 use winit::event::WindowEvent;
 use winit::event_loop::ActiveEventLoop;
-use winit::platform::windows::WindowAttributesWindows;
+use winit::event_loop::ControlFlow;
+use winit::window::ImeCapabilities;
+use winit::window::ImeEnableRequest;
+use winit::window::ImeRequest;
+use winit::window::ImeRequestData;
 use winit::window::Window;
 use winit::window::WindowAttributes;
 use winit::window::WindowId;
+use winit::window::WindowLevel;
 
+use crate::graphics::AdapterSettings;
 use crate::graphics::Canvas;
 use crate::graphics::GraphicsContext;
+use crate::shell::CursorIcon;
+use crate::shell::ImeEvent;
 use crate::shell::Input;
 use crate::shell::KeyboardEvent;
+use crate::shell::WindowAction;
 use crate::shell::WindowConfig;
+use crate::shell::WindowIcon;
+use crate::shell::WindowShape;
+use crate::shell::WindowSize;
+use crate::ui::Pixels;
+use crate::ui::Theme;
 use crate::ui::UiBuilder;
+use crate::ui::context::HitTargets;
 use crate::ui::context::UiContext;
 
 use super::app_context::AppContext;
 use super::app_context::AppLifecycleHandler;
 use super::frame::Context;
 use super::input::DoubleClickTracker;
+use super::input::cursor_screen_position;
+use super::timer::Timers;
+
+/// How far one notch of a mouse wheel scrolls. Platforms that report wheel
+/// movement in lines don't say how tall a line is.
+const LINE_SCROLL_PIXELS: f32 = 40.0;
+
+/// How often the cursor is checked while clicks pass through a shaped window,
+/// to notice it moving back inside the shape.
+const CURSOR_POLL_INTERVAL: Duration = Duration::from_millis(16);
 
 pub(super) struct WinitWindow {
     pub window: Arc<dyn Window>,
     pub double_click_tracker: DoubleClickTracker,
 
+    /// The frame time at which this window was last drawn, used to compute
+    /// the elapsed time between its frames.
+    pub last_frame_time: Option<Duration>,
+    /// The refresh interval of the monitor the window is currently on, if the
+    /// platform reports one.
+    pub refresh_interval: Option<Duration>,
+    pub repaint: RepaintSchedule,
+
     pub canvas: Canvas,
     pub ui_context: UiContext,
+    /// The widgets drawn last frame, for [`Context::hit_test`].
+    pub hit_targets: HitTargets,
     pub input: Input,
     pub config: WindowConfig,
     pub handler: Box<dyn FnMut(Context, UiBuilder)>,
+
+    /// The caret last reported to the input method, or `None` if it is
+    /// disabled.
+    pub ime_cursor_area: Option<Rect<Pixels>>,
+    /// The mouse cursor last shown over the window.
+    pub cursor_icon: CursorIcon,
+    /// Where the window was and how big its surface was before it went
+    /// fullscreen, to restore once it leaves.
+    pub windowed_geometry: Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>,
+    /// Whether the user tried to close the window since it was last drawn,
+    /// while closing is intercepted.
+    pub close_requested: bool,
+    pub timers: Timers,
+    /// Whether clicks pass through the window because the cursor is over a
+    /// transparent part of its shape.
+    pub ignores_cursor: bool,
+}
+
+impl WinitWindow {
+    /// Lets clicks on the transparent parts of a shaped window through to
+    /// whatever is below it.
+    ///
+    /// The window gets no pointer events while clicks pass through it, so
+    /// this is only done where the cursor can be polled to tell when it
+    /// moves back inside the shape.
+    pub fn update_cursor_hittest(&mut self, pointer: Point2<Pixels>) {
+        let size = self.input.window_size;
+        let is_over_window =
+            (0.0..=size.width).contains(&pointer.x) && (0.0..=size.height).contains(&pointer.y);
+        let mouse = &self.input.mouse_state;
+        let is_dragging = mouse.is_left_down() || mouse.is_right_down() || mouse.is_middle_down();
+
+        let ignores_cursor = self.config.shape != WindowShape::Rectangle
+            && is_over_window
+            && !is_dragging
+            && !self.config.shape.contains(size, pointer)
+            && cursor_screen_position().is_some();
+
+        if ignores_cursor == self.ignores_cursor {
+            return;
+        }
+
+        match self.window.set_cursor_hittest(!ignores_cursor) {
+            Ok(()) => self.ignores_cursor = ignores_cursor,
+            Err(error) => warn!(%error, "Unable to let clicks through the window"),
+        }
+    }
+
+    /// Enables the input method while a text edit is focused, and keeps its
+    /// candidate window next to the caret.
+    pub fn update_ime(&mut self, cursor_area: Option<Rect<Pixels>>) {
+        if cursor_area == self.ime_cursor_area {
+            return;
+        }
+
+        let request = match cursor_area {
+            Some(area) => {
+                let data = ImeRequestData::default().with_cursor_area(
+                    LogicalPosition::new(area.origin.x, area.origin.y).into(),
+                    LogicalSize::new(area.size.width, area.size.height).into(),
+                );
+
+                if self.ime_cursor_area.is_some() {
+                    ImeRequest::Update(data)
+                } else {
+                    let capabilities = ImeCapabilities::new().with_cursor_area();
+                    // Can't fail, the cursor area is both requested and set.
+                    ImeRequest::Enable(ImeEnableRequest::new(capabilities, data).unwrap())
+                }
+            }
+            None => ImeRequest::Disable,
+        };
+
+        if let Err(error) = self.window.request_ime_update(request) {
+            warn!("failed to update the input method: {error}");
+        }
+
+        self.ime_cursor_area = cursor_area;
+    }
+
+    pub fn apply_action(&mut self, action: WindowAction) {
+        let result = match action {
+            WindowAction::Drag => self.window.drag_window(),
+            WindowAction::Resize(edge) => self.window.drag_resize_window(edge.into()),
+            WindowAction::ToggleMaximized => {
+                self.window.set_maximized(!self.window.is_maximized());
+                Ok(())
+            }
+        };
+
+        if let Err(error) = result {
+            warn!(%error, ?action, "Unable to move or resize window");
+        }
+
+        // The platform takes over the press, and may not report its release.
+        if action != WindowAction::ToggleMaximized {
+            self.input.mouse_state.left_click_count = 0;
+        }
+    }
+
+    pub fn update_cursor(&mut self, icon: CursorIcon) {
+        if icon != self.cursor_icon {
+            self.window.set_cursor(icon.into());
+            self.cursor_icon = icon;
+        }
+    }
+}
+
+/// When a window should be drawn again without waiting for input.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct RepaintSchedule {
+    /// Draw every frame, paced by the window's present mode.
+    pub continuous: bool,
+    /// The earliest time that a redraw has been requested for.
+    pub deadline: Option<Instant>,
+}
+
+impl RepaintSchedule {
+    pub fn request_at(&mut self, at: Instant) {
+        self.deadline = Some(self.deadline.map_or(at, |deadline| deadline.min(at)));
+    }
 }
 
 pub(super) enum DeferredCommand {
@@ -39,6 +207,10 @@ pub(super) enum DeferredCommand {
         config: WindowConfig,
         handler: Box<dyn FnMut(Context, UiBuilder)>,
     },
+    SetAdapterSettings(AdapterSettings),
+    SetTheme(Theme),
+    SetWindowTheme(WindowId, Option<Theme>),
+    Close(WindowId),
 }
 
 pub(super) struct WinitApp<App> {
@@ -49,43 +221,161 @@ pub(super) struct WinitApp<App> {
 }
 
 impl<App> WinitApp<App> {
+    fn close_window(&mut self, window_id: WindowId) {
+        if self.windows.remove(&window_id).is_some() {
+            let graphics = self.runtime.graphics.as_mut().unwrap();
+            graphics.destroy_surface(window_id);
+        }
+    }
+
     fn handle_deferred_commands(&mut self, event_loop: &dyn ActiveEventLoop) {
-        for command in self.runtime.deferred_commands.drain(..) {
+        // Taken rather than drained so that commands can be handled with
+        // methods that need all of `self`.
+        let commands = std::mem::take(&mut self.runtime.deferred_commands);
+        for command in commands {
             match command {
-                DeferredCommand::Create { config, handler } => {
-                    let window = Arc::<dyn Window>::from(
-                        event_loop
-                            .create_window(
-                                WindowAttributes::default()
-                                    .with_visible(false)
-                                    .with_platform_attributes(Box::new(
-                                        WindowAttributesWindows::default()
-                                            .with_no_redirection_bitmap(true),
-                                    )),
-                            )
-                            .unwrap(),
-                    );
+                DeferredCommand::Create {
+                    mut config,
+                    handler,
+                } => {
+                    config.transparent |= config.blur;
 
-                    let graphics = self
-                        .runtime
-                        .graphics
-                        .get_or_insert_with(|| GraphicsContext::new(window.clone()));
+                    let mut attributes = window_attributes(config.blur)
+                        .with_title(config.title.as_ref())
+                        .with_surface_size(PhysicalSize::new(config.width, config.height))
+                        .with_resizable(config.resizable)
+                        .with_decorations(config.decorations)
+                        .with_maximized(config.maximized)
+                        .with_window_icon(config.icon.as_ref().and_then(WindowIcon::to_winit));
+                    if let Some((width, height)) = config.min_size {
+                        attributes =
+                            attributes.with_min_surface_size(PhysicalSize::new(width, height));
+                    }
+                    if let Some((width, height)) = config.max_size {
+                        attributes =
+                            attributes.with_max_surface_size(PhysicalSize::new(width, height));
+                    }
+                    if let Some(position) = config.position {
+                        attributes = attributes.with_position(PhysicalPosition::new(
+                            position.x as i32,
+                            position.y as i32,
+                        ));
+                    }
+                    if config.always_on_top {
+                        attributes = attributes.with_window_level(WindowLevel::AlwaysOnTop);
+                    }
+                    if config.shape != WindowShape::Rectangle {
+                        attributes = attributes.with_transparent(true).with_decorations(false);
+                        config.transparent = true;
+                    } else if config.transparent {
+                        attributes = attributes.with_transparent(true);
+                    }
+                    config.present.transparent |= config.transparent;
+                    attributes = attributes.with_content_protected(config.content_protected);
+
+                    let window =
+                        Arc::<dyn Window>::from(event_loop.create_window(attributes).unwrap());
 
+                    if let Some(graphics) = &mut self.runtime.graphics {
+                        graphics.init_surface(window.clone(), config.present);
+                    }
+
+                    let graphics = self.runtime.graphics.get_or_insert_with(|| {
+                        let graphics = GraphicsContext::new(
+                            window.clone(),
+                            self.runtime.adapter_settings.clone(),
+                            config.present,
+                        );
+
+                        let proxy = event_loop.create_proxy();
+                        graphics.set_fetch_waker(Arc::new(move || proxy.wake_up()));
+                        graphics
+                    });
+
+                    let continuous_repaint = config.continuous_repaint;
                     self.windows.insert(
                         window.id(),
                         WinitWindow {
                             canvas: graphics.create_canvas(),
                             handler,
                             ui_context: UiContext::default(),
-                            input: Input::default(),
+                            hit_targets: HitTargets::default(),
+                            input: Input {
+                                window_size: surface_window_size(window.as_ref()),
+                                window_position: surface_screen_position(window.as_ref()),
+                                scale_factor: window.scale_factor() as f32,
+                                ..Default::default()
+                            },
                             config,
                             double_click_tracker: DoubleClickTracker::load_parameters(
                                 window.scale_factor(),
                             ),
+                            last_frame_time: None,
+                            refresh_interval: monitor_refresh_interval(window.as_ref()),
+                            repaint: RepaintSchedule {
+                                continuous: continuous_repaint,
+                                deadline: None,
+                            },
+                            ime_cursor_area: None,
+                            cursor_icon: CursorIcon::Default,
+                            windowed_geometry: None,
+                            close_requested: false,
+                            timers: Timers::default(),
+                            ignores_cursor: false,
                             window,
                         },
                     );
                 }
+                DeferredCommand::SetAdapterSettings(settings) => {
+                    self.runtime.adapter_settings = settings.clone();
+
+                    let Some(graphics) = self.runtime.graphics.as_mut() else {
+                        continue;
+                    };
+
+                    if let Err(error) = graphics.set_adapter_settings(settings) {
+                        warn!(%error, "Unable to switch graphics adapter");
+                    }
+
+                    for window in self.windows.values() {
+                        window.window.request_redraw();
+                    }
+                }
+                DeferredCommand::SetTheme(theme) => {
+                    self.runtime.set_theme(theme);
+
+                    for window in self.windows.values() {
+                        window.window.request_redraw();
+                    }
+                }
+                DeferredCommand::SetWindowTheme(window_id, theme) => {
+                    let Some(window) = self.windows.get_mut(&window_id) else {
+                        continue;
+                    };
+
+                    // Cached text layouts and style resolutions are keyed by
+                    // revision, so the window must not see the same revision
+                    // from two themes.
+                    let app_theme = &mut self.runtime.theme;
+                    let previous = window
+                        .config
+                        .theme
+                        .as_ref()
+                        .map_or(app_theme.revision(), |theme| theme.revision());
+
+                    window.config.theme = match theme {
+                        Some(mut theme) => {
+                            theme.follow_revision(previous);
+                            Some(Rc::new(theme))
+                        }
+                        None => {
+                            app_theme.follow_revision(previous);
+                            None
+                        }
+                    };
+                    window.window.request_redraw();
+                }
+                DeferredCommand::Close(window_id) => self.close_window(window_id),
             }
         }
 
@@ -105,6 +395,14 @@ impl<App: AppLifecycleHandler> ApplicationHandler for WinitApp<App> {
         }));
     }
 
+    fn proxy_wake_up(&mut self, _event_loop: &dyn ActiveEventLoop) {
+        // Only sent when an image has been fetched. Which windows draw it
+        // isn't known, so all of them are redrawn to pick it up.
+        for window in self.windows.values() {
+            window.window.request_redraw();
+        }
+    }
+
     fn window_event(
         &mut self,
         event_loop: &dyn ActiveEventLoop,
@@ -115,10 +413,8 @@ impl<App: AppLifecycleHandler> ApplicationHandler for WinitApp<App> {
             WindowEvent::PointerMoved { position, .. } => {
                 let window = self.windows.get_mut(&window_id).unwrap();
 
-                window.input.pointer = glamour::Point2 {
-                    x: position.x as f32,
-                    y: position.y as f32,
-                };
+                window.input.pointer = logical_point(position, window.window.scale_factor());
+                window.update_cursor_hittest(window.input.pointer);
 
                 window.window.request_redraw();
             }
@@ -129,6 +425,17 @@ impl<App: AppLifecycleHandler> ApplicationHandler for WinitApp<App> {
                     return;
                 };
 
+                // Releases still count, so that a press that strays outside
+                // the shape can end.
+                if state == winit::event::ElementState::Pressed
+                    && !window
+                        .config
+                        .shape
+                        .contains(window.input.window_size, window.input.pointer)
+                {
+                    return;
+                }
+
                 let click_count =
                     window
                         .double_click_tracker
@@ -160,6 +467,35 @@ impl<App: AppLifecycleHandler> ApplicationHandler for WinitApp<App> {
 
                 window.window.request_redraw();
             }
+            // The pointer doesn't move while files are dragged over the
+            // window, so the drag's position stands in for it.
+            WindowEvent::DragEntered { paths, position } => {
+                let window = self.windows.get_mut(&window_id).unwrap();
+
+                window.input.hovered_files = paths;
+                window.input.pointer = logical_point(position, window.window.scale_factor());
+                window.window.request_redraw();
+            }
+            WindowEvent::DragMoved { position } => {
+                let window = self.windows.get_mut(&window_id).unwrap();
+
+                window.input.pointer = logical_point(position, window.window.scale_factor());
+                window.window.request_redraw();
+            }
+            WindowEvent::DragDropped { paths, position } => {
+                let window = self.windows.get_mut(&window_id).unwrap();
+
+                window.input.hovered_files.clear();
+                window.input.dropped_files = paths;
+                window.input.pointer = logical_point(position, window.window.scale_factor());
+                window.window.request_redraw();
+            }
+            WindowEvent::DragLeft { .. } => {
+                let window = self.windows.get_mut(&window_id).unwrap();
+
+                window.input.hovered_files.clear();
+                window.window.request_redraw();
+            }
             WindowEvent::KeyboardInput { event, .. } => {
                 let window = self.windows.get_mut(&window_id).unwrap();
 
@@ -173,10 +509,52 @@ impl<App: AppLifecycleHandler> ApplicationHandler for WinitApp<App> {
 
                 window.window.request_redraw();
             }
+            WindowEvent::Ime(ime) => {
+                let window = self.windows.get_mut(&window_id).unwrap();
+
+                let event = match ime {
+                    Ime::Preedit(text, cursor) => ImeEvent::Preedit {
+                        text,
+                        cursor: cursor.map(|(start, end)| start..end),
+                    },
+                    Ime::Commit(text) => ImeEvent::Commit(text),
+                    // Anything still being composed is abandoned.
+                    Ime::Disabled => ImeEvent::Preedit {
+                        text: String::new(),
+                        cursor: None,
+                    },
+                    _ => return,
+                };
+
+                window.input.ime_events.push(event);
+                window.window.request_redraw();
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let window = self.windows.get_mut(&window_id).unwrap();
+
+                let (x, y) = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => {
+                        (x * LINE_SCROLL_PIXELS, y * LINE_SCROLL_PIXELS)
+                    }
+                    MouseScrollDelta::PixelDelta(position) => {
+                        let delta = logical_point(position, window.window.scale_factor());
+                        (delta.x, delta.y)
+                    }
+                };
+
+                window.input.scroll_delta.x += x;
+                window.input.scroll_delta.y += y;
+
+                window.window.request_redraw();
+            }
             WindowEvent::ModifiersChanged(modifiers) => {
                 let window = self.windows.get_mut(&window_id).unwrap();
 
                 window.input.modifiers = modifiers.state();
+
+                // Widgets can depend on held modifiers alone, e.g. the style
+                // inspector.
+                window.window.request_redraw();
             }
             WindowEvent::SurfaceResized(physical_size) => {
                 let window = self.windows.get_mut(&window_id).unwrap();
@@ -184,14 +562,17 @@ impl<App: AppLifecycleHandler> ApplicationHandler for WinitApp<App> {
                 window.config.width = physical_size.width;
                 window.config.height = physical_size.height;
 
-                window.input.window_size.width = physical_size.width as f32;
-                window.input.window_size.height = physical_size.height as f32;
+                window.input.window_size = surface_window_size(window.window.as_ref());
             }
             WindowEvent::CloseRequested => {
-                self.windows.remove(&window_id);
+                let window = self.windows.get_mut(&window_id).unwrap();
 
-                let graphics = self.runtime.graphics.as_mut().unwrap();
-                graphics.destroy_surface(window_id);
+                if window.config.intercept_close {
+                    window.close_requested = true;
+                    window.window.request_redraw();
+                } else {
+                    self.close_window(window_id);
+                }
             }
             WindowEvent::RedrawRequested => {
                 let window = self.windows.get_mut(&window_id).unwrap();
@@ -207,10 +588,180 @@ impl<App: AppLifecycleHandler> ApplicationHandler for WinitApp<App> {
             WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
                 let window = self.windows.get_mut(&window_id).unwrap();
                 window.double_click_tracker.on_dpi_changed(scale_factor);
+                window.input.scale_factor = scale_factor as f32;
+                // Not every platform follows a scale change with a resize
+                // event, so pick up the new backing size here.
+                window.input.window_size = surface_window_size(window.window.as_ref());
+                window.input.window_position = surface_screen_position(window.window.as_ref());
+                window.window.request_redraw();
+                window.refresh_interval = monitor_refresh_interval(window.window.as_ref());
+            }
+            WindowEvent::ThemeChanged(_) => {
+                let window = self.windows.get_mut(&window_id).unwrap();
+                window.window.request_redraw();
+            }
+            WindowEvent::Moved(_) => {
+                // The window may have moved to a monitor with a different
+                // refresh rate.
+                let window = self.windows.get_mut(&window_id).unwrap();
+                window.refresh_interval = monitor_refresh_interval(window.window.as_ref());
+                window.input.window_position = surface_screen_position(window.window.as_ref());
             }
             _ => {}
         }
 
         self.handle_deferred_commands(event_loop);
     }
+
+    fn about_to_wait(&mut self, event_loop: &dyn ActiveEventLoop) {
+        let now = Instant::now();
+        let mut next_deadline: Option<Instant> = None;
+
+        for window in self.windows.values_mut() {
+            // The window gets no pointer events while clicks pass through it.
+            if window.ignores_cursor {
+                if let Some(pointer) =
+                    cursor_screen_position().and_then(|point| window.input.screen_to_window(point))
+                {
+                    window.update_cursor_hittest(pointer);
+                }
+
+                if window.ignores_cursor {
+                    let check = now + CURSOR_POLL_INTERVAL;
+                    next_deadline = Some(next_deadline.map_or(check, |next| next.min(check)));
+                }
+            }
+
+            if window.timers.poll(now) {
+                window.window.request_redraw();
+            }
+            if let Some(deadline) = window.timers.next_deadline() {
+                next_deadline = Some(next_deadline.map_or(deadline, |next| next.min(deadline)));
+            }
+
+            match window.repaint.deadline {
+                Some(deadline) if deadline <= now => {
+                    window.repaint.deadline = None;
+                    window.window.request_redraw();
+                }
+                Some(deadline) => {
+                    next_deadline = Some(next_deadline.map_or(deadline, |next| next.min(deadline)));
+                }
+                None => {}
+            }
+        }
+
+        let mut has_progress = false;
+        self.runtime.tasks.retain(|task| {
+            // Checked before taking changes, so that the last change of a
+            // finished task isn't missed.
+            let is_finished = task.is_finished();
+            has_progress |= task.take_changed();
+            !is_finished
+        });
+
+        if has_progress {
+            for window in self.windows.values() {
+                window.window.request_redraw();
+            }
+        }
+
+        if !self.runtime.tasks.is_empty() {
+            let check = now + super::task::POLL_INTERVAL;
+            next_deadline = Some(next_deadline.map_or(check, |next| next.min(check)));
+        }
+
+        #[cfg(feature = "theme-files")]
+        if let Some(watcher) = &mut self.runtime.theme_watcher {
+            if watcher.poll(now, &mut self.runtime.theme) {
+                for window in self.windows.values() {
+                    window.window.request_redraw();
+                }
+            }
+
+            let check = watcher.next_check();
+            next_deadline = Some(next_deadline.map_or(check, |next| next.min(check)));
+        }
+
+        #[cfg(feature = "gamepad")]
+        if let Some(gamepads) = &mut self.runtime.gamepads {
+            let mut events = smallvec::SmallVec::<[_; 4]>::new();
+            gamepads.poll(now, &mut events);
+
+            if !events.is_empty()
+                && let Some(window) = self.windows.values_mut().find(|w| w.window.has_focus())
+            {
+                window.input.navigation_events.extend(events);
+                window.window.request_redraw();
+            }
+
+            let check = gamepads.next_check();
+            next_deadline = Some(next_deadline.map_or(check, |next| next.min(check)));
+        }
+
+        event_loop.set_control_flow(match next_deadline {
+            Some(deadline) => ControlFlow::WaitUntil(deadline),
+            None => ControlFlow::Wait,
+        });
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn window_attributes(blur: bool) -> WindowAttributes {
+    use winit::platform::windows::BackdropType;
+    use winit::platform::windows::WindowAttributesWindows;
+
+    let mut platform = WindowAttributesWindows::default().with_no_redirection_bitmap(true);
+    if blur {
+        platform = platform.with_system_backdrop(BackdropType::TransientWindow);
+    }
+
+    WindowAttributes::default()
+        .with_visible(false)
+        .with_platform_attributes(Box::new(platform))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn window_attributes(blur: bool) -> WindowAttributes {
+    WindowAttributes::default()
+        .with_visible(false)
+        .with_blur(blur)
+}
+
+/// The size of the window's surface in logical pixels, which the UI is laid
+/// out in.
+fn surface_window_size(window: &dyn Window) -> WindowSize {
+    let size = window
+        .surface_size()
+        .to_logical::<f32>(window.scale_factor());
+    WindowSize {
+        width: size.width,
+        height: size.height,
+    }
+}
+
+/// Converts a position in physical pixels, as winit reports it, to the
+/// logical pixels the UI is laid out in.
+fn logical_point(position: PhysicalPosition<f64>, scale_factor: f64) -> Point2<Pixels> {
+    let position = position.to_logical::<f32>(scale_factor);
+    Point2::new(position.x, position.y)
+}
+
+/// Where the top-left corner of the window's surface is on the screen.
+fn surface_screen_position(window: &dyn Window) -> Option<Point2<Pixels>> {
+    let outer = window.outer_position().ok()?;
+    let offset = window.surface_position();
+    Some(Point2::new(
+        (outer.x + offset.x) as f32,
+        (outer.y + offset.y) as f32,
+    ))
+}
+
+fn monitor_refresh_interval(window: &dyn Window) -> Option<Duration> {
+    let millihertz = window
+        .current_monitor()?
+        .current_video_mode()?
+        .refresh_rate_millihertz()?;
+
+    Some(Duration::from_secs_f64(1000.0 / f64::from(millihertz.get())))
 }
@@ -0,0 +1,136 @@
+#[cfg(feature = "accessibility")]
+use accesskit::Action;
+#[cfg(feature = "accessibility")]
+use accesskit::ActionRequest;
+#[cfg(feature = "accessibility")]
+use accesskit::Node;
+#[cfg(feature = "accessibility")]
+use accesskit::NodeId;
+#[cfg(feature = "accessibility")]
+use accesskit::Role;
+#[cfg(feature = "accessibility")]
+use accesskit::Tree;
+#[cfg(feature = "accessibility")]
+use accesskit::TreeUpdate;
+
+#[cfg(feature = "accessibility")]
+use super::WidgetId;
+#[cfg(feature = "accessibility")]
+use super::context::UiContext;
+
+/// What a widget is to a screen reader, see [`UiBuilder::accessible`](super::UiBuilder::accessible).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessRole {
+    Button,
+    Label,
+    TextInput,
+    Tab,
+    Image,
+}
+
+#[cfg(feature = "accessibility")]
+impl From<AccessRole> for Role {
+    fn from(role: AccessRole) -> Self {
+        match role {
+            AccessRole::Button => Role::Button,
+            AccessRole::Label => Role::Label,
+            AccessRole::TextInput => Role::TextInput,
+            AccessRole::Tab => Role::Tab,
+            AccessRole::Image => Role::Image,
+        }
+    }
+}
+
+/// A widget described to screen readers this frame.
+#[cfg(feature = "accessibility")]
+pub(crate) struct AccessibleNode {
+    pub role: AccessRole,
+    pub label: String,
+}
+
+/// Widget ids are never zero, so the window can't collide with a widget.
+#[cfg(feature = "accessibility")]
+const WINDOW_NODE: NodeId = NodeId(0);
+
+#[cfg(feature = "accessibility")]
+impl UiContext {
+    /// The accessibility tree of the last frame: the window, with the
+    /// accessible widgets as its children in the order they were drawn.
+    pub(crate) fn accessibility_tree(&self, title: &str) -> TreeUpdate {
+        let mut nodes = Vec::with_capacity(self.accessible_nodes.len() + 1);
+        let mut children = Vec::with_capacity(self.accessible_nodes.len());
+
+        for (id, accessible) in &self.accessible_nodes {
+            // Widgets that weren't laid out, such as those with no size, have
+            // no bounds to report.
+            let Some(container) = self.widget_states.get(id) else {
+                continue;
+            };
+            let state = &container.state;
+
+            let mut node = Node::new(accessible.role.into());
+            if !accessible.label.is_empty() {
+                node.set_label(accessible.label.as_str());
+            }
+
+            let placement = state.placement;
+            node.set_bounds(accesskit::Rect {
+                x0: placement.origin.x as f64,
+                y0: placement.origin.y as f64,
+                x1: (placement.origin.x + placement.size.width) as f64,
+                y1: (placement.origin.y + placement.size.height) as f64,
+            });
+
+            if state.pointer_phase.is_some() {
+                node.add_action(Action::Click);
+                node.add_action(Action::Focus);
+            }
+
+            let node_id = NodeId(id.to_u64());
+            children.push((state.layer, state.draw_order, node_id));
+            nodes.push((node_id, node));
+        }
+
+        children.sort_unstable_by_key(|&(layer, draw_order, _)| (layer, draw_order));
+
+        let mut window = Node::new(Role::Window);
+        window.set_label(title);
+        window.set_children(
+            children
+                .into_iter()
+                .map(|(_, _, node_id)| node_id)
+                .collect::<Vec<_>>(),
+        );
+        nodes.push((WINDOW_NODE, window));
+
+        let focus = self
+            .focused_widget
+            .get()
+            .filter(|id| self.accessible_nodes.contains_key(id))
+            .map_or(WINDOW_NODE, |id| NodeId(id.to_u64()));
+
+        TreeUpdate {
+            nodes,
+            tree: Some(Tree::new(WINDOW_NODE)),
+            focus,
+        }
+    }
+
+    /// Applies a screen reader's request before the frame is built, so that
+    /// widgets see it as if it were input.
+    pub(crate) fn apply_accessibility_action(&mut self, request: &ActionRequest) {
+        let Some(target) = WidgetId::from_u64(request.target.0) else {
+            return;
+        };
+
+        match request.action {
+            Action::Focus => {
+                self.focused_widget.set(Some(target));
+                self.prev_focused_widget = Some(target);
+                self.is_focus_visible = true;
+            }
+            Action::Click => self.accessibility_click = Some(target),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use std::any::Any;
+use std::time::Duration;
+
+use glamour::Vector2;
+
+use crate::graphics::Color;
+
+use super::IdMap;
+use super::Pixels;
+use super::WidgetId;
+
+/// How an animation moves from its start to its end over time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Starts slowly and speeds up.
+    EaseIn,
+    /// Starts quickly and slows down.
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps the fraction of an animation's duration that has passed to how
+    /// far the value has moved from start to end.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (2.0 - 2.0 * t).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// A value that can be animated with
+/// [`UiBuilder::animate`](super::UiBuilder::animate).
+pub trait Animatable: Copy + PartialEq + 'static {
+    /// The value `t` of the way from `self` to `to`.
+    fn lerp(self, to: Self, t: f32) -> Self;
+}
+
+impl Animatable for f32 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Animatable for Color {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        Color::linear(
+            Animatable::lerp(self.r, to.r, t),
+            Animatable::lerp(self.g, to.g, t),
+            Animatable::lerp(self.b, to.b, t),
+            Animatable::lerp(self.a, to.a, t),
+        )
+    }
+}
+
+impl Animatable for Vector2<Pixels> {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        Vector2::new(
+            Animatable::lerp(self.x, to.x, t),
+            Animatable::lerp(self.y, to.y, t),
+        )
+    }
+}
+
+/// How a widget animates when it appears and after it disappears, set with
+/// [`UiBuilder::transition`](super::UiBuilder::transition).
+///
+/// Widgets enter from the transition's opacity and offset and exit back to
+/// them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transition {
+    pub duration: Duration,
+    pub easing: Easing,
+    pub opacity: f32,
+    /// How far from its place the widget is drawn, in pixels.
+    pub offset: [f32; 2],
+}
+
+impl Transition {
+    pub fn fade(duration: Duration) -> Self {
+        Self {
+            duration,
+            easing: Easing::EaseOut,
+            opacity: 0.0,
+            offset: [0.0; 2],
+        }
+    }
+
+    /// Slides in from, and out to, `offset` while fading.
+    pub fn slide(duration: Duration, offset: [f32; 2]) -> Self {
+        Self {
+            offset,
+            ..Self::fade(duration)
+        }
+    }
+
+    /// How much of the way from hidden to shown a widget moves in the given
+    /// time.
+    pub(super) fn step(&self, time_delta: Duration) -> f32 {
+        if self.duration.is_zero() {
+            1.0
+        } else {
+            time_delta.as_secs_f32() / self.duration.as_secs_f32()
+        }
+    }
+
+    /// The opacity and offset of a widget `shown` of the way from hidden to
+    /// shown.
+    pub(super) fn at(&self, shown: f32) -> (f32, [f32; 2]) {
+        let t = self.easing.apply(shown);
+        (
+            Animatable::lerp(self.opacity, 1.0, t),
+            [
+                Animatable::lerp(self.offset[0], 0.0, t),
+                Animatable::lerp(self.offset[1], 0.0, t),
+            ],
+        )
+    }
+}
+
+struct Tween {
+    /// The start and end values, as `(T, T)`.
+    values: Box<dyn Any>,
+    elapsed: Duration,
+    duration: Duration,
+    easing: Easing,
+    /// Whether the tween was asked for this frame. Tweens that weren't are
+    /// dropped at the start of the next frame.
+    seen: bool,
+}
+
+impl Tween {
+    fn new<T: Animatable>(value: T, duration: Duration, easing: Easing) -> Self {
+        Self {
+            values: Box::new((value, value)),
+            elapsed: duration,
+            duration,
+            easing,
+            seen: false,
+        }
+    }
+
+    fn progress(&self) -> f32 {
+        if self.elapsed >= self.duration {
+            1.0
+        } else {
+            self.easing
+                .apply(self.elapsed.as_secs_f32() / self.duration.as_secs_f32())
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.elapsed < self.duration
+    }
+}
+
+/// Values being animated by widgets, advanced once per frame.
+#[derive(Default)]
+pub(super) struct Animator {
+    tweens: IdMap<Tween>,
+}
+
+impl Animator {
+    /// Advances every animation by the time since the last frame, and drops
+    /// the animations of widgets that weren't built last frame.
+    pub(super) fn begin_frame(&mut self, time_delta: Duration) {
+        self.tweens.retain(|_, tween| tween.seen);
+
+        for tween in self.tweens.values_mut() {
+            tween.seen = false;
+            tween.elapsed = tween.elapsed.saturating_add(time_delta);
+        }
+    }
+
+    /// The value of the animation with the given ID this frame, and whether
+    /// it is still running.
+    ///
+    /// Changing the target starts a new animation from the current value. An
+    /// animation's first target is reached immediately.
+    pub(super) fn animate<T: Animatable>(
+        &mut self,
+        id: WidgetId,
+        target: T,
+        duration: Duration,
+        easing: Easing,
+    ) -> (T, bool) {
+        let tween = self
+            .tweens
+            .entry(id)
+            .or_insert_with(|| Tween::new(target, duration, easing));
+
+        // An ID reused for a value of another type starts over.
+        if !tween.values.is::<(T, T)>() {
+            *tween = Tween::new(target, duration, easing);
+        }
+
+        let progress = tween.progress();
+        let (from, to) = tween.values.downcast_mut::<(T, T)>().unwrap();
+        let current = from.lerp(*to, progress);
+
+        if *to != target {
+            *from = current;
+            *to = target;
+            tween.elapsed = Duration::ZERO;
+            tween.duration = duration;
+            tween.easing = easing;
+        }
+
+        tween.seen = true;
+
+        let (from, to) = *tween.values.downcast_ref::<(T, T)>().unwrap();
+        (from.lerp(to, tween.progress()), tween.is_running())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DURATION: Duration = Duration::from_millis(250);
+
+    #[test]
+    fn first_target_is_immediate() {
+        let mut animator = Animator::default();
+        let id = WidgetId::new("a");
+
+        assert_eq!(
+            animator.animate(id, 1.0f32, DURATION, Easing::Linear),
+            (1.0, false)
+        );
+    }
+
+    #[test]
+    fn new_target_is_tweened() {
+        let mut animator = Animator::default();
+        let id = WidgetId::new("a");
+
+        animator.animate(id, 0.0f32, DURATION, Easing::Linear);
+        assert_eq!(
+            animator.animate(id, 10.0f32, DURATION, Easing::Linear),
+            (0.0, true)
+        );
+
+        animator.begin_frame(DURATION / 2);
+        assert_eq!(
+            animator.animate(id, 10.0f32, DURATION, Easing::Linear),
+            (5.0, true)
+        );
+
+        animator.begin_frame(DURATION);
+        assert_eq!(
+            animator.animate(id, 10.0f32, DURATION, Easing::Linear),
+            (10.0, false)
+        );
+    }
+
+    #[test]
+    fn retargeting_starts_from_current_value() {
+        let mut animator = Animator::default();
+        let id = WidgetId::new("a");
+
+        animator.animate(id, 0.0f32, DURATION, Easing::Linear);
+        animator.animate(id, 10.0f32, DURATION, Easing::Linear);
+        animator.begin_frame(DURATION / 2);
+
+        assert_eq!(
+            animator.animate(id, 0.0f32, DURATION, Easing::Linear),
+            (5.0, true)
+        );
+    }
+
+    #[test]
+    fn unseen_animations_are_dropped() {
+        let mut animator = Animator::default();
+        let id = WidgetId::new("a");
+
+        animator.animate(id, 0.0f32, DURATION, Easing::Linear);
+        animator.begin_frame(DURATION);
+        animator.begin_frame(DURATION);
+
+        assert_eq!(
+            animator.animate(id, 10.0f32, DURATION, Easing::Linear),
+            (10.0, false)
+        );
+    }
+}
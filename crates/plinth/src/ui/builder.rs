@@ -1,37 +1,71 @@
 use std::hash::Hash;
+use std::hash::Hasher;
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 
+use glamour::Contains;
+use glamour::Point2;
+use rapidhash::fast::RapidHasher;
 use rapidhash::v3::rapidhash_v3;
+use winit::keyboard::ModifiersState;
+use winit::keyboard::PhysicalKey;
 
+use crate::graphics::AlphaMask;
+use crate::graphics::BorderColors;
 use crate::graphics::Color;
 use crate::graphics::GradientPaint;
 use crate::graphics::Paint;
+use crate::graphics::SpanStyle;
 use crate::graphics::TextAlignment;
 use crate::graphics::TextLayoutContext;
+use crate::graphics::TextSpan;
 use crate::shell::Clipboard;
+use crate::shell::CursorIcon;
 use crate::shell::Input;
+use crate::shell::KeyboardEvent;
+use crate::shell::WindowAction;
+use crate::shell::WindowEdge;
 
+use super::AccessRole;
 use super::Alignment;
+use super::Animatable;
 use super::Atom;
-use super::Flex;
+use super::Easing;
+use super::Grid;
+use super::GridSpan;
 use super::LayoutDirection;
+use super::Length;
+use super::Overflow;
 use super::OverlayPosition;
 use super::Padding;
+use super::PixelSize;
+use super::Pixels;
 use super::Position;
 use super::Size;
+use super::Transition;
 use super::UiElementId;
 use super::WidgetId;
+#[cfg(feature = "accessibility")]
+use super::accessibility::AccessibleNode;
 use super::context::LayoutContent;
 use super::context::UiContext;
+use super::layout::LengthMetrics;
 use super::style::BorderWidths;
 use super::style::CornerRadii;
 use super::style::StateFlags;
 use super::style::StyleId;
 use super::text::TextLayoutStorage;
 use super::text::TextOverflow;
+use super::text::push_span_styles;
+use super::theme::ClassKey;
 use super::theme::StyleClass;
 use super::theme::Theme;
+use super::widget::ClickBehavior;
+use super::widget::Interaction;
+use super::widget::PointerPhase;
 use super::widget::WidgetState;
+use super::widget::scroll_area::scroll_overflow;
 
 pub struct UiBuilder<'a> {
     pub(super) id: WidgetId,
@@ -40,7 +74,7 @@ pub struct UiBuilder<'a> {
     pub(super) input: &'a Input,
 
     pub(super) context: &'a mut UiContext,
-    pub clipboard: &'a mut Clipboard,
+    pub clipboard: &'a Clipboard,
     pub format_buffer: &'a mut String,
     pub text_context: &'a mut TextLayoutContext,
     pub text_layouts: &'a mut TextLayoutStorage,
@@ -49,11 +83,15 @@ pub struct UiBuilder<'a> {
     /// Overlay children receive `parent.layer + 1`.
     pub(super) layer: u8,
     pub(super) is_modal: bool,
+    /// Propagated to children.
+    pub(super) is_disabled: bool,
 
     pub(super) style_id: StyleId,
     pub(super) state: StateFlags,
     pub(super) num_child_widgets: usize,
     pub(super) text_overflow: TextOverflow,
+    /// Propagated to children.
+    pub(super) drag_threshold: f32,
 }
 
 impl UiBuilder<'_> {
@@ -61,44 +99,161 @@ impl UiBuilder<'_> {
         self.input
     }
 
+    /// The time at which the current frame began, relative to application
+    /// start.
+    pub fn frame_time(&self) -> Duration {
+        self.context.frame_time
+    }
+
+    /// The time elapsed since the previous frame of this window began.
+    ///
+    /// This is zero on the first frame of a window.
     pub fn time_delta(&self) -> &Duration {
         &self.context.time_delta
     }
 
+    /// The refresh interval of the monitor the window is displayed on, if the
+    /// platform reports one.
+    pub fn refresh_interval(&self) -> Option<Duration> {
+        self.context.refresh_interval
+    }
+
+    /// Animates a value of this widget towards `target`, returning its value
+    /// for this frame. `tag` tells apart the animations of one widget.
+    ///
+    /// Changing the target starts a new animation from the current value.
+    /// The first target is reached immediately, so nothing animates when a
+    /// widget first appears.
+    pub fn animate<T: Animatable>(
+        &mut self,
+        tag: impl Hash,
+        target: T,
+        duration: Duration,
+        easing: Easing,
+    ) -> T {
+        let id = self.id.then(tag);
+        let (value, is_running) = self.context.animator.animate(id, target, duration, easing);
+
+        if is_running {
+            self.context.needs_redraw = true;
+        }
+
+        value
+    }
+
+    /// The topmost widget that `point` was over last frame, for custom
+    /// gestures and debugging tools. Parts of widgets that were clipped away
+    /// or masked out with [`hit_mask`](Self::hit_mask) can't be hit.
+    pub fn hit_test(&self, point: Point2<Pixels>) -> Option<WidgetId> {
+        self.context.hit_test(point)
+    }
+
+    /// Draws the window again once `delay` has passed, even if there is no
+    /// input, e.g. to blink a cursor or step an animation.
+    pub fn request_repaint_after(&mut self, delay: Duration) -> &mut Self {
+        let context = &mut *self.context;
+        context.repaint_after = Some(context.repaint_after.map_or(delay, |d| d.min(delay)));
+        self
+    }
+
     pub fn theme(&self) -> &Theme {
         self.theme
     }
 
+    /// What lengths are measured against for this node's style.
+    pub(crate) fn length_metrics(&self) -> LengthMetrics {
+        self.theme.length_metrics(self.style_id, self.state)
+    }
+
     pub fn apply_style(&mut self, class: StyleClass, state: StateFlags) -> &mut Self {
-        let style = self.theme.get(class);
+        self.apply_style_id(self.theme.get_id(class), state)
+    }
+
+    /// Applies a class registered with [`Theme::register_class`], or the
+    /// default style if no class has that name.
+    pub fn apply_named_style(&mut self, name: &str, state: StateFlags) -> &mut Self {
+        self.apply_style_id(self.theme.get_named_id(name), state)
+    }
+
+    /// Applies a class registered with [`Theme::register_class`] without
+    /// looking it up by name.
+    pub fn apply_class_style(&mut self, key: ClassKey, state: StateFlags) -> &mut Self {
+        self.apply_style_id(self.theme.get_class_id(key), state)
+    }
+
+    fn apply_style_id(&mut self, style_id: StyleId, state: StateFlags) -> &mut Self {
+        let style = self.theme.get_style(style_id);
+
+        // Lengths are measured against the new style's font size.
+        self.style_id = style_id;
+        self.state = state;
+        let metrics = self.length_metrics();
+
+        if self.context.inspector_pointer.is_some() {
+            self.context
+                .node_styles
+                .insert(self.index, (self.style_id, state));
+        }
 
         // Paint
-        let paint = style.background.get(state);
-        let border = style.border.get(state);
+        let opacity = style.opacity.get(state);
+        let paint = style.background.get(state).with_opacity(opacity);
+        let border = style.border.get(state).with_opacity(opacity);
+        let border_colors = style
+            .side_border_colors
+            .get(state)
+            .map(|colors| colors.with_opacity(opacity));
         let border_width = style.border_widths.get(state);
         let corner_radii = style.corner_radii.get(state);
-        self.paint(paint, border, border_width, corner_radii);
+        self.paint(paint, border, border_width, corner_radii)
+            .border_colors(border_colors);
 
         // Layout
-        self.style_id = self.theme.get_id(class);
-        self.state = state;
-
         let atom = self.context.ui_tree.atom_mut(self.index);
         // Preserve overlay fields set by the overlay builder API before applying the style.
         let position = atom.position;
         let z_layer = atom.z_layer;
         let is_modal = atom.is_modal;
+        let scroll_offset = atom.scroll_offset;
+        let is_scrolled = atom.overflow == Overflow::Scroll;
+        let grid = atom.grid;
+        let grid_span = atom.grid_span;
+        let stack = atom.stack;
+        let align_self = atom.align_self;
+        let reverse = atom.reverse;
+        let z_index = atom.z_index;
         *atom = Atom {
-            width: style.width.get(state),
-            height: style.height.get(state),
-            inner_padding: style.padding.get(state),
+            width: style.width.get(state).resolve(&metrics).constrain(
+                style.min_width.get(state).resolve(&metrics),
+                style.max_width.get(state).resolve(&metrics),
+            ),
+            height: style.height.get(state).resolve(&metrics).constrain(
+                style.min_height.get(state).resolve(&metrics),
+                style.max_height.get(state).resolve(&metrics),
+            ),
+            inner_padding: style.padding.get(state).resolve(&metrics),
             major_align: style.child_major_alignment.get(state),
             minor_align: style.child_minor_alignment.get(state),
             direction: style.child_direction.get(state),
-            inter_child_padding: style.child_spacing.get(state),
-            clip_overflow: style.clip_children.get(state),
+            inter_child_padding: style.child_spacing.get(state).resolve(&metrics),
+            reverse,
+            grid,
+            grid_span,
+            stack,
+            align_self,
+            // Scrolling is set up by the builder, and styles only choose
+            // whether to clip.
+            overflow: if is_scrolled {
+                Overflow::Scroll
+            } else if style.clip_children.get(state) {
+                Overflow::Clip
+            } else {
+                Overflow::Visible
+            },
+            scroll_offset,
             position,
             z_layer,
+            z_index,
             is_modal,
         };
 
@@ -116,6 +271,7 @@ impl UiBuilder<'_> {
                 *content = LayoutContent::Fill {
                     paint: Paint::solid(color.into()),
                     border: GradientPaint::default(),
+                    border_colors: None,
                     border_width: Default::default(),
                     corner_radii: Default::default(),
                 };
@@ -125,13 +281,96 @@ impl UiBuilder<'_> {
         self
     }
 
-    pub fn clip_children(&mut self) -> &mut Self {
-        self.context.ui_tree.atom_mut(self.index).clip_overflow = true;
+    /// Labels this node in GPU captures such as RenderDoc or PIX.
+    ///
+    /// The label is emitted as a debug group around the draws of this node and
+    /// its descendants on the same layer. Descendants in overlays are drawn
+    /// separately and need labels of their own.
+    pub fn debug_label(&mut self, label: impl Into<String>) -> &mut Self {
+        self.context.debug_labels.insert(self.index, label.into());
         self
     }
 
+    /// Shows the id, style, state, placement, and resolved style properties of
+    /// the widget under the pointer while `modifiers` are held.
+    ///
+    /// Only widgets styled after this is called can be inspected, so call it on
+    /// the root before building anything else.
+    pub fn style_inspector(&mut self, modifiers: ModifiersState) -> &mut Self {
+        if !self.input.modifiers.contains(modifiers) {
+            return self;
+        }
+
+        // Built before the inspector is activated so that it doesn't inspect
+        // itself.
+        if let Some(node) = self.context.inspected_node {
+            super::inspector::show(self, &node);
+        }
+
+        self.context.inspector_pointer = Some(self.input.pointer);
+        self
+    }
+
+    /// Draws this node and its descendants into a texture of their own, which
+    /// is then drawn in their place.
+    ///
+    /// `scale` is the texture's resolution relative to the window, so a scale
+    /// of 0.5 renders a cheaper, lower resolution copy. Only descendants on the
+    /// same layer are included. The texture starts out transparent, so give
+    /// the node an opaque background to avoid darkened translucent edges.
+    pub fn render_to_texture(&mut self, scale: f32) -> &mut Self {
+        debug_assert!(scale > 0.0, "Texture scale must be positive");
+        self.context.texture_layer_scales.insert(self.index, scale);
+        self
+    }
+
+    /// Fades or slides this node and its descendants in when the widget first
+    /// appears, and out once it is no longer built.
+    ///
+    /// The node is drawn into a texture as with [`Self::render_to_texture`],
+    /// which keeps being drawn where the widget was while it transitions out.
+    pub fn transition(&mut self, transition: Transition) -> &mut Self {
+        self.context
+            .texture_layer_scales
+            .entry(self.index)
+            .or_insert(1.0);
+        self.context.transitions.insert(self.index, transition);
+        self
+    }
+
+    /// Limits where this widget can be hovered and pressed to the opaque
+    /// parts of `mask`, stretched over the widget. Presses on the rest of it
+    /// go to whatever is underneath.
+    ///
+    /// Images decoded from files have a mask, see
+    /// [`Texture::alpha_mask`](crate::graphics::Texture::alpha_mask).
+    pub fn hit_mask(&mut self, mask: Arc<AlphaMask>) -> &mut Self {
+        self.context.hit_masks.insert(self.index, mask);
+        self
+    }
+
+    pub fn clip_children(&mut self) -> &mut Self {
+        self.overflow(Overflow::Clip)
+    }
+
     pub fn set_clip_children(&mut self, clip: bool) -> &mut Self {
-        self.context.ui_tree.atom_mut(self.index).clip_overflow = clip;
+        self.overflow(if clip {
+            Overflow::Clip
+        } else {
+            Overflow::Visible
+        })
+    }
+
+    /// Sets what happens to the parts of this node's children that extend
+    /// past it. With [`Overflow::Scroll`], the node scrolls vertically like a
+    /// [`ScrollArea`](super::widget::ScrollArea).
+    pub fn overflow(&mut self, overflow: Overflow) -> &mut Self {
+        self.context.ui_tree.atom_mut(self.index).overflow = overflow;
+
+        if overflow == Overflow::Scroll {
+            scroll_overflow(self);
+        }
+
         self
     }
 
@@ -142,9 +381,12 @@ impl UiBuilder<'_> {
         border_width: BorderWidths,
         corner_radii: CornerRadii,
     ) -> &mut Self {
+        let border_width = border_width.resolve(&self.length_metrics());
+
         self.context.ui_tree.content_mut(self.index).0 = LayoutContent::Fill {
             paint,
             border,
+            border_colors: None,
             border_width,
             corner_radii,
         };
@@ -152,20 +394,36 @@ impl UiBuilder<'_> {
         self
     }
 
+    /// Draws each side of the border set by [`paint`](Self::paint) in its own
+    /// color instead of the border's gradient, or goes back to the gradient
+    /// if `colors` is `None`.
+    pub fn border_colors(&mut self, colors: impl Into<Option<BorderColors>>) -> &mut Self {
+        if let LayoutContent::Fill { border_colors, .. } =
+            &mut self.context.ui_tree.content_mut(self.index).0
+        {
+            *border_colors = colors.into();
+        }
+
+        self
+    }
+
     pub fn width(&mut self, width: impl Into<Size>) -> &mut Self {
-        self.context.ui_tree.atom_mut(self.index).width = width.into();
+        let width = width.into().resolve(&self.length_metrics());
+        self.context.ui_tree.atom_mut(self.index).width = width;
         self
     }
 
     pub fn height(&mut self, height: impl Into<Size>) -> &mut Self {
-        self.context.ui_tree.atom_mut(self.index).height = height.into();
+        let height = height.into().resolve(&self.length_metrics());
+        self.context.ui_tree.atom_mut(self.index).height = height;
         self
     }
 
     pub fn size(&mut self, width: impl Into<Size>, height: impl Into<Size>) -> &mut Self {
+        let metrics = self.length_metrics();
         let atom = self.context.ui_tree.atom_mut(self.index);
-        atom.width = width.into();
-        atom.height = height.into();
+        atom.width = width.into().resolve(&metrics);
+        atom.height = height.into().resolve(&metrics);
         self
     }
 
@@ -191,7 +449,22 @@ impl UiBuilder<'_> {
         self
     }
 
-    pub fn child_spacing(&mut self, spacing: f32) -> &mut Self {
+    /// Lays out this widget's children from the end of its child direction
+    /// instead of the start, without changing the order they're built in.
+    pub fn reverse_children(&mut self, reverse: bool) -> &mut Self {
+        self.context.ui_tree.atom_mut(self.index).reverse = reverse;
+        self
+    }
+
+    /// Draws this widget above siblings with a lower z-index, and below those
+    /// with a higher one, without changing where it is laid out.
+    pub fn z_index(&mut self, z_index: i16) -> &mut Self {
+        self.context.ui_tree.atom_mut(self.index).z_index = z_index;
+        self
+    }
+
+    pub fn child_spacing(&mut self, spacing: impl Into<Length>) -> &mut Self {
+        let spacing = spacing.into().resolve(&self.length_metrics());
         self.context
             .ui_tree
             .atom_mut(self.index)
@@ -199,7 +472,37 @@ impl UiBuilder<'_> {
         self
     }
 
+    /// Lays out this widget's children in a grid instead of along its child
+    /// direction.
+    pub fn grid(&mut self, grid: Grid) -> &mut Self {
+        let grid = grid.resolve(&self.length_metrics());
+        self.context.ui_tree.atom_mut(self.index).grid = Some(grid);
+        self
+    }
+
+    /// Makes this widget cover more than one cell of its parent's grid.
+    pub fn grid_span(&mut self, columns: u16, rows: u16) -> &mut Self {
+        self.context.ui_tree.atom_mut(self.index).grid_span = GridSpan { columns, rows };
+        self
+    }
+
+    /// Places this widget's children on top of each other, each over the
+    /// whole widget, instead of along its child direction. Later children are
+    /// drawn over earlier ones.
+    pub fn stack_children(&mut self) -> &mut Self {
+        self.context.ui_tree.atom_mut(self.index).stack = true;
+        self
+    }
+
+    /// Aligns this widget within its parent's stack, instead of by the
+    /// parent's child alignment.
+    pub fn align_self(&mut self, horizontal: Alignment, vertical: Alignment) -> &mut Self {
+        self.context.ui_tree.atom_mut(self.index).align_self = Some((horizontal, vertical));
+        self
+    }
+
     pub fn padding(&mut self, padding: Padding) -> &mut Self {
+        let padding = padding.resolve(&self.length_metrics());
         self.context.ui_tree.atom_mut(self.index).inner_padding = padding;
         self
     }
@@ -217,6 +520,19 @@ impl UiBuilder<'_> {
         self.text_overflow(TextOverflow::Wrap)
     }
 
+    /// Scales text to the largest font size between `min_size` and
+    /// `max_size` at which it fits its node on one line, e.g. for a value
+    /// shown in a dashboard tile.
+    pub fn fit_text(&mut self, min_size: u16, max_size: u16) -> &mut Self {
+        self.text_overflow(TextOverflow::Fit { min_size, max_size })
+    }
+
+    /// Cuts text off with an ellipsis where it would overflow `max_lines`
+    /// lines, e.g. for a long file name in a narrow column.
+    pub fn ellipsize_text(&mut self, max_lines: u16) -> &mut Self {
+        self.text_overflow(TextOverflow::Ellipsis { max_lines })
+    }
+
     pub fn prev_state(&self) -> Option<&WidgetState> {
         self.context
             .widget_states
@@ -224,6 +540,52 @@ impl UiBuilder<'_> {
             .map(|container| &container.state)
     }
 
+    /// Converts a point relative to this widget's top-left corner to window
+    /// coordinates, using where the widget was placed last frame.
+    pub fn local_to_window(&self, point: Point2<Pixels>) -> Point2<Pixels> {
+        let origin = self
+            .prev_state()
+            .map(|s| s.placement.origin)
+            .unwrap_or_default();
+        Point2::new(origin.x + point.x, origin.y + point.y)
+    }
+
+    /// The files dropped on this widget this frame, or `None` if there were
+    /// none. Files dropped on a widget are also dropped on its ancestors.
+    pub fn files_dropped(&self) -> Option<&[PathBuf]> {
+        let files = &self.input.dropped_files;
+        (!files.is_empty() && self.is_pointer_over()).then_some(files.as_slice())
+    }
+
+    /// The files being dragged over this widget from another application, or
+    /// `None` if there are none, e.g. to highlight a drop target.
+    pub fn files_hovered(&self) -> Option<&[PathBuf]> {
+        let files = &self.input.hovered_files;
+        (!files.is_empty() && self.is_pointer_over()).then_some(files.as_slice())
+    }
+
+    fn is_pointer_over(&self) -> bool {
+        self.prev_state()
+            .is_some_and(|s| s.hit_test(self.input.pointer))
+    }
+
+    /// Converts a point in window coordinates to one relative to this
+    /// widget's top-left corner, using where the widget was placed last frame.
+    pub fn window_to_local(&self, point: Point2<Pixels>) -> Point2<Pixels> {
+        let origin = self
+            .prev_state()
+            .map(|s| s.placement.origin)
+            .unwrap_or_default();
+        Point2::new(point.x - origin.x, point.y - origin.y)
+    }
+
+    /// Converts a point relative to this widget's top-left corner to screen
+    /// coordinates, if the window's position is known. See
+    /// [`Input::window_to_screen`].
+    pub fn local_to_screen(&self, point: Point2<Pixels>) -> Option<Point2<Pixels>> {
+        self.input.window_to_screen(self.local_to_window(point))
+    }
+
     /// Set whether this widget is currently being actively pressed.
     /// Used for click detection across frames.
     pub fn set_active(&mut self, active: bool) {
@@ -237,17 +599,113 @@ impl UiBuilder<'_> {
         }
     }
 
+    /// Intercepts pointer presses on this widget's descendants, so that this
+    /// widget's [`Interaction`](super::widget::Interaction) is activated instead of theirs. Descendants in
+    /// overlays are not affected.
+    ///
+    /// Like hovering, this takes effect from the next frame.
+    pub fn capture_pointer(&mut self) -> &mut Self {
+        self.context
+            .pointer_handlers
+            .get_mut()
+            .insert(self.id, PointerPhase::Capture);
+        self
+    }
+
+    /// This frame's keyboard events that no widget has consumed.
+    ///
+    /// Widgets see events in the order they are built, so a container that
+    /// reads events before building its children can consume them before
+    /// its children see them, and one that reads them afterwards only sees
+    /// the events its children left alone.
+    pub fn keyboard_events(&self) -> impl Iterator<Item = &KeyboardEvent> {
+        let context = &*self.context;
+
+        self.input
+            .keyboard_events
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !context.is_key_event_consumed(*index))
+            .map(|(_, event)| event)
+    }
+
+    /// Makes this widget move the window when pressed, for a title bar drawn
+    /// by the UI in a window without [decorations](crate::shell::WindowConfig::decorations).
+    /// Double-clicking it maximizes or restores the window.
+    ///
+    /// Widgets inside it that handle presses, like buttons, still get them.
+    pub fn window_drag_region(&mut self) -> &mut Self {
+        let (interaction, state) =
+            Interaction::compute(self, ClickBehavior::OnPress, StateFlags::PRESSED);
+        self.set_active(state.contains(StateFlags::PRESSED));
+
+        if interaction.double_clicked {
+            self.context.window_action = Some(WindowAction::ToggleMaximized);
+        } else if interaction.is_activated {
+            self.context.window_action = Some(WindowAction::Drag);
+        }
+        self
+    }
+
+    /// Makes this widget resize the window from `edge` when pressed, for
+    /// borders drawn by the UI in a window without
+    /// [decorations](crate::shell::WindowConfig::decorations). Shows a resize
+    /// cursor while hovered.
+    pub fn window_resize_region(&mut self, edge: WindowEdge) -> &mut Self {
+        let (interaction, state) = Interaction::compute(
+            self,
+            ClickBehavior::OnPress,
+            StateFlags::HOVERED | StateFlags::PRESSED,
+        );
+        self.set_active(state.contains(StateFlags::PRESSED));
+
+        if interaction.is_hovered {
+            self.set_cursor(edge.cursor());
+        }
+        if interaction.is_activated {
+            self.context.window_action = Some(WindowAction::Resize(edge));
+        }
+        self
+    }
+
+    /// Shows `icon` as the mouse cursor over the window this frame, e.g.
+    /// while this widget is hovered. Widgets built later take precedence, so
+    /// a child's cursor replaces its parent's.
+    pub fn set_cursor(&mut self, icon: CursorIcon) -> &mut Self {
+        self.context.cursor_icon = icon;
+        self
+    }
+
+    /// Whether the shortcut registered for `command` with
+    /// [`Context::register_shortcut`](crate::shell::Context::register_shortcut)
+    /// was pressed this frame.
+    pub fn shortcut_pressed(&self, command: &str) -> bool {
+        self.context.pressed_shortcuts.contains(&command)
+    }
+
+    /// Consumes this frame's events for `key`, so that widgets built later in
+    /// the frame don't also react to them.
+    pub fn consume_key(&mut self, key: PhysicalKey) -> &mut Self {
+        for (index, event) in self.input.keyboard_events.iter().enumerate() {
+            if event.key == key {
+                self.context.consume_key_event(index);
+            }
+        }
+        self
+    }
+
     pub fn rect(
         &mut self,
         width: impl Into<Size>,
         height: impl Into<Size>,
         color: impl Into<Color>,
     ) -> &mut Self {
+        let metrics = self.length_metrics();
         self.context.ui_tree.add(
             Some(self.index),
             Atom {
-                width: width.into(),
-                height: height.into(),
+                width: width.into().resolve(&metrics),
+                height: height.into().resolve(&metrics),
                 z_layer: self.layer,
                 is_modal: self.is_modal,
                 ..Default::default()
@@ -256,6 +714,7 @@ impl UiBuilder<'_> {
                 LayoutContent::Fill {
                     paint: Paint::solid(color.into()),
                     border: GradientPaint::default(),
+                    border_colors: None,
                     border_width: Default::default(),
                     corner_radii: Default::default(),
                 },
@@ -267,17 +726,78 @@ impl UiBuilder<'_> {
     }
 
     pub fn text(&mut self, text: &str, height: impl Into<Size>) -> &mut Self {
-        let (text_id, text_layout) = self.context.static_text_layout(self.text_layouts, self.id);
+        self.text_with_spans(text, &[], hash_string(text), height.into(), None)
+    }
+
+    /// Adds a paragraph of text made of spans that each override parts of
+    /// this widget's text style.
+    pub fn rich_text(&mut self, spans: &[TextSpan], height: impl Into<Size>) -> &mut Self {
+        let text = spans.iter().map(|span| span.text).collect::<String>();
+        // The text node is tracked so that the text can be hit tested next
+        // frame without the widget's padding offsetting it.
+        let text_id = self.id.then("text");
+        self.text_with_spans(
+            &text,
+            spans,
+            hash_spans(spans),
+            height.into(),
+            Some(text_id),
+        )
+    }
+
+    /// The byte offset in this widget's rich text of the character under the
+    /// pointer, as the text was laid out last frame.
+    pub(crate) fn text_index_at_pointer(&mut self) -> Option<usize> {
+        let pointer = self.input.pointer;
+        let placement = self
+            .context
+            .widget_states
+            .get(&self.id.then("text"))?
+            .state
+            .placement;
+        if !placement.contains(&pointer) {
+            return None;
+        }
+
+        let (_, text_layout) = self.context.static_text_layout(self.text_layouts, self.id);
+        let local = pointer - placement.origin;
+        let (cluster, _) = parley::Cluster::from_point(&text_layout.layout, local.x, local.y)?;
+        Some(cluster.text_range().start)
+    }
+
+    fn text_with_spans(
+        &mut self,
+        text: &str,
+        spans: &[TextSpan],
+        text_hash: u64,
+        height: Size,
+        text_id: Option<WidgetId>,
+    ) -> &mut Self {
+        let (layout_id, text_layout) = self.context.static_text_layout(self.text_layouts, self.id);
 
-        let text_hash = hash_string(text);
         let theme_revision = self.theme.revision();
 
+        let is_fit = matches!(self.text_overflow, TextOverflow::Fit { .. });
+        let is_ellipsis = matches!(self.text_overflow, TextOverflow::Ellipsis { .. });
+
         let needs_rebuild = text_layout.style_id != self.style_id
             || text_layout.theme_revision != theme_revision
             || text_layout.state != self.state
-            || text_layout.text_hash != text_hash;
+            || text_layout.text_hash != text_hash
+            || (text_layout.scale != 1.0 && !is_fit)
+            || (text_layout.truncated_width.is_some() && !is_ellipsis);
 
         if needs_rebuild {
+            text_layout.spans.clear();
+            let mut start = 0;
+            for span in spans {
+                let end = start + span.text.len();
+                if span.style != SpanStyle::default() {
+                    text_layout.spans.push((start..end, span.style.clone()));
+                }
+                start = end;
+            }
+
             let mut builder = self.text_context.layouts.ranged_builder(
                 &mut self.text_context.fonts,
                 text,
@@ -287,7 +807,13 @@ impl UiBuilder<'_> {
 
             self.theme
                 .push_text_defaults(self.style_id, self.state, &mut builder);
+            push_span_styles(
+                &text_layout.spans,
+                self.theme.font_fallbacks(),
+                &mut builder,
+            );
             builder.build_into(&mut text_layout.layout, text);
+            self.context.text_layouts_built += 1;
 
             // Update cache tracking fields
             text_layout.style_id = self.style_id;
@@ -295,7 +821,11 @@ impl UiBuilder<'_> {
             text_layout.state = self.state;
             text_layout.text_hash = text_hash;
             text_layout.raw_text = text.to_string();
+            text_layout.scale = 1.0;
+            text_layout.truncated_width = None;
             text_layout.needs_line_break = true;
+        } else {
+            self.context.text_layouts_reused += 1;
         }
 
         let alignment = self
@@ -303,26 +833,52 @@ impl UiBuilder<'_> {
             .resolve_style::<TextAlignment>(self.style_id, self.state);
         let size = text_layout.layout.calculate_content_widths();
 
+        let width = match self.text_overflow {
+            TextOverflow::Fit { min_size, max_size } => {
+                // Measured at the style's font size, then stretched to cover
+                // every font size the text may be fit to.
+                let font_size = self.theme.font_size(self.style_id, self.state);
+                let width = size.max / text_layout.scale / font_size.max(1.0);
+                PixelSize::Flex {
+                    min: width * f32::from(min_size),
+                    max: width * f32::from(max_size),
+                }
+            }
+            // Text that is cut off can shrink to nothing, and grows back to
+            // the width of the whole text.
+            TextOverflow::Ellipsis { .. } => PixelSize::Flex {
+                min: 0.0,
+                max: text_layout.truncated_width.unwrap_or(size.max),
+            },
+            _ => PixelSize::Flex {
+                min: size.min,
+                max: size.max,
+            },
+        };
+        let height = height.resolve(&self.length_metrics());
+
         self.context.ui_tree.add(
             Some(self.index),
             Atom {
-                width: Flex {
-                    min: size.min,
-                    max: size.max,
-                },
-                height: height.into(),
+                width,
+                height,
                 z_layer: self.layer,
                 is_modal: self.is_modal,
-                clip_overflow: matches!(self.text_overflow, TextOverflow::Clip),
+                overflow: match self.text_overflow {
+                    TextOverflow::Clip
+                    | TextOverflow::Fit { .. }
+                    | TextOverflow::Ellipsis { .. } => Overflow::Clip,
+                    _ => Overflow::Visible,
+                },
                 ..Default::default()
             },
             (
                 LayoutContent::Text {
-                    layout: text_id,
+                    layout: layout_id,
                     alignment,
                     overflow: self.text_overflow,
                 },
-                None,
+                text_id,
             ),
         );
 
@@ -335,6 +891,10 @@ impl UiBuilder<'_> {
 
     pub fn named_child(&mut self, name: impl Hash) -> UiBuilder<'_> {
         let child_id = self.id.then(name);
+        // Disabled widgets can't keep focus they had before being disabled.
+        if self.is_disabled && self.context.focused_widget.get() == Some(child_id) {
+            self.context.focused_widget.set(None);
+        }
 
         let child_index = self.context.ui_tree.add(
             Some(self.index),
@@ -359,6 +919,7 @@ impl UiBuilder<'_> {
 
             is_modal: self.is_modal,
             layer: self.layer,
+            is_disabled: self.is_disabled,
 
             id: child_id,
             index: child_index,
@@ -366,6 +927,7 @@ impl UiBuilder<'_> {
             state: self.state,
             num_child_widgets: 0,
             text_overflow: self.text_overflow,
+            drag_threshold: self.drag_threshold,
         }
     }
 
@@ -383,20 +945,99 @@ impl UiBuilder<'_> {
         self
     }
 
+    /// Enables or disables the widgets built from this builder from here on,
+    /// including those built inside them.
+    ///
+    /// Disabled widgets can't be hovered, pressed, or focused, and are styled
+    /// with [`StateFlags::DISABLED`].
+    pub fn enabled(&mut self, enabled: bool) -> &mut Self {
+        self.is_disabled = !enabled;
+        if self.is_disabled {
+            self.release_focus();
+        }
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.is_disabled
+    }
+
+    /// Sets how far, in pixels, the pointer must move while pressed before
+    /// the press becomes a drag, for the widgets built from this builder from
+    /// here on, including those built inside them. Defaults to
+    /// [`DEFAULT_DRAG_THRESHOLD`].
+    ///
+    /// A threshold of zero starts dragging as soon as the pointer moves, as
+    /// sliders do.
+    pub fn drag_threshold(&mut self, pixels: f32) -> &mut Self {
+        self.drag_threshold = pixels;
+        self
+    }
+
+    /// Describes this widget in a line or so of text, to be shown in a status
+    /// bar while the widget is hovered or focused. Hovered widgets take
+    /// precedence, and of those the one built last, so that a widget's help
+    /// text replaces that of its container.
+    pub fn help_text(&mut self, text: &str) -> &mut Self {
+        let layer_blocked = self.context.active_pointer_layer > self.layer
+            || self
+                .context
+                .input_block_layer
+                .is_some_and(|block_layer| self.layer < block_layer);
+
+        let is_hovered = !layer_blocked
+            && self
+                .prev_state()
+                .is_some_and(|s| s.hit_test(self.input.pointer));
+
+        if is_hovered {
+            self.context.hovered_help_text = Some(text.to_string());
+        }
+
+        if self.is_focused() {
+            self.context.focused_help_text = Some(text.to_string());
+        }
+
+        self
+    }
+
+    /// The [help text](Self::help_text) of the widget that was hovered, or
+    /// else focused, at the end of the last frame.
+    pub fn status_hint(&self) -> Option<&str> {
+        self.context.status_hint.as_deref()
+    }
+
+    /// Describes the widget to screen readers, with the `accessibility`
+    /// feature. Only widgets described this way are published to them.
+    pub fn accessible(&mut self, role: AccessRole, label: &str) -> &mut Self {
+        #[cfg(feature = "accessibility")]
+        self.context.accessible_nodes.insert(
+            self.id,
+            AccessibleNode {
+                role,
+                label: label.to_string(),
+            },
+        );
+        #[cfg(not(feature = "accessibility"))]
+        let _ = (role, label);
+
+        self
+    }
+
     /// Check if this widget currently has focus
     pub fn is_focused(&self) -> bool {
-        self.context.focused_widget == Some(self.id)
+        self.context.focused_widget.get() == Some(self.id)
     }
 
     /// Request focus for this widget
     pub fn request_focus(&mut self) {
-        self.context.focused_widget = Some(self.id);
+        self.context.focused_widget.set(Some(self.id));
     }
 
     /// Release focus if this widget has it
     pub fn release_focus(&mut self) {
-        if self.context.focused_widget == Some(self.id) {
-            self.context.focused_widget = None;
+        if self.context.focused_widget.get() == Some(self.id) {
+            self.context.focused_widget.set(None);
         }
     }
 
@@ -415,6 +1056,23 @@ impl UiBuilder<'_> {
         self.overlay_offset_child(name, pos, 1)
     }
 
+    /// Creates a child positioned relative to this node's layout result using
+    /// `OverlayPosition` anchor semantics, such as a close button pinned to a
+    /// corner with [`OverlayPosition::inside`].
+    ///
+    /// Unlike [`overlay_child`](Self::overlay_child), the child stays on this
+    /// node's layer and is clipped like its in-flow siblings. It does not
+    /// participate in this node's sizing or sibling alignment.
+    pub fn anchored_child(
+        &mut self,
+        name: impl std::hash::Hash,
+        pos: OverlayPosition,
+    ) -> UiBuilder<'_> {
+        let layer = self.layer;
+        let is_modal = self.is_modal;
+        self.overlay_child_inner(name, Position::Anchored(pos), layer, is_modal)
+    }
+
     /// Creates an out-of-flow child that additionally blocks pointer and keyboard input
     /// from reaching any widget on a lower z_layer, regardless of pointer position.
     ///
@@ -465,7 +1123,7 @@ impl UiBuilder<'_> {
         self.overlay_child_inner(name, pos, child_layer, true)
     }
 
-    fn overlay_child_inner(
+    pub(super) fn overlay_child_inner(
         &mut self,
         name: impl std::hash::Hash,
         position: Position,
@@ -473,6 +1131,10 @@ impl UiBuilder<'_> {
         is_modal: bool,
     ) -> UiBuilder<'_> {
         let child_id = self.id.then(name);
+        // Disabled widgets can't keep focus they had before being disabled.
+        if self.is_disabled && self.context.focused_widget.get() == Some(child_id) {
+            self.context.focused_widget.set(None);
+        }
 
         let child_index = self.context.ui_tree.add(
             Some(self.index),
@@ -504,7 +1166,9 @@ impl UiBuilder<'_> {
 
             is_modal,
             layer: child_layer,
+            is_disabled: self.is_disabled,
             text_overflow: self.text_overflow,
+            drag_threshold: self.drag_threshold,
         }
     }
 }
@@ -513,3 +1177,22 @@ impl UiBuilder<'_> {
 fn hash_string(text: &str) -> u64 {
     rapidhash_v3(text.as_bytes())
 }
+
+fn hash_spans(spans: &[TextSpan]) -> u64 {
+    let mut hasher = RapidHasher::new(0);
+    for span in spans {
+        let SpanStyle {
+            font,
+            weight,
+            size,
+            color,
+            underline,
+        } = &span.style;
+
+        (span.text, font, underline).hash(&mut hasher);
+        weight.map(|weight| weight.0.to_bits()).hash(&mut hasher);
+        size.map(f32::to_bits).hash(&mut hasher);
+        color.map(bytemuck::cast::<_, [u32; 4]>).hash(&mut hasher);
+    }
+    hasher.finish()
+}
@@ -1,17 +1,22 @@
+use crate::graphics::TextSpan;
 use crate::graphics::Texture;
 
 use super::Size;
 use super::widget::Button;
 use super::widget::Container;
+use super::widget::ContextMenu;
 use super::widget::Dropdown;
 use super::widget::DropdownItem;
 use super::widget::EditableTextBuffer;
+use super::widget::Form;
 use super::widget::Frame;
 use super::widget::HorizontalSeparator;
 use super::widget::Image;
 use super::widget::Interaction;
 use super::widget::Label;
+use super::widget::ScrollArea;
 use super::widget::Surface;
+use super::widget::TabBar;
 use super::widget::TextEdit;
 use super::widget::TextEditorState;
 use super::widget::VerticalSeparator;
@@ -40,6 +45,15 @@ pub trait CommonWidgetsExt<'a>: Container<'a> {
             .finish()
     }
 
+    /// Creates an invisible container that scrolls its children with the
+    /// mouse wheel.
+    fn scroll_area<'this>(&'this mut self) -> ScrollArea<'this>
+    where
+        'a: 'this,
+    {
+        ScrollArea::new(self.builder_mut())
+    }
+
     fn surface<'this>(&'this mut self) -> Surface<'this>
     where
         'a: 'this,
@@ -53,6 +67,23 @@ pub trait CommonWidgetsExt<'a>: Container<'a> {
         self
     }
 
+    /// Creates a container of labelled fields with a submit button that is
+    /// disabled until every field is valid.
+    fn form<'this>(&'this mut self, id: &str) -> Form<'this>
+    where
+        'a: 'this,
+    {
+        Form::new(self.builder_mut(), id)
+    }
+
+    /// Creates a row of tabs that can be reordered and torn off by dragging.
+    fn tab_bar<'this>(&'this mut self, id: &str, selected: usize) -> TabBar<'this>
+    where
+        'a: 'this,
+    {
+        TabBar::new(self.builder_mut(), id, selected)
+    }
+
     fn text_button(&mut self, label: &str) -> Interaction {
         Button::new(self.builder_mut(), Some(label)).finish()
     }
@@ -72,6 +103,15 @@ pub trait CommonWidgetsExt<'a>: Container<'a> {
         Label::new(self.builder_mut(), text)
     }
 
+    /// Creates a label whose text mixes styles, such as bold or colored
+    /// words within a sentence.
+    fn rich_label<'this>(&'this mut self, spans: &[TextSpan]) -> Label<'this>
+    where
+        'a: 'this,
+    {
+        Label::rich(self.builder_mut(), spans)
+    }
+
     fn horizontal_separator<'this>(&'this mut self) -> HorizontalSeparator<'this>
     where
         'a: 'this,
@@ -102,6 +142,23 @@ pub trait CommonWidgetsExt<'a>: Container<'a> {
         let (selected_idx, _) = dropdown.finish();
         selected_idx.or(selected)
     }
+
+    /// Attaches a context menu to this widget, opened by right-clicking it or
+    /// by pressing the Menu key or Shift+F10 while it has focus. Returns the
+    /// index of the item chosen this frame, if any.
+    fn context_menu<D: DropdownItem>(
+        &mut self,
+        id: &str,
+        items: impl IntoIterator<Item = D>,
+    ) -> Option<usize> {
+        let mut menu = ContextMenu::new(self.builder_mut(), id);
+
+        for item in items.into_iter() {
+            menu.item(item);
+        }
+
+        menu.finish()
+    }
 }
 
 impl<'a, C: Container<'a>> CommonWidgetsExt<'a> for C {}
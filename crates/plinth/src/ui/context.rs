@@ -1,49 +1,121 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::RangeBounds;
+use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
 
 use glamour::Contains;
 use glamour::Point2;
 use glamour::Rect;
 use glamour::Size2;
+use smallvec::SmallVec;
 
+use crate::graphics::AlphaMask;
+use crate::graphics::BorderColors;
+use crate::graphics::CacheStats;
 use crate::graphics::Canvas;
+use crate::graphics::ClipRect;
 use crate::graphics::Color;
 use crate::graphics::GradientPaint;
 use crate::graphics::Paint;
 use crate::graphics::Primitive;
 use crate::graphics::TextAlignment;
 use crate::graphics::TextLayoutContext;
+use crate::graphics::Texture;
 use crate::shell::Clipboard;
+use crate::shell::CursorIcon;
 use crate::shell::Input;
+use crate::shell::NavigationDirection;
+use crate::shell::NavigationEvent;
+use crate::shell::Shortcuts;
+use crate::shell::WindowAction;
+use crate::shell::WindowShape;
 use crate::ui::theme::Theme;
 
 use super::Atom;
 use super::IdMap;
 use super::LayoutTree;
+use super::Pixels;
 use super::Position;
 use super::StyleClass;
 use super::UiBuilder;
 use super::UiElementId;
 use super::WidgetId;
+#[cfg(feature = "accessibility")]
+use super::accessibility::AccessibleNode;
+use super::animation::Animator;
+use super::animation::Transition;
+use super::inspector::InspectedNode;
 use super::layout::NodeLayout;
-use super::style::BorderWidths;
+use super::retained::RetainedSubtree;
 use super::style::CornerRadii;
+use super::style::StateFlags;
+use super::style::StyleId;
+use super::style::TextDecoration;
 use super::text::TextLayoutId;
 use super::text::TextLayoutMut;
 use super::text::TextLayoutStorage;
 use super::text::TextOverflow;
+use super::widget::DEFAULT_DRAG_THRESHOLD;
+use super::widget::PointerPhase;
 use super::widget::WidgetState;
+use super::widget::context_menu::ContextMenuRequest;
+use super::widget::context_menu::OpenContextMenu;
+use super::widget::context_menu::resolve_context_menu;
+use super::widget::scroll_area::ScrollAreaState;
+use super::widget::scroll_area::ScrollGroup;
+use super::widget::scroll_area::apply_scroll_input;
+use super::widget::scroll_area::update_scroll_areas;
+
+/// The widgets that could be hit last frame, topmost first.
+#[derive(Default)]
+pub(crate) struct HitTargets {
+    targets: Vec<((u8, u16), WidgetId, WidgetState)>,
+}
+
+impl HitTargets {
+    pub fn hit_test(&self, point: Point2<Pixels>) -> Option<WidgetId> {
+        self.targets
+            .iter()
+            .find(|(_, _, state)| state.hit_test(point))
+            .map(|&(_, id, _)| id)
+    }
+}
 
 #[derive(Default)]
 pub(crate) struct UiContext {
+    pub(super) frame_time: Duration,
     pub(super) time_delta: Duration,
+    pub(super) refresh_interval: Option<Duration>,
+    pub(super) animator: Animator,
 
     pub(super) ui_tree: LayoutTree<(LayoutContent, Option<WidgetId>)>,
     pub(super) widget_states: IdMap<WidgetContainer>,
 
     pub(super) frame_counter: u64,
-    pub(super) focused_widget: Option<WidgetId>,
+    pub(super) focused_widget: Cell<Option<WidgetId>>,
+    /// Whether the focused widget was focused with the keyboard.
+    pub(super) is_focus_visible: bool,
+    /// The focused widget at the end of the last frame, to tell when focus
+    /// moves.
+    pub(super) prev_focused_widget: Option<WidgetId>,
+    /// Whether keys, but no mouse buttons, were pressed this frame.
+    is_keyboard_frame: bool,
+    /// Whether the focused widget is activated by navigation this frame.
+    pub(super) navigation_activated: bool,
+    /// The widget a screen reader clicked, which is activated this frame.
+    pub(super) accessibility_click: Option<WidgetId>,
+    /// The widgets described to screen readers this frame, see
+    /// [`UiBuilder::accessible`].
+    #[cfg(feature = "accessibility")]
+    pub(super) accessible_nodes: IdMap<AccessibleNode>,
+    /// The first focusable widget built since this was last reset. Forms use
+    /// it to find the input of each field.
+    pub(super) focus_candidate: Cell<Option<WidgetId>>,
 
-    /// The highest z_layer that contains any widget whose previous-frame placement
+    /// The highest z_layer that contains any widget whose previous-frame visible area
     /// contains the current pointer position. Computed at the start of each frame.
     /// Used by `Interaction::compute` to suppress hover on lower layers.
     pub(super) active_pointer_layer: u8,
@@ -55,39 +127,225 @@ pub(crate) struct UiContext {
     /// receive input. Code that consumes this field must use `layer < input_block_layer`,
     /// never `layer <= input_block_layer`.
     pub(super) input_block_layer: Option<u8>,
+
+    /// The widget that receives pointer presses this frame, chosen from the
+    /// widgets that handled presses last frame. `None` if no such widget is
+    /// under the pointer.
+    pub(super) pointer_target: Option<WidgetId>,
+    /// The widgets that handle pointer presses this frame. Widgets register
+    /// themselves while computing their interaction, which only borrows the
+    /// builder.
+    pub(super) pointer_handlers: RefCell<IdMap<PointerPhase>>,
+    /// The widget being dragged, which keeps the pointer to itself until the
+    /// button is released, even once the pointer leaves it.
+    pub(super) drag_capture: Cell<Option<WidgetId>>,
+
+    /// Whether each of this frame's keyboard events has been handled by a
+    /// widget, by index.
+    consumed_key_events: Vec<bool>,
+    /// Whether each keyboard event pressed a shortcut, by index. They are
+    /// consumed when the frame begins.
+    shortcut_key_events: Vec<bool>,
+    /// The commands whose shortcuts were pressed this frame.
+    pub(super) pressed_shortcuts: SmallVec<[&'static str; 2]>,
+    /// Whether a text edit was focused this frame, so that shortcuts which
+    /// type text are left to it next frame.
+    pub(super) is_text_focused: bool,
+
+    /// Whether the secondary (right) mouse button went down this frame.
+    pub(super) secondary_pressed: bool,
+    was_secondary_down: bool,
+
+    pub(super) context_menu: Option<OpenContextMenu>,
+    /// The best candidate for opening a context menu seen so far this frame.
+    /// Resolved in `finish` so that priority doesn't depend on build order.
+    pub(super) context_menu_request: Option<ContextMenuRequest>,
+
+    pub(super) scroll_areas: IdMap<ScrollAreaState>,
+    /// Groups of scroll areas linked with [`ScrollArea::link`](super::widget::ScrollArea::link).
+    pub(super) scroll_groups: IdMap<ScrollGroup>,
+
+    /// The help text of the hovered widget built last so far this frame, see
+    /// [`UiBuilder::help_text`].
+    pub(super) hovered_help_text: Option<String>,
+    /// The help text of the focused widget, if it has any.
+    pub(super) focused_help_text: Option<String>,
+    /// The help text chosen at the end of the last frame.
+    pub(super) status_hint: Option<String>,
+
+    /// Set when the UI changed in a way that only becomes visible on the next
+    /// frame, so the shell should not wait for input before drawing again.
+    pub(super) needs_redraw: bool,
+    /// The shortest delay after which a widget asked to be drawn again.
+    pub(super) repaint_after: Option<Duration>,
+    /// The caret of the focused text edit in window coordinates, for placing
+    /// the input method's candidate window. `None` disables the input method.
+    pub(super) ime_cursor_area: Option<Rect<Pixels>>,
+    /// The mouse cursor to show over the window, see
+    /// [`UiBuilder::set_cursor`].
+    pub(super) cursor_icon: CursorIcon,
+    /// What a UI-drawn title bar or border asked the window to do this frame.
+    pub(super) window_action: Option<WindowAction>,
+
+    /// The number of text layouts shaped this frame, for frame budgets.
+    pub(super) text_layouts_built: usize,
+    /// The number of text layouts that were unchanged and not shaped again.
+    pub(super) text_layouts_reused: usize,
+    /// How often text inputs could skip restyling their text this frame.
+    pub(super) style_cache: CacheStats,
+    /// How long the last call to [`Self::finish`] spent computing layout.
+    layout_time: Duration,
+
+    /// Labels attached to nodes this frame with [`UiBuilder::debug_label`].
+    pub(super) debug_labels: HashMap<UiElementId, String>,
+
+    /// The pointer position while [`UiBuilder::style_inspector`] is active this
+    /// frame.
+    pub(super) inspector_pointer: Option<Point2<Pixels>>,
+    /// The style and state of each node styled this frame, only recorded
+    /// while the style inspector is active.
+    pub(super) node_styles: HashMap<UiElementId, (StyleId, StateFlags)>,
+    /// The widget that was under the pointer the last time the style inspector
+    /// was active.
+    pub(super) inspected_node: Option<InspectedNode>,
+
+    /// Nodes marked this frame with [`UiBuilder::render_to_texture`], and the
+    /// resolution scale of each.
+    pub(super) texture_layer_scales: HashMap<UiElementId, f32>,
+    /// Nodes given a hit mask this frame with [`UiBuilder::hit_mask`].
+    pub(super) hit_masks: HashMap<UiElementId, Arc<AlphaMask>>,
+    /// Nodes given a transition this frame with [`UiBuilder::transition`].
+    pub(super) transitions: HashMap<UiElementId, Transition>,
+    /// Canvases and textures for subtrees rendered to a texture, kept between
+    /// frames so that the textures can be reused.
+    texture_layers: IdMap<TextureLayer>,
 }
 
 impl UiContext {
     #[expect(clippy::too_many_arguments)]
     pub(crate) fn begin_frame<'a>(
         &'a mut self,
-        clipboard: &'a mut Clipboard,
+        clipboard: &'a Clipboard,
         text_context: &'a mut TextLayoutContext,
         text_layouts: &'a mut TextLayoutStorage,
         format_buffer: &'a mut String,
         theme: &'a Theme,
         input: &'a Input,
+        frame_time: Duration,
         time_delta: Duration,
+        refresh_interval: Option<Duration>,
+        window_shape: WindowShape,
     ) -> UiBuilder<'a> {
         self.ui_tree.clear();
+        self.debug_labels.clear();
+        self.texture_layer_scales.clear();
+        self.transitions.clear();
+        self.hit_masks.clear();
+        self.inspector_pointer = None;
+        self.node_styles.clear();
+        #[cfg(feature = "accessibility")]
+        self.accessible_nodes.clear();
 
-        // Single pass over previous-frame widget states to compute both layer gates.
+        // Single pass over previous-frame widget states to compute both layer
+        // gates and the topmost pointer handler under the pointer.
         let mut active_pointer_layer = 0u8;
         let mut input_block_layer: Option<u8> = None;
-        for wc in self.widget_states.values() {
+        let mut pointer_target: Option<(WidgetId, &WidgetState)> = None;
+        for (id, wc) in &self.widget_states {
             let s = &wc.state;
-            if s.placement.contains(&input.pointer) && s.layer > active_pointer_layer {
+            if s.hit_test(input.pointer) && s.layer > active_pointer_layer {
                 active_pointer_layer = s.layer;
             }
             if s.is_modal && input_block_layer.is_none_or(|cur| s.layer > cur) {
                 input_block_layer = Some(s.layer);
             }
+            if s.pointer_phase.is_some()
+                && s.hit_test(input.pointer)
+                && pointer_target
+                    .is_none_or(|(_, t)| (s.layer, s.draw_order) > (t.layer, t.draw_order))
+            {
+                pointer_target = Some((*id, s));
+            }
         }
         self.active_pointer_layer = active_pointer_layer;
         self.input_block_layer = input_block_layer;
 
+        // The outermost ancestor that captures presses takes them from the
+        // widget that would otherwise receive them.
+        self.pointer_target = pointer_target.map(|(target_id, target)| {
+            self.widget_states
+                .iter()
+                .filter(|(_, wc)| {
+                    let s = &wc.state;
+                    s.pointer_phase == Some(PointerPhase::Capture)
+                        && s.layer == target.layer
+                        && (s.draw_order..=s.subtree_end).contains(&target.draw_order)
+                })
+                .min_by_key(|(_, wc)| wc.state.draw_order)
+                .map_or(target_id, |(id, _)| *id)
+        });
+        self.pointer_handlers.get_mut().clear();
+        if !input.mouse_state.is_left_down() {
+            self.drag_capture.set(None);
+        }
+        self.consumed_key_events.clear();
+        self.consumed_key_events
+            .extend_from_slice(&self.shortcut_key_events);
+        self.consumed_key_events
+            .resize(input.keyboard_events.len(), false);
+        self.shortcut_key_events.clear();
+        self.is_text_focused = false;
+
+        apply_scroll_input(self, input);
+
+        self.navigation_activated = false;
+        for event in &input.navigation_events {
+            match event {
+                NavigationEvent::Move(direction) => {
+                    if let Some(target) = self.navigation_target(*direction) {
+                        // Set here rather than in `finish`, since navigating
+                        // isn't a keyboard frame but should still show focus.
+                        self.focused_widget.set(Some(target));
+                        self.prev_focused_widget = Some(target);
+                        self.is_focus_visible = true;
+                    }
+                }
+                NavigationEvent::Activate => self.navigation_activated = true,
+                NavigationEvent::Cancel => {
+                    self.focused_widget.set(None);
+                    self.prev_focused_widget = None;
+                }
+            }
+        }
+
+        let is_secondary_down = input.mouse_state.is_right_down();
+        self.secondary_pressed = is_secondary_down && !self.was_secondary_down;
+        self.was_secondary_down = is_secondary_down;
+
+        let is_mouse_down = input.mouse_state.is_left_down()
+            || input.mouse_state.is_right_down()
+            || input.mouse_state.is_middle_down();
+        self.is_keyboard_frame = !is_mouse_down
+            && input
+                .keyboard_events
+                .iter()
+                .any(|event| event.state.is_pressed());
+
+        if let Some(menu) = self.context_menu.as_mut() {
+            menu.seen = false;
+        }
+        self.needs_redraw = false;
+        self.repaint_after = None;
+        self.ime_cursor_area = None;
+        self.cursor_icon = CursorIcon::Default;
+        self.window_action = None;
+        self.text_layouts_built = 0;
+        self.text_layouts_reused = 0;
+        self.style_cache = CacheStats::default();
+
         // Set up the root node.
         let id = WidgetId::new("root");
+        let corner_radius = window_shape.corner_radius(input.window_size);
 
         let root = self.ui_tree.add(
             None,
@@ -100,14 +358,23 @@ impl UiContext {
                 LayoutContent::Fill {
                     paint: Paint::solid(Color::WHITE),
                     border: GradientPaint::vertical_gradient(Color::BLACK, Color::BLACK),
+                    border_colors: None,
                     border_width: Default::default(),
-                    corner_radii: Default::default(),
+                    corner_radii: CornerRadii {
+                        top_left: corner_radius,
+                        top_right: corner_radius,
+                        bottom_right: corner_radius,
+                        bottom_left: corner_radius,
+                    },
                 },
                 Some(id),
             ),
         );
 
+        self.frame_time = frame_time;
         self.time_delta = time_delta;
+        self.refresh_interval = refresh_interval;
+        self.animator.begin_frame(time_delta);
 
         UiBuilder {
             theme,
@@ -127,10 +394,177 @@ impl UiContext {
 
             layer: 0,
             is_modal: false,
+            is_disabled: false,
             text_overflow: TextOverflow::Clip,
+            drag_threshold: DEFAULT_DRAG_THRESHOLD,
+        }
+    }
+
+    /// Finds the shortcuts pressed by this frame's keyboard events. Must be
+    /// called before [`Self::begin_frame`].
+    pub(crate) fn match_shortcuts(&mut self, input: &Input, shortcuts: &Shortcuts) {
+        self.pressed_shortcuts.clear();
+        self.shortcut_key_events.clear();
+
+        for event in &input.keyboard_events {
+            let count = self.pressed_shortcuts.len();
+            self.pressed_shortcuts.extend(shortcuts.matching(
+                event,
+                input.modifiers,
+                self.is_text_focused,
+            ));
+
+            self.shortcut_key_events
+                .push(self.pressed_shortcuts.len() > count);
+        }
+    }
+
+    pub(super) fn consume_key_event(&mut self, index: usize) {
+        self.consumed_key_events[index] = true;
+    }
+
+    pub(super) fn is_key_event_consumed(&self, index: usize) -> bool {
+        self.consumed_key_events[index]
+    }
+
+    pub(crate) fn needs_redraw(&self) -> bool {
+        self.needs_redraw
+    }
+
+    pub(crate) fn repaint_after(&self) -> Option<Duration> {
+        self.repaint_after
+    }
+
+    pub(crate) fn ime_cursor_area(&self) -> Option<Rect<Pixels>> {
+        self.ime_cursor_area
+    }
+
+    pub(crate) fn cursor_icon(&self) -> CursorIcon {
+        self.cursor_icon
+    }
+
+    pub(crate) fn window_action(&self) -> Option<WindowAction> {
+        self.window_action
+    }
+
+    /// The widget that handled presses last frame that is nearest the focused
+    /// widget in `direction`, or the first such widget if none is focused.
+    /// Widgets blocked by a modal overlay are skipped.
+    fn navigation_target(&self, direction: NavigationDirection) -> Option<WidgetId> {
+        let center = |rect: Rect<Pixels>| -> Point2<Pixels> {
+            Point2::new(
+                rect.origin.x + rect.size.width / 2.0,
+                rect.origin.y + rect.size.height / 2.0,
+            )
+        };
+
+        let candidates = self.widget_states.iter().filter(|(id, wc)| {
+            let s = &wc.state;
+            s.pointer_phase.is_some()
+                && Some(**id) != self.focused_widget.get()
+                && s.visible_area.size.width > 0.0
+                && s.visible_area.size.height > 0.0
+                && self.input_block_layer.is_none_or(|bl| s.layer >= bl)
+        });
+
+        let Some(from) = self
+            .focused_widget
+            .get()
+            .and_then(|id| self.widget_states.get(&id))
+            .map(|wc| center(wc.state.visible_area))
+        else {
+            return candidates
+                .min_by_key(|(_, wc)| (std::cmp::Reverse(wc.state.layer), wc.state.draw_order))
+                .map(|(id, _)| *id);
+        };
+
+        // Widgets straight ahead are preferred over nearer ones off to the
+        // side.
+        candidates
+            .filter_map(|(id, wc)| {
+                let to = center(wc.state.visible_area);
+                let (ahead, aside) = match direction {
+                    NavigationDirection::Up => (from.y - to.y, to.x - from.x),
+                    NavigationDirection::Down => (to.y - from.y, to.x - from.x),
+                    NavigationDirection::Left => (from.x - to.x, to.y - from.y),
+                    NavigationDirection::Right => (to.x - from.x, to.y - from.y),
+                };
+                (ahead > 0.0).then_some((*id, ahead + 2.0 * aside.abs()))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(id, _)| id)
+    }
+
+    /// The topmost widget that `point` was over last frame, by layer and then
+    /// draw order, whether or not it handles input.
+    pub fn hit_test(&self, point: Point2<Pixels>) -> Option<WidgetId> {
+        self.widget_states
+            .iter()
+            .filter(|(_, container)| container.state.hit_test(point))
+            .max_by_key(|(_, container)| (container.state.layer, container.state.draw_order))
+            .map(|(id, _)| *id)
+    }
+
+    /// Copies what [`Self::hit_test`] needs out of this frame's widgets, so
+    /// that the next frame can be hit tested while the context is borrowed
+    /// to build it.
+    pub(crate) fn update_hit_targets(&self, targets: &mut HitTargets) {
+        targets.targets.clear();
+        targets
+            .targets
+            .extend(self.widget_states.iter().map(|(id, container)| {
+                let state = &container.state;
+                let mut target = WidgetState::default();
+                target.placement = state.placement;
+                target.visible_area = state.visible_area;
+                target.hit_mask = state.hit_mask.clone();
+                ((state.layer, state.draw_order), *id, target)
+            }));
+        targets
+            .targets
+            .sort_unstable_by_key(|&(order, _, _)| std::cmp::Reverse(order));
+    }
+
+    /// The number of layout nodes built this frame.
+    pub(crate) fn num_nodes(&self) -> usize {
+        self.ui_tree.len()
+    }
+
+    pub(crate) fn text_layouts_built(&self) -> usize {
+        self.text_layouts_built
+    }
+
+    pub(crate) fn text_layout_stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.text_layouts_reused,
+            misses: self.text_layouts_built,
+            ..Default::default()
         }
     }
 
+    pub(crate) fn style_cache_stats(&self) -> CacheStats {
+        self.style_cache
+    }
+
+    pub(crate) fn layout_time(&self) -> Duration {
+        self.layout_time
+    }
+
+    /// The subtrees drawn by the last call to [`Self::finish`] that must be
+    /// rendered into their textures before the window canvas. Subtrees that
+    /// drew the same as on the previous frame keep their textures as they are.
+    pub(crate) fn texture_layers(&self) -> impl Iterator<Item = (&Canvas, &Texture)> {
+        self.texture_layers
+            .values()
+            .filter_map(|layer| Some((layer, layer.target.as_ref()?)))
+            // Targets lose their contents and become unready if the device
+            // is lost.
+            .filter(|(layer, target)| {
+                layer.is_new_target || layer.canvas.has_damage() || !target.is_ready()
+            })
+            .map(|(layer, target)| (&layer.canvas, target))
+    }
+
     pub fn state_mut(&mut self, widget_id: WidgetId) -> &mut WidgetState {
         let container = self
             .widget_states
@@ -186,10 +620,27 @@ impl UiContext {
 
     pub fn finish(
         &mut self,
+        theme: &Theme,
         text_context: &mut TextLayoutContext,
         text_layouts: &mut TextLayoutStorage,
         canvas: &mut Canvas,
     ) {
+        resolve_context_menu(self);
+
+        let layout_start = Instant::now();
+        self.ui_tree.hash_subtrees(|(content, _)| match content {
+            LayoutContent::Text {
+                layout,
+                alignment,
+                overflow,
+                ..
+            } => text_layouts.layout_key(*layout, *alignment, *overflow),
+            // Text edits measure their text anew as it's typed.
+            LayoutContent::EditableText { .. } => None,
+            _ => Some(0),
+        });
+
+        let text_layouts_built = &mut self.text_layouts_built;
         self.ui_tree.compute_layout(|(content, _), max_width| {
             let (layout_id, alignment, overflow) = match content {
                 LayoutContent::Text {
@@ -204,20 +655,137 @@ impl UiContext {
                 _ => return None,
             };
 
+            if let TextOverflow::Fit { min_size, max_size } = *overflow
+                && text_layouts.fit_to_width(
+                    *layout_id,
+                    max_width,
+                    (min_size, max_size),
+                    text_context,
+                    theme,
+                )
+            {
+                *text_layouts_built += 1;
+            }
+
+            if let TextOverflow::Ellipsis { max_lines } = *overflow
+                && text_layouts.ellipsize(*layout_id, max_width, max_lines, text_context, theme)
+            {
+                *text_layouts_built += 1;
+            }
+
             text_layouts.break_lines(*layout_id, max_width, *alignment, *overflow)
         });
+        self.layout_time = layout_start.elapsed();
+
+        update_scroll_areas(self);
+
+        // The first and last node of each open debug group's subtree. Groups
+        // only span a single layer because layers are drawn one after another.
+        // Siblings may be drawn out of order, so a subtree is left once a node
+        // outside of it is drawn.
+        let mut debug_groups = SmallVec::<[(UiElementId, UiElementId); 8]>::new();
+        let mut current_layer = 0;
+
+        // The first and last node of the subtree being drawn into a texture,
+        // and the widget that owns the texture. Like debug groups, this only
+        // spans a single layer.
+        let mut texture_layer: Option<(UiElementId, UiElementId, WidgetId)> = None;
+
+        // Nodes are drawn bottom to top, so the last one under the pointer is
+        // the one that is inspected.
+        let mut inspected_node = None;
+
+        for (draw_order, (id, node, (content, widget_id))) in
+            self.ui_tree.iter_nodes_by_layer().enumerate()
+        {
+            if node.atom.z_layer != current_layer {
+                for _ in debug_groups.drain(..) {
+                    canvas.pop_debug_group();
+                }
+                texture_layer = None;
+
+                // Exiting widgets are drawn over the rest of their layer,
+                // including layers that no longer have anything on them.
+                draw_exiting_layers(
+                    &self.texture_layers,
+                    self.frame_counter,
+                    current_layer..node.atom.z_layer,
+                    canvas,
+                );
+                current_layer = node.atom.z_layer;
+            }
+
+            if texture_layer.is_some_and(|(start, end, _)| !(start.0..=end.0).contains(&id.0)) {
+                texture_layer = None;
+            }
+
+            while debug_groups
+                .last()
+                .is_some_and(|(start, end)| !(start.0..=end.0).contains(&id.0))
+            {
+                debug_groups.pop();
+                canvas.pop_debug_group();
+            }
+
+            if let Some(label) = self.debug_labels.get(&id) {
+                canvas.push_debug_group(label);
+                debug_groups.push((id, self.ui_tree.last_descendant(id)));
+            }
 
-        for (node, (content, widget_id)) in self.ui_tree.iter_nodes_by_layer() {
             let layout = &node.result;
             if layout.width == 0.0 || layout.height == 0.0 {
                 continue;
             }
 
+            // Nested texture layers are drawn into the outer layer's texture
+            // directly.
+            if texture_layer.is_none() {
+                let scale = self.texture_layer_scales.get(&id);
+                if let (Some(&scale), Some(widget_id)) = (scale, widget_id) {
+                    let transition = self.transitions.get(&id).copied();
+                    let layer =
+                        self.texture_layers
+                            .entry(*widget_id)
+                            .or_insert_with(|| TextureLayer {
+                                canvas: canvas.new_sibling(),
+                                target: None,
+                                is_new_target: false,
+                                frame_last_used: 0,
+                                point: [0.0; 2],
+                                size: [0.0; 2],
+                                clip: layout.effective_clip,
+                                z_layer: 0,
+                                transition,
+                                shown: if transition.is_some() { 0.0 } else { 1.0 },
+                            });
+
+                    layer.frame_last_used = self.frame_counter;
+                    layer.z_layer = node.atom.z_layer;
+                    layer.transition = transition;
+                    layer.shown = match transition {
+                        Some(transition) => {
+                            (layer.shown + transition.step(self.time_delta)).min(1.0)
+                        }
+                        None => 1.0,
+                    };
+                    layer.begin(canvas, layout, scale);
+                    texture_layer = Some((id, self.ui_tree.last_descendant(id), *widget_id));
+                }
+            }
+
+            let canvas = match texture_layer {
+                Some((_, _, widget_id)) => {
+                    &mut self.texture_layers.get_mut(&widget_id).unwrap().canvas
+                }
+                None => &mut *canvas,
+            };
+
             match content {
                 LayoutContent::None => {}
                 LayoutContent::Fill {
                     paint,
                     border,
+                    border_colors,
                     border_width,
                     corner_radii,
                 } => {
@@ -227,9 +795,11 @@ impl UiContext {
                         clip: node.result.effective_clip,
                         paint: paint.clone(),
                         border: *border,
-                        border_width: border_width.into_array(),
+                        border_colors: *border_colors,
+                        border_width: *border_width,
                         corner_radii: corner_radii.into_array(),
                         use_nearest_sampling: false,
+                        rotation: 0.0,
                     });
                 }
                 LayoutContent::Text {
@@ -239,16 +809,32 @@ impl UiContext {
                 } => match text_layouts.get_mut(*text_layout_id) {
                     None => {}
                     Some(TextLayoutMut::Static(text_layout)) => {
-                        canvas.draw_text_layout(
-                            text_layout,
-                            [layout.x, layout.y],
-                            node.result.effective_clip,
-                        );
+                        let origin = [layout.x, layout.y];
+                        let clip = node.result.effective_clip;
+                        canvas.draw_text_layout(&text_layout.layout, origin, clip);
+
+                        let decoration = theme
+                            .get_style(text_layout.style_id)
+                            .text_decoration
+                            .get(text_layout.state);
+                        if decoration.contains(TextDecoration::OVERLINE) {
+                            canvas.draw_text_overlines(&text_layout.layout, origin, clip);
+                        }
                     }
                 },
                 LayoutContent::EditableText { content, visuals } => {
                     content.draw(text_context, canvas, layout, *visuals);
                 }
+                LayoutContent::Retained(subtree) => {
+                    let is_stale = subtree.borrow_mut().draw(
+                        theme,
+                        text_context,
+                        text_layouts,
+                        canvas,
+                        layout,
+                    );
+                    self.needs_redraw |= is_stale;
+                }
             }
 
             if let Some(widget_id) = widget_id {
@@ -265,11 +851,116 @@ impl UiContext {
                         height: node.result.height,
                     },
                 };
+                let visible = node.result.effective_clip.next(&ClipRect {
+                    point: [node.result.x, node.result.y],
+                    size: [node.result.width, node.result.height],
+                });
+                container.state.visible_area = Rect {
+                    origin: Point2 {
+                        x: visible.point[0],
+                        y: visible.point[1],
+                    },
+                    size: Size2 {
+                        width: visible.size[0],
+                        height: visible.size[1],
+                    },
+                };
                 container.state.layer = node.atom.z_layer;
+                container.state.hit_mask = self.hit_masks.remove(&id);
                 container.state.is_modal = node.atom.is_modal;
+                container.state.pointer_phase =
+                    self.pointer_handlers.get_mut().get(widget_id).copied();
+                // Siblings are drawn in z_index order, so ids don't say which
+                // of two overlapping widgets is on top.
+                container.state.draw_order = draw_order as u16;
+                container.state.subtree_end =
+                    if container.state.pointer_phase == Some(PointerPhase::Capture) {
+                        (draw_order + self.ui_tree.layer_subtree_len(id) - 1) as u16
+                    } else {
+                        draw_order as u16
+                    };
+
+                if let Some(pointer) = self.inspector_pointer
+                    && let Some(&(style_id, state)) = self.node_styles.get(&id)
+                    && container.state.visible_area.contains(&pointer)
+                {
+                    inspected_node = Some(InspectedNode {
+                        widget_id: *widget_id,
+                        style_id,
+                        state,
+                        placement: container.state.placement,
+                    });
+                }
             }
         }
 
+        // Widgets see focus changes on the next frame, and only then know
+        // whether to show a focus ring.
+        if self.focused_widget.get() != self.prev_focused_widget {
+            self.prev_focused_widget = self.focused_widget.get();
+            self.is_focus_visible = self.prev_focused_widget.is_some() && self.is_keyboard_frame;
+            self.needs_redraw = true;
+        }
+
+        // Status bars may be built before the widget they describe, so they
+        // show the hint from the last frame.
+        let status_hint = self
+            .hovered_help_text
+            .take()
+            .or_else(|| self.focused_help_text.take());
+        self.focused_help_text = None;
+        if status_hint != self.status_hint {
+            self.status_hint = status_hint;
+            self.needs_redraw = true;
+        }
+
+        // The inspector is built before the widgets it inspects, so it shows
+        // a change on the frame after it happens.
+        if inspected_node != self.inspected_node {
+            self.inspected_node = inspected_node;
+            self.needs_redraw = true;
+        }
+
+        for _ in debug_groups.drain(..) {
+            canvas.pop_debug_group();
+        }
+
+        draw_exiting_layers(
+            &self.texture_layers,
+            self.frame_counter,
+            current_layer..=u8::MAX,
+            canvas,
+        );
+
+        // Layers of widgets that are gone are kept until they have
+        // transitioned out.
+        let time_delta = self.time_delta;
+        let frame_counter = self.frame_counter;
+        self.texture_layers.retain(|_, layer| {
+            if layer.frame_last_used == frame_counter {
+                return true;
+            }
+
+            let Some(transition) = layer.transition else {
+                return false;
+            };
+
+            layer.shown -= transition.step(time_delta);
+            layer.shown > 0.0
+        });
+
+        if self.texture_layers.values().any(|layer| layer.shown < 1.0) {
+            self.needs_redraw = true;
+        }
+
+        if self
+            .texture_layers
+            .values()
+            .any(|layer| layer.canvas.has_unready_textures())
+        {
+            self.needs_redraw = true;
+        }
+
         let removed = self
             .widget_states
             .extract_if(|_, container| container.frame_last_used < self.frame_counter);
@@ -284,16 +975,120 @@ impl UiContext {
             self.widget_states.shrink_to_fit();
         }
 
+        self.accessibility_click = None;
         self.frame_counter += 1;
     }
 }
 
+/// A subtree that is drawn into a texture of its own, which is then drawn in
+/// the subtree's place.
+struct TextureLayer {
+    canvas: Canvas,
+    target: Option<Texture>,
+    /// Set when the target was created this frame and has yet to be drawn.
+    is_new_target: bool,
+    frame_last_used: u64,
+
+    /// Where the texture was last drawn, so that it can be drawn there while
+    /// its widget transitions out.
+    point: [f32; 2],
+    size: [f32; 2],
+    clip: ClipRect,
+    z_layer: u8,
+
+    transition: Option<Transition>,
+    /// How far the layer has transitioned in, from 0 when hidden to 1 when
+    /// fully shown.
+    shown: f32,
+}
+
+impl TextureLayer {
+    /// Prepares the layer's canvas for a subtree with the given layout and
+    /// draws the layer's texture into `canvas`.
+    fn begin(&mut self, canvas: &mut Canvas, layout: &NodeLayout, scale: f32) {
+        // Relative to the canvas the layer is drawn into, which is already
+        // scaled to the display.
+        let scale = scale * canvas.scale();
+        let width = (layout.width * scale)
+            .ceil()
+            .clamp(1.0, f32::from(u16::MAX)) as u16;
+        let height = (layout.height * scale)
+            .ceil()
+            .clamp(1.0, f32::from(u16::MAX)) as u16;
+
+        self.is_new_target = self
+            .target
+            .as_ref()
+            .is_none_or(|target| target.size() != [width, height]);
+
+        if self.is_new_target {
+            self.target = Some(canvas.create_render_target(width, height));
+        }
+
+        self.canvas.reset(Color::TRANSPARENT);
+        self.canvas.set_view([layout.x, layout.y], scale);
+
+        // The texture is rounded up to whole pixels, so it is drawn at the
+        // size it covers rather than stretched to the node.
+        self.point = [layout.x, layout.y];
+        self.size = [f32::from(width) / scale, f32::from(height) / scale];
+        self.clip = layout.effective_clip;
+        self.draw(canvas);
+    }
+
+    /// Draws the layer's texture where it was last placed, faded and offset
+    /// by its transition.
+    fn draw(&self, canvas: &mut Canvas) {
+        let Some(target) = &self.target else {
+            return;
+        };
+
+        let (opacity, offset) = self
+            .transition
+            .map_or((1.0, [0.0; 2]), |transition| transition.at(self.shown));
+
+        canvas.draw(Primitive {
+            point: [self.point[0] + offset[0], self.point[1] + offset[1]],
+            size: self.size,
+            clip: self.clip,
+            paint: Paint::textured(target.clone(), Color::WHITE.mul_alpha(opacity)),
+            border: GradientPaint::default(),
+            border_colors: None,
+            border_width: [0.0; 4],
+            corner_radii: [0.0; 4],
+            use_nearest_sampling: false,
+            rotation: 0.0,
+        });
+    }
+}
+
+/// Draws the layers of widgets that weren't built this frame and are still
+/// transitioning out.
+fn draw_exiting_layers(
+    layers: &IdMap<TextureLayer>,
+    frame_counter: u64,
+    z_layers: impl RangeBounds<u8>,
+    canvas: &mut Canvas,
+) {
+    for layer in layers.values() {
+        if layer.frame_last_used < frame_counter && z_layers.contains(&layer.z_layer) {
+            layer.draw(canvas);
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 pub(super) struct EditableTextVisuals {
     pub(super) alignment: TextAlignment,
     pub(super) cursor_size: f32,
+    pub(super) cursor_width: f32,
     pub(super) selection_color: Color,
     pub(super) cursor_color: Color,
+    /// Whether the placeholder is drawn while the text is empty.
+    pub(super) show_placeholder: bool,
+    /// Whether the text style has an overline, which parley doesn't draw
+    /// like other decorations.
+    pub(super) has_overline: bool,
 }
 
 pub(super) trait EditableTextContent {
@@ -324,7 +1119,9 @@ pub(super) enum LayoutContent {
     Fill {
         paint: Paint,
         border: GradientPaint,
-        border_width: BorderWidths,
+        border_colors: Option<BorderColors>,
+        /// In pixels, as [left, top, right, bottom].
+        border_width: [f32; 4],
         corner_radii: CornerRadii,
     },
     Text {
@@ -336,4 +1133,79 @@ pub(super) enum LayoutContent {
         content: std::rc::Rc<dyn EditableTextContent>,
         visuals: EditableTextVisuals,
     },
+    Retained(std::rc::Rc<std::cell::RefCell<RetainedSubtree>>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::graphics::GraphicsContext;
+    use crate::shell::WindowSize;
+    use crate::ui::AxisAnchor;
+    use crate::ui::OverlayPosition;
+    use crate::ui::widget::ClickBehavior;
+    use crate::ui::widget::Interaction;
+
+    #[test]
+    fn press_goes_to_sibling_with_higher_z_index() {
+        let Some(mut graphics) = GraphicsContext::headless() else {
+            eprintln!("No graphics adapter available, skipping");
+            return;
+        };
+
+        let clipboard = Clipboard::new();
+        let mut text_context = TextLayoutContext::default();
+        let mut text_layouts = TextLayoutStorage::default();
+        let mut format_buffer = String::new();
+        let theme = Theme::default();
+        let mut canvas = graphics.create_canvas();
+        let mut context = UiContext::default();
+
+        let mut input = Input {
+            window_size: WindowSize {
+                width: 200.0,
+                height: 200.0,
+            },
+            pointer: Point2::new(50.0, 50.0),
+            ..Default::default()
+        };
+
+        // Builds two siblings covering the same area, the first drawn above
+        // the second, and returns whether each was activated.
+        let mut frame = |context: &mut UiContext, input: &Input| {
+            let mut builder = context.begin_frame(
+                &clipboard,
+                &mut text_context,
+                &mut text_layouts,
+                &mut format_buffer,
+                &theme,
+                input,
+                Duration::ZERO,
+                Duration::ZERO,
+                None,
+                WindowShape::default(),
+            );
+
+            let mut activated = [false; 2];
+            for (i, z_index) in [1, 0].into_iter().enumerate() {
+                let position =
+                    OverlayPosition::inside(AxisAnchor::Start, AxisAnchor::Start, (0.0, 0.0));
+                let mut child = builder.overlay_offset_child(i, position, 0);
+                child.size(100.0, 100.0).z_index(z_index);
+
+                let (interaction, _) =
+                    Interaction::compute(&child, ClickBehavior::OnPress, StateFlags::PRESSED);
+                activated[i] = interaction.is_activated;
+            }
+
+            context.finish(&theme, &mut text_context, &mut text_layouts, &mut canvas);
+            activated
+        };
+
+        frame(&mut context, &input);
+
+        input.mouse_state.left_click_count = 1;
+        assert_eq!(frame(&mut context, &input), [true, false]);
+    }
 }
@@ -29,6 +29,18 @@ impl WidgetId {
         let value = hasher.finish();
         WidgetId(NonZeroU64::new(value).unwrap_or(NonZeroU64::MIN))
     }
+
+    #[cfg(feature = "accessibility")]
+    #[allow(dead_code)]
+    pub(crate) fn to_u64(self) -> u64 {
+        self.0.get()
+    }
+
+    #[cfg(feature = "accessibility")]
+    #[allow(dead_code)]
+    pub(crate) fn from_u64(value: u64) -> Option<Self> {
+        NonZeroU64::new(value).map(WidgetId)
+    }
 }
 
 pub(crate) type IdMap<V> = HashMap<WidgetId, V, IdHasherBuilder>;
@@ -0,0 +1,103 @@
+use glamour::Rect;
+
+use crate::graphics::Color;
+use crate::graphics::GradientPaint;
+use crate::graphics::Paint;
+
+use super::CommonWidgetsExt;
+use super::LayoutDirection;
+use super::PixelSize;
+use super::Pixels;
+use super::Position;
+use super::StyleClass;
+use super::UiBuilder;
+use super::WidgetId;
+use super::style::BorderWidths;
+use super::style::CornerRadii;
+use super::style::StateFlags;
+use super::style::StyleId;
+
+const OUTLINE_COLOR: Color = Color::linear(1.0, 0.0, 1.0, 1.0);
+
+/// How far the panel is drawn from the pointer, so that it doesn't cover what
+/// is being inspected.
+const PANEL_OFFSET: f32 = 16.0;
+
+/// The widget under the pointer while the style inspector is active.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct InspectedNode {
+    pub(super) widget_id: WidgetId,
+    pub(super) style_id: StyleId,
+    pub(super) state: StateFlags,
+    pub(super) placement: Rect<Pixels>,
+}
+
+/// Outlines the inspected widget and shows what it was built with in a panel
+/// next to the pointer.
+pub(super) fn show(builder: &mut UiBuilder<'_>, node: &InspectedNode) {
+    let placement = node.placement;
+
+    let mut outline = builder.overlay_child_inner(
+        "style_inspector_outline",
+        Position::Absolute {
+            x: placement.origin.x,
+            y: placement.origin.y,
+        },
+        u8::MAX,
+        false,
+    );
+    // The placement is already in pixels.
+    let atom = outline.context.ui_tree.atom_mut(outline.index);
+    atom.width = PixelSize::Fixed(placement.size.width);
+    atom.height = PixelSize::Fixed(placement.size.height);
+    outline.paint(
+        Paint::solid(Color::TRANSPARENT),
+        GradientPaint::solid(OUTLINE_COLOR),
+        BorderWidths::uniform(2.0),
+        CornerRadii::default(),
+    );
+
+    let pointer = builder.input.pointer;
+    let window_size = builder.input.window_size;
+
+    let mut panel = builder.overlay_child_inner(
+        "style_inspector_panel",
+        Position::Absolute { x: 0.0, y: 0.0 },
+        u8::MAX,
+        false,
+    );
+    panel.apply_style(StyleClass::DropdownMenu, StateFlags::NORMAL);
+    panel.child_direction(LayoutDirection::Vertical);
+
+    // The panel's size is only known from the last frame it was shown. It is
+    // flipped to the other side of the pointer if it would leave the window.
+    let (width, height) = panel.prev_state().map_or((0.0, 0.0), |state| {
+        (state.placement.width(), state.placement.height())
+    });
+    let x = if pointer.x + PANEL_OFFSET + width > window_size.width {
+        pointer.x - PANEL_OFFSET - width
+    } else {
+        pointer.x + PANEL_OFFSET
+    };
+    let y = if pointer.y + PANEL_OFFSET + height > window_size.height {
+        pointer.y - PANEL_OFFSET - height
+    } else {
+        pointer.y + PANEL_OFFSET
+    };
+    panel.context.ui_tree.atom_mut(panel.index).position = Position::Absolute {
+        x: x.max(0.0),
+        y: y.max(0.0),
+    };
+
+    panel.label(&format!("{:?}", node.widget_id));
+    panel.label(&format!("{:?}", node.style_id));
+    panel.label(&format!("State: {:?}", node.state));
+    panel.label(&format!(
+        "Rect: {}, {}, {} x {}",
+        placement.origin.x, placement.origin.y, placement.size.width, placement.size.height
+    ));
+
+    for property in panel.theme.resolve_all(node.style_id, node.state) {
+        panel.label(&format!("{property:?}"));
+    }
+}
@@ -1,5 +1,7 @@
 use crate::graphics::ClipRect;
 
+use super::grid;
+use super::stack;
 use super::tree::LayoutNode;
 use super::tree::NodeIndexArray;
 use super::tree::NodeLayout;
@@ -8,9 +10,9 @@ use super::types::Alignment;
 use super::types::AxisAnchor;
 use super::types::LayoutDirection;
 use super::types::OverlayPosition;
+use super::types::PixelSize;
+use super::types::PixelSize::*;
 use super::types::Position;
-use super::types::Size;
-use super::types::Size::*;
 
 pub(super) fn compute_major_axis_fit_sizes<D: LayoutDirectionExt>(
     nodes: &mut [LayoutNode],
@@ -21,6 +23,20 @@ pub(super) fn compute_major_axis_fit_sizes<D: LayoutDirectionExt>(
     let node = &nodes[node_id.0 as usize];
     let node_children = &children[node_id.0 as usize];
 
+    if node.is_reused {
+        return D::major_size_result(node);
+    }
+
+    if let Some(grid) = node.atom.grid {
+        let is_width = D::DIRECTION == LayoutDirection::Horizontal;
+        return grid::compute_fit_size(nodes, children, node_id, grid, is_width, parent_limit);
+    }
+
+    if node.atom.stack {
+        let is_width = D::DIRECTION == LayoutDirection::Horizontal;
+        return stack::compute_fit_size(nodes, children, node_id, is_width, parent_limit);
+    }
+
     if !(node.atom.direction == D::DIRECTION) {
         return compute_minor_axis_fit_sizes::<D::Other>(nodes, children, node_id, parent_limit);
     }
@@ -54,10 +70,11 @@ pub(super) fn compute_major_axis_fit_sizes<D: LayoutDirectionExt>(
     };
 
     let mut size = match size_spec {
-        Size::Fixed(size) => size,
-        Size::Fit { min, max } => child_sizes.clamp(min, max),
-        Size::Flex { max, .. } => max,
-        Size::Grow => {
+        PixelSize::Fixed(size) => size,
+        PixelSize::Fit { min, max } => child_sizes.clamp(min, max),
+        PixelSize::Flex { max, .. } => max,
+        PixelSize::GrowClamped { min, .. } => min,
+        PixelSize::Grow | PixelSize::Percent(_) => {
             // Grow is handled in the offsets phase
             0.0
         }
@@ -80,6 +97,20 @@ pub(super) fn compute_major_axis_grow_sizes<D: LayoutDirectionExt>(
     let node = &nodes[node_id.0 as usize];
     let node_children = &children[node_id.0 as usize];
 
+    if node.is_reused {
+        return;
+    }
+
+    if let Some(grid) = node.atom.grid {
+        let is_width = D::DIRECTION == LayoutDirection::Horizontal;
+        return grid::compute_grow_sizes(nodes, children, node_id, grid, is_width);
+    }
+
+    if node.atom.stack {
+        let is_width = D::DIRECTION == LayoutDirection::Horizontal;
+        return stack::compute_grow_sizes(nodes, children, node_id, is_width);
+    }
+
     if !(node.atom.direction == D::DIRECTION) {
         return compute_minor_axis_grow_sizes::<D::Other>(nodes, children, node_id);
     }
@@ -91,22 +122,27 @@ pub(super) fn compute_major_axis_grow_sizes<D: LayoutDirectionExt>(
         .count();
     let inter_child = node.atom.inter_child_padding * in_flow_count.saturating_sub(1) as f32;
     let padding = D::major_axis_padding_start(node) + D::major_axis_padding_end(node);
+    let content_size = D::major_size_result(node) - padding;
     let mut grow_children = NodeIndexArray::new();
-    let mut remaining_size = D::major_size_result(node) - inter_child - padding;
+    let mut remaining_size = content_size - inter_child;
 
-    // Step 1: Find in-flow children that can grow.
+    // Step 1: Size percentage children and find in-flow children that can grow.
     for child_id in node_children {
-        let child = &nodes[child_id.0 as usize];
+        let child = &mut nodes[child_id.0 as usize];
         if !child.atom.position.is_in_flow() {
             continue;
         }
 
+        if let Percent(percent) = D::major_size_spec(child) {
+            D::set_major_size(child, content_size * percent / 100.0);
+        }
+
         let child_size = D::major_size_result(child);
         remaining_size -= child_size;
 
         match D::major_size_spec(child) {
-            Fixed(_) | Fit { .. } => {} // already computed
-            Flex { .. } | Grow => grow_children.push(*child_id),
+            Fixed(_) | Fit { .. } | Percent(_) => {} // already computed
+            Flex { .. } | Grow | GrowClamped { .. } => grow_children.push(*child_id),
         }
     }
 
@@ -119,7 +155,7 @@ pub(super) fn compute_major_axis_grow_sizes<D: LayoutDirectionExt>(
             let child_size = D::major_size_result(child);
 
             match D::major_size_spec(child) {
-                Fixed(_) | Fit { .. } => false,
+                Fixed(_) | Fit { .. } | Percent(_) => false,
                 Flex { max, .. } => {
                     let tentative_size = child_size + distributed_size;
 
@@ -140,6 +176,13 @@ pub(super) fn compute_major_axis_grow_sizes<D: LayoutDirectionExt>(
                     true
                 }
                 Grow => false,
+                GrowClamped { max, .. } if remaining_size > 0.0 => {
+                    let actual_size = (child_size + distributed_size).min(max);
+                    D::set_major_size(child, actual_size);
+                    remaining_size -= actual_size - child_size;
+                    actual_size < max
+                }
+                GrowClamped { .. } => false,
             }
         });
     }
@@ -158,6 +201,21 @@ pub(super) fn compute_major_axis_offsets<D: LayoutDirectionExt>(
 ) -> f32 {
     let node = &mut nodes[node_id.0 as usize];
 
+    if node.is_reused {
+        D::set_major_offset(node, current_offset);
+        return current_offset + D::major_size_result(node);
+    }
+
+    if let Some(grid) = node.atom.grid {
+        let is_width = D::DIRECTION == LayoutDirection::Horizontal;
+        return grid::compute_offsets(nodes, children, node_id, grid, is_width, current_offset);
+    }
+
+    if node.atom.stack {
+        let is_width = D::DIRECTION == LayoutDirection::Horizontal;
+        return stack::compute_offsets(nodes, children, node_id, is_width, current_offset);
+    }
+
     if node.atom.direction != D::DIRECTION {
         return compute_minor_axis_offsets::<D::Other>(nodes, children, node_id, current_offset);
     }
@@ -170,9 +228,23 @@ pub(super) fn compute_major_axis_offsets<D: LayoutDirectionExt>(
     let padding_end = D::major_axis_padding_end(node);
     // Copy major_align so the mutable borrow of `nodes` through `node` ends here,
     // allowing the immutable borrow needed by the in_flow_count filter below.
-    let major_align = node.atom.major_align;
-
-    let node_children = &children[node_id.0 as usize];
+    let mut major_align = node.atom.major_align;
+    let is_reversed = node.atom.reverse;
+
+    // Reversed layouts run from the end of the axis, so they place children in
+    // reverse order and pack them against the end instead of the start.
+    let reversed_children: NodeIndexArray;
+    let node_children: &[UiElementId] = if is_reversed {
+        reversed_children = children[node_id.0 as usize].iter().rev().copied().collect();
+        major_align = match major_align {
+            Alignment::Start => Alignment::End,
+            Alignment::End => Alignment::Start,
+            other => other,
+        };
+        &reversed_children
+    } else {
+        &children[node_id.0 as usize]
+    };
 
     // Count in-flow children for alignment calculations.
     let in_flow_count = node_children
@@ -275,6 +347,36 @@ pub(super) fn compute_major_axis_offsets<D: LayoutDirectionExt>(
                 }
             }
         }
+        Alignment::SpaceAround | Alignment::SpaceEvenly => {
+            let mut in_flow_content_size = padding_start
+                + padding_end
+                + padding_internal * in_flow_count.saturating_sub(1) as f32;
+            for &child_id in node_children {
+                if nodes[child_id.0 as usize].atom.position.is_in_flow() {
+                    in_flow_content_size += D::major_size_result(&nodes[child_id.0 as usize]);
+                }
+            }
+
+            let unused_space = (size - in_flow_content_size).max(0.0);
+            let (leading_space, extra_spacing) = if major_align == Alignment::SpaceAround {
+                let share = unused_space / in_flow_count.max(1) as f32;
+                (share / 2.0, share)
+            } else {
+                let share = unused_space / (in_flow_count + 1) as f32;
+                (share, share)
+            };
+
+            let mut advance = current_offset + padding_start + leading_space;
+            for &child_id in node_children {
+                if nodes[child_id.0 as usize].atom.position.is_in_flow() {
+                    advance = compute_major_axis_offsets::<D>(nodes, children, child_id, advance)
+                        + padding_internal
+                        + extra_spacing;
+                } else {
+                    recurse_out_of_flow!(child_id);
+                }
+            }
+        }
     }
 
     current_offset + size
@@ -285,6 +387,10 @@ pub(super) fn compute_text_heights<'a, T: 'a>(
     nodes: impl Iterator<Item = (&'a mut LayoutNode, &'a mut T)>,
 ) {
     for (node, content) in nodes {
+        if node.is_reused {
+            continue;
+        }
+
         let Some(text_height) = measure_text(content, node.result.width) else {
             continue;
         };
@@ -293,10 +399,12 @@ pub(super) fn compute_text_heights<'a, T: 'a>(
             Fixed(height) => Fixed(height),
             Fit { min, max } => Fixed(text_height.clamp(min, max)),
             Grow => Grow,
+            GrowClamped { min, max } => GrowClamped { min, max },
             Flex { min, max } => Flex {
                 min: text_height.clamp(min, max),
                 max,
             },
+            Percent(percent) => Percent(percent),
         };
     }
 }
@@ -309,6 +417,20 @@ pub(super) fn compute_minor_axis_fit_sizes<D: LayoutDirectionExt>(
 ) -> f32 {
     let node = &nodes[node_id.0 as usize];
 
+    if node.is_reused {
+        return D::minor_size_result(node);
+    }
+
+    if let Some(grid) = node.atom.grid {
+        let is_width = D::DIRECTION == LayoutDirection::Vertical;
+        return grid::compute_fit_size(nodes, children, node_id, grid, is_width, parent_limit);
+    }
+
+    if node.atom.stack {
+        let is_width = D::DIRECTION == LayoutDirection::Vertical;
+        return stack::compute_fit_size(nodes, children, node_id, is_width, parent_limit);
+    }
+
     if node.atom.direction != D::DIRECTION {
         return compute_major_axis_fit_sizes::<D::Other>(nodes, children, node_id, parent_limit);
     }
@@ -336,7 +458,8 @@ pub(super) fn compute_minor_axis_fit_sizes<D: LayoutDirectionExt>(
         Fixed(size) => size,
         Fit { min, max } => (child_sizes + size_padding).clamp(min, max),
         Flex { max, .. } => max,
-        Grow => 0.0, // Grow is handled later
+        GrowClamped { min, .. } => min,
+        Grow | Percent(_) => 0.0, // Grow and Percent are handled later
     };
 
     if let Some(limit) = parent_limit {
@@ -354,6 +477,20 @@ pub(super) fn compute_minor_axis_grow_sizes<D: LayoutDirectionExt>(
 ) {
     let node = &nodes[node_id.0 as usize];
 
+    if node.is_reused {
+        return;
+    }
+
+    if let Some(grid) = node.atom.grid {
+        let is_width = D::DIRECTION == LayoutDirection::Vertical;
+        return grid::compute_grow_sizes(nodes, children, node_id, grid, is_width);
+    }
+
+    if node.atom.stack {
+        let is_width = D::DIRECTION == LayoutDirection::Vertical;
+        return stack::compute_grow_sizes(nodes, children, node_id, is_width);
+    }
+
     if !(node.atom.direction == D::DIRECTION) {
         return compute_major_axis_grow_sizes::<D::Other>(nodes, children, node_id);
     }
@@ -365,8 +502,13 @@ pub(super) fn compute_minor_axis_grow_sizes<D: LayoutDirectionExt>(
         // Only in-flow children consume the parent's minor-axis remaining space.
         if nodes[child_id.0 as usize].atom.position.is_in_flow() {
             let child = &mut nodes[child_id.0 as usize];
-            if matches!(D::minor_size_spec(child), Grow) {
-                D::set_minor_size(child, remaining_size);
+            match D::minor_size_spec(child) {
+                Grow => D::set_minor_size(child, remaining_size),
+                GrowClamped { min, max } => {
+                    D::set_minor_size(child, remaining_size.min(max).max(min));
+                }
+                Percent(percent) => D::set_minor_size(child, remaining_size * percent / 100.0),
+                Fixed(_) | Fit { .. } | Flex { .. } => {}
             }
         }
 
@@ -383,6 +525,21 @@ pub(super) fn compute_minor_axis_offsets<D: LayoutDirectionExt>(
     let node = &mut nodes[node_id.0 as usize];
     let node_children = &children[node_id.0 as usize];
 
+    if node.is_reused {
+        D::set_minor_offset(node, current_offset);
+        return current_offset + D::minor_size_result(node);
+    }
+
+    if let Some(grid) = node.atom.grid {
+        let is_width = D::DIRECTION == LayoutDirection::Vertical;
+        return grid::compute_offsets(nodes, children, node_id, grid, is_width, current_offset);
+    }
+
+    if node.atom.stack {
+        let is_width = D::DIRECTION == LayoutDirection::Vertical;
+        return stack::compute_offsets(nodes, children, node_id, is_width, current_offset);
+    }
+
     if node.atom.direction != D::DIRECTION {
         return compute_major_axis_offsets::<D::Other>(nodes, children, node_id, current_offset);
     }
@@ -394,9 +551,9 @@ pub(super) fn compute_minor_axis_offsets<D: LayoutDirectionExt>(
     let padding_end = D::minor_axis_padding_end(node);
 
     match node.atom.minor_align {
-        // Justified layouts don't make sense in the minor axis, so we treat
-        // them as start-aligned.
-        Alignment::Start | Alignment::Justify => {
+        // Distributing space doesn't make sense in the minor axis, so we treat
+        // those modes as start-aligned.
+        Alignment::Start | Alignment::Justify | Alignment::SpaceAround | Alignment::SpaceEvenly => {
             let inset = current_offset + padding_start;
 
             for &child_id in node_children {
@@ -439,8 +596,8 @@ pub(super) trait LayoutDirectionExt {
     type Other: LayoutDirectionExt;
     const DIRECTION: LayoutDirection;
 
-    fn major_size_spec(node: &LayoutNode) -> Size;
-    fn minor_size_spec(node: &LayoutNode) -> Size;
+    fn major_size_spec(node: &LayoutNode) -> PixelSize;
+    fn minor_size_spec(node: &LayoutNode) -> PixelSize;
 
     fn set_major_size(node: &mut LayoutNode, size: f32);
     fn set_minor_size(node: &mut LayoutNode, size: f32);
@@ -464,11 +621,11 @@ impl LayoutDirectionExt for HorizontalMode {
     type Other = VerticalMode;
     const DIRECTION: LayoutDirection = LayoutDirection::Horizontal;
 
-    fn major_size_spec(node: &LayoutNode) -> Size {
+    fn major_size_spec(node: &LayoutNode) -> PixelSize {
         node.atom.width
     }
 
-    fn minor_size_spec(node: &LayoutNode) -> Size {
+    fn minor_size_spec(node: &LayoutNode) -> PixelSize {
         node.atom.height
     }
 
@@ -519,11 +676,11 @@ impl LayoutDirectionExt for VerticalMode {
     type Other = HorizontalMode;
     const DIRECTION: LayoutDirection = LayoutDirection::Vertical;
 
-    fn major_size_spec(node: &LayoutNode) -> Size {
+    fn major_size_spec(node: &LayoutNode) -> PixelSize {
         node.atom.height
     }
 
-    fn minor_size_spec(node: &LayoutNode) -> Size {
+    fn minor_size_spec(node: &LayoutNode) -> PixelSize {
         node.atom.width
     }
 
@@ -604,7 +761,7 @@ fn resolve_overlay_position(
     match child.atom.position {
         Position::Absolute { x, y } => (x, y),
         Position::InFlow => (child.result.x, child.result.y), // should not happen
-        Position::OutOfFlow(pos) => {
+        Position::OutOfFlow(pos) | Position::Anchored(pos) => {
             let (x, y) = compute_anchored_position(parent, &child.result, pos);
 
             // Apply viewport-aware flipping.
@@ -686,6 +843,24 @@ fn flip(anchor: AxisAnchor) -> AxisAnchor {
     }
 }
 
+/// Shifts the in-flow children of scrolled nodes by their parents' scroll
+/// offsets. Out-of-flow children are positioned afterwards, so they don't
+/// scroll with their parents.
+pub(super) fn apply_scroll_offsets(nodes: &mut [LayoutNode], children: &[NodeIndexArray]) {
+    for idx in 0..nodes.len() {
+        let (dx, dy) = nodes[idx].atom.scroll_offset;
+        if dx == 0.0 && dy == 0.0 {
+            continue;
+        }
+
+        for &child_id in &children[idx] {
+            if nodes[child_id.0 as usize].atom.position.is_in_flow() {
+                adjust_subtree_offsets(nodes, children, child_id, -dx, -dy);
+            }
+        }
+    }
+}
+
 /// Recursively shift all nodes in a subtree by (dx, dy).
 fn adjust_subtree_offsets(
     nodes: &mut [LayoutNode],
@@ -713,13 +888,12 @@ pub(super) fn compute_clip_rects(
 
     // Out-of-flow nodes escape their logical parent's clip hierarchy: they start from
     // the viewport clip instead, so they are never clipped by a scroll container ancestor.
-    let base_clip = if nodes[idx].atom.position.is_in_flow() {
-        current_clip
-    } else {
-        viewport_clip
+    let base_clip = match nodes[idx].atom.position {
+        Position::InFlow | Position::Anchored(_) => current_clip,
+        Position::OutOfFlow(_) | Position::Absolute { .. } => viewport_clip,
     };
 
-    let effective = if nodes[idx].atom.clip_overflow {
+    let effective = if nodes[idx].atom.overflow.is_clipped() {
         let r = &nodes[idx].result;
         base_clip.next(&ClipRect {
             point: [r.x, r.y],
@@ -0,0 +1,373 @@
+use smallvec::SmallVec;
+
+use super::compute::HorizontalMode;
+use super::compute::compute_major_axis_fit_sizes;
+use super::compute::compute_major_axis_grow_sizes;
+use super::compute::compute_major_axis_offsets;
+use super::compute::compute_minor_axis_fit_sizes;
+use super::compute::compute_minor_axis_grow_sizes;
+use super::compute::compute_minor_axis_offsets;
+use super::tree::LayoutNode;
+use super::tree::NodeIndexArray;
+use super::tree::UiElementId;
+use super::types::PixelGrid;
+use super::types::PixelGridColumns;
+use super::types::PixelSize;
+
+/// The cells covered by one of a grid's in-flow children.
+struct Cell {
+    child: UiElementId,
+    column: u16,
+    row: u16,
+    columns: u16,
+    rows: u16,
+}
+
+/// Where a grid's children are placed, for a given number of columns.
+struct Placement {
+    cells: SmallVec<[Cell; 16]>,
+    num_columns: u16,
+    num_rows: u16,
+}
+
+impl Placement {
+    /// Places in-flow children row by row. A child that doesn't fit in the
+    /// rest of a row, or would overlap a child spanning several rows, moves
+    /// along to the next free cells. Cells that are passed over stay empty.
+    fn new(nodes: &[LayoutNode], node_children: &[UiElementId], num_columns: u16) -> Self {
+        let width = usize::from(num_columns);
+        let mut occupied = Vec::<bool>::new();
+        let mut cells = SmallVec::new();
+        let (mut row, mut column) = (0usize, 0usize);
+        let mut num_rows = 0;
+
+        for &child in node_children {
+            let atom = &nodes[child.0 as usize].atom;
+            if !atom.position.is_in_flow() {
+                continue;
+            }
+
+            let columns = usize::from(atom.grid_span.columns.clamp(1, num_columns));
+            let rows = usize::from(atom.grid_span.rows.max(1));
+
+            loop {
+                if column + columns > width {
+                    row += 1;
+                    column = 0;
+                    continue;
+                }
+
+                occupied.resize(occupied.len().max((row + rows) * width), false);
+                let is_free = (row..row + rows).all(|r| {
+                    !occupied[r * width + column..r * width + column + columns].contains(&true)
+                });
+
+                if is_free {
+                    break;
+                }
+
+                column += 1;
+            }
+
+            for r in row..row + rows {
+                occupied[r * width + column..r * width + column + columns].fill(true);
+            }
+
+            cells.push(Cell {
+                child,
+                column: column as u16,
+                row: row as u16,
+                columns: columns as u16,
+                rows: rows as u16,
+            });
+
+            num_rows = num_rows.max(row + rows);
+            column += columns;
+        }
+
+        Self {
+            cells,
+            num_columns,
+            num_rows: num_rows as u16,
+        }
+    }
+
+    /// The height of each row, which is enough for the children in it. Rows
+    /// are made taller for children spanning several rows only if the
+    /// children in just those rows don't already make room.
+    fn row_heights(&self, nodes: &[LayoutNode], row_gap: f32) -> SmallVec<[f32; 8]> {
+        let mut heights: SmallVec<[f32; 8]> = SmallVec::from_elem(0.0, usize::from(self.num_rows));
+
+        for cell in self.cells.iter().filter(|cell| cell.rows == 1) {
+            let height = nodes[cell.child.0 as usize].result.height;
+            let row = usize::from(cell.row);
+            heights[row] = heights[row].max(height);
+        }
+
+        for cell in self.cells.iter().filter(|cell| cell.rows > 1) {
+            let height = nodes[cell.child.0 as usize].result.height;
+            let rows = usize::from(cell.row)..usize::from(cell.row + cell.rows);
+            let spanned =
+                heights[rows.clone()].iter().sum::<f32>() + row_gap * f32::from(cell.rows - 1);
+
+            if height > spanned {
+                heights[rows.end - 1] += height - spanned;
+            }
+        }
+
+        heights
+    }
+}
+
+fn num_in_flow(nodes: &[LayoutNode], node_children: &[UiElementId]) -> usize {
+    node_children
+        .iter()
+        .filter(|&&id| nodes[id.0 as usize].atom.position.is_in_flow())
+        .count()
+}
+
+/// The number of columns in a grid whose columns share `inner_width`, or
+/// whose width fits its content if that isn't known yet.
+fn num_columns(grid: PixelGrid, inner_width: Option<f32>, num_children: usize) -> u16 {
+    match grid.columns {
+        PixelGridColumns::Count(count) => count.max(1),
+        PixelGridColumns::AutoFill { min_width } => match inner_width {
+            Some(width) => ((width + grid.column_gap) / (min_width + grid.column_gap).max(1.0))
+                .floor()
+                .clamp(1.0, f32::from(u16::MAX)) as u16,
+            None => num_children.clamp(1, usize::from(u16::MAX)) as u16,
+        },
+    }
+}
+
+/// The placement of a grid's children and the width of its columns, once
+/// the grid's width is known.
+fn placement(
+    nodes: &[LayoutNode],
+    node_children: &[UiElementId],
+    node_id: UiElementId,
+    grid: PixelGrid,
+) -> (Placement, f32) {
+    let node = &nodes[node_id.0 as usize];
+    let padding = node.atom.inner_padding;
+    let inner_width = node.result.width - padding.left - padding.right;
+
+    let count = num_columns(grid, Some(inner_width), num_in_flow(nodes, node_children));
+    let gaps = grid.column_gap * f32::from(count - 1);
+    let column_width = ((inner_width - gaps) / f32::from(count)).max(0.0);
+
+    (Placement::new(nodes, node_children, count), column_width)
+}
+
+/// Applies a grid or stack's size spec to the size of its content, as for
+/// other nodes.
+pub(super) fn constrain(size_spec: PixelSize, content_size: f32, parent_limit: Option<f32>) -> f32 {
+    let size = match size_spec {
+        PixelSize::Fixed(size) => size,
+        PixelSize::Fit { min, max } => content_size.clamp(min, max),
+        PixelSize::Flex { max, .. } => max,
+        PixelSize::GrowClamped { min, .. } => min,
+        PixelSize::Grow | PixelSize::Percent(_) => 0.0,
+    };
+
+    match parent_limit {
+        Some(limit) => size.min(limit),
+        None => size,
+    }
+}
+
+/// Fills a cell with a child that grows or takes a percentage of it, and
+/// shrinks a child that fits its content to the cell.
+pub(super) fn fill_cell(size_spec: PixelSize, current: f32, cell_size: f32) -> Option<f32> {
+    match size_spec {
+        PixelSize::Fixed(_) => None,
+        PixelSize::Fit { .. } => Some(current.min(cell_size)),
+        PixelSize::Grow => Some(cell_size),
+        PixelSize::Percent(percent) => Some(cell_size * percent / 100.0),
+        PixelSize::Flex { min, max } | PixelSize::GrowClamped { min, max } => {
+            Some(cell_size.min(max).max(min))
+        }
+    }
+}
+
+/// Computes the fit width or height of a grid and its children.
+pub(super) fn compute_fit_size(
+    nodes: &mut [LayoutNode],
+    children: &[NodeIndexArray],
+    node_id: UiElementId,
+    grid: PixelGrid,
+    is_width: bool,
+    parent_limit: Option<f32>,
+) -> f32 {
+    let node_children = &children[node_id.0 as usize];
+    let padding = nodes[node_id.0 as usize].atom.inner_padding;
+    let padding = if is_width {
+        padding.left + padding.right
+    } else {
+        padding.top + padding.bottom
+    };
+    let child_parent_limit = parent_limit.map(|limit| (limit - padding).max(0.0));
+
+    for &child_id in node_children {
+        if is_width {
+            compute_major_axis_fit_sizes::<HorizontalMode>(
+                nodes,
+                children,
+                child_id,
+                child_parent_limit,
+            );
+        } else {
+            compute_minor_axis_fit_sizes::<HorizontalMode>(
+                nodes,
+                children,
+                child_id,
+                child_parent_limit,
+            );
+        }
+    }
+
+    let node = &nodes[node_id.0 as usize];
+    let size = if is_width {
+        // Columns are as wide as the widest child, with spanning children
+        // sharing their width between their columns.
+        let count = num_columns(grid, None, num_in_flow(nodes, node_children));
+        let placement = Placement::new(nodes, node_children, count);
+
+        let min_width = match grid.columns {
+            PixelGridColumns::Count(_) => 0.0,
+            PixelGridColumns::AutoFill { min_width } => min_width,
+        };
+        let column_width = placement.cells.iter().fold(min_width, |width, cell| {
+            let child_width = nodes[cell.child.0 as usize].result.width;
+            let gaps = grid.column_gap * f32::from(cell.columns - 1);
+            width.max((child_width - gaps) / f32::from(cell.columns))
+        });
+
+        let content = column_width * f32::from(count) + grid.column_gap * f32::from(count - 1);
+        constrain(node.atom.width, content + padding, parent_limit)
+    } else {
+        let (placement, _) = placement(nodes, node_children, node_id, grid);
+        let rows = placement.row_heights(nodes, grid.row_gap);
+
+        let content =
+            rows.iter().sum::<f32>() + grid.row_gap * placement.num_rows.saturating_sub(1) as f32;
+        constrain(node.atom.height, content + padding, parent_limit)
+    };
+
+    let node = &mut nodes[node_id.0 as usize];
+    if is_width {
+        node.result.width = size;
+    } else {
+        node.result.height = size;
+    }
+
+    size
+}
+
+/// Sizes the children of a grid that grow to fill their cells.
+pub(super) fn compute_grow_sizes(
+    nodes: &mut [LayoutNode],
+    children: &[NodeIndexArray],
+    node_id: UiElementId,
+    grid: PixelGrid,
+    is_width: bool,
+) {
+    let node_children = &children[node_id.0 as usize];
+    let (placement, column_width) = placement(nodes, node_children, node_id, grid);
+
+    if is_width {
+        for cell in &placement.cells {
+            let cell_width = column_width * f32::from(cell.columns)
+                + grid.column_gap * f32::from(cell.columns - 1);
+
+            let child = &mut nodes[cell.child.0 as usize];
+            if let Some(width) = fill_cell(child.atom.width, child.result.width, cell_width) {
+                child.result.width = width;
+            }
+        }
+    } else {
+        let rows = placement.row_heights(nodes, grid.row_gap);
+
+        for cell in &placement.cells {
+            let spanned = usize::from(cell.row)..usize::from(cell.row + cell.rows);
+            let cell_height =
+                rows[spanned].iter().sum::<f32>() + grid.row_gap * f32::from(cell.rows - 1);
+
+            let child = &mut nodes[cell.child.0 as usize];
+            if let Some(height) = fill_cell(child.atom.height, child.result.height, cell_height) {
+                child.result.height = height;
+            }
+        }
+    }
+
+    for &child_id in node_children {
+        if is_width {
+            compute_major_axis_grow_sizes::<HorizontalMode>(nodes, children, child_id);
+        } else {
+            compute_minor_axis_grow_sizes::<HorizontalMode>(nodes, children, child_id);
+        }
+    }
+}
+
+/// Places a grid's children in their cells along one axis.
+pub(super) fn compute_offsets(
+    nodes: &mut [LayoutNode],
+    children: &[NodeIndexArray],
+    node_id: UiElementId,
+    grid: PixelGrid,
+    is_width: bool,
+    current_offset: f32,
+) -> f32 {
+    let node_children = &children[node_id.0 as usize];
+    let (placement, column_width) = placement(nodes, node_children, node_id, grid);
+
+    let node = &mut nodes[node_id.0 as usize];
+    let padding = node.atom.inner_padding;
+    let size = if is_width {
+        node.result.x = current_offset;
+        node.result.width
+    } else {
+        node.result.y = current_offset;
+        node.result.height
+    };
+
+    // The offset of each column or row from the start of the content.
+    let mut starts = SmallVec::<[f32; 8]>::new();
+    if is_width {
+        starts.extend(
+            (0..placement.num_columns)
+                .map(|column| f32::from(column) * (column_width + grid.column_gap)),
+        );
+    } else {
+        let mut start = 0.0;
+        for height in placement.row_heights(nodes, grid.row_gap) {
+            starts.push(start);
+            start += height + grid.row_gap;
+        }
+    }
+
+    for cell in &placement.cells {
+        if is_width {
+            let offset = current_offset + padding.left + starts[usize::from(cell.column)];
+            compute_major_axis_offsets::<HorizontalMode>(nodes, children, cell.child, offset);
+        } else {
+            let offset = current_offset + padding.top + starts[usize::from(cell.row)];
+            compute_minor_axis_offsets::<HorizontalMode>(nodes, children, cell.child, offset);
+        }
+    }
+
+    // Out-of-flow children are positioned later, see `compute_overlay_positions`.
+    for &child_id in node_children {
+        if nodes[child_id.0 as usize].atom.position.is_in_flow() {
+            continue;
+        }
+
+        if is_width {
+            compute_major_axis_offsets::<HorizontalMode>(nodes, children, child_id, 0.0);
+        } else {
+            compute_minor_axis_offsets::<HorizontalMode>(nodes, children, child_id, 0.0);
+        }
+    }
+
+    current_offset + size
+}
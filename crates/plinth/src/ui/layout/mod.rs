@@ -1,4 +1,6 @@
 mod compute;
+mod grid;
+mod stack;
 mod tree;
 mod types;
 
@@ -0,0 +1,168 @@
+use super::compute::HorizontalMode;
+use super::compute::compute_major_axis_fit_sizes;
+use super::compute::compute_major_axis_grow_sizes;
+use super::compute::compute_major_axis_offsets;
+use super::compute::compute_minor_axis_fit_sizes;
+use super::compute::compute_minor_axis_grow_sizes;
+use super::compute::compute_minor_axis_offsets;
+use super::grid::constrain;
+use super::grid::fill_cell;
+use super::tree::LayoutNode;
+use super::tree::NodeIndexArray;
+use super::tree::UiElementId;
+use super::types::Alignment;
+use super::types::LayoutDirection;
+
+fn inner_padding(node: &LayoutNode, is_width: bool) -> (f32, f32) {
+    let padding = node.atom.inner_padding;
+    if is_width {
+        (padding.left, padding.right)
+    } else {
+        (padding.top, padding.bottom)
+    }
+}
+
+/// Computes the fit width or height of a stack, which is that of its largest
+/// in-flow child.
+pub(super) fn compute_fit_size(
+    nodes: &mut [LayoutNode],
+    children: &[NodeIndexArray],
+    node_id: UiElementId,
+    is_width: bool,
+    parent_limit: Option<f32>,
+) -> f32 {
+    let (start, end) = inner_padding(&nodes[node_id.0 as usize], is_width);
+    let child_parent_limit = parent_limit.map(|limit| (limit - start - end).max(0.0));
+
+    let mut content_size = 0.0f32;
+    for &child_id in &children[node_id.0 as usize] {
+        let child_size = if is_width {
+            compute_major_axis_fit_sizes::<HorizontalMode>(
+                nodes,
+                children,
+                child_id,
+                child_parent_limit,
+            )
+        } else {
+            compute_minor_axis_fit_sizes::<HorizontalMode>(
+                nodes,
+                children,
+                child_id,
+                child_parent_limit,
+            )
+        };
+
+        if nodes[child_id.0 as usize].atom.position.is_in_flow() {
+            content_size = content_size.max(child_size);
+        }
+    }
+
+    let node = &mut nodes[node_id.0 as usize];
+    if is_width {
+        let size = constrain(node.atom.width, content_size + start + end, parent_limit);
+        node.result.width = size;
+        size
+    } else {
+        let size = constrain(node.atom.height, content_size + start + end, parent_limit);
+        node.result.height = size;
+        size
+    }
+}
+
+/// Sizes the children of a stack that grow to fill it.
+pub(super) fn compute_grow_sizes(
+    nodes: &mut [LayoutNode],
+    children: &[NodeIndexArray],
+    node_id: UiElementId,
+    is_width: bool,
+) {
+    let node = &nodes[node_id.0 as usize];
+    let (start, end) = inner_padding(node, is_width);
+    let available = if is_width {
+        node.result.width - start - end
+    } else {
+        node.result.height - start - end
+    };
+
+    for &child_id in &children[node_id.0 as usize] {
+        let child = &mut nodes[child_id.0 as usize];
+        if child.atom.position.is_in_flow() {
+            if is_width {
+                if let Some(width) = fill_cell(child.atom.width, child.result.width, available) {
+                    child.result.width = width;
+                }
+            } else if let Some(height) =
+                fill_cell(child.atom.height, child.result.height, available)
+            {
+                child.result.height = height;
+            }
+        }
+
+        if is_width {
+            compute_major_axis_grow_sizes::<HorizontalMode>(nodes, children, child_id);
+        } else {
+            compute_minor_axis_grow_sizes::<HorizontalMode>(nodes, children, child_id);
+        }
+    }
+}
+
+/// Places each of a stack's children within it along one axis, by the
+/// child's own alignment or else the stack's.
+pub(super) fn compute_offsets(
+    nodes: &mut [LayoutNode],
+    children: &[NodeIndexArray],
+    node_id: UiElementId,
+    is_width: bool,
+    current_offset: f32,
+) -> f32 {
+    let node = &mut nodes[node_id.0 as usize];
+    let (start, end) = inner_padding(node, is_width);
+    let size = if is_width {
+        node.result.x = current_offset;
+        node.result.width
+    } else {
+        node.result.y = current_offset;
+        node.result.height
+    };
+
+    // The stack's own alignments are given along and across its direction.
+    let is_major = is_width == (node.atom.direction == LayoutDirection::Horizontal);
+    let default_align = if is_major {
+        node.atom.major_align
+    } else {
+        node.atom.minor_align
+    };
+
+    for &child_id in &children[node_id.0 as usize] {
+        let child = &nodes[child_id.0 as usize];
+
+        // Out-of-flow children are positioned later, see `compute_overlay_positions`.
+        let offset = if child.atom.position.is_in_flow() {
+            let (align, child_size) = if is_width {
+                (child.atom.align_self.map(|(x, _)| x), child.result.width)
+            } else {
+                (child.atom.align_self.map(|(_, y)| y), child.result.height)
+            };
+
+            let unused_space = size - start - end - child_size;
+            match align.unwrap_or(default_align) {
+                Alignment::Start
+                | Alignment::Justify
+                | Alignment::SpaceAround
+                | Alignment::SpaceEvenly => current_offset + start,
+                Alignment::Center => current_offset + start + (unused_space / 2.0).round(),
+                Alignment::End => current_offset + start + unused_space,
+            }
+        } else {
+            0.0
+        };
+
+        if is_width {
+            compute_major_axis_offsets::<HorizontalMode>(nodes, children, child_id, offset);
+        } else {
+            compute_minor_axis_offsets::<HorizontalMode>(nodes, children, child_id, offset);
+        }
+    }
+
+    current_offset + size
+}
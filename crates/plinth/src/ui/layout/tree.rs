@@ -1,9 +1,15 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::hash::Hasher;
+
+use rapidhash::fast::RapidHasher;
 use smallvec::SmallVec;
 
 use crate::graphics::ClipRect;
 use crate::ui::layout::compute::compute_clip_rects;
 
 use super::compute::HorizontalMode;
+use super::compute::apply_scroll_offsets;
 use super::compute::compute_major_axis_fit_sizes;
 use super::compute::compute_major_axis_grow_sizes;
 use super::compute::compute_major_axis_offsets;
@@ -13,30 +19,54 @@ use super::compute::compute_minor_axis_offsets;
 use super::compute::compute_overlay_positions;
 use super::compute::compute_text_heights;
 use super::types::Alignment;
+use super::types::GridSpan;
 use super::types::LayoutDirection;
-use super::types::Padding;
+use super::types::Overflow;
+use super::types::PixelGrid;
+use super::types::PixelGridColumns;
+use super::types::PixelPadding;
+use super::types::PixelSize;
 use super::types::Position;
-use super::types::Size;
 
 #[derive(Debug, Default)]
 pub struct Atom {
-    pub width: Size,
-    pub height: Size,
-    pub inner_padding: Padding,
+    pub width: PixelSize,
+    pub height: PixelSize,
+    pub inner_padding: PixelPadding,
 
     // Container layout properties
     pub major_align: Alignment,
     pub minor_align: Alignment,
     pub direction: LayoutDirection,
     pub inter_child_padding: f32,
+    /// Lays out in-flow children from the end of `direction` instead of the
+    /// start, so the first child is placed last.
+    pub reverse: bool,
+    /// Lays out children in a grid instead of along `direction`.
+    pub grid: Option<PixelGrid>,
+    /// The cells this node covers in its parent's grid, if it has one.
+    pub grid_span: GridSpan,
+    /// Places every child over the whole node instead of along `direction`.
+    pub stack: bool,
+    /// How this node is aligned horizontally and vertically in its parent's
+    /// stack, instead of by the parent's alignment.
+    pub align_self: Option<(Alignment, Alignment)>,
 
-    pub clip_overflow: bool,
+    pub overflow: Overflow,
+    /// How far this node's in-flow children are shifted left and up, for
+    /// scrolling. Children shifted out of the node stay visible unless it
+    /// clips its overflow.
+    pub scroll_offset: (f32, f32),
 
     /// How this node participates in its parent's layout. Defaults to [`Position::InFlow`].
     pub position: Position,
     /// Rendering layer. Nodes with higher `z_layer` render above nodes with lower `z_layer`.
     /// Overlay children automatically receive `parent.z_layer + 1`.
     pub z_layer: u8,
+    /// Draw order among siblings in the same layer. Siblings with a higher
+    /// `z_index` are drawn above those with a lower one, and ties are drawn in
+    /// creation order.
+    pub z_index: i16,
     /// When true, this overlay blocks pointer and keyboard input from reaching any widget
     /// on a lower `z_layer`, regardless of pointer position. Use for modal dialogs.
     pub is_modal: bool,
@@ -60,6 +90,9 @@ pub(super) type NodeIndexArray = SmallVec<[UiElementId; 8]>;
 pub(crate) struct LayoutNode {
     pub atom: Atom,
     pub result: NodeLayout,
+    /// Whether the node's layout was copied from the last frame, in which
+    /// case the layout passes don't visit it.
+    pub is_reused: bool,
 }
 
 pub(crate) struct LayoutTree<T> {
@@ -82,6 +115,28 @@ pub(crate) struct LayoutTree<T> {
     /// Every node is appended here at `add()` time. Used by the renderer to guarantee
     /// correct layer ordering without sorting.
     layer_buckets: SmallVec<[SmallVec<[UiElementId; 64]>; 2]>,
+
+    /// The hash of each node's subtree, or `None` if it can't be reused. Only
+    /// filled in by [`Self::hash_subtrees`].
+    hashes: Vec<Option<u64>>,
+    /// The nodes with a fixed width and height whose subtrees have a hash, in
+    /// creation order.
+    boundaries: Vec<UiElementId>,
+    cache: LayoutCache,
+}
+
+/// Last frame's layout, for reuse by subtrees that haven't changed.
+///
+/// Only subtrees whose root has a fixed width and height are reused, since
+/// nothing outside of them can change their layout except for where they
+/// are placed.
+#[derive(Default)]
+struct LayoutCache {
+    /// The position and size of each node, before scrolling and overlays
+    /// were applied.
+    results: Vec<[f32; 4]>,
+    /// The first and last node of each reusable subtree, by hash.
+    subtrees: HashMap<u64, (UiElementId, UiElementId)>,
 }
 
 impl<T> Default for LayoutTree<T> {
@@ -98,16 +153,79 @@ impl<T> LayoutTree<T> {
             content: Vec::new(),
             out_of_flow_nodes: SmallVec::new(),
             layer_buckets: SmallVec::new(),
+            hashes: Vec::new(),
+            boundaries: Vec::new(),
+            cache: LayoutCache::default(),
         }
     }
 
-    /// Iterate nodes in layer order (ascending z_layer, creation order within each layer).
-    /// This is the correct order for rendering: base layer first, then overlay layers on top.
-    pub fn iter_nodes_by_layer(&self) -> impl Iterator<Item = (&LayoutNode, &T)> {
+    /// Iterate nodes in layer order (ascending z_layer, then tree order with
+    /// siblings sorted by z_index within each layer). This is the correct order
+    /// for rendering: base layer first, then overlay layers on top.
+    pub fn iter_nodes_by_layer(&self) -> impl Iterator<Item = (UiElementId, &LayoutNode, &T)> {
         self.layer_buckets
             .iter()
             .flat_map(|bucket| bucket.iter())
-            .map(|&id| (&self.nodes[id.0 as usize], &self.content[id.0 as usize]))
+            .map(|&id| (id, &self.nodes[id.0 as usize], &self.content[id.0 as usize]))
+    }
+
+    /// The most recently created node in the subtree rooted at `node`.
+    ///
+    /// Children are always created after their parent and before the parent's
+    /// next sibling, so the subtree occupies the ids from `node` up to and
+    /// including this one.
+    pub fn last_descendant(&self, node: UiElementId) -> UiElementId {
+        let mut last = node;
+        while let Some(&child) = self.children[last.0 as usize].last() {
+            last = child;
+        }
+        last
+    }
+
+    /// The number of nodes in the subtree rooted at `node` that are on its
+    /// layer, including `node`. They are contiguous in
+    /// [`Self::iter_nodes_by_layer`].
+    pub fn layer_subtree_len(&self, node: UiElementId) -> usize {
+        let layer = self.nodes[node.0 as usize].atom.z_layer;
+        self.nodes[node.0 as usize..=self.last_descendant(node).0 as usize]
+            .iter()
+            .filter(|node| node.atom.z_layer == layer)
+            .count()
+    }
+
+    /// The layout of a node. Only valid after [`Self::compute_layout`].
+    pub fn result(&self, node: UiElementId) -> &NodeLayout {
+        &self.nodes[node.0 as usize].result
+    }
+
+    /// The size of the area covered by a node's in-flow children and its
+    /// padding, as if the node weren't scrolled. Only valid after
+    /// [`Self::compute_layout`].
+    pub fn content_size(&self, node: UiElementId) -> (f32, f32) {
+        let parent = &self.nodes[node.0 as usize];
+        let (offset_x, offset_y) = parent.atom.scroll_offset;
+        let padding = parent.atom.inner_padding;
+
+        let mut width = 0.0f32;
+        let mut height = 0.0f32;
+        for &child in &self.children[node.0 as usize] {
+            let child = &self.nodes[child.0 as usize];
+            if !child.atom.position.is_in_flow() {
+                continue;
+            }
+
+            width = width.max(child.result.x + child.result.width - parent.result.x);
+            height = height.max(child.result.y + child.result.height - parent.result.y);
+        }
+
+        (
+            width + offset_x + padding.right,
+            height + offset_y + padding.bottom,
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
     }
 
     pub fn atom_mut(&mut self, node: UiElementId) -> &mut Atom {
@@ -138,6 +256,7 @@ impl<T> LayoutTree<T> {
         let node = LayoutNode {
             atom,
             result: NodeLayout::default(),
+            is_reused: false,
         };
 
         self.nodes.push(node);
@@ -156,6 +275,8 @@ impl<T> LayoutTree<T> {
         self.children.clear();
         self.content.clear();
         self.out_of_flow_nodes.clear();
+        self.hashes.clear();
+        self.boundaries.clear();
         // Clear each bucket in place so spilled inner SmallVec allocations
         // are reused next frame; truncating the outer vec would drop them.
         for bucket in &mut self.layer_buckets {
@@ -163,11 +284,123 @@ impl<T> LayoutTree<T> {
         }
     }
 
+    /// Hashes everything that the layout of each subtree depends on, so that
+    /// [`Self::compute_layout`] can reuse the layout of subtrees that are
+    /// unchanged since the last frame. `content_key` identifies the content
+    /// of a node that is measured, or is `None` if it can't be reused.
+    pub fn hash_subtrees(&mut self, mut content_key: impl FnMut(&T) -> Option<u64>) {
+        self.hashes.clear();
+        self.hashes.resize(self.nodes.len(), None);
+        self.boundaries.clear();
+
+        // Children are created after their parents, so they are hashed first.
+        for index in (0..self.nodes.len()).rev() {
+            let Some(key) = content_key(&self.content[index]) else {
+                continue;
+            };
+
+            let mut hasher = RapidHasher::new(SUBTREE_HASH_SEED);
+            key.hash(&mut hasher);
+            hash_atom(&self.nodes[index].atom, &mut hasher);
+
+            let children = &self.children[index];
+            children.len().hash(&mut hasher);
+            let is_reusable = children.iter().all(|child| {
+                let hash = self.hashes[child.0 as usize];
+                hash.hash(&mut hasher);
+                hash.is_some()
+            });
+
+            if !is_reusable {
+                continue;
+            }
+
+            self.hashes[index] = Some(hasher.finish());
+
+            let atom = &self.nodes[index].atom;
+            if matches!(
+                (atom.width, atom.height),
+                (PixelSize::Fixed(_), PixelSize::Fixed(_))
+            ) {
+                self.boundaries.push(UiElementId(index as u16));
+            }
+        }
+
+        self.boundaries.reverse();
+    }
+
+    /// Copies last frame's layout into the outermost subtrees that are
+    /// unchanged since, and returns the root of each along with the node it
+    /// was copied from.
+    fn reuse_cached_subtrees(&mut self) -> SmallVec<[(UiElementId, UiElementId); 8]> {
+        let mut reused = SmallVec::new();
+        let mut next_unreused = 0;
+
+        for &root in &self.boundaries {
+            if root.0 < next_unreused {
+                continue;
+            }
+
+            let hash = self.hashes[root.0 as usize].unwrap();
+            let Some(&(first, last)) = self.cache.subtrees.get(&hash) else {
+                continue;
+            };
+
+            let end = self.last_descendant(root);
+            for (node, &[x, y, width, height]) in self.nodes[root.0 as usize..=end.0 as usize]
+                .iter_mut()
+                .zip(&self.cache.results[first.0 as usize..=last.0 as usize])
+            {
+                node.result.x = x;
+                node.result.y = y;
+                node.result.width = width;
+                node.result.height = height;
+                node.is_reused = true;
+            }
+
+            reused.push((root, first));
+            next_unreused = end.0 + 1;
+        }
+
+        reused
+    }
+
+    /// Moves the subtrees copied from the last frame to where their roots
+    /// were placed, and remembers this frame's layout for the next.
+    fn update_cache(&mut self, reused: &[(UiElementId, UiElementId)]) {
+        for &(root, first) in reused {
+            let [x, y, ..] = self.cache.results[first.0 as usize];
+            let result = &self.nodes[root.0 as usize].result;
+            let (dx, dy) = (result.x - x, result.y - y);
+
+            let end = self.last_descendant(root);
+            for node in &mut self.nodes[root.0 as usize + 1..=end.0 as usize] {
+                node.result.x += dx;
+                node.result.y += dy;
+            }
+        }
+
+        self.cache.results.clear();
+        self.cache.results.extend(self.nodes.iter().map(|node| {
+            let r = &node.result;
+            [r.x, r.y, r.width, r.height]
+        }));
+
+        self.cache.subtrees.clear();
+        for &root in &self.boundaries {
+            let hash = self.hashes[root.0 as usize].unwrap();
+            let end = self.last_descendant(root);
+            self.cache.subtrees.insert(hash, (root, end));
+        }
+    }
+
     pub fn compute_layout(&mut self, measure_text: impl FnMut(&mut T, f32) -> Option<f32>) {
         if self.nodes.is_empty() {
             return;
         }
 
+        let reused = self.reuse_cached_subtrees();
+
         let nodes: &mut [LayoutNode] = &mut self.nodes;
         let node_id = UiElementId(0);
 
@@ -188,6 +421,13 @@ impl<T> LayoutTree<T> {
         compute_major_axis_offsets::<HorizontalMode>(nodes, &self.children, node_id, 0.0);
         compute_minor_axis_offsets::<HorizontalMode>(nodes, &self.children, node_id, 0.0);
 
+        // Scrolling and overlays are applied to reused subtrees as to any
+        // other, so they are left out of the cache.
+        self.update_cache(&reused);
+
+        let nodes: &mut [LayoutNode] = &mut self.nodes;
+        apply_scroll_offsets(nodes, &self.children);
+
         // Pass 7.5: position out-of-flow (overlay) nodes relative to their parents.
         let viewport_clip = {
             let r = &nodes[0].result;
@@ -210,12 +450,111 @@ impl<T> LayoutTree<T> {
             ClipRect::default(),
             viewport_clip,
         );
+
+        self.sort_layers_by_z_index();
+    }
+
+    /// Reorders each layer so that siblings with a higher z_index, and their
+    /// subtrees, come after those with a lower one.
+    fn sort_layers_by_z_index(&mut self) {
+        // Layers are already in creation order, which is the order when no
+        // z_index is set.
+        if self.nodes.iter().all(|node| node.atom.z_index == 0) {
+            return;
+        }
+
+        for bucket in &mut self.layer_buckets {
+            bucket.clear();
+        }
+
+        let mut stack = vec![UiElementId(0)];
+        while let Some(id) = stack.pop() {
+            let layer = self.nodes[id.0 as usize].atom.z_layer as usize;
+            self.layer_buckets[layer].push(id);
+
+            let mut children = self.children[id.0 as usize].clone();
+            children.sort_by_key(|child| self.nodes[child.0 as usize].atom.z_index);
+            stack.extend(children.into_iter().rev());
+        }
+    }
+}
+
+const SUBTREE_HASH_SEED: u64 = 5678;
+
+/// Hashes the parts of an atom that its layout and the layout of its
+/// children depend on.
+fn hash_atom(atom: &Atom, hasher: &mut impl Hasher) {
+    // Scroll offsets and overlays are applied after layout, and clipping and
+    // layers don't change it.
+    let Atom {
+        width,
+        height,
+        inner_padding,
+        major_align,
+        minor_align,
+        direction,
+        inter_child_padding,
+        reverse,
+        grid,
+        grid_span,
+        stack,
+        align_self,
+        overflow: _,
+        scroll_offset: _,
+        position,
+        z_layer: _,
+        z_index: _,
+        is_modal: _,
+    } = atom;
+
+    let mut f32s = |values: &[f32]| {
+        for value in values {
+            value.to_bits().hash(hasher);
+        }
+    };
+
+    for size in [width, height] {
+        match *size {
+            PixelSize::Fixed(value) => f32s(&[0.0, value]),
+            PixelSize::Fit { min, max } => f32s(&[1.0, min, max]),
+            PixelSize::Grow => f32s(&[2.0]),
+            PixelSize::GrowClamped { min, max } => f32s(&[3.0, min, max]),
+            PixelSize::Flex { min, max } => f32s(&[4.0, min, max]),
+            PixelSize::Percent(percent) => f32s(&[5.0, percent]),
+        }
     }
+
+    let PixelPadding {
+        left,
+        right,
+        top,
+        bottom,
+    } = *inner_padding;
+    f32s(&[left, right, top, bottom, *inter_child_padding]);
+
+    if let Some(grid) = grid {
+        match grid.columns {
+            PixelGridColumns::Count(count) => f32s(&[0.0, f32::from(count)]),
+            PixelGridColumns::AutoFill { min_width } => f32s(&[1.0, min_width]),
+        }
+        f32s(&[grid.column_gap, grid.row_gap]);
+    }
+
+    match *position {
+        Position::InFlow => f32s(&[0.0]),
+        Position::OutOfFlow(_) => f32s(&[1.0]),
+        Position::Anchored(_) => f32s(&[2.0]),
+        Position::Absolute { .. } => f32s(&[3.0]),
+    }
+
+    (major_align, minor_align, direction, reverse, grid.is_some()).hash(hasher);
+    (grid_span.columns, grid_span.rows, stack, align_self).hash(hasher);
 }
 
 #[cfg(test)]
 mod tests {
-    use super::super::types::Size::*;
+    use super::super::types::PixelGridColumns;
+    use super::super::types::PixelSize::*;
     use super::super::types::{AxisAnchor, OverlayPosition};
     use super::*;
 
@@ -308,7 +647,7 @@ mod tests {
             Atom {
                 width: Fixed(80.0),
                 height: Fixed(30.0),
-                inner_padding: Padding {
+                inner_padding: PixelPadding {
                     left: 10.0,
                     top: 10.0,
                     ..Default::default()
@@ -390,6 +729,50 @@ mod tests {
 
     // ── Clip rect escape ─────────────────────────────────────────────────────
 
+    #[test]
+    fn anchored_child_is_pinned_and_clipped_by_parent() {
+        let mut tree = LayoutTree::new();
+        let root = tree.add(
+            None,
+            Atom {
+                width: Fixed(800.0),
+                height: Fixed(600.0),
+                ..Default::default()
+            },
+            (),
+        );
+        let panel = tree.add(
+            Some(root),
+            Atom {
+                width: Fixed(200.0),
+                height: Fixed(100.0),
+                overflow: Overflow::Clip,
+                ..Default::default()
+            },
+            (),
+        );
+        let close = tree.add(
+            Some(panel),
+            Atom {
+                width: Fixed(16.0),
+                height: Fixed(16.0),
+                position: Position::Anchored(OverlayPosition::inside(
+                    AxisAnchor::End,
+                    AxisAnchor::Start,
+                    (-4.0, 4.0),
+                )),
+                ..Default::default()
+            },
+            (),
+        );
+        tree.compute_layout(|_, _| None);
+
+        let close_r = node_result(&tree, close);
+        assert_eq!((close_r.x, close_r.y), (180.0, 4.0));
+        assert_eq!(close_r.effective_clip.size, [200.0, 100.0]);
+        assert_eq!(node_result(&tree, panel).width, 200.0);
+    }
+
     #[test]
     fn overlay_escapes_parent_clip() {
         // Parent clips its overflow (100×100 at origin). Out-of-flow child placed below at y=200.
@@ -409,7 +792,7 @@ mod tests {
             Atom {
                 width: Fixed(100.0),
                 height: Fixed(100.0),
-                clip_overflow: true,
+                overflow: Overflow::Clip,
                 ..Default::default()
             },
             (),
@@ -658,4 +1041,567 @@ mod tests {
         assert_eq!(r2.x, r1.x, "ov2 x should match ov1 x");
         assert_eq!(r2.y, r1.y + r1.height, "ov2 y should be below ov1");
     }
+
+    // ── Subtree ranges ───────────────────────────────────────────────────────
+
+    #[test]
+    fn last_descendant_spans_subtree() {
+        let mut tree = LayoutTree::new();
+        let root = tree.add(None, Atom::default(), ());
+        let a = tree.add(Some(root), Atom::default(), ());
+        let a1 = tree.add(Some(a), Atom::default(), ());
+        let a1x = tree.add(Some(a1), Atom::default(), ());
+        let a2 = tree.add(Some(a), Atom::default(), ());
+        let b = tree.add(Some(root), Atom::default(), ());
+
+        assert_eq!(tree.last_descendant(a), a2);
+        assert_eq!(tree.last_descendant(a1), a1x);
+        assert_eq!(tree.last_descendant(b), b);
+        assert_eq!(tree.last_descendant(root), b);
+    }
+
+    // ── Scrolling ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn scroll_offset_shifts_in_flow_children() {
+        let mut tree = LayoutTree::new();
+        let root = tree.add(
+            None,
+            Atom {
+                width: Fixed(400.0),
+                height: Fixed(400.0),
+                ..Default::default()
+            },
+            (),
+        );
+        let area = tree.add(
+            Some(root),
+            Atom {
+                width: Fixed(100.0),
+                height: Fixed(100.0),
+                direction: LayoutDirection::Vertical,
+                inner_padding: PixelPadding::default(),
+                inter_child_padding: 0.0,
+                scroll_offset: (0.0, 30.0),
+                ..Default::default()
+            },
+            (),
+        );
+        let first = tree.add(
+            Some(area),
+            Atom {
+                width: Fixed(100.0),
+                height: Fixed(80.0),
+                ..Default::default()
+            },
+            (),
+        );
+        let second = tree.add(
+            Some(area),
+            Atom {
+                width: Fixed(100.0),
+                height: Fixed(80.0),
+                ..Default::default()
+            },
+            (),
+        );
+
+        tree.compute_layout(|_, _| None);
+
+        let area_y = node_result(&tree, area).y;
+        assert_eq!(node_result(&tree, first).y, area_y - 30.0);
+        assert_eq!(node_result(&tree, second).y, area_y + 50.0);
+        assert_eq!(tree.content_size(area), (100.0, 160.0));
+    }
+
+    // ── Grids ────────────────────────────────────────────────────────────────
+
+    fn add_cell(tree: &mut LayoutTree<()>, grid: UiElementId, height: f32) -> UiElementId {
+        tree.add(
+            Some(grid),
+            Atom {
+                width: Grow,
+                height: Fixed(height),
+                ..Default::default()
+            },
+            (),
+        )
+    }
+
+    #[test]
+    fn grid_places_children_in_rows() {
+        let mut tree = LayoutTree::new();
+        let grid = tree.add(
+            None,
+            Atom {
+                width: Fixed(210.0),
+                height: Fit {
+                    min: 0.0,
+                    max: f32::MAX,
+                },
+                grid: Some(PixelGrid {
+                    columns: PixelGridColumns::Count(2),
+                    column_gap: 10.0,
+                    row_gap: 5.0,
+                }),
+                ..Default::default()
+            },
+            (),
+        );
+        let a = add_cell(&mut tree, grid, 20.0);
+        let b = add_cell(&mut tree, grid, 30.0);
+        let c = add_cell(&mut tree, grid, 20.0);
+
+        tree.compute_layout(|_, _| None);
+
+        assert_eq!(node_result(&tree, a).width, 100.0);
+        assert_eq!(
+            (node_result(&tree, a).x, node_result(&tree, a).y),
+            (0.0, 0.0)
+        );
+        assert_eq!(
+            (node_result(&tree, b).x, node_result(&tree, b).y),
+            (110.0, 0.0)
+        );
+        assert_eq!(
+            (node_result(&tree, c).x, node_result(&tree, c).y),
+            (0.0, 35.0)
+        );
+        assert_eq!(node_result(&tree, grid).height, 55.0);
+    }
+
+    #[test]
+    fn grid_children_span_cells() {
+        let mut tree = LayoutTree::new();
+        let grid = tree.add(
+            None,
+            Atom {
+                width: Fixed(300.0),
+                height: Fit {
+                    min: 0.0,
+                    max: f32::MAX,
+                },
+                grid: Some(PixelGrid {
+                    columns: PixelGridColumns::Count(3),
+                    column_gap: 0.0,
+                    row_gap: 0.0,
+                }),
+                ..Default::default()
+            },
+            (),
+        );
+        let wide = tree.add(
+            Some(grid),
+            Atom {
+                width: Grow,
+                height: Fixed(20.0),
+                grid_span: GridSpan {
+                    columns: 2,
+                    rows: 1,
+                },
+                ..Default::default()
+            },
+            (),
+        );
+        let tall = tree.add(
+            Some(grid),
+            Atom {
+                width: Grow,
+                height: Grow,
+                grid_span: GridSpan {
+                    columns: 1,
+                    rows: 2,
+                },
+                ..Default::default()
+            },
+            (),
+        );
+        let a = add_cell(&mut tree, grid, 30.0);
+        let b = add_cell(&mut tree, grid, 30.0);
+
+        tree.compute_layout(|_, _| None);
+
+        assert_eq!(node_result(&tree, wide).width, 200.0);
+        assert_eq!(node_result(&tree, tall).x, 200.0);
+        assert_eq!(node_result(&tree, tall).height, 50.0);
+        assert_eq!(
+            (node_result(&tree, a).x, node_result(&tree, a).y),
+            (0.0, 20.0)
+        );
+        assert_eq!(
+            (node_result(&tree, b).x, node_result(&tree, b).y),
+            (100.0, 20.0)
+        );
+    }
+
+    #[test]
+    fn auto_fill_grid_fits_columns_to_width() {
+        let mut tree = LayoutTree::new();
+        let grid = tree.add(
+            None,
+            Atom {
+                width: Fixed(250.0),
+                height: Fixed(100.0),
+                grid: Some(PixelGrid {
+                    columns: PixelGridColumns::AutoFill { min_width: 80.0 },
+                    column_gap: 5.0,
+                    row_gap: 0.0,
+                }),
+                ..Default::default()
+            },
+            (),
+        );
+        let cells: Vec<_> = (0..4).map(|_| add_cell(&mut tree, grid, 10.0)).collect();
+
+        tree.compute_layout(|_, _| None);
+
+        assert_eq!(node_result(&tree, cells[0]).width, 80.0);
+        assert_eq!(node_result(&tree, cells[2]).x, 170.0);
+        assert_eq!(node_result(&tree, cells[3]).y, 10.0);
+    }
+
+    // ── Stacks ───────────────────────────────────────────────────────────────
+
+    #[test]
+    fn stacked_children_share_the_parent_rect() {
+        let mut tree = LayoutTree::new();
+        let stack = tree.add(
+            None,
+            Atom {
+                width: Fit {
+                    min: 0.0,
+                    max: f32::MAX,
+                },
+                height: Fit {
+                    min: 0.0,
+                    max: f32::MAX,
+                },
+                stack: true,
+                ..Default::default()
+            },
+            (),
+        );
+        let image = tree.add(
+            Some(stack),
+            Atom {
+                width: Fixed(100.0),
+                height: Fixed(80.0),
+                ..Default::default()
+            },
+            (),
+        );
+        let caption = tree.add(
+            Some(stack),
+            Atom {
+                width: Grow,
+                height: Fixed(20.0),
+                align_self: Some((Alignment::Start, Alignment::End)),
+                ..Default::default()
+            },
+            (),
+        );
+        let badge = tree.add(
+            Some(stack),
+            Atom {
+                width: Fixed(10.0),
+                height: Fixed(10.0),
+                align_self: Some((Alignment::End, Alignment::Start)),
+                ..Default::default()
+            },
+            (),
+        );
+
+        tree.compute_layout(|_, _| None);
+
+        assert_eq!(node_result(&tree, stack).width, 100.0);
+        assert_eq!(node_result(&tree, stack).height, 80.0);
+        assert_eq!(
+            (node_result(&tree, image).x, node_result(&tree, image).y),
+            (0.0, 0.0)
+        );
+        assert_eq!(node_result(&tree, caption).width, 100.0);
+        assert_eq!(node_result(&tree, caption).y, 60.0);
+        assert_eq!(
+            (node_result(&tree, badge).x, node_result(&tree, badge).y),
+            (90.0, 0.0)
+        );
+    }
+
+    // ── Percentage sizes ─────────────────────────────────────────────────────
+
+    #[test]
+    fn percent_sizes_are_relative_to_parent_content_box() {
+        let mut tree = LayoutTree::new();
+        let root = tree.add(
+            None,
+            Atom {
+                width: Fixed(420.0),
+                height: Fixed(220.0),
+                inner_padding: PixelPadding {
+                    left: 10.0,
+                    right: 10.0,
+                    top: 10.0,
+                    bottom: 10.0,
+                },
+                ..Default::default()
+            },
+            (),
+        );
+        let half = tree.add(
+            Some(root),
+            Atom {
+                width: Percent(50.0),
+                height: Percent(25.0),
+                ..Default::default()
+            },
+            (),
+        );
+        let rest = tree.add(
+            Some(root),
+            Atom {
+                width: Grow,
+                height: Fixed(10.0),
+                ..Default::default()
+            },
+            (),
+        );
+
+        tree.compute_layout(|_, _| None);
+
+        assert_eq!(node_result(&tree, half).width, 200.0);
+        assert_eq!(node_result(&tree, half).height, 50.0);
+        assert_eq!(node_result(&tree, rest).width, 200.0);
+        assert_eq!(node_result(&tree, rest).x, 210.0);
+    }
+
+    // ── Clamped grow sizes ───────────────────────────────────────────────────
+
+    #[test]
+    fn clamped_grow_stays_within_bounds() {
+        let sidebar_width = |window_width: f32| {
+            let mut tree = LayoutTree::new();
+            let root = tree.add(
+                None,
+                Atom {
+                    width: Fixed(window_width),
+                    height: Fixed(100.0),
+                    ..Default::default()
+                },
+                (),
+            );
+            let sidebar = tree.add(
+                Some(root),
+                Atom {
+                    width: GrowClamped {
+                        min: 150.0,
+                        max: 300.0,
+                    },
+                    ..Default::default()
+                },
+                (),
+            );
+            tree.add(
+                Some(root),
+                Atom {
+                    width: Grow,
+                    ..Default::default()
+                },
+                (),
+            );
+
+            tree.compute_layout(|_, _| None);
+            node_result(&tree, sidebar).width
+        };
+
+        // Clamped children grow from their minimum, sharing the space left
+        // over with the other growing children.
+        assert_eq!(sidebar_width(400.0), 275.0);
+        assert_eq!(sidebar_width(100.0), 150.0);
+        assert_eq!(sidebar_width(1000.0), 300.0);
+    }
+
+    // ── Layout caching ───────────────────────────────────────────────────────
+
+    #[test]
+    fn unchanged_fixed_size_subtrees_reuse_last_layout() {
+        let mut tree = LayoutTree::new();
+        let mut num_measured = 0;
+        let mut text_layouts = Vec::new();
+
+        for spacer_width in [10.0, 10.0, 30.0] {
+            tree.clear();
+            let root = tree.add(
+                None,
+                Atom {
+                    width: Fixed(400.0),
+                    height: Fixed(300.0),
+                    ..Default::default()
+                },
+                (),
+            );
+            tree.add(
+                Some(root),
+                Atom {
+                    width: Fixed(spacer_width),
+                    height: Fixed(10.0),
+                    ..Default::default()
+                },
+                (),
+            );
+            let panel = tree.add(
+                Some(root),
+                Atom {
+                    width: Fixed(100.0),
+                    height: Fixed(100.0),
+                    direction: LayoutDirection::Vertical,
+                    ..Default::default()
+                },
+                (),
+            );
+            let text = tree.add(
+                Some(panel),
+                Atom {
+                    width: Grow,
+                    ..Default::default()
+                },
+                (),
+            );
+
+            tree.hash_subtrees(|_| Some(0));
+            tree.compute_layout(|_, _| {
+                num_measured += 1;
+                Some(20.0)
+            });
+
+            let r = node_result(&tree, text);
+            text_layouts.push((r.x, r.y, r.width, r.height));
+        }
+
+        // All four nodes are measured in the first frame. The second reuses
+        // the whole tree, and the third reuses the panel in its new place.
+        assert_eq!(num_measured, 6);
+        assert_eq!(
+            text_layouts,
+            [
+                (10.0, 0.0, 100.0, 20.0),
+                (10.0, 0.0, 100.0, 20.0),
+                (30.0, 0.0, 100.0, 20.0),
+            ]
+        );
+    }
+
+    // ── Space distribution ───────────────────────────────────────────────────
+
+    #[test]
+    fn space_around_and_evenly_distribute_unused_space() {
+        for (align, width, expected) in [
+            (Alignment::SpaceEvenly, 200.0, [35.0, 90.0, 145.0]),
+            (Alignment::SpaceAround, 210.0, [25.0, 95.0, 165.0]),
+        ] {
+            let mut tree = LayoutTree::new();
+            let root = tree.add(
+                None,
+                Atom {
+                    width: Fixed(width),
+                    height: Fixed(50.0),
+                    major_align: align,
+                    ..Default::default()
+                },
+                (),
+            );
+            let ids: Vec<_> = (0..3)
+                .map(|_| {
+                    tree.add(
+                        Some(root),
+                        Atom {
+                            width: Fixed(20.0),
+                            height: Fixed(20.0),
+                            ..Default::default()
+                        },
+                        (),
+                    )
+                })
+                .collect();
+
+            tree.compute_layout(|_, _| None);
+
+            let xs: Vec<f32> = ids.iter().map(|&id| node_result(&tree, id).x).collect();
+            assert_eq!(xs, expected, "{align:?}");
+        }
+    }
+
+    // ── Reversed layouts ─────────────────────────────────────────────────────
+
+    #[test]
+    fn reversed_children_are_placed_from_the_end() {
+        let mut tree = LayoutTree::new();
+        let root = tree.add(
+            None,
+            Atom {
+                width: Fixed(100.0),
+                height: Fixed(200.0),
+                ..Default::default()
+            },
+            (),
+        );
+        let column = tree.add(
+            Some(root),
+            Atom {
+                width: Fixed(100.0),
+                height: Fixed(200.0),
+                direction: LayoutDirection::Vertical,
+                inter_child_padding: 10.0,
+                reverse: true,
+                ..Default::default()
+            },
+            (),
+        );
+        let first = tree.add(
+            Some(column),
+            Atom {
+                width: Fixed(100.0),
+                height: Fixed(20.0),
+                ..Default::default()
+            },
+            (),
+        );
+        let second = tree.add(
+            Some(column),
+            Atom {
+                width: Fixed(100.0),
+                height: Fixed(30.0),
+                ..Default::default()
+            },
+            (),
+        );
+
+        tree.compute_layout(|_, _| None);
+
+        assert_eq!(node_result(&tree, first).y, 180.0);
+        assert_eq!(node_result(&tree, second).y, 140.0);
+    }
+
+    // ── Z-index ──────────────────────────────────────────────────────────────
+
+    #[test]
+    fn z_index_reorders_sibling_subtrees() {
+        let mut tree = LayoutTree::new();
+        let root = tree.add(None, Atom::default(), ());
+        let raised = tree.add(
+            Some(root),
+            Atom {
+                z_index: 1,
+                ..Default::default()
+            },
+            (),
+        );
+        let raised_child = tree.add(Some(raised), Atom::default(), ());
+        let first = tree.add(Some(root), Atom::default(), ());
+        let second = tree.add(Some(root), Atom::default(), ());
+
+        tree.compute_layout(|_, _| None);
+
+        let order: Vec<_> = tree.iter_nodes_by_layer().map(|(id, _, _)| id).collect();
+        assert_eq!(order, [root, first, second, raised, raised_child]);
+    }
 }
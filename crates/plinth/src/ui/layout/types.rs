@@ -31,6 +31,22 @@ pub struct OverlayPosition {
     pub flip_y: bool,
 }
 
+impl OverlayPosition {
+    /// Pins the node inside its parent, at the same corner or edge of both,
+    /// e.g. a close button in the top-right corner of a panel.
+    pub fn inside(x: AxisAnchor, y: AxisAnchor, offset: (f32, f32)) -> Self {
+        Self {
+            parent_x: x,
+            parent_y: y,
+            self_x: x,
+            self_y: y,
+            offset,
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+}
+
 /// Controls how a node participates in its parent's layout.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub enum Position {
@@ -40,6 +56,10 @@ pub enum Position {
     /// Out of flow: does not affect parent/sibling layout; positioned relative
     /// to the parent's computed result in the same frame.
     OutOfFlow(OverlayPosition),
+    /// Out of flow: positioned relative to the parent's computed result like
+    /// [`OutOfFlow`](Self::OutOfFlow), but clipped by the parent's ancestors
+    /// like an in-flow node.
+    Anchored(OverlayPosition),
     /// Out of flow: positioned at an explicit screen-space coordinate.
     /// Used for overlays that persist their own position across frames (draggable panels).
     Absolute { x: f32, y: f32 },
@@ -52,25 +72,209 @@ impl Position {
     }
 }
 
+/// What happens to the parts of a node's children that extend past its
+/// bounds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// The children are drawn in full.
+    #[default]
+    Visible,
+    /// The children are clipped to the node.
+    Clip,
+    /// The children are clipped to the node, and scrolled with the mouse
+    /// wheel like a [`ScrollArea`](crate::ui::widget::ScrollArea).
+    Scroll,
+}
+
+impl Overflow {
+    #[inline]
+    pub fn is_clipped(self) -> bool {
+        !matches!(self, Overflow::Visible)
+    }
+}
+
+/// A distance in one of several units, converted to pixels when it is
+/// applied to a node.
+///
+/// In theme files, a number is a length in pixels, and other units are
+/// written as strings such as `"12pt"`, `"1.5em"`, or `"2rem"`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Length {
+    /// Pixels at 100% display scaling, of which there are 96 to an inch.
+    Px(f32),
+    /// Points, of which there are 72 to an inch.
+    Pt(f32),
+    /// Multiples of the font size of the node's style.
+    Em(f32),
+    /// Multiples of the theme's root font size, see
+    /// [`Theme::root_font_size`](crate::ui::Theme::root_font_size).
+    Rem(f32),
+}
+
+impl Length {
+    pub const ZERO: Self = Self::Px(0.0);
+    /// A length that is never exceeded, for sizes without an upper bound.
+    pub const MAX: Self = Self::Px(f32::MAX);
+
+    /// Converts the length to pixels.
+    pub(crate) fn resolve(self, metrics: &LengthMetrics) -> f32 {
+        self.to_unscaled(metrics.font_size, metrics.root_font_size)
+    }
+
+    /// Converts the length to pixels at 100% display scaling.
+    pub(crate) fn to_unscaled(self, font_size: f32, root_font_size: f32) -> f32 {
+        match self {
+            Self::Px(value) => value,
+            Self::Pt(value) => value * 96.0 / 72.0,
+            Self::Em(value) => value * font_size,
+            Self::Rem(value) => value * root_font_size,
+        }
+    }
+}
+
+impl From<f32> for Length {
+    fn from(value: f32) -> Self {
+        Self::Px(value)
+    }
+}
+
+impl Default for Length {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Length {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Self::Px(value) => serializer.serialize_f32(value),
+            Self::Pt(value) => serializer.collect_str(&format_args!("{value}pt")),
+            Self::Em(value) => serializer.collect_str(&format_args!("{value}em")),
+            Self::Rem(value) => serializer.collect_str(&format_args!("{value}rem")),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Length {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct LengthVisitor;
+
+        impl serde::de::Visitor<'_> for LengthVisitor {
+            type Value = Length;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str(
+                    "a number of pixels, or a string such as \"12pt\", \"1.5em\", or \"2rem\"",
+                )
+            }
+
+            fn visit_f64<E: serde::de::Error>(self, value: f64) -> Result<Length, E> {
+                Ok(Length::Px(value as f32))
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Length, E> {
+                Ok(Length::Px(value as f32))
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Length, E> {
+                Ok(Length::Px(value as f32))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Length, E> {
+                let value = value.trim();
+                // "rem" is checked before "em", which it ends with.
+                let (number, unit): (&str, fn(f32) -> Length) =
+                    if let Some(number) = value.strip_suffix("rem") {
+                        (number, Length::Rem)
+                    } else if let Some(number) = value.strip_suffix("em") {
+                        (number, Length::Em)
+                    } else if let Some(number) = value.strip_suffix("pt") {
+                        (number, Length::Pt)
+                    } else if let Some(number) = value.strip_suffix("px") {
+                        (number, Length::Px)
+                    } else {
+                        return Err(E::invalid_value(serde::de::Unexpected::Str(value), &self));
+                    };
+
+                match number.trim().parse() {
+                    Ok(number) => Ok(unit(number)),
+                    Err(_) => Err(E::invalid_value(serde::de::Unexpected::Str(value), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(LengthVisitor)
+    }
+}
+
+/// What lengths are measured against when they are converted to pixels.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct LengthMetrics {
+    /// The font size of the node's style, in pixels at 100% scaling.
+    pub font_size: f32,
+    /// The theme's root font size, in pixels at 100% scaling.
+    pub root_font_size: f32,
+}
+
 /// Single-dimension size for UI elements.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Size {
-    Fixed(f32),
+    Fixed(Length),
     /// Size to fit content, with optional min and max constraints.
     Fit {
-        min: f32,
-        max: f32,
+        min: Length,
+        max: Length,
     },
     Grow,
+    /// Grow like [`Grow`](Self::Grow), but never smaller than `min` or larger
+    /// than `max`.
+    GrowClamped {
+        min: Length,
+        max: Length,
+    },
     /// Size to fit container, with optional min and max constraints.
     Flex {
-        min: f32,
-        max: f32,
+        min: Length,
+        max: Length,
     },
+    /// A percentage of the size of the parent's content box, e.g.
+    /// `Percent(50.0)` for half of it.
+    Percent(f32),
+}
+
+impl Size {
+    pub(crate) fn resolve(self, metrics: &LengthMetrics) -> PixelSize {
+        match self {
+            Size::Fixed(value) => PixelSize::Fixed(value.resolve(metrics)),
+            Size::Fit { min, max } => PixelSize::Fit {
+                min: min.resolve(metrics),
+                max: max.resolve(metrics),
+            },
+            Size::Grow => PixelSize::Grow,
+            Size::GrowClamped { min, max } => PixelSize::GrowClamped {
+                min: min.resolve(metrics),
+                max: max.resolve(metrics),
+            },
+            Size::Flex { min, max } => PixelSize::Flex {
+                min: min.resolve(metrics),
+                max: max.resolve(metrics),
+            },
+            Size::Percent(percent) => PixelSize::Percent(percent),
+        }
+    }
 }
 
 impl From<f32> for Size {
     fn from(value: f32) -> Self {
+        Size::Fixed(Length::Px(value))
+    }
+}
+
+impl From<Length> for Size {
+    fn from(value: Length) -> Self {
         Size::Fixed(value)
     }
 }
@@ -78,8 +282,8 @@ impl From<f32> for Size {
 impl Default for Size {
     fn default() -> Self {
         Size::Fit {
-            min: 0.0,
-            max: f32::MAX,
+            min: Length::ZERO,
+            max: Length::MAX,
         }
     }
 }
@@ -90,16 +294,72 @@ impl From<Option<Size>> for Size {
     }
 }
 
+/// A [`Size`] converted to pixels on the window's display, as used by layout.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PixelSize {
+    Fixed(f32),
+    Fit { min: f32, max: f32 },
+    Grow,
+    GrowClamped { min: f32, max: f32 },
+    Flex { min: f32, max: f32 },
+    Percent(f32),
+}
+
+impl PixelSize {
+    /// Narrows the size to lie within `min..=max`.
+    ///
+    /// `Grow` and `Percent` have no bounds of their own and are returned
+    /// unchanged.
+    pub fn constrain(self, min: f32, max: f32) -> Self {
+        let clamp = |value: f32| value.max(min).min(max.max(min));
+
+        match self {
+            PixelSize::Fixed(value) => PixelSize::Fixed(clamp(value)),
+            PixelSize::Fit { min: lo, max: hi } => PixelSize::Fit {
+                min: clamp(lo),
+                max: clamp(hi),
+            },
+            PixelSize::Grow => PixelSize::Grow,
+            PixelSize::GrowClamped { min: lo, max: hi } => PixelSize::GrowClamped {
+                min: clamp(lo),
+                max: clamp(hi),
+            },
+            PixelSize::Percent(percent) => PixelSize::Percent(percent),
+            PixelSize::Flex { min: lo, max: hi } => PixelSize::Flex {
+                min: clamp(lo),
+                max: clamp(hi),
+            },
+        }
+    }
+}
+
+impl From<f32> for PixelSize {
+    fn from(value: f32) -> Self {
+        PixelSize::Fixed(value)
+    }
+}
+
+impl Default for PixelSize {
+    fn default() -> Self {
+        PixelSize::Fit {
+            min: 0.0,
+            max: f32::MAX,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Padding {
-    pub left: f32,
-    pub right: f32,
-    pub top: f32,
-    pub bottom: f32,
+    pub left: Length,
+    pub right: Length,
+    pub top: Length,
+    pub bottom: Length,
 }
 
 impl Padding {
-    pub fn equal(value: f32) -> Self {
+    pub fn equal(value: impl Into<Length>) -> Self {
+        let value = value.into();
         Self {
             left: value,
             right: value,
@@ -107,20 +367,118 @@ impl Padding {
             bottom: value,
         }
     }
+
+    pub(crate) fn resolve(self, metrics: &LengthMetrics) -> PixelPadding {
+        PixelPadding {
+            left: self.left.resolve(metrics),
+            right: self.right.resolve(metrics),
+            top: self.top.resolve(metrics),
+            bottom: self.bottom.resolve(metrics),
+        }
+    }
+}
+
+/// A [`Padding`] converted to pixels on the window's display, as used by
+/// layout.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PixelPadding {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LayoutDirection {
     #[default]
     Horizontal,
     Vertical,
 }
 
+/// Lays out a node's children in rows of equally wide columns, instead of
+/// in a single row or column. Children fill the grid row by row, and each
+/// row is as tall as its tallest child.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Grid {
+    pub columns: GridColumns,
+    pub column_gap: Length,
+    pub row_gap: Length,
+}
+
+impl Grid {
+    pub(crate) fn resolve(self, metrics: &LengthMetrics) -> PixelGrid {
+        PixelGrid {
+            columns: match self.columns {
+                GridColumns::Count(count) => PixelGridColumns::Count(count.max(1)),
+                GridColumns::AutoFill { min_width } => PixelGridColumns::AutoFill {
+                    min_width: min_width.resolve(metrics),
+                },
+            },
+            column_gap: self.column_gap.resolve(metrics),
+            row_gap: self.row_gap.resolve(metrics),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GridColumns {
+    Count(u16),
+    /// As many columns as fit in the grid's width without any being narrower
+    /// than `min_width`. A grid that fits its content puts every child in one
+    /// row.
+    AutoFill {
+        min_width: Length,
+    },
+}
+
+/// A [`Grid`] converted to pixels on the window's display, as used by
+/// layout.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PixelGrid {
+    pub columns: PixelGridColumns,
+    pub column_gap: f32,
+    pub row_gap: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PixelGridColumns {
+    Count(u16),
+    AutoFill { min_width: f32 },
+}
+
+/// How many columns and rows of its parent's [`Grid`] a node covers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GridSpan {
+    pub columns: u16,
+    pub rows: u16,
+}
+
+impl Default for GridSpan {
+    fn default() -> Self {
+        Self {
+            columns: 1,
+            rows: 1,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Alignment {
     #[default]
     Start,
     Center,
     End,
+    /// Spreads children along the major axis with equal space between them,
+    /// and none before the first or after the last. Along the minor axis,
+    /// this is the same as `Start`.
     Justify,
+    /// Spreads children along the major axis with equal space on both sides
+    /// of each, so that the space at the ends is half that between children.
+    /// Along the minor axis, this is the same as `Start`.
+    SpaceAround,
+    /// Spreads children along the major axis with equal space between them
+    /// and at the ends. Along the minor axis, this is the same as `Start`.
+    SpaceEvenly,
 }
@@ -1,18 +1,39 @@
 use glamour::Unit;
 
+pub use accessibility::AccessRole;
+pub use animation::Animatable;
+pub use animation::Easing;
+pub use animation::Transition;
 pub use builder::*;
 pub use common_widgets::CommonWidgetsExt;
 pub use id::*;
 pub use layout::*;
+pub use retained::RetainedUi;
 pub use text::TextOverflow;
+pub use theme::ClassKey;
+#[cfg(feature = "theme-files")]
+pub use theme::PropertyDefinition;
 pub use theme::StyleClass;
+#[cfg(feature = "theme-files")]
+pub use theme::StyleDefinition;
 pub use theme::Theme;
+#[cfg(feature = "theme-files")]
+pub use theme::ThemeDocument;
+#[cfg(feature = "theme-files")]
+pub use theme::ThemeLoadError;
 
+// The tree is built, but not published until there is an accesskit_winit
+// adapter for the winit version this workspace uses.
+#[cfg_attr(feature = "accessibility", expect(dead_code))]
+mod accessibility;
+mod animation;
 mod builder;
 mod common_widgets;
 pub(super) mod context;
 mod id;
+mod inspector;
 mod layout;
+mod retained;
 pub mod style;
 pub(crate) mod text;
 mod theme;
@@ -0,0 +1,170 @@
+use std::cell::RefCell;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::graphics::Canvas;
+use crate::graphics::TextLayoutContext;
+use crate::shell::Input;
+use crate::shell::WindowShape;
+use crate::shell::WindowSize;
+
+use super::Size;
+use super::UiBuilder;
+use super::WidgetId;
+use super::context::LayoutContent;
+use super::context::UiContext;
+use super::layout::NodeLayout;
+use super::text::TextLayoutStorage;
+use super::theme::Theme;
+use super::widget::Container;
+
+/// A subtree that is only rebuilt when its inputs change, for large views
+/// that rarely change, such as log viewers.
+///
+/// The subtree has a layout tree of its own. On frames where it isn't
+/// rebuilt, what it drew when it was last built is drawn again in its place.
+/// Its widgets only see input on frames where it is rebuilt, so anything
+/// they react to, such as the pointer position, must be part of its inputs.
+///
+/// ```ignore
+/// // Kept with the rest of the window's state.
+/// let mut log_view = RetainedUi::default();
+///
+/// // Every frame:
+/// log_view.show(&mut ui, log.len(), |ui| {
+///     for line in &log {
+///         ui.label(line);
+///     }
+/// });
+/// ```
+#[derive(Default)]
+pub struct RetainedUi {
+    subtree: Rc<RefCell<RetainedSubtree>>,
+}
+
+impl RetainedUi {
+    /// Adds the subtree as a child of `container` that fills the space it is
+    /// given, calling `build` to rebuild it if `inputs`, that space, or the
+    /// theme changed since it was last built.
+    ///
+    /// The space is only known once the child has been laid out, so the
+    /// subtree is empty on the first frame it is shown.
+    pub fn show<'a>(
+        &mut self,
+        container: &mut impl Container<'a>,
+        inputs: impl Hash,
+        build: impl FnOnce(&mut UiBuilder),
+    ) {
+        let mut child = container.child();
+        child.size(Size::Grow, Size::Grow);
+
+        let Some(placement) = child.prev_state().map(|state| state.placement) else {
+            child.context.needs_redraw = true;
+            return;
+        };
+
+        let subtree = &mut *self.subtree.borrow_mut();
+        let inputs = WidgetId::new(inputs);
+        let size = (placement.width(), placement.height());
+
+        let is_stale = subtree.is_dirty
+            || subtree.inputs != Some(inputs)
+            || subtree.size != size
+            || subtree.theme_revision != child.theme.revision();
+
+        if is_stale {
+            let input = Input {
+                pointer: child.input.pointer - placement.origin.to_vector(),
+                prev_pointer: child.input.prev_pointer - placement.origin.to_vector(),
+                window_size: WindowSize {
+                    width: size.0,
+                    height: size.1,
+                },
+                ..child.input.clone()
+            };
+
+            let mut ui = subtree.context.begin_frame(
+                child.clipboard,
+                child.text_context,
+                child.text_layouts,
+                child.format_buffer,
+                child.theme,
+                &input,
+                child.context.frame_time,
+                child.context.time_delta,
+                child.context.refresh_interval,
+                WindowShape::Rectangle,
+            );
+            build(&mut ui);
+
+            subtree.inputs = Some(inputs);
+            subtree.size = size;
+            subtree.theme_revision = child.theme.revision();
+            subtree.is_dirty = false;
+            subtree.is_pending = true;
+        }
+
+        *child.context.ui_tree.content_mut(child.index) = (
+            LayoutContent::Retained(self.subtree.clone()),
+            Some(child.id),
+        );
+    }
+
+    /// Rebuilds the subtree the next time it is shown, even if its inputs
+    /// haven't changed.
+    pub fn invalidate(&mut self) {
+        self.subtree.borrow_mut().is_dirty = true;
+    }
+}
+
+#[derive(Default)]
+pub(super) struct RetainedSubtree {
+    context: UiContext,
+    /// What the subtree drew when it was last built, relative to its origin.
+    canvas: Option<Canvas>,
+
+    inputs: Option<WidgetId>,
+    size: (f32, f32),
+    theme_revision: u64,
+    /// Set when the subtree asked to be drawn again after it was built.
+    is_dirty: bool,
+    /// Set when the subtree was rebuilt but has yet to be laid out and drawn.
+    is_pending: bool,
+}
+
+impl RetainedSubtree {
+    /// Lays out and records the subtree if it was rebuilt, then draws it into
+    /// `canvas`. Returns whether the subtree needs to be rebuilt and drawn
+    /// again.
+    pub(super) fn draw(
+        &mut self,
+        theme: &Theme,
+        text_context: &mut TextLayoutContext,
+        text_layouts: &mut TextLayoutStorage,
+        canvas: &mut Canvas,
+        layout: &NodeLayout,
+    ) -> bool {
+        if self.is_pending {
+            let recorded = self.canvas.get_or_insert_with(|| canvas.new_sibling());
+            recorded.reset(None);
+            recorded.set_view([0.0, 0.0], canvas.scale());
+
+            self.context
+                .finish(theme, text_context, text_layouts, recorded);
+
+            self.is_pending = false;
+            self.is_dirty = self.context.needs_redraw()
+                || self.context.repaint_after().is_some()
+                || recorded.has_unready_textures();
+        }
+
+        if let Some(recorded) = &self.canvas {
+            // Text was rasterized for the display it was recorded on, so the
+            // subtree is rebuilt after the window moves to another.
+            self.is_dirty |= recorded.scale() != canvas.scale();
+            canvas.draw_canvas(recorded, [layout.x, layout.y], layout.effective_clip);
+        }
+
+        self.is_dirty || self.size != (layout.width, layout.height)
+    }
+}
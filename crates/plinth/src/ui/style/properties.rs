@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use crate::graphics::BorderColors;
 use crate::graphics::Color;
 use crate::graphics::FontStyle;
 use crate::graphics::GradientPaint;
@@ -7,7 +8,9 @@ use crate::graphics::Paint;
 use crate::graphics::TextAlignment;
 use crate::ui::Alignment;
 use crate::ui::LayoutDirection;
+use crate::ui::Length;
 use crate::ui::Size;
+use crate::ui::layout::LengthMetrics;
 use crate::ui::layout::Padding;
 
 use crate::ui::style::StatefulProperty;
@@ -15,7 +18,10 @@ use crate::ui::style::registry::PropertyKey;
 
 bitflags::bitflags! {
     #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
-    pub struct StateFlags: u8 {
+    // Serialized by name, e.g. "HOVERED | PRESSED".
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(transparent))]
+    pub struct StateFlags: u16 {
         const HOVERED  = 0b00000001;
         const PRESSED  = 0b00000010;
         const SELECTED = 0b00000100;
@@ -24,21 +30,39 @@ bitflags::bitflags! {
         const CHECKED  = 0b00100000;
         const INVALID  = 0b01000000;
         const EXPANDED = 0b10000000;
+        /// Set along with `FOCUSED` when focus was moved with the keyboard,
+        /// for drawing focus rings that pointer users don't need.
+        const FOCUS_VISIBLE = 0b1_00000000;
 
         const NORMAL = 0;
     }
 }
 
+bitflags::bitflags! {
+    /// Lines drawn along text.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    // Serialized by name, e.g. "UNDERLINE | OVERLINE".
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(transparent))]
+    pub struct TextDecoration: u8 {
+        const UNDERLINE     = 0b001;
+        const STRIKETHROUGH = 0b010;
+        const OVERLINE      = 0b100;
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BorderWidths {
-    pub left: f32,
-    pub right: f32,
-    pub top: f32,
-    pub bottom: f32,
+    pub left: Length,
+    pub right: Length,
+    pub top: Length,
+    pub bottom: Length,
 }
 
 impl BorderWidths {
-    pub fn uniform(width: f32) -> Self {
+    pub fn uniform(width: impl Into<Length>) -> Self {
+        let width = width.into();
         Self {
             left: width,
             right: width,
@@ -47,13 +71,19 @@ impl BorderWidths {
         }
     }
 
-    /// Convert to array [left, top, right, bottom]
-    pub fn into_array(self) -> [f32; 4] {
-        [self.left, self.top, self.right, self.bottom]
+    /// Convert to pixels as an array [left, top, right, bottom]
+    pub(crate) fn resolve(self, metrics: &LengthMetrics) -> [f32; 4] {
+        [
+            self.left.resolve(metrics),
+            self.top.resolve(metrics),
+            self.right.resolve(metrics),
+            self.bottom.resolve(metrics),
+        ]
     }
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CornerRadii {
     pub top_left: f32,
     pub top_right: f32,
@@ -77,28 +107,40 @@ macros::declare_style! {
     pub struct Style {
         background: Background(Paint) = Paint::solid(Color::WHITE),
         border: Border(GradientPaint) = GradientPaint::vertical_gradient(Color::BLACK, Color::BLACK),
-        border_widths: BorderWidths(use BorderWidths) = BorderWidths { left: 1.0, right: 1.0, top: 1.0, bottom: 1.0 },
+        // Drawn instead of `border` when set.
+        side_border_colors: SideBorderColors(Option<BorderColors>) = None,
+        border_widths: BorderWidths(use BorderWidths) = BorderWidths::uniform(1.0),
         corner_radii: CornerRadii(use CornerRadii) = CornerRadii::default(),
+        // Multiplies the alpha of the background and border.
+        opacity: Opacity(f32) = 1.0,
 
         // layout styles
-        padding: Padding(use Padding) = Padding { top: 4.0, right: 4.0, bottom: 4.0, left: 4.0 },
+        padding: Padding(use Padding) = Padding::equal(4.0),
         child_major_alignment: ChildMajorAlignment(Alignment) = Alignment::Start,
         child_minor_alignment: ChildMinorAlignment(Alignment) = Alignment::Center,
-        child_spacing: ChildSpacing(f32) = 4.0,
+        child_spacing: ChildSpacing(Length) = Length::Px(4.0),
         child_direction: ChildDirection(LayoutDirection) = LayoutDirection::Horizontal,
         clip_children: ClipChildren(bool) = false,
-        width: Width(Size) = Size::Fit { min: 20.0, max: f32::MAX },
-        height: Height(Size) = Size::Fit { min: 10.0, max: f32::MAX },
+        width: Width(Size) = Size::Fit { min: Length::Px(20.0), max: Length::MAX },
+        height: Height(Size) = Size::Fit { min: Length::Px(10.0), max: Length::MAX },
+        // Bounds applied on top of `width` and `height`, so that a theme can
+        // set control metrics without dictating how each control is sized.
+        min_width: MinWidth(Length) = Length::ZERO,
+        max_width: MaxWidth(Length) = Length::MAX,
+        min_height: MinHeight(Length) = Length::ZERO,
+        max_height: MaxHeight(Length) = Length::MAX,
 
         // text styles
         font: Font(Arc<crate::graphics::Font>) = Arc::new(crate::graphics::Font::default()),
-        font_size: FontSize(u16) = 14,
+        font_size: FontSize(Length) = Length::Px(14.0),
         font_style: FontStyle(use FontStyle) = FontStyle::Normal,
         font_weight: FontWeight(u16) = 400,
         strikethrough_color: StrikethroughColor(Color) = Color::BLACK,
+        // Distances above the baseline, where 0.0 uses the font's metrics.
         strikethrough_offset: StrikethroughOffset(f32) = 0.0,
         text_align: TextAlignment(use TextAlignment) = TextAlignment::Start,
         text_color: TextColor(Color) = Color::BLACK,
+        text_decoration: TextDecorationLine(TextDecoration) = TextDecoration::empty(),
         underline_color: UnderlineColor(Color) = Color::BLACK,
         underline_offset: UnderlineOffset(f32) = 0.0,
 
@@ -106,6 +148,7 @@ macros::declare_style! {
         selection_color: SelectionColor(Color) = Color::srgb_nonlinear(0.2, 0.4, 0.8, 0.3),
         selection_text_color: SelectionTextColor(Color) = Color::WHITE,
         cursor_color: CursorColor(Color) = Color::BLACK,
+        cursor_width: CursorWidth(f32) = 2.0,
     }
 }
 
@@ -217,6 +260,18 @@ mod macros {
                     }
                 }
 
+                /// The value of every property in the given state.
+                pub(crate) fn resolve_all(&self, flags: StateFlags) -> Vec<StyleProperty> {
+                    vec![
+                        $(
+                            StyleProperty::$new_key(self.$new_field.get(flags)),
+                        )*
+                        $(
+                            StyleProperty::$use_key(self.$use_field.get(flags)),
+                        )*
+                    ]
+                }
+
                 /// Apply multiple property overrides.
                 pub(crate) fn apply_all(&mut self, properties: impl IntoIterator<Item = (StateFlags, StyleProperty)>) {
                     for (flags, prop) in properties {
@@ -227,6 +282,7 @@ mod macros {
 
             // Enum for dynamically specifying property values
             #[derive(Clone, Debug, PartialEq)]
+            #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
             $vis enum StyleProperty {
                 $(
                     $new_key($new_content),
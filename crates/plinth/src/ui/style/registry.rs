@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
 use slotmap::SecondaryMap;
 use slotmap::SlotMap;
 use slotmap::new_key_type;
@@ -16,6 +19,84 @@ const MAX_STYLE_TREE_DEPTH: usize = 32;
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum StyleError {
     StyleTreeDepthLimitExceeded,
+    /// A style in a [`StyleBatch`] names a parent that isn't earlier in the
+    /// batch.
+    UnknownParent(String),
+}
+
+/// The parent of a style in a [`StyleBatch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StyleParent {
+    /// A style that was registered before the batch.
+    Id(StyleId),
+    /// A style earlier in the same batch.
+    Name(String),
+}
+
+impl From<StyleId> for StyleParent {
+    fn from(id: StyleId) -> Self {
+        StyleParent::Id(id)
+    }
+}
+
+impl From<&str> for StyleParent {
+    fn from(name: &str) -> Self {
+        StyleParent::Name(name.to_owned())
+    }
+}
+
+/// A tree of related styles to register in one call, with parents referred
+/// to by name, e.g. a base button style and the styles of its variants.
+#[derive(Debug, Default)]
+pub struct StyleBatch {
+    styles: Vec<BatchStyle>,
+}
+
+#[derive(Debug)]
+struct BatchStyle {
+    name: String,
+    parent: Option<StyleParent>,
+    overrides: Vec<(StateFlags, StyleProperty)>,
+}
+
+impl StyleBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a style without a parent in the batch.
+    pub fn style(
+        self,
+        name: impl Into<String>,
+        properties: impl IntoIterator<Item = (StateFlags, StyleProperty)>,
+    ) -> Self {
+        self.add(name.into(), None, properties)
+    }
+
+    /// Adds a style that inherits from `parent`, which must be registered
+    /// already or be earlier in the batch.
+    pub fn child(
+        self,
+        name: impl Into<String>,
+        parent: impl Into<StyleParent>,
+        properties: impl IntoIterator<Item = (StateFlags, StyleProperty)>,
+    ) -> Self {
+        self.add(name.into(), Some(parent.into()), properties)
+    }
+
+    fn add(
+        mut self,
+        name: String,
+        parent: Option<StyleParent>,
+        properties: impl IntoIterator<Item = (StateFlags, StyleProperty)>,
+    ) -> Self {
+        self.styles.push(BatchStyle {
+            name,
+            parent,
+            overrides: properties.into_iter().collect(),
+        });
+        self
+    }
 }
 
 pub(crate) struct StyleRegistry {
@@ -101,6 +182,53 @@ impl StyleRegistry {
         Ok(id)
     }
 
+    /// Register every style in a batch, giving styles without a parent the
+    /// parent `root`. Returns the ID of each style by name.
+    ///
+    /// Either every style is registered or, if one can't be, none are.
+    pub fn register_many(
+        &mut self,
+        batch: StyleBatch,
+        root: Option<StyleId>,
+    ) -> Result<HashMap<String, StyleId>, StyleError> {
+        // Check names up front so that a bad batch registers nothing.
+        let mut names = HashSet::with_capacity(batch.styles.len());
+        for style in &batch.styles {
+            if let Some(StyleParent::Name(parent)) = &style.parent
+                && !names.contains(parent.as_str())
+            {
+                return Err(StyleError::UnknownParent(parent.clone()));
+            }
+
+            names.insert(style.name.as_str());
+        }
+
+        let mut ids = HashMap::with_capacity(batch.styles.len());
+        let mut registered = Vec::with_capacity(batch.styles.len());
+        for style in batch.styles {
+            let parent = match style.parent {
+                None => root,
+                Some(StyleParent::Id(id)) => Some(id),
+                Some(StyleParent::Name(name)) => Some(ids[&name]),
+            };
+
+            match self.register(parent, style.overrides) {
+                Ok(id) => {
+                    ids.insert(style.name, id);
+                    registered.push(id);
+                }
+                Err(error) => {
+                    for id in registered.into_iter().rev() {
+                        self.unregister(id);
+                    }
+                    return Err(error);
+                }
+            }
+        }
+
+        Ok(ids)
+    }
+
     /// Update a style's overrides and regenerate it and all descendants.
     pub fn update(
         &mut self,
@@ -115,12 +243,61 @@ impl StyleRegistry {
         self.regenerate(style_id);
     }
 
+    /// Remove a style, freeing its definition and resolved style.
+    ///
+    /// The style's children are moved to its parent and take on its overrides,
+    /// so they resolve the same as before.
+    pub fn unregister(&mut self, style_id: StyleId) {
+        if style_id == self.default_style {
+            panic!("Attempted to unregister the default style");
+        }
+
+        let Some(def) = self.definitions.remove(style_id) else {
+            panic!("Attempted to unregister style that does not exist");
+        };
+
+        self.resolved.remove(style_id);
+        let children = self.children.remove(style_id).unwrap_or_default();
+
+        if let Some(parent_id) = def.parent
+            && let Some(siblings) = self.children.get_mut(parent_id)
+        {
+            siblings.retain(|id| *id != style_id);
+            siblings.extend_from_slice(&children);
+        }
+
+        for child_id in children {
+            let child = &mut self.definitions[child_id];
+            child.parent = def.parent;
+            child
+                .overrides
+                .insert_many(0, def.overrides.iter().cloned());
+        }
+    }
+
     /// Get the resolved style for a StyleId.
     #[inline]
     pub fn get(&self, style_id: StyleId) -> Option<&Style> {
         self.resolved.get(style_id)
     }
 
+    /// Whether the style or one of its parents sets a property matching
+    /// `is_property`, in any state.
+    pub fn sets(&self, style_id: StyleId, is_property: impl Fn(&StyleProperty) -> bool) -> bool {
+        let mut next = Some(style_id);
+        while let Some(def) = next.and_then(|id| self.definitions.get(id)) {
+            if def
+                .overrides
+                .iter()
+                .any(|(_, property)| is_property(property))
+            {
+                return true;
+            }
+            next = def.parent;
+        }
+        false
+    }
+
     /// Type-safe property resolution with default fallback.
     #[inline]
     pub fn resolve<K: PropertyKey>(&self, style_id: StyleId, state: StateFlags) -> K::Value {
@@ -128,6 +305,19 @@ impl StyleRegistry {
         K::get(style, state)
     }
 
+    /// Like [`resolve`](Self::resolve), but returns `None` instead of
+    /// panicking if the style does not exist.
+    #[inline]
+    pub fn try_resolve<K: PropertyKey>(
+        &self,
+        style_id: StyleId,
+        state: StateFlags,
+    ) -> Option<K::Value> {
+        self.resolved
+            .get(style_id)
+            .map(|style| K::get(style, state))
+    }
+
     /// Build a resolved Style from a StyleDef.
     fn build_resolved(&self, def: &StyleDef) -> Style {
         // Start from parent's resolved style or default
@@ -815,6 +1005,167 @@ mod tests {
         );
     }
 
+    // ==================== Unregistration Tests ====================
+
+    #[test]
+    fn unregister_frees_style() {
+        let mut registry = StyleRegistry::default();
+
+        let parent = registry.register(None, vec![]).unwrap();
+        let style = registry.register(Some(parent), vec![]).unwrap();
+
+        registry.unregister(style);
+
+        assert!(registry.get(style).is_none());
+        assert!(registry.children[parent].is_empty());
+    }
+
+    #[test]
+    fn register_many_resolves_parents_by_name() {
+        let mut registry = StyleRegistry::default();
+
+        let ids = registry
+            .register_many(
+                StyleBatch::new()
+                    .style(
+                        "button",
+                        vec![(StateFlags::NORMAL, StyleProperty::TextColor(rgb(0, 0, 255)))],
+                    )
+                    .child(
+                        "danger",
+                        "button",
+                        vec![(
+                            StateFlags::HOVERED,
+                            StyleProperty::TextColor(rgb(255, 0, 0)),
+                        )],
+                    ),
+                None,
+            )
+            .unwrap();
+
+        let danger = ids["danger"];
+        assert_eq!(
+            registry.resolve::<TextColor>(danger, StateFlags::NORMAL),
+            rgb(0, 0, 255)
+        );
+        assert_eq!(
+            registry.resolve::<TextColor>(danger, StateFlags::HOVERED),
+            rgb(255, 0, 0)
+        );
+    }
+
+    #[test]
+    fn register_many_with_unknown_parent_registers_nothing() {
+        let mut registry = StyleRegistry::default();
+
+        let result = registry.register_many(
+            StyleBatch::new()
+                .style("button", vec![])
+                .child("danger", "missing", vec![]),
+            None,
+        );
+
+        assert_eq!(result, Err(StyleError::UnknownParent("missing".into())));
+        assert_eq!(registry.definitions.len(), 1);
+    }
+
+    #[test]
+    fn try_resolve_unregistered_style_returns_none() {
+        let mut registry = StyleRegistry::default();
+
+        let style = registry
+            .register(
+                None,
+                vec![(StateFlags::NORMAL, StyleProperty::TextColor(rgb(0, 0, 255)))],
+            )
+            .unwrap();
+
+        assert_eq!(
+            registry.try_resolve::<TextColor>(style, StateFlags::NORMAL),
+            Some(rgb(0, 0, 255))
+        );
+
+        registry.unregister(style);
+
+        assert_eq!(
+            registry.try_resolve::<TextColor>(style, StateFlags::NORMAL),
+            None
+        );
+    }
+
+    #[test]
+    fn unregister_moves_children_to_parent() {
+        let mut registry = StyleRegistry::default();
+
+        let grandparent = registry.register(None, vec![]).unwrap();
+        let parent = registry
+            .register(
+                Some(grandparent),
+                vec![
+                    (
+                        StateFlags::NORMAL,
+                        StyleProperty::Background(Paint::solid(rgb(50, 50, 50))),
+                    ),
+                    (
+                        StateFlags::NORMAL,
+                        StyleProperty::TextColor(rgb(255, 255, 255)),
+                    ),
+                ],
+            )
+            .unwrap();
+        let child = registry
+            .register(
+                Some(parent),
+                vec![(
+                    StateFlags::NORMAL,
+                    StyleProperty::Background(Paint::solid(rgb(100, 100, 100))),
+                )],
+            )
+            .unwrap();
+
+        registry.unregister(parent);
+
+        // The child resolves as it did before its parent was removed.
+        assert_eq!(
+            registry.resolve::<Background>(child, StateFlags::NORMAL),
+            Paint::solid(rgb(100, 100, 100))
+        );
+        assert_eq!(
+            registry.resolve::<TextColor>(child, StateFlags::NORMAL),
+            rgb(255, 255, 255)
+        );
+
+        // And now follows its former grandparent.
+        registry.update(
+            grandparent,
+            vec![(
+                StateFlags::HOVERED,
+                StyleProperty::TextColor(rgb(0, 0, 255)),
+            )],
+        );
+        assert_eq!(
+            registry.resolve::<TextColor>(child, StateFlags::HOVERED),
+            rgb(0, 0, 255)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "default style")]
+    fn unregister_panics_on_default_style() {
+        let mut registry = StyleRegistry::default();
+        let default_id = registry.default_style_id();
+        registry.unregister(default_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "style that does not exist")]
+    fn unregister_panics_on_invalid_id() {
+        let mut registry = StyleRegistry::default();
+        let style = registry.register(None, vec![]).unwrap();
+        registry.unregister(style);
+        registry.unregister(style);
+    }
+
     // ==================== Accessor Tests ====================
 
     #[test]
@@ -880,4 +1231,28 @@ mod tests {
             Paint::solid(rgb(150, 150, 150))
         );
     }
+
+    #[test]
+    fn sets_checks_parents() {
+        let mut registry = StyleRegistry::default();
+
+        let parent = registry
+            .register(
+                None,
+                vec![(
+                    StateFlags::HOVERED,
+                    StyleProperty::TextColor(rgb(255, 255, 255)),
+                )],
+            )
+            .unwrap();
+        let child = registry.register(Some(parent), vec![]).unwrap();
+
+        let is_text_color =
+            |property: &StyleProperty| matches!(property, StyleProperty::TextColor(_));
+        let is_background =
+            |property: &StyleProperty| matches!(property, StyleProperty::Background(_));
+
+        assert!(registry.sets(child, is_text_color));
+        assert!(!registry.sets(child, is_background));
+    }
 }
@@ -1,12 +1,21 @@
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::ops::Range;
+
 use parley::Layout;
+use rapidhash::fast::RapidHasher;
 use slotmap::SlotMap;
 use slotmap::new_key_type;
 
 use crate::graphics::Color;
+use crate::graphics::FontFamily;
+use crate::graphics::SpanStyle;
 use crate::graphics::TextAlignment;
+use crate::graphics::TextLayoutContext;
 
 use super::style::StateFlags;
 use super::style::StyleId;
+use super::theme::Theme;
 
 new_key_type! {
     pub struct StaticTextLayoutId;
@@ -21,28 +30,68 @@ pub struct StaticTextLayout {
     pub state: StateFlags,
     pub text_hash: u64,
     pub raw_text: String,
+    /// The styles of each span of rich text, by byte range in `raw_text`.
+    pub spans: Vec<(Range<usize>, SpanStyle)>,
     pub prev_width: f32,
     pub prev_alignment: Option<TextAlignment>,
     pub prev_overflow: TextOverflow,
+    /// The scale the layout was built at, which differs from 1.0 only for
+    /// text that is fit to its width.
+    pub scale: f32,
+    /// The width of the whole text on one line, if the layout holds a copy of
+    /// the text that was cut off to fit.
+    pub truncated_width: Option<f32>,
 
     // Track if line breaking and alignment need to be recomputed
     pub needs_line_break: bool,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum TextLayoutId {
     Static(StaticTextLayoutId),
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub enum TextOverflow {
     #[default]
     Clip,
     Wrap,
+    /// Keeps the text on one line and scales its font size to fill the
+    /// available width, within the given bounds in pixels at 100% display
+    /// scaling. Text that doesn't fit at `min_size` is clipped.
+    Fit {
+        min_size: u16,
+        max_size: u16,
+    },
+    /// Wraps the text onto at most `max_lines` lines, and cuts off what
+    /// doesn't fit with an ellipsis.
+    Ellipsis {
+        max_lines: u16,
+    },
 }
 
 pub enum TextLayoutMut<'a> {
-    Static(&'a mut Layout<Color>),
+    Static(&'a mut StaticTextLayout),
+}
+
+impl StaticTextLayout {
+    /// Rebuilds the layout for `text`, which need not be the widget's text,
+    /// styled like the widget's text.
+    fn build(
+        &mut self,
+        text: &str,
+        spans: &[(Range<usize>, SpanStyle)],
+        text_context: &mut TextLayoutContext,
+        theme: &Theme,
+    ) {
+        let mut builder =
+            text_context
+                .layouts
+                .ranged_builder(&mut text_context.fonts, text, self.scale, false);
+        theme.push_text_defaults(self.style_id, self.state, &mut builder);
+        push_span_styles(spans, theme.font_fallbacks(), &mut builder);
+        builder.build_into(&mut self.layout, text);
+    }
 }
 
 pub struct TextLayoutStorage {
@@ -74,9 +123,12 @@ impl TextLayoutStorage {
                     state: Default::default(),
                     text_hash: 0,
                     raw_text: String::new(),
+                    spans: Vec::new(),
                     prev_width: 0.0,
                     prev_alignment: None,
                     prev_overflow: TextOverflow::Clip,
+                    scale: 1.0,
+                    truncated_width: None,
                     needs_line_break: true,
                 };
                 let id = self.static_layouts.insert(layout);
@@ -110,11 +162,11 @@ impl TextLayoutStorage {
 
                 if text.needs_line_break || width_changed || overflow_changed {
                     match overflow {
-                        TextOverflow::Clip => {
+                        TextOverflow::Clip | TextOverflow::Fit { .. } => {
                             // Keep text on a single line while still producing drawable line data.
                             text.layout.break_all_lines(None);
                         }
-                        TextOverflow::Wrap => {
+                        TextOverflow::Wrap | TextOverflow::Ellipsis { .. } => {
                             text.layout.break_all_lines(Some(max_width));
                         }
                     }
@@ -133,12 +185,224 @@ impl TextLayoutStorage {
         }
     }
 
+    /// Rebuilds a layout with `overflow: Fit` at the font size that fills
+    /// `max_width`. Returns whether the layout was rebuilt.
+    pub(crate) fn fit_to_width(
+        &mut self,
+        layout_id: TextLayoutId,
+        max_width: f32,
+        (min_size, max_size): (u16, u16),
+        text_context: &mut TextLayoutContext,
+        theme: &Theme,
+    ) -> bool {
+        let TextLayoutId::Static(id) = layout_id;
+        let Some(text) = self.static_layouts.get_mut(id) else {
+            return false;
+        };
+
+        let overflow = TextOverflow::Fit { min_size, max_size };
+        if !text.needs_line_break && text.prev_width == max_width && text.prev_overflow == overflow
+        {
+            return false;
+        }
+
+        let font_size = theme.font_size(text.style_id, text.state);
+        let natural_width = text.layout.calculate_content_widths().max / text.scale;
+        if font_size <= 0.0 || natural_width <= 0.0 {
+            return false;
+        }
+
+        // Width grows roughly linearly with font size. Rounding down to half
+        // points keeps small width changes from rebuilding the layout.
+        let fitted = (font_size * max_width / natural_width * 2.0).floor() / 2.0;
+        let scale = fitted.clamp(f32::from(min_size), f32::from(max_size)) / font_size;
+
+        if scale == text.scale {
+            return false;
+        }
+
+        let mut builder = text_context.layouts.ranged_builder(
+            &mut text_context.fonts,
+            &text.raw_text,
+            scale,
+            false,
+        );
+        theme.push_text_defaults(text.style_id, text.state, &mut builder);
+        push_span_styles(&text.spans, theme.font_fallbacks(), &mut builder);
+        builder.build_into(&mut text.layout, &text.raw_text);
+
+        text.scale = scale;
+        text.needs_line_break = true;
+        true
+    }
+
+    /// Rebuilds a layout with `overflow: Ellipsis` so that it fits in
+    /// `max_lines` lines of `max_width`, cutting it off with an ellipsis if it
+    /// doesn't. Returns whether the layout was rebuilt.
+    pub(crate) fn ellipsize(
+        &mut self,
+        layout_id: TextLayoutId,
+        max_width: f32,
+        max_lines: u16,
+        text_context: &mut TextLayoutContext,
+        theme: &Theme,
+    ) -> bool {
+        let TextLayoutId::Static(id) = layout_id;
+        let Some(text) = self.static_layouts.get_mut(id) else {
+            return false;
+        };
+
+        let overflow = TextOverflow::Ellipsis { max_lines };
+        if !text.needs_line_break && text.prev_width == max_width && text.prev_overflow == overflow
+        {
+            return false;
+        }
+
+        let max_lines = usize::from(max_lines.max(1));
+        let raw_text = std::mem::take(&mut text.raw_text);
+        let spans = std::mem::take(&mut text.spans);
+
+        let mut is_rebuilt = false;
+        if text.truncated_width.take().is_some() {
+            text.build(&raw_text, &spans, text_context, theme);
+            is_rebuilt = true;
+        }
+
+        text.layout.break_all_lines(Some(max_width));
+        let fits =
+            |layout: &Layout<Color>| layout.len() <= max_lines && layout.width() <= max_width;
+
+        // Copied out so that the layout isn't borrowed while it's rebuilt.
+        let last_line_range = text
+            .layout
+            .lines()
+            .nth(max_lines - 1)
+            .map(|line| line.text_range());
+
+        if !fits(&text.layout)
+            && let Some(line_range) = last_line_range
+        {
+            let natural_width = text.layout.calculate_content_widths().max;
+
+            // The longest prefix that fits with the ellipsis is found by
+            // binary search, since each attempt shapes the text again.
+            let cuts = raw_text[line_range.clone()]
+                .char_indices()
+                .map(|(i, _)| line_range.start + i)
+                .chain([line_range.end])
+                .collect::<Vec<_>>();
+
+            let mut truncated = String::new();
+            let mut truncated_spans = Vec::new();
+            let mut build_truncated = |text: &mut StaticTextLayout, cut: usize| {
+                let prefix = raw_text[..cut].trim_end();
+                truncated.clear();
+                truncated.push_str(prefix);
+                truncated.push('…');
+
+                truncated_spans.clear();
+                truncated_spans.extend(
+                    spans
+                        .iter()
+                        .filter(|(range, _)| range.start < prefix.len())
+                        .map(|(range, style)| {
+                            let end = if range.end >= prefix.len() {
+                                truncated.len()
+                            } else {
+                                range.end
+                            };
+                            (range.start..end, style.clone())
+                        }),
+                );
+
+                text.build(&truncated, &truncated_spans, text_context, theme);
+                text.layout.break_all_lines(Some(max_width));
+            };
+
+            let (mut low, mut high) = (0, cuts.len() - 1);
+            while low < high {
+                let mid = (low + high).div_ceil(2);
+                build_truncated(text, cuts[mid]);
+                if fits(&text.layout) {
+                    low = mid;
+                } else {
+                    high = mid - 1;
+                }
+            }
+
+            build_truncated(text, cuts[low]);
+            text.truncated_width = Some(natural_width);
+            is_rebuilt = true;
+        }
+
+        text.raw_text = raw_text;
+        text.spans = spans;
+        text.needs_line_break = true;
+        is_rebuilt
+    }
+
+    /// Identifies everything the height of a layout depends on other than
+    /// its width, for reusing the layout of unchanged subtrees. Returns `None`
+    /// if the layout must be measured again to be drawn, e.g. because its
+    /// text changed.
+    pub(crate) fn layout_key(
+        &self,
+        layout_id: TextLayoutId,
+        alignment: TextAlignment,
+        overflow: TextOverflow,
+    ) -> Option<u64> {
+        let TextLayoutId::Static(id) = layout_id;
+        let text = self.static_layouts.get(id)?;
+
+        if text.needs_line_break
+            || text.prev_alignment != Some(alignment)
+            || text.prev_overflow != overflow
+        {
+            return None;
+        }
+
+        let mut hasher = RapidHasher::new(0);
+        (layout_id, text.style_id, text.theme_revision, text.state).hash(&mut hasher);
+        (text.text_hash, text.prev_overflow).hash(&mut hasher);
+        for value in [text.prev_width, text.scale] {
+            value.to_bits().hash(&mut hasher);
+        }
+
+        Some(hasher.finish())
+    }
+
     pub(crate) fn get_mut<'a>(&'a mut self, layout_id: TextLayoutId) -> Option<TextLayoutMut<'a>> {
         match layout_id {
-            TextLayoutId::Static(id) => self
-                .static_layouts
-                .get_mut(id)
-                .map(|l| TextLayoutMut::Static(&mut l.layout)),
+            TextLayoutId::Static(id) => self.static_layouts.get_mut(id).map(TextLayoutMut::Static),
+        }
+    }
+}
+
+/// Pushes the styles of rich text spans over the theme's defaults.
+pub(crate) fn push_span_styles(
+    spans: &[(Range<usize>, SpanStyle)],
+    fallbacks: &[FontFamily],
+    builder: &mut parley::RangedBuilder<Color>,
+) {
+    use parley::StyleProperty as Prop;
+
+    for (range, style) in spans {
+        if let Some(font) = &style.font {
+            font.family.with_parley_family(fallbacks, |family| {
+                builder.push(Prop::FontFamily(family), range.clone());
+            });
+        }
+        if let Some(weight) = style.weight {
+            builder.push(Prop::FontWeight(weight.into()), range.clone());
+        }
+        if let Some(size) = style.size {
+            builder.push(Prop::FontSize(size), range.clone());
+        }
+        if let Some(color) = style.color {
+            builder.push(Prop::Brush(color), range.clone());
+        }
+        if let Some(underline) = style.underline {
+            builder.push(Prop::Underline(underline), range.clone());
         }
     }
 }
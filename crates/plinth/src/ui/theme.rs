@@ -1,29 +1,53 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::sync::OnceLock;
 
 use parley::FontFeatures;
-use smallvec::SmallVec;
+use tracing::warn;
 
 use crate::graphics::Color;
-use crate::graphics::FontStack;
+use crate::graphics::FontFamily;
 use crate::graphics::GradientPaint;
 use crate::graphics::Paint;
 
+use super::LayoutDirection;
+use super::Length;
 use super::Size;
+use super::layout::LengthMetrics;
 use super::style::BorderWidths;
 use super::style::CornerRadii;
+use super::style::FontSize;
 use super::style::PropertyKey;
 use super::style::StateFlags;
 use super::style::Style;
+use super::style::StyleBatch;
 use super::style::StyleError;
 use super::style::StyleId;
 use super::style::StyleProperty;
 use super::style::StyleRegistry;
+use super::style::TextDecoration;
+
+#[cfg(feature = "theme-files")]
+mod document;
+
+#[cfg(feature = "theme-files")]
+pub use document::PropertyDefinition;
+#[cfg(feature = "theme-files")]
+pub use document::StyleDefinition;
+#[cfg(feature = "theme-files")]
+pub use document::ThemeDocument;
+#[cfg(feature = "theme-files")]
+pub use document::ThemeLoadError;
 
 static DEFAULT_FONT_FEATURES: OnceLock<FontFeatures<'static>> = OnceLock::new();
 
+/// What a relative font size on the default style is relative to, as in web
+/// browsers.
+const INITIAL_FONT_SIZE: f32 = 16.0;
+
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StyleClass {
     Surface = 0,
     Button,
@@ -34,17 +58,40 @@ pub enum StyleClass {
     VerticalSeparator,
     DropdownMenu,
     DropdownItem,
+    /// Text shown in an empty `TextEdit`.
+    TextEditPlaceholder,
+    /// Leading and trailing icons inside a `TextEdit`.
+    TextEditIcon,
+    TextEditClearButton,
+    TextEditCounter,
+    /// A labelled group of inputs in a `Form`.
+    FormField,
+    /// The error message shown below an invalid form field.
+    FormError,
 }
 
 impl StyleClass {
     /// Number of style class variants. Update when adding new variants.
-    pub const COUNT: usize = 9;
+    pub const COUNT: usize = 15;
 }
 
+/// A style class registered by name with [`Theme::register_class`].
+///
+/// Looking a class up by key is cheaper than by name, so widgets that are
+/// built every frame can look the name up once and keep the key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClassKey(u32);
+
 pub struct Theme {
     well_known_classes: [Option<StyleId>; StyleClass::COUNT],
+    named_classes: Vec<Option<StyleId>>,
+    class_keys: HashMap<String, ClassKey>,
     styles: StyleRegistry,
+    font_fallbacks: Vec<FontFamily>,
     revision: u64,
+
+    #[cfg(feature = "theme-files")]
+    document_styles: document::DocumentStyles,
 }
 
 impl Theme {
@@ -54,7 +101,12 @@ impl Theme {
         Self {
             styles,
             well_known_classes: [None; StyleClass::COUNT],
+            named_classes: Vec::new(),
+            class_keys: HashMap::new(),
+            font_fallbacks: Vec::new(),
             revision: 0,
+            #[cfg(feature = "theme-files")]
+            document_styles: document::DocumentStyles::default(),
         }
     }
 
@@ -64,10 +116,38 @@ impl Theme {
         self.revision
     }
 
+    /// Moves the revision past `previous`, for a theme that replaces another.
+    pub(crate) fn follow_revision(&mut self, previous: u64) {
+        self.revision = self.revision.max(previous) + 1;
+    }
+
     /// Gets the style assigned to a style class.
     pub fn get(&self, class: StyleClass) -> &Style {
-        let styled_id = self.get_id(class);
-        self.styles.get(styled_id).unwrap()
+        self.get_style(self.get_id(class))
+    }
+
+    /// Gets a style by ID, or the default style if it doesn't exist, e.g.
+    /// because it was removed or belongs to another theme.
+    pub(crate) fn get_style(&self, style_id: StyleId) -> &Style {
+        self.styles.get(style_id).unwrap_or_else(|| {
+            warn!(
+                ?style_id,
+                "Unknown style, falling back to the default style"
+            );
+            self.styles.get(self.styles.default_style_id()).unwrap()
+        })
+    }
+
+    /// The families tried, in order, for characters that a style's fonts
+    /// don't cover, e.g. CJK or emoji. They are tried before the system's own
+    /// fallbacks.
+    pub fn font_fallbacks(&self) -> &[FontFamily] {
+        &self.font_fallbacks
+    }
+
+    pub fn set_font_fallbacks(&mut self, families: impl IntoIterator<Item = FontFamily>) {
+        self.font_fallbacks = families.into_iter().collect();
+        self.revision += 1;
     }
 
     /// Gets the style ID assigned to a style class.
@@ -114,6 +194,64 @@ impl Theme {
         Ok(style)
     }
 
+    /// Modifies a named style class by replacing its properties, registering
+    /// the class and a new style for it if the name isn't known yet.
+    ///
+    /// Classes with the same name share a key, so this returns the same key
+    /// every time it is called with the same name.
+    pub fn register_class(
+        &mut self,
+        name: &str,
+        parent: Option<StyleId>,
+        properties: impl IntoIterator<Item = (StateFlags, StyleProperty)>,
+    ) -> Result<ClassKey, StyleError> {
+        let key = self.intern_class(name);
+
+        let style = if let Some(current) = self.named_classes[key.0 as usize] {
+            self.update_style(current, properties);
+            current
+        } else {
+            self.create_style(parent, properties)?
+        };
+
+        self.set_class(key, style);
+        Ok(key)
+    }
+
+    /// The key of a class registered with [`Self::register_class`].
+    pub fn class_key(&self, name: &str) -> Option<ClassKey> {
+        self.class_keys.get(name).copied()
+    }
+
+    /// Gets the style ID assigned to a named style class.
+    pub fn get_class_id(&self, key: ClassKey) -> StyleId {
+        self.named_classes[key.0 as usize].unwrap_or(self.styles.default_style_id())
+    }
+
+    /// Gets the style ID assigned to a named style class, or the default style
+    /// if no class has that name.
+    pub fn get_named_id(&self, name: &str) -> StyleId {
+        self.class_key(name)
+            .map_or(self.styles.default_style_id(), |key| self.get_class_id(key))
+    }
+
+    /// Assigns a style to a named style class.
+    pub fn set_class(&mut self, key: ClassKey, style_id: StyleId) {
+        self.named_classes[key.0 as usize] = Some(style_id);
+        self.revision += 1;
+    }
+
+    fn intern_class(&mut self, name: &str) -> ClassKey {
+        if let Some(key) = self.class_key(name) {
+            return key;
+        }
+
+        let key = ClassKey(self.named_classes.len() as u32);
+        self.named_classes.push(None);
+        self.class_keys.insert(name.to_owned(), key);
+        key
+    }
+
     /// Resolves a property for a specific style class and state combination.
     pub fn resolve<K: PropertyKey>(&self, style: StyleClass, state: StateFlags) -> K::Value {
         let style_id =
@@ -122,8 +260,27 @@ impl Theme {
     }
 
     /// Resolves a property for a specific style ID and state combination.
+    ///
+    /// Falls back to the default style if the style doesn't exist.
     pub fn resolve_style<K: PropertyKey>(&self, style_id: StyleId, state: StateFlags) -> K::Value {
-        self.styles.resolve::<K>(style_id, state)
+        self.styles
+            .try_resolve::<K>(style_id, state)
+            .unwrap_or_else(|| {
+                warn!(
+                    ?style_id,
+                    "Unknown style, falling back to the default style"
+                );
+                self.styles
+                    .resolve::<K>(self.styles.default_style_id(), state)
+            })
+    }
+
+    /// The value of every property of a style in the given state, or none if
+    /// the style was removed.
+    pub(crate) fn resolve_all(&self, style_id: StyleId, state: StateFlags) -> Vec<StyleProperty> {
+        self.styles
+            .get(style_id)
+            .map_or_else(Vec::new, |style| style.resolve_all(state))
     }
 
     /// Creates a new style with the given parent and properties.
@@ -141,6 +298,19 @@ impl Theme {
         Ok(style_id)
     }
 
+    /// Creates a tree of related styles in one call, with parents referred to
+    /// by name. Styles without a parent in the batch inherit from the base
+    /// style. Returns the ID of each style by name.
+    pub fn create_styles(
+        &mut self,
+        batch: StyleBatch,
+    ) -> Result<HashMap<String, StyleId>, StyleError> {
+        let root = self.styles.default_style_id();
+        let ids = self.styles.register_many(batch, Some(root))?;
+        self.revision += 1;
+        Ok(ids)
+    }
+
     /// Modifies an existing style by replacing its properties.
     ///
     pub fn update_style(
@@ -152,6 +322,71 @@ impl Theme {
         self.revision += 1;
     }
 
+    /// Removes a style created with [`Self::create_style`], e.g. one made for
+    /// a document that has since been closed.
+    ///
+    /// Styles that inherited from it inherit from its parent instead, without
+    /// changing their appearance. Style classes assigned to it fall back to
+    /// the default style.
+    pub fn remove_style(&mut self, style_id: StyleId) {
+        self.styles.unregister(style_id);
+
+        for class in self
+            .well_known_classes
+            .iter_mut()
+            .chain(self.named_classes.iter_mut())
+        {
+            if *class == Some(style_id) {
+                *class = None;
+            }
+        }
+
+        #[cfg(feature = "theme-files")]
+        self.document_styles.forget(style_id);
+
+        self.revision += 1;
+    }
+
+    /// Whether the style or one of its parents sets a property matching
+    /// `is_property`, rather than leaving it at its default.
+    pub(crate) fn sets_property(
+        &self,
+        style_id: StyleId,
+        is_property: impl Fn(&StyleProperty) -> bool,
+    ) -> bool {
+        self.styles.sets(style_id, is_property)
+    }
+
+    /// The font size that [`Length::Rem`] is a multiple of, in pixels at 100%
+    /// display scaling. This is the font size of the default style.
+    pub fn root_font_size(&self) -> f32 {
+        self.styles
+            .resolve::<FontSize>(self.styles.default_style_id(), StateFlags::NORMAL)
+            .to_unscaled(INITIAL_FONT_SIZE, INITIAL_FONT_SIZE)
+    }
+
+    /// What the lengths of a style are measured against.
+    pub(crate) fn length_metrics(&self, style_id: StyleId, state: StateFlags) -> LengthMetrics {
+        let root_font_size = self.root_font_size();
+
+        // Styles don't inherit font sizes from the node's parent, so relative
+        // font sizes are relative to the root.
+        let font_size = self
+            .styles
+            .resolve::<FontSize>(style_id, state)
+            .to_unscaled(root_font_size, root_font_size);
+
+        LengthMetrics {
+            font_size,
+            root_font_size,
+        }
+    }
+
+    /// The font size of a style in pixels.
+    pub(crate) fn font_size(&self, style_id: StyleId, state: StateFlags) -> f32 {
+        self.length_metrics(style_id, state).font_size
+    }
+
     pub(crate) fn push_text_defaults(
         &self,
         style_id: StyleId,
@@ -160,55 +395,53 @@ impl Theme {
     ) {
         use parley::StyleProperty as Prop;
 
-        let style = self.enumerate_styles(style_id, state, |prop| {
+        let font_size = self.font_size(style_id, state);
+        let style = self.enumerate_styles(style_id, state, font_size, |prop| {
             builder.push_default(prop);
         });
 
-        match &style.font.get(state).family {
-            FontStack::Source(cow) => {
-                builder.push_default(Prop::FontFamily(parley::FontFamily::Source(cow.clone())));
-            }
-            FontStack::Single(font_family) => {
-                builder.push_default(Prop::FontFamily(parley::FontFamily::Single(
-                    font_family.clone().into(),
-                )));
-            }
-            FontStack::List(cow) => {
-                let families = cow
-                    .iter()
-                    .cloned()
-                    .map(|f| f.into())
-                    .collect::<SmallVec<[parley::FontFamilyName; 4]>>();
-                builder.push_default(Prop::FontFamily(families.as_slice().into()));
-            }
-        }
+        style
+            .font
+            .get(state)
+            .family
+            .with_parley_family(&self.font_fallbacks, |family| {
+                builder.push_default(Prop::FontFamily(family));
+            });
     }
 
     fn enumerate_styles<'a>(
         &self,
         style_id: StyleId,
         state: StateFlags,
+        font_size: f32,
         mut callback: impl FnMut(parley::StyleProperty<'a, Color>),
     ) -> &Style {
         use parley::StyleProperty as Prop;
 
-        let style = self.styles.get(style_id).unwrap();
+        let style = self.get_style(style_id);
 
         callback(Prop::FontFeatures(default_font_features()));
         callback(Prop::Brush(style.text_color.get(state)));
-        callback(Prop::FontSize(style.font_size.get(state) as f32));
+        callback(Prop::FontSize(font_size));
         callback(Prop::FontStyle(style.font_style.get(state).into()));
         callback(Prop::FontWeight(parley::FontWeight::new(
             style.font_weight.get(state) as f32,
         )));
+        let decoration = style.text_decoration.get(state);
+        callback(Prop::Strikethrough(
+            decoration.contains(TextDecoration::STRIKETHROUGH),
+        ));
         callback(Prop::StrikethroughBrush(Some(
             style.strikethrough_color.get(state),
         )));
-        callback(Prop::StrikethroughOffset(Some(
+        callback(Prop::StrikethroughOffset(decoration_offset(
             style.strikethrough_offset.get(state),
         )));
+        callback(Prop::Underline(
+            decoration.contains(TextDecoration::UNDERLINE),
+        ));
         callback(Prop::UnderlineBrush(Some(style.underline_color.get(state))));
-        callback(Prop::UnderlineOffset(Some(
+        callback(Prop::UnderlineOffset(decoration_offset(
             style.underline_offset.get(state),
         )));
 
@@ -216,12 +449,25 @@ impl Theme {
     }
 }
 
+/// An offset for parley, where `None` uses the font's metrics.
+pub(crate) fn decoration_offset(offset: f32) -> Option<f32> {
+    (offset != 0.0).then_some(offset)
+}
+
 impl Default for Theme {
     fn default() -> Self {
         default_theme()
     }
 }
 
+impl std::fmt::Debug for Theme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Theme")
+            .field("revision", &self.revision)
+            .finish_non_exhaustive()
+    }
+}
+
 pub(crate) fn default_font_features() -> parley::FontFeatures<'static> {
     DEFAULT_FONT_FEATURES
         .get_or_init(|| {
@@ -240,20 +486,41 @@ pub(crate) fn default_font_features() -> parley::FontFeatures<'static> {
 
 fn default_theme() -> Theme {
     let mut theme = Theme::new();
+    let focus_ring = Color::srgb_nonlinear(0.2, 0.4, 0.8, 1.0);
 
     theme
         .set_style_class(
             StyleClass::Button,
             None,
-            [(
-                StateFlags::empty(),
-                StyleProperty::CornerRadii(CornerRadii {
-                    top_left: 5.0,
-                    top_right: 5.0,
-                    bottom_right: 5.0,
-                    bottom_left: 5.0,
-                }),
-            )],
+            [
+                (
+                    StateFlags::empty(),
+                    StyleProperty::CornerRadii(CornerRadii {
+                        top_left: 5.0,
+                        top_right: 5.0,
+                        bottom_right: 5.0,
+                        bottom_left: 5.0,
+                    }),
+                ),
+                (
+                    StateFlags::DISABLED,
+                    StyleProperty::TextColor(Color::srgb_nonlinear(0.55, 0.55, 0.55, 1.0)),
+                ),
+                (
+                    StateFlags::DISABLED,
+                    StyleProperty::Background(Paint::solid(Color::srgb_nonlinear(
+                        0.92, 0.92, 0.92, 1.0,
+                    ))),
+                ),
+                (
+                    StateFlags::FOCUSED | StateFlags::FOCUS_VISIBLE,
+                    StyleProperty::Border(GradientPaint::solid(focus_ring)),
+                ),
+                (
+                    StateFlags::FOCUSED | StateFlags::FOCUS_VISIBLE,
+                    StyleProperty::BorderWidths(BorderWidths::uniform(2.0)),
+                ),
+            ],
         )
         .unwrap();
 
@@ -265,16 +532,146 @@ fn default_theme() -> Theme {
                 (
                     StateFlags::empty(),
                     StyleProperty::BorderWidths(BorderWidths {
-                        left: 0.0,
-                        right: 0.0,
-                        top: 0.0,
-                        bottom: 0.0,
+                        left: Length::Px(0.0),
+                        right: Length::Px(0.0),
+                        top: Length::Px(0.0),
+                        bottom: Length::Px(0.0),
                     }),
                 ),
                 (
                     StateFlags::empty(),
                     StyleProperty::Background(Paint::solid(Color::TRANSPARENT)),
                 ),
+                (
+                    StateFlags::DISABLED,
+                    StyleProperty::TextColor(Color::srgb_nonlinear(0.55, 0.55, 0.55, 1.0)),
+                ),
+            ],
+        )
+        .unwrap();
+
+    theme
+        .set_style_class(
+            StyleClass::TextEdit,
+            None,
+            [
+                (StateFlags::empty(), StyleProperty::Width(Size::Grow)),
+                (
+                    StateFlags::DISABLED,
+                    StyleProperty::TextColor(Color::srgb_nonlinear(0.55, 0.55, 0.55, 1.0)),
+                ),
+                (
+                    StateFlags::FOCUSED | StateFlags::FOCUS_VISIBLE,
+                    StyleProperty::Border(GradientPaint::solid(focus_ring)),
+                ),
+                (
+                    StateFlags::FOCUSED | StateFlags::FOCUS_VISIBLE,
+                    StyleProperty::BorderWidths(BorderWidths::uniform(2.0)),
+                ),
+            ],
+        )
+        .unwrap();
+
+    theme
+        .set_style_class(
+            StyleClass::TextEditPlaceholder,
+            Some(theme.get_id(StyleClass::TextEdit)),
+            [(
+                StateFlags::empty(),
+                StyleProperty::TextColor(Color::srgb_nonlinear(0.55, 0.55, 0.55, 1.0)),
+            )],
+        )
+        .unwrap();
+
+    theme
+        .set_style_class(
+            StyleClass::TextEditIcon,
+            None,
+            [
+                (
+                    StateFlags::empty(),
+                    StyleProperty::BorderWidths(BorderWidths::default()),
+                ),
+                (StateFlags::empty(), StyleProperty::Width(16.0.into())),
+                (StateFlags::empty(), StyleProperty::Height(16.0.into())),
+            ],
+        )
+        .unwrap();
+
+    theme
+        .set_style_class(
+            StyleClass::TextEditClearButton,
+            Some(theme.get_id(StyleClass::Label)),
+            [
+                (
+                    StateFlags::empty(),
+                    StyleProperty::TextColor(Color::srgb_nonlinear(0.55, 0.55, 0.55, 1.0)),
+                ),
+                (StateFlags::HOVERED, StyleProperty::TextColor(Color::BLACK)),
+                (
+                    StateFlags::empty(),
+                    StyleProperty::Padding(crate::ui::Padding::equal(0.0)),
+                ),
+            ],
+        )
+        .unwrap();
+
+    theme
+        .set_style_class(
+            StyleClass::TextEditCounter,
+            Some(theme.get_id(StyleClass::Label)),
+            [
+                (
+                    StateFlags::empty(),
+                    StyleProperty::TextColor(Color::srgb_nonlinear(0.55, 0.55, 0.55, 1.0)),
+                ),
+                (
+                    StateFlags::empty(),
+                    StyleProperty::FontSize(Length::Px(12.0)),
+                ),
+                (
+                    StateFlags::empty(),
+                    StyleProperty::Padding(crate::ui::Padding::equal(0.0)),
+                ),
+            ],
+        )
+        .unwrap();
+
+    theme
+        .set_style_class(
+            StyleClass::FormField,
+            None,
+            [
+                (
+                    StateFlags::empty(),
+                    StyleProperty::BorderWidths(BorderWidths::default()),
+                ),
+                (
+                    StateFlags::empty(),
+                    StyleProperty::Background(Paint::solid(Color::TRANSPARENT)),
+                ),
+                (
+                    StateFlags::empty(),
+                    StyleProperty::ChildDirection(LayoutDirection::Vertical),
+                ),
+                (StateFlags::empty(), StyleProperty::Width(Size::Grow)),
+            ],
+        )
+        .unwrap();
+
+    theme
+        .set_style_class(
+            StyleClass::FormError,
+            Some(theme.get_id(StyleClass::Label)),
+            [
+                (
+                    StateFlags::empty(),
+                    StyleProperty::TextColor(Color::srgb_nonlinear(0.8, 0.1, 0.1, 1.0)),
+                ),
+                (
+                    StateFlags::empty(),
+                    StyleProperty::FontSize(Length::Px(12.0)),
+                ),
             ],
         )
         .unwrap();
@@ -287,10 +684,10 @@ fn default_theme() -> Theme {
                 (
                     StateFlags::empty(),
                     StyleProperty::BorderWidths(BorderWidths {
-                        left: 0.0,
-                        right: 0.0,
-                        top: 0.0,
-                        bottom: 0.0,
+                        left: Length::Px(0.0),
+                        right: Length::Px(0.0),
+                        top: Length::Px(0.0),
+                        bottom: Length::Px(0.0),
                     }),
                 ),
                 (
@@ -311,10 +708,10 @@ fn default_theme() -> Theme {
                 (
                     StateFlags::empty(),
                     StyleProperty::BorderWidths(BorderWidths {
-                        left: 0.0,
-                        right: 0.0,
-                        top: 0.0,
-                        bottom: 0.0,
+                        left: Length::Px(0.0),
+                        right: Length::Px(0.0),
+                        top: Length::Px(0.0),
+                        bottom: Length::Px(0.0),
                     }),
                 ),
                 (
@@ -343,10 +740,10 @@ fn default_theme() -> Theme {
                 (
                     StateFlags::empty(),
                     StyleProperty::BorderWidths(BorderWidths {
-                        left: 1.0,
-                        right: 1.0,
-                        top: 0.0,
-                        bottom: 1.0,
+                        left: Length::Px(1.0),
+                        right: Length::Px(1.0),
+                        top: Length::Px(0.0),
+                        bottom: Length::Px(1.0),
                     }),
                 ),
                 (
@@ -357,7 +754,10 @@ fn default_theme() -> Theme {
                     StateFlags::empty(),
                     StyleProperty::Padding(crate::ui::Padding::equal(1.0)),
                 ),
-                (StateFlags::empty(), StyleProperty::ChildSpacing(0.0)),
+                (
+                    StateFlags::empty(),
+                    StyleProperty::ChildSpacing(Length::ZERO),
+                ),
             ],
         )
         .unwrap();
@@ -386,19 +786,19 @@ fn default_theme() -> Theme {
                 (
                     StateFlags::empty(),
                     StyleProperty::BorderWidths(BorderWidths {
-                        left: 0.0,
-                        right: 0.0,
-                        top: 0.0,
-                        bottom: 0.0,
+                        left: Length::Px(0.0),
+                        right: Length::Px(0.0),
+                        top: Length::Px(0.0),
+                        bottom: Length::Px(0.0),
                     }),
                 ),
                 (
                     StateFlags::empty(),
                     StyleProperty::Padding(crate::ui::Padding {
-                        left: 1.0,
-                        right: 1.0,
-                        top: 0.0,
-                        bottom: 0.0,
+                        left: Length::Px(1.0),
+                        right: Length::Px(1.0),
+                        top: Length::Px(0.0),
+                        bottom: Length::Px(0.0),
                     }),
                 ),
                 (StateFlags::empty(), StyleProperty::Width(Size::Grow)),
@@ -0,0 +1,498 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::ui::style::StateFlags;
+use crate::ui::style::StyleError;
+use crate::ui::style::StyleId;
+use crate::ui::style::StyleProperty;
+
+use super::StyleClass;
+use super::Theme;
+
+/// A declarative description of a theme, as stored in a theme file.
+///
+/// ```toml
+/// base = [{ FontSize = 15 }]
+///
+/// [styles.rounded]
+/// properties = [
+///     { CornerRadii = { top_left = 5.0, top_right = 5.0, bottom_right = 5.0, bottom_left = 5.0 } },
+/// ]
+///
+/// [classes.Button]
+/// parent = "rounded"
+/// properties = [
+///     { TextColor = { r = 0.0, g = 0.0, b = 0.0, a = 1.0 } },
+///     { state = "HOVERED", TextColor = { r = 0.0, g = 0.0, b = 1.0, a = 1.0 } },
+/// ]
+///
+/// [named_classes.danger-button]
+/// parent = "rounded"
+/// properties = [{ TextColor = { r = 1.0, g = 0.0, b = 0.0, a = 1.0 } }]
+/// ```
+///
+/// Colors are in linear sRGB, as in [`Color`](crate::graphics::Color).
+/// Lengths are numbers of pixels, or strings with a unit, such as `"12pt"`,
+/// `"1.5em"`, or `"2rem"`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeDocument {
+    /// Properties set on the default style, which every style inherits.
+    pub base: Vec<PropertyDefinition>,
+    /// Styles that can be named as the parent of other styles.
+    pub styles: HashMap<String, StyleDefinition>,
+    pub classes: HashMap<StyleClass, StyleDefinition>,
+    /// Classes registered by name, as with [`Theme::register_class`].
+    pub named_classes: HashMap<String, StyleDefinition>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StyleDefinition {
+    /// The name of a style in [`ThemeDocument::styles`] to inherit from.
+    /// Styles without a parent inherit from the default style.
+    pub parent: Option<String>,
+    pub properties: Vec<PropertyDefinition>,
+}
+
+/// A property and the state it applies to.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PropertyDefinition {
+    #[serde(default, skip_serializing_if = "StateFlags::is_empty")]
+    pub state: StateFlags,
+    #[serde(flatten)]
+    pub property: StyleProperty,
+}
+
+impl From<(StateFlags, StyleProperty)> for PropertyDefinition {
+    fn from((state, property): (StateFlags, StyleProperty)) -> Self {
+        Self { state, property }
+    }
+}
+
+#[derive(Debug)]
+pub enum ThemeLoadError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    Json(serde_json::Error),
+    /// The file extension is neither `toml` nor `json`.
+    UnknownFormat(PathBuf),
+    /// A style names a parent that isn't defined in the document.
+    UnknownParent(String),
+    /// A style is its own ancestor.
+    ParentCycle(String),
+    Style(StyleError),
+}
+
+impl std::fmt::Display for ThemeLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThemeLoadError::Io(error) => write!(f, "failed to read theme: {error}"),
+            ThemeLoadError::Toml(error) => write!(f, "invalid theme: {error}"),
+            ThemeLoadError::Json(error) => write!(f, "invalid theme: {error}"),
+            ThemeLoadError::UnknownFormat(path) => {
+                write!(f, "unknown theme format: {}", path.display())
+            }
+            ThemeLoadError::UnknownParent(name) => write!(f, "unknown parent style: {name}"),
+            ThemeLoadError::ParentCycle(name) => {
+                write!(f, "style {name} inherits from itself")
+            }
+            ThemeLoadError::Style(error) => write!(f, "invalid style: {error:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ThemeLoadError {}
+
+impl From<std::io::Error> for ThemeLoadError {
+    fn from(error: std::io::Error) -> Self {
+        ThemeLoadError::Io(error)
+    }
+}
+
+impl From<StyleError> for ThemeLoadError {
+    fn from(error: StyleError) -> Self {
+        ThemeLoadError::Style(error)
+    }
+}
+
+impl ThemeDocument {
+    /// Reads a document from a `.toml` or `.json` file.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, ThemeLoadError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&text).map_err(ThemeLoadError::Toml),
+            Some("json") => serde_json::from_str(&text).map_err(ThemeLoadError::Json),
+            _ => Err(ThemeLoadError::UnknownFormat(path.to_owned())),
+        }
+    }
+}
+
+impl Theme {
+    /// Builds a theme from a `.toml` or `.json` file. See [`ThemeDocument`]
+    /// for the format.
+    ///
+    /// The theme starts out empty rather than from the default theme. Use
+    /// [`Self::apply_file`] to override parts of another theme instead.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ThemeLoadError> {
+        let mut theme = Theme::new();
+        theme.apply_file(path)?;
+        Ok(theme)
+    }
+
+    /// Applies a theme file on top of this theme, replacing the properties
+    /// of the classes it defines.
+    pub fn apply_file(&mut self, path: impl AsRef<Path>) -> Result<(), ThemeLoadError> {
+        let document = ThemeDocument::read(path)?;
+        self.apply_document(&document)
+    }
+
+    /// Applies a theme document on top of this theme, replacing the
+    /// properties of the classes it defines.
+    ///
+    /// Applying a document again, e.g. after its file has changed, updates
+    /// the styles it created in place. The theme is left unchanged if the
+    /// document's styles can't be resolved.
+    pub fn apply_document(&mut self, document: &ThemeDocument) -> Result<(), ThemeLoadError> {
+        // Check parents up front so that a bad document doesn't leave the
+        // theme half-applied.
+        for (name, style) in &document.styles {
+            check_ancestry(document, name, style)?;
+        }
+
+        for style in document
+            .classes
+            .values()
+            .chain(document.named_classes.values())
+        {
+            if let Some(parent) = &style.parent
+                && !document.styles.contains_key(parent)
+            {
+                return Err(ThemeLoadError::UnknownParent(parent.clone()));
+            }
+        }
+
+        if !document.base.is_empty() {
+            self.set_base_style(properties(&document.base));
+        }
+
+        let mut named = HashMap::with_capacity(document.styles.len());
+        for name in document.styles.keys() {
+            register_named(self, document, name, &mut named)?;
+        }
+
+        for (class, style) in &document.classes {
+            let Some(parent_name) = &style.parent else {
+                self.set_style_class(*class, None, properties(&style.properties))?;
+                continue;
+            };
+
+            // Styles can't be moved to a new parent, so a class given a
+            // parent gets a style of its own unless it already has one with
+            // that parent from an earlier document.
+            let parent = named[parent_name];
+            let current = self.get_id(*class);
+
+            match self.document_styles.classes.get(class) {
+                Some(&(previous_parent, id)) if previous_parent == parent && id == current => {
+                    self.update_style(id, properties(&style.properties));
+                }
+                _ => {
+                    let id = self.create_style(Some(parent), properties(&style.properties))?;
+                    self.set(*class, id);
+                    self.document_styles.classes.insert(*class, (parent, id));
+                }
+            }
+        }
+
+        for (name, style) in &document.named_classes {
+            let Some(parent_name) = &style.parent else {
+                self.register_class(name, None, properties(&style.properties))?;
+                continue;
+            };
+
+            let parent = named[parent_name];
+            let key = self.intern_class(name);
+            let current = self.get_class_id(key);
+
+            match self.document_styles.named_classes.get(name) {
+                Some(&(previous_parent, id)) if previous_parent == parent && id == current => {
+                    self.update_style(id, properties(&style.properties));
+                }
+                _ => {
+                    let id = self.create_style(Some(parent), properties(&style.properties))?;
+                    self.set_class(key, id);
+                    self.document_styles
+                        .named_classes
+                        .insert(name.clone(), (parent, id));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Styles created by applying documents to a theme, so that applying a
+/// changed document again updates them in place instead of creating new ones.
+#[derive(Default)]
+pub(super) struct DocumentStyles {
+    /// Named styles and the parents they were created with.
+    named: HashMap<String, (Option<StyleId>, StyleId)>,
+    /// Classes that were given a style with a parent.
+    classes: HashMap<StyleClass, (StyleId, StyleId)>,
+    /// Named classes that were given a style with a parent.
+    named_classes: HashMap<String, (StyleId, StyleId)>,
+}
+
+impl DocumentStyles {
+    /// Drops a style that was removed from the theme, so that applying a
+    /// document again creates it anew.
+    pub(super) fn forget(&mut self, style_id: StyleId) {
+        self.named.retain(|_, (_, id)| *id != style_id);
+        self.classes.retain(|_, (_, id)| *id != style_id);
+        self.named_classes.retain(|_, (_, id)| *id != style_id);
+    }
+}
+
+fn properties(
+    definitions: &[PropertyDefinition],
+) -> impl Iterator<Item = (StateFlags, StyleProperty)> + '_ {
+    definitions
+        .iter()
+        .map(|definition| (definition.state, definition.property.clone()))
+}
+
+fn check_ancestry(
+    document: &ThemeDocument,
+    name: &str,
+    style: &StyleDefinition,
+) -> Result<(), ThemeLoadError> {
+    let mut current = style;
+    let mut depth = 0;
+
+    while let Some(parent) = &current.parent {
+        if parent == name || depth > document.styles.len() {
+            return Err(ThemeLoadError::ParentCycle(name.to_owned()));
+        }
+
+        current = document
+            .styles
+            .get(parent)
+            .ok_or_else(|| ThemeLoadError::UnknownParent(parent.clone()))?;
+        depth += 1;
+    }
+
+    Ok(())
+}
+
+/// Registers a named style after its ancestors. Ancestry must have been
+/// checked with [`check_ancestry`].
+fn register_named(
+    theme: &mut Theme,
+    document: &ThemeDocument,
+    name: &str,
+    named: &mut HashMap<String, StyleId>,
+) -> Result<StyleId, ThemeLoadError> {
+    if let Some(id) = named.get(name) {
+        return Ok(*id);
+    }
+
+    let style = &document.styles[name];
+    let parent = match &style.parent {
+        Some(parent) => Some(register_named(theme, document, parent, named)?),
+        None => None,
+    };
+
+    let id = match theme.document_styles.named.get(name) {
+        Some(&(previous_parent, id)) if previous_parent == parent => {
+            theme.update_style(id, properties(&style.properties));
+            id
+        }
+        _ => {
+            let id = theme.create_style(parent, properties(&style.properties))?;
+            theme
+                .document_styles
+                .named
+                .insert(name.to_owned(), (parent, id));
+            id
+        }
+    };
+
+    named.insert(name.to_owned(), id);
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::Color;
+    use crate::ui::Length;
+    use crate::ui::style::ChildSpacing;
+    use crate::ui::style::FontSize;
+    use crate::ui::style::TextColor;
+
+    use super::*;
+
+    #[test]
+    fn parse_toml_document() {
+        let document: ThemeDocument = toml::from_str(
+            r#"
+            [styles.base]
+            properties = [{ FontSize = 20 }]
+
+            [classes.Label]
+            parent = "base"
+            properties = [
+                { TextColor = { r = 1.0, g = 0.0, b = 0.0, a = 1.0 } },
+                { state = "HOVERED", TextColor = { r = 0.0, g = 0.0, b = 1.0, a = 1.0 } },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        let mut theme = Theme::new();
+        theme.apply_document(&document).unwrap();
+
+        assert_eq!(
+            theme.resolve::<TextColor>(StyleClass::Label, StateFlags::NORMAL),
+            Color::RED
+        );
+        assert_eq!(
+            theme.resolve::<TextColor>(StyleClass::Label, StateFlags::HOVERED),
+            Color::BLUE
+        );
+        assert_eq!(
+            theme.resolve::<FontSize>(StyleClass::Label, StateFlags::NORMAL),
+            Length::Px(20.0)
+        );
+    }
+
+    #[test]
+    fn reapplying_updates_styles_in_place() {
+        let parse = |color: &str| -> ThemeDocument {
+            toml::from_str(&format!(
+                r#"
+                [styles.base]
+                properties = [{{ TextColor = {color} }}]
+
+                [classes.Label]
+                parent = "base"
+                "#
+            ))
+            .unwrap()
+        };
+
+        let mut theme = Theme::new();
+        theme
+            .apply_document(&parse("{ r = 1.0, g = 0.0, b = 0.0, a = 1.0 }"))
+            .unwrap();
+        let label = theme.get_id(StyleClass::Label);
+
+        theme
+            .apply_document(&parse("{ r = 0.0, g = 0.0, b = 1.0, a = 1.0 }"))
+            .unwrap();
+
+        assert_eq!(theme.get_id(StyleClass::Label), label);
+        assert_eq!(
+            theme.resolve::<TextColor>(StyleClass::Label, StateFlags::NORMAL),
+            Color::BLUE
+        );
+    }
+
+    #[test]
+    fn parse_lengths_with_units() {
+        let document: ThemeDocument = toml::from_str(
+            r#"
+            base = [{ FontSize = "12pt" }, { ChildSpacing = "0.5em" }]
+            "#,
+        )
+        .unwrap();
+
+        let mut theme = Theme::new();
+        theme.apply_document(&document).unwrap();
+
+        assert_eq!(
+            theme.resolve::<FontSize>(StyleClass::Label, StateFlags::NORMAL),
+            Length::Pt(12.0)
+        );
+        assert_eq!(
+            theme.resolve::<ChildSpacing>(StyleClass::Label, StateFlags::NORMAL),
+            Length::Em(0.5)
+        );
+    }
+
+    #[test]
+    fn parent_cycle_is_rejected() {
+        let document: ThemeDocument = serde_json::from_str(
+            r#"{
+                "styles": {
+                    "a": { "parent": "b" },
+                    "b": { "parent": "a" }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let mut theme = Theme::new();
+        let revision = theme.revision();
+
+        assert!(matches!(
+            theme.apply_document(&document),
+            Err(ThemeLoadError::ParentCycle(_))
+        ));
+        assert_eq!(theme.revision(), revision);
+    }
+
+    #[test]
+    fn named_classes_resolve_by_name_and_key() {
+        let document: ThemeDocument = toml::from_str(
+            r#"
+            [styles.base]
+            properties = [{ FontSize = 20 }]
+
+            [named_classes.danger-button]
+            parent = "base"
+            properties = [{ TextColor = { r = 1.0, g = 0.0, b = 0.0, a = 1.0 } }]
+            "#,
+        )
+        .unwrap();
+
+        let mut theme = Theme::new();
+        theme.apply_document(&document).unwrap();
+
+        let key = theme.class_key("danger-button").unwrap();
+        let style_id = theme.get_named_id("danger-button");
+        assert_eq!(theme.get_class_id(key), style_id);
+        assert_eq!(
+            theme.resolve_style::<TextColor>(style_id, StateFlags::NORMAL),
+            Color::RED
+        );
+        assert_eq!(
+            theme.resolve_style::<FontSize>(style_id, StateFlags::NORMAL),
+            Length::Px(20.0)
+        );
+
+        theme.apply_document(&document).unwrap();
+        assert_eq!(theme.class_key("danger-button"), Some(key));
+        assert_eq!(theme.get_class_id(key), style_id);
+
+        let registered = theme
+            .register_class(
+                "danger-button",
+                None,
+                [(StateFlags::NORMAL, StyleProperty::TextColor(Color::BLUE))],
+            )
+            .unwrap();
+        assert_eq!(registered, key);
+        assert_eq!(
+            theme.resolve_style::<TextColor>(style_id, StateFlags::NORMAL),
+            Color::BLUE
+        );
+    }
+}
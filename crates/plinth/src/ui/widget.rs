@@ -1,11 +1,16 @@
+use std::cell::Cell;
 use std::hash::Hash;
+use std::sync::Arc;
 
 use bytemuck::NoUninit;
 use bytemuck::Pod;
 use glamour::Contains;
+use glamour::Point2;
 use glamour::Rect;
+use glamour::Vector2;
 use std::mem::size_of;
 
+use crate::graphics::AlphaMask;
 use crate::ui::Pixels;
 use crate::ui::text::TextLayoutId;
 
@@ -15,38 +20,79 @@ use super::UiBuilder;
 use super::style::StateFlags;
 
 mod button;
+pub(crate) mod context_menu;
 mod dropdown;
+mod form;
 mod frame;
 mod horizontal_separator;
 mod image;
 mod label;
 pub(crate) mod macros;
+pub(crate) mod scroll_area;
 mod surface;
+mod tab_bar;
 mod text_edit;
 mod vertical_separator;
 
 pub use button::Button;
+pub use context_menu::ContextMenu;
 pub use dropdown::Dropdown;
 pub use dropdown::DropdownItem;
+pub use form::Form;
 pub use frame::Frame;
 pub use horizontal_separator::HorizontalSeparator;
 pub use image::Image;
 pub use label::Label;
+pub use scroll_area::ScrollArea;
+pub use scroll_area::ScrollAxes;
+pub use scroll_area::ScrollSync;
 pub use surface::Surface;
+pub use tab_bar::TabBar;
+pub use tab_bar::TabBarResponse;
 pub use text_edit::EditableTextBuffer;
 pub use text_edit::EditableTextLayout;
+pub use text_edit::NewlineKey;
+pub use text_edit::NumberFormat;
+pub use text_edit::NumericEntry;
 pub use text_edit::PlainTextBuffer;
 pub use text_edit::PlainTextEditorState;
+pub use text_edit::SelectionBehavior;
+pub use text_edit::SelectionGranularity;
 pub use text_edit::TextEdit;
 pub use text_edit::TextEditMotion;
 pub use text_edit::TextEditorState;
 pub use vertical_separator::VerticalSeparator;
 
+/// How far, in pixels, the pointer must move while pressed before the press
+/// becomes a drag, unless set with [`UiBuilder::drag_threshold`].
+pub const DEFAULT_DRAG_THRESHOLD: f32 = 4.0;
+
 #[derive(Clone, Copy, Debug)]
 pub struct Interaction {
     pub is_activated: bool,
     pub is_hovered: bool,
     pub is_focused: bool,
+    /// Whether the pointer moved onto the widget this frame.
+    pub hover_entered: bool,
+    /// Whether the pointer moved off the widget this frame.
+    pub hover_exited: bool,
+    /// Whether the widget was activated by the second click of a
+    /// double-click.
+    pub double_clicked: bool,
+    /// Whether the widget was activated by the third click of a
+    /// triple-click.
+    pub triple_clicked: bool,
+    /// Whether the widget is being dragged: it was pressed, and the pointer
+    /// has since moved past the [drag threshold](UiBuilder::drag_threshold)
+    /// with the button still held.
+    pub is_dragging: bool,
+    /// How far the pointer moved while dragging this frame. On the frame the
+    /// drag starts, this includes the movement up to the threshold.
+    pub drag_delta: Vector2<Pixels>,
+    /// Whether the drag started this frame.
+    pub drag_started: bool,
+    /// Whether the drag ended this frame, when the button was released.
+    pub drag_ended: bool,
 }
 
 pub trait Container<'a>: Sized {
@@ -85,6 +131,28 @@ pub trait Container<'a>: Sized {
         self.child_alignment(major, minor);
         self
     }
+
+    /// Enables or disables the widgets built inside this one from here on.
+    /// See [`UiBuilder::enabled`].
+    fn enabled(&mut self, enabled: bool) -> &mut Self {
+        self.builder_mut().enabled(enabled);
+        self
+    }
+
+    fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled(enabled);
+        self
+    }
+
+    fn debug_label(&mut self, label: impl Into<String>) -> &mut Self {
+        self.builder_mut().debug_label(label);
+        self
+    }
+
+    fn with_debug_label(mut self, label: impl Into<String>) -> Self {
+        self.debug_label(label);
+        self
+    }
 }
 
 impl<'a> Container<'a> for UiBuilder<'a> {
@@ -117,17 +185,74 @@ pub enum ClickBehavior {
     OnRelease,
 }
 
+/// How a widget takes part in routing pointer presses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PointerPhase {
+    /// The widget receives presses unless a descendant under the pointer
+    /// also handles them.
+    Bubble,
+    /// The widget receives presses anywhere in its subtree, before any of
+    /// its descendants.
+    Capture,
+}
+
 impl Interaction {
     /// Compute interaction state for a widget.
     ///
+    /// Widgets interested in [`StateFlags::PRESSED`] handle pointer presses.
+    /// Each press goes to only one of them: the topmost one under the
+    /// pointer, which for nested widgets is the innermost, unless an ancestor
+    /// intercepts it with [`UiBuilder::capture_pointer`].
+    ///
+    /// Widgets that are not [enabled](UiBuilder::enabled) are never hovered,
+    /// pressed, or focused, and their state is always [`StateFlags::DISABLED`].
+    ///
     /// Returns the interaction result and whether the widget is currently active (being pressed).
     pub fn compute(
         builder: &UiBuilder<'_>,
         behavior: ClickBehavior,
         interest: StateFlags,
     ) -> (Self, StateFlags) {
+        if builder.is_disabled {
+            let interaction = Self {
+                is_activated: false,
+                is_hovered: false,
+                is_focused: false,
+                hover_entered: false,
+                hover_exited: false,
+                double_clicked: false,
+                triple_clicked: false,
+                is_dragging: false,
+                drag_delta: Vector2::default(),
+                drag_started: false,
+                drag_ended: false,
+            };
+            return (interaction, StateFlags::DISABLED);
+        }
+
         let was_focused = builder.is_focused();
 
+        if interest.contains(StateFlags::FOCUSED) {
+            let candidate = &builder.context.focus_candidate;
+            candidate.set(candidate.get().or(Some(builder.id)));
+        }
+
+        let receives_presses = if interest.contains(StateFlags::PRESSED) {
+            builder
+                .context
+                .pointer_handlers
+                .borrow_mut()
+                .entry(builder.id)
+                .or_insert(PointerPhase::Bubble);
+
+            builder
+                .context
+                .pointer_target
+                .is_none_or(|target| target == builder.id)
+        } else {
+            true
+        };
+
         // Layer-aware hit testing: a widget can only be hovered if no higher layer
         // has a widget under the pointer, and no modal overlay blocks this layer.
         // input_block_layer uses strict-less-than so that the modal overlay's own
@@ -137,42 +262,121 @@ impl Interaction {
                 .context
                 .input_block_layer
                 .is_some_and(|bl| builder.layer < bl);
-
-        let (was_active, is_hovered) = builder
-            .prev_state()
-            .map(|s| {
-                (
-                    s.was_active,
-                    !layer_blocked && s.placement.contains(&builder.input.pointer),
-                )
-            })
-            .unwrap_or_default();
-
+        // Other widgets ignore the pointer while one is dragged, so that
+        // dragging across them doesn't hover them.
+        let is_captured_elsewhere = builder
+            .context
+            .drag_capture
+            .get()
+            .is_some_and(|id| id != builder.id);
+
+        let (was_active, was_hovered, press_count, mut drag_origin, was_dragging, is_hovered) =
+            builder
+                .prev_state()
+                .map(|s| {
+                    (
+                        s.was_active,
+                        s.was_hovered.get(),
+                        s.press_count.get(),
+                        s.drag_origin.get(),
+                        s.is_dragging.get(),
+                        !layer_blocked
+                            && !is_captured_elsewhere
+                            && s.hit_test(builder.input.pointer),
+                    )
+                })
+                .unwrap_or_default();
+
+        let left_click_count = builder.input.mouse_state.left_click_count;
         let is_left_down = builder.input.mouse_state.is_left_down();
         let just_pressed = is_left_down && !was_active;
         let just_released = !is_left_down && was_active;
 
-        let is_activated = match behavior {
-            ClickBehavior::OnPress => is_hovered && just_pressed,
-            ClickBehavior::OnRelease => is_hovered && just_released,
+        let is_activated = receives_presses
+            && match behavior {
+                ClickBehavior::OnPress => is_hovered && just_pressed,
+                ClickBehavior::OnRelease => is_hovered && just_released,
+            };
+        let handles_presses = interest.contains(StateFlags::PRESSED);
+        let is_activated = is_activated
+            || (handles_presses && was_focused && builder.context.navigation_activated)
+            || (handles_presses && builder.context.accessibility_click == Some(builder.id));
+
+        // Releases don't carry a click count, so the count of the press that
+        // is being released is remembered.
+        let click_count = match behavior {
+            ClickBehavior::OnPress => left_click_count,
+            ClickBehavior::OnRelease => press_count,
         };
 
+        let pointer = builder.input.pointer;
+        if is_hovered && just_pressed && receives_presses {
+            drag_origin = Some(pointer);
+        } else if !is_left_down {
+            drag_origin = None;
+        }
+
+        let is_dragging = drag_origin.is_some_and(|origin| {
+            was_dragging || (pointer - origin).length() >= builder.drag_threshold
+        });
+        let drag_delta = match drag_origin {
+            Some(origin) if is_dragging && !was_dragging => pointer - origin,
+            Some(_) if is_dragging => pointer - builder.input.prev_pointer,
+            _ => Vector2::default(),
+        };
+
+        if is_dragging && !was_dragging {
+            builder.context.drag_capture.set(Some(builder.id));
+        }
+
+        if let Some(state) = builder.prev_state() {
+            state.was_hovered.set(is_hovered);
+            state.drag_origin.set(drag_origin);
+            state.is_dragging.set(is_dragging);
+            if is_left_down {
+                state.press_count.set(left_click_count);
+            }
+        }
+
         let mut state = StateFlags::NORMAL;
         if is_hovered {
             state |= StateFlags::HOVERED & interest;
         }
-        if is_hovered && is_left_down {
+        if is_hovered && is_left_down && receives_presses {
             state |= StateFlags::PRESSED & interest;
         }
         if is_activated || ((is_hovered || !just_pressed) && was_focused) {
             state |= StateFlags::FOCUSED & interest;
         }
+        // Widgets that only handle presses can be focused by navigation, and
+        // lose focus to a press anywhere else.
+        if was_focused && handles_presses && !interest.contains(StateFlags::FOCUSED) {
+            if just_pressed && !is_hovered {
+                let focused = &builder.context.focused_widget;
+                if focused.get() == Some(builder.id) {
+                    focused.set(None);
+                }
+            } else if builder.context.is_focus_visible {
+                state |= StateFlags::FOCUSED;
+            }
+        }
+        if state.contains(StateFlags::FOCUSED) && builder.context.is_focus_visible {
+            state |= StateFlags::FOCUS_VISIBLE;
+        }
 
         (
             Self {
                 is_activated,
                 is_hovered,
                 is_focused: state.contains(StateFlags::FOCUSED),
+                hover_entered: is_hovered && !was_hovered,
+                hover_exited: !is_hovered && was_hovered,
+                double_clicked: is_activated && click_count == 2,
+                triple_clicked: is_activated && click_count == 3,
+                is_dragging,
+                drag_delta,
+                drag_started: is_dragging && !was_dragging,
+                drag_ended: was_dragging && !is_dragging,
             },
             state,
         )
@@ -192,16 +396,56 @@ pub struct WidgetState {
 
     /// Whether the widget was being actively pressed last frame
     pub was_active: bool,
+    /// Whether the widget was hovered when its interaction was last computed.
+    pub(crate) was_hovered: Cell<bool>,
+    /// The click count of the latest press of the left mouse button while
+    /// the widget's interaction was computed.
+    pub(crate) press_count: Cell<u8>,
+    /// Where the pointer was pressed on the widget, while the press lasts.
+    pub(crate) drag_origin: Cell<Option<Point2<Pixels>>>,
+    /// Whether the press has become a drag.
+    pub(crate) is_dragging: Cell<bool>,
     /// The z_layer of the node this widget occupied last frame. Used to determine
     /// hit-test priority when multiple layers are present.
     pub layer: u8,
     /// Whether this widget's overlay was modal last frame (blocks input to lower layers).
     pub is_modal: bool,
+    /// The part of `placement` that was not clipped away by an ancestor last
+    /// frame, e.g. by a scroll area. Only this part can be hovered.
+    pub visible_area: Rect<Pixels>,
 
     custom_data_size: u8,
+
+    /// How this widget handled pointer presses last frame, if it did.
+    pub(crate) pointer_phase: Option<PointerPhase>,
+    /// The draw order of this widget's node last frame, and of the last node
+    /// in its subtree on the same layer. Used to find the topmost widget under
+    /// the pointer.
+    pub(crate) draw_order: u16,
+    pub(crate) subtree_end: u16,
+
+    /// Where the widget could be hit last frame, set with
+    /// [`UiBuilder::hit_mask`].
+    pub(crate) hit_mask: Option<Arc<AlphaMask>>,
 }
 
 impl WidgetState {
+    /// Whether `point` was over the part of the widget that can be hovered
+    /// and pressed last frame.
+    pub fn hit_test(&self, point: Point2<Pixels>) -> bool {
+        if !self.visible_area.contains(&point) {
+            return false;
+        }
+
+        self.hit_mask.as_ref().is_none_or(|mask| {
+            let placement = self.placement;
+            mask.is_opaque_at(
+                (point.x - placement.origin.x) / placement.size.width,
+                (point.y - placement.origin.y) / placement.size.height,
+            )
+        })
+    }
+
     /// Copy a [Pod] value previously stored with [set_custom_data].
     ///
     /// Returns `None` if no custom data has been written or `size_of::<T>() > 8`.
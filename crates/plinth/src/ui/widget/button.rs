@@ -1,3 +1,5 @@
+use crate::shell::CursorIcon;
+use crate::ui::AccessRole;
 use crate::ui::StyleClass;
 use crate::ui::UiBuilder;
 use crate::ui::style::StateFlags;
@@ -27,6 +29,10 @@ impl Button<'_> {
 
         builder.apply_style(StyleClass::Button, state);
         builder.set_active(state.contains(StateFlags::PRESSED));
+        if interaction.is_hovered {
+            builder.set_cursor(CursorIcon::Pointer);
+        }
+        builder.accessible(AccessRole::Button, label.unwrap_or_default());
 
         if let Some(label_text) = label {
             builder.text(label_text, None);
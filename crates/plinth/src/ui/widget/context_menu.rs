@@ -0,0 +1,258 @@
+use crate::ui::AxisAnchor;
+use crate::ui::LayoutDirection;
+use crate::ui::OverlayPosition;
+use crate::ui::Position;
+use crate::ui::StyleClass;
+use crate::ui::UiBuilder;
+use crate::ui::WidgetId;
+use crate::ui::context::UiContext;
+use crate::ui::style::StateFlags;
+
+use super::ClickBehavior;
+use super::DropdownItem;
+use super::Interaction;
+use super::dropdown::MenuList;
+
+/// A popup menu attached to a widget, opened by right-clicking the widget or
+/// by pressing the Menu key or Shift+F10 while it has focus.
+///
+/// Only one context menu can be open in a window at a time. When several
+/// widgets with context menus are under the pointer, the one on the highest
+/// layer wins, then the smallest one, so nested widgets take priority over
+/// their containers regardless of the order they were built in.
+pub struct ContextMenu<'a> {
+    builder: Option<UiBuilder<'a>>,
+    menu: MenuList,
+}
+
+impl<'a> ContextMenu<'a> {
+    pub fn new(target: &'a mut UiBuilder<'_>, id: &str) -> Self {
+        let menu_id = target.id.then(id);
+
+        let is_open = target
+            .context
+            .context_menu
+            .as_ref()
+            .is_some_and(|menu| menu.menu_id == menu_id);
+
+        if !is_open {
+            request_open(target, menu_id);
+            return Self {
+                builder: None,
+                menu: MenuList::new(None, false, false),
+            };
+        }
+
+        let open = target.context.context_menu.as_mut().unwrap();
+        open.seen = true;
+        let anchor = open.anchor;
+        let highlighted_index = open.highlighted_index;
+        let keyboard_active = open.keyboard_active;
+
+        // The dismiss layer shares the panel's z_layer for the same reason as
+        // the dropdown's: a modal blocks strictly lower layers only.
+        let (dismiss_activated, dismiss_hovered) = {
+            let window_w = target.input.window_size.width;
+            let window_h = target.input.window_size.height;
+            let mut dismiss = target.modal_offset_child(
+                (id, "dismiss"),
+                Position::Absolute { x: 0.0, y: 0.0 },
+                2,
+            );
+            dismiss.size(window_w, window_h);
+            let (i, _) = Interaction::compute(
+                &dismiss,
+                ClickBehavior::OnPress,
+                StateFlags::HOVERED | StateFlags::PRESSED,
+            );
+            (i.is_activated, i.is_hovered)
+        };
+
+        let secondary_pressed = target.context.secondary_pressed;
+
+        let position = match anchor {
+            ContextMenuAnchor::Pointer { x, y } => Position::Absolute { x, y },
+            ContextMenuAnchor::Below => Position::OutOfFlow(OverlayPosition {
+                parent_x: AxisAnchor::Start,
+                parent_y: AxisAnchor::End,
+                self_x: AxisAnchor::Start,
+                self_y: AxisAnchor::Start,
+                offset: (0.0, 0.0),
+                flip_x: true,
+                flip_y: true,
+            }),
+        };
+
+        let mut panel = target.modal_offset_child((id, "panel"), position, 2);
+
+        let (panel_interaction, panel_state) = Interaction::compute(
+            &panel,
+            ClickBehavior::OnPress,
+            StateFlags::HOVERED | StateFlags::PRESSED,
+        );
+
+        panel.apply_style(StyleClass::DropdownMenu, panel_state);
+        panel.child_spacing(0.0);
+        panel.set_active(panel_state.contains(StateFlags::PRESSED));
+        panel.child_direction(LayoutDirection::Vertical);
+        panel.request_focus();
+
+        let close_requested = !panel_interaction.is_hovered
+            && (dismiss_activated || (dismiss_hovered && secondary_pressed));
+
+        Self {
+            builder: Some(panel),
+            menu: MenuList::new(highlighted_index, keyboard_active, close_requested),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.builder.is_some()
+    }
+
+    pub fn item<T>(&mut self, item: T) -> &mut Self
+    where
+        T: DropdownItem,
+    {
+        self.item_inner(&item)
+    }
+
+    /// Returns the index of the item chosen this frame, if any.
+    pub fn finish(mut self) -> Option<usize> {
+        let builder = self.builder.as_mut()?;
+
+        if builder.is_focused() && builder.input.is_context_menu_key_pressed() {
+            self.menu.close_requested = true;
+        }
+        self.menu.handle_keyboard_input(builder);
+
+        let context = &mut *builder.context;
+
+        if self.menu.close_requested {
+            close(context);
+        } else if let Some(menu) = context.context_menu.as_mut() {
+            menu.highlighted_index = self.menu.highlighted_index;
+            menu.keyboard_active = self.menu.keyboard_active;
+        }
+
+        self.menu.selected_index()
+    }
+
+    fn item_inner(&mut self, item: &dyn DropdownItem) -> &mut Self {
+        if let Some(builder) = self.builder.as_mut() {
+            let (mut child, _) = self.menu.item(builder);
+            item.build(&mut child);
+        }
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum ContextMenuAnchor {
+    /// Opened with the pointer; the menu's top-left corner sits at the
+    /// pointer position.
+    Pointer { x: f32, y: f32 },
+    /// Opened with the keyboard; the menu is placed below the widget.
+    Below,
+}
+
+pub(crate) struct OpenContextMenu {
+    menu_id: WidgetId,
+    anchor: ContextMenuAnchor,
+    /// The widget that had focus when the menu was opened. Focus returns to
+    /// it when the menu closes.
+    return_focus: Option<WidgetId>,
+    highlighted_index: Option<u32>,
+    keyboard_active: bool,
+    /// Whether the owning widget built the menu this frame. A menu whose
+    /// owner disappears is closed at the end of the frame.
+    pub(crate) seen: bool,
+}
+
+pub(crate) struct ContextMenuRequest {
+    menu_id: WidgetId,
+    anchor: ContextMenuAnchor,
+    return_focus: Option<WidgetId>,
+    layer: u8,
+    area: f32,
+}
+
+impl ContextMenuRequest {
+    fn takes_priority_over(&self, other: &Self) -> bool {
+        self.layer > other.layer || (self.layer == other.layer && self.area < other.area)
+    }
+}
+
+/// Closes menus whose owner was not built this frame and opens the winning
+/// request, if any. Called once per frame after all widgets have been built.
+pub(crate) fn resolve_context_menu(context: &mut UiContext) {
+    if context.context_menu.as_ref().is_some_and(|menu| !menu.seen) {
+        close(context);
+    }
+
+    if let Some(request) = context.context_menu_request.take() {
+        context.context_menu = Some(OpenContextMenu {
+            menu_id: request.menu_id,
+            anchor: request.anchor,
+            return_focus: request.return_focus,
+            highlighted_index: matches!(request.anchor, ContextMenuAnchor::Below).then_some(0),
+            keyboard_active: matches!(request.anchor, ContextMenuAnchor::Below),
+            seen: true,
+        });
+
+        // The menu is only built on the next frame, which would otherwise
+        // wait for the next input event.
+        context.needs_redraw = true;
+    }
+}
+
+fn close(context: &mut UiContext) {
+    if let Some(menu) = context.context_menu.take() {
+        context.focused_widget.set(menu.return_focus);
+        context.needs_redraw = true;
+    }
+}
+
+fn request_open(target: &mut UiBuilder<'_>, menu_id: WidgetId) {
+    if target.is_disabled {
+        return;
+    }
+
+    let pointer = target.input.pointer;
+
+    let anchor = if target.context.secondary_pressed
+        && Interaction::compute(target, ClickBehavior::OnPress, StateFlags::NORMAL)
+            .0
+            .is_hovered
+    {
+        ContextMenuAnchor::Pointer {
+            x: pointer.x,
+            y: pointer.y,
+        }
+    } else if target.is_focused() && target.input.is_context_menu_key_pressed() {
+        ContextMenuAnchor::Below
+    } else {
+        return;
+    };
+
+    let Some(placement) = target.prev_state().map(|s| s.placement) else {
+        return;
+    };
+
+    let request = ContextMenuRequest {
+        menu_id,
+        anchor,
+        return_focus: target.context.focused_widget.get(),
+        layer: target.layer,
+        area: placement.width() * placement.height(),
+    };
+
+    let context = &mut *target.context;
+    if context
+        .context_menu_request
+        .as_ref()
+        .is_none_or(|current| request.takes_priority_over(current))
+    {
+        context.context_menu_request = Some(request);
+    }
+}
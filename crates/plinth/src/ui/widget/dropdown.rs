@@ -5,6 +5,7 @@ use winit::keyboard::PhysicalKey;
 use crate::ui::AxisAnchor;
 use crate::ui::LayoutDirection;
 use crate::ui::OverlayPosition;
+use crate::ui::PixelSize;
 use crate::ui::Position;
 use crate::ui::Size;
 use crate::ui::StyleClass;
@@ -21,11 +22,7 @@ pub struct Dropdown<'a> {
     builder: Option<UiBuilder<'a>>,
     root_id: WidgetId,
     interaction: Interaction,
-    num_items: u32,
-    highlighted_index: Option<u32>,
-    keyboard_active: bool,
-    selected_index: Option<u32>,
-    close_requested: bool,
+    menu: MenuList,
 }
 
 impl<'a> Dropdown<'a> {
@@ -69,7 +66,7 @@ impl<'a> Dropdown<'a> {
             .map(|s| f32::to_bits(s.placement.width()))
             .unwrap_or(root_state.trigger_width_bits);
 
-        let is_open = was_open ^ interaction.is_activated;
+        let is_open = (was_open ^ interaction.is_activated) && button.is_enabled();
 
         if is_open {
             let button_style = button.theme().get(StyleClass::Button);
@@ -165,7 +162,9 @@ impl<'a> Dropdown<'a> {
                 num_child_widgets: 0,
                 is_modal: true,
                 layer: child_layer,
+                is_disabled: root.is_disabled,
                 text_overflow: TextOverflow::Clip,
+                drag_threshold: root.drag_threshold,
             })
         } else {
             root.context.state_mut(root_id).set_custom_data(RootState {
@@ -185,8 +184,10 @@ impl<'a> Dropdown<'a> {
             overlay_hovered = overlay_interaction.is_hovered;
 
             overlay.apply_style(StyleClass::DropdownMenu, overlay_state);
+            // The trigger's width is already in pixels, so it bypasses the
+            // length conversion in `width()`.
             if let Some(width) = trigger_width {
-                overlay.width(Size::Fixed(width));
+                overlay.context.ui_tree.atom_mut(overlay.index).width = PixelSize::Fixed(width);
             }
             overlay.child_spacing(0.0);
             overlay.set_active(overlay_state.contains(StateFlags::PRESSED));
@@ -201,18 +202,16 @@ impl<'a> Dropdown<'a> {
             .and_then(|o| o.prev_state())
             .and_then(|s| s.custom_data::<OverlayState>())
             .unwrap_or_default();
-        let highlighted_index = prev_overlay_state.highlighted_index();
-        let keyboard_active = prev_overlay_state.keyboard_active != 0;
 
         Self {
             builder: overlay,
             root_id,
             interaction,
-            num_items: 0,
-            highlighted_index,
-            keyboard_active,
-            selected_index: None,
-            close_requested: dismiss_activated && !overlay_hovered && !pointer_over_trigger,
+            menu: MenuList::new(
+                prev_overlay_state.highlighted_index(),
+                prev_overlay_state.keyboard_active != 0,
+                dismiss_activated && !overlay_hovered && !pointer_over_trigger,
+            ),
         }
     }
 
@@ -252,14 +251,10 @@ impl<'a> Dropdown<'a> {
     }
 
     pub fn finish(mut self) -> (Option<usize>, Interaction) {
-        if self.builder.is_none() {
-            return (self.selected_index.map(|i| i as usize), self.interaction);
-        }
-
-        self.handle_keyboard_input();
-
         if let Some(builder) = self.builder.as_mut() {
-            if self.close_requested {
+            self.menu.handle_keyboard_input(builder);
+
+            if self.menu.close_requested {
                 // Reset overlay state so the next open starts fresh, and flip root
                 // back to closed so was_open is false on the next frame.
                 builder
@@ -280,21 +275,71 @@ impl<'a> Dropdown<'a> {
                     .context
                     .state_mut(builder.id)
                     .set_custom_data(OverlayState {
-                        highlighted: self.highlighted_index.unwrap_or(OverlayState::NO_HIGHLIGHT),
-                        keyboard_active: self.keyboard_active as u32,
+                        highlighted: self
+                            .menu
+                            .highlighted_index
+                            .unwrap_or(OverlayState::NO_HIGHLIGHT),
+                        keyboard_active: self.menu.keyboard_active as u32,
                     });
             }
         }
 
-        (self.selected_index.map(|i| i as usize), self.interaction)
+        (self.menu.selected_index(), self.interaction)
     }
 
     fn item_inner(&mut self, callback: &dyn DropdownItem) -> &mut Self {
-        let item_index = self.num_items;
         let Some(builder) = self.builder.as_mut() else {
             return self;
         };
 
+        let (mut item, state) = self.menu.item(builder);
+
+        // Use the Button style's padding for item content so text alignment matches
+        // the trigger label, giving the open menu a visually consistent inset.
+        let button_padding = item.theme().get(StyleClass::Button).padding.get(state);
+        item.padding(button_padding);
+
+        callback.build(&mut item);
+        self
+    }
+}
+
+/// The items of an open dropdown or context menu, which can be highlighted
+/// with the pointer or the arrow keys.
+pub(super) struct MenuList {
+    num_items: u32,
+    pub highlighted_index: Option<u32>,
+    pub keyboard_active: bool,
+    selected_index: Option<u32>,
+    pub close_requested: bool,
+}
+
+impl MenuList {
+    pub fn new(
+        highlighted_index: Option<u32>,
+        keyboard_active: bool,
+        close_requested: bool,
+    ) -> Self {
+        Self {
+            num_items: 0,
+            highlighted_index,
+            keyboard_active,
+            selected_index: None,
+            close_requested,
+        }
+    }
+
+    /// Returns the index of the item chosen this frame, if any.
+    pub fn selected_index(&self) -> Option<usize> {
+        self.selected_index.map(|i| i as usize)
+    }
+
+    /// Adds the next item to `builder`, returning it with the state it was
+    /// styled with.
+    pub fn item<'b>(&mut self, builder: &'b mut UiBuilder<'_>) -> (UiBuilder<'b>, StateFlags) {
+        let item_index = self.num_items;
+        self.num_items += 1;
+
         let mouse_moved = {
             let input = builder.input();
             input.pointer != input.prev_pointer
@@ -326,42 +371,28 @@ impl<'a> Dropdown<'a> {
             effective_state |= StateFlags::HOVERED;
         }
 
-        // Use the Button style's padding for item content so text alignment matches
-        // the trigger label, giving the open menu a visually consistent inset.
-        let button_padding = item
-            .theme()
-            .get(StyleClass::Button)
-            .padding
-            .get(effective_state);
-
         item.apply_style(StyleClass::DropdownItem, effective_state);
         item.set_clip_children(true);
         item.set_active(item_state.contains(StateFlags::PRESSED));
-        item.padding(button_padding);
 
-        callback.build(&mut item);
-
-        self.num_items += 1;
-        self
+        (item, effective_state)
     }
 
-    fn handle_keyboard_input(&mut self) {
-        let Some(builder) = self.builder.as_ref() else {
-            return;
-        };
-
+    /// Moves the highlight with the arrow keys, chooses the highlighted item
+    /// with Enter and closes the menu with Escape, if the menu has focus.
+    pub fn handle_keyboard_input(&mut self, builder: &mut UiBuilder) {
         if !builder.is_focused() {
             return;
         }
 
-        let input = builder.input();
+        let input = builder.input;
 
-        for event in input.keyboard_events.iter() {
-            if !event.state.is_pressed() {
+        for (index, event) in input.keyboard_events.iter().enumerate() {
+            if !event.state.is_pressed() || builder.context.is_key_event_consumed(index) {
                 continue;
             }
 
-            match event.key {
+            let handled = match event.key {
                 PhysicalKey::Code(KeyCode::ArrowUp) if self.num_items > 0 => {
                     self.keyboard_active = true;
                     let next = match self.highlighted_index {
@@ -369,6 +400,7 @@ impl<'a> Dropdown<'a> {
                         None => self.num_items - 1,
                     };
                     self.highlighted_index = Some(next);
+                    true
                 }
                 PhysicalKey::Code(KeyCode::ArrowDown) if self.num_items > 0 => {
                     self.keyboard_active = true;
@@ -377,17 +409,27 @@ impl<'a> Dropdown<'a> {
                         None => 0,
                     };
                     self.highlighted_index = Some(next);
+                    true
                 }
                 PhysicalKey::Code(KeyCode::Enter) => {
-                    if let Some(idx) = self.highlighted_index {
-                        self.selected_index = Some(idx);
-                        self.close_requested = true;
+                    match self.highlighted_index.filter(|&i| i < self.num_items) {
+                        Some(idx) => {
+                            self.selected_index = Some(idx);
+                            self.close_requested = true;
+                            true
+                        }
+                        None => false,
                     }
                 }
                 PhysicalKey::Code(KeyCode::Escape) => {
                     self.close_requested = true;
+                    true
                 }
-                _ => {}
+                _ => false,
+            };
+
+            if handled {
+                builder.context.consume_key_event(index);
             }
         }
     }
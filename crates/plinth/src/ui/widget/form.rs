@@ -0,0 +1,156 @@
+use crate::ui::LayoutDirection;
+use crate::ui::StyleClass;
+use crate::ui::UiBuilder;
+use crate::ui::WidgetId;
+use crate::ui::style::StateFlags;
+
+use super::ClickBehavior;
+use super::Interaction;
+use super::macros::forward_properties;
+use super::macros::impl_container;
+
+/// A container of labelled fields that tracks whether each field is valid.
+///
+/// Fields are added with [`Form::field`], and the form is completed with
+/// [`Form::finish`], which adds a submit button that is disabled until every
+/// field is valid. Pressing the disabled button shows the fields' error
+/// messages and focuses the first invalid field instead of submitting.
+pub struct Form<'a> {
+    builder: UiBuilder<'a>,
+    show_errors: bool,
+    num_invalid: usize,
+    first_invalid: Option<WidgetId>,
+}
+
+impl<'a> Form<'a> {
+    pub fn new(builder: &'a mut UiBuilder<'_>, id: &str) -> Self {
+        let mut builder = builder.named_child(id);
+        builder.child_direction(LayoutDirection::Vertical);
+
+        let show_errors = builder
+            .prev_state()
+            .and_then(|s| s.custom_data::<FormState>())
+            .is_some_and(|s| s.show_errors != 0);
+
+        Self {
+            builder,
+            show_errors,
+            num_invalid: 0,
+            first_invalid: None,
+        }
+    }
+
+    forward_properties!(width, height, size, padding);
+
+    /// Adds a labelled field. `build` creates the field's input and returns
+    /// an error message if its current value is invalid.
+    ///
+    /// The first focusable widget created by `build` receives focus when the
+    /// form is submitted while this field is invalid. Returns whether the
+    /// field is valid.
+    pub fn field(
+        &mut self,
+        label: &str,
+        build: impl FnOnce(&mut UiBuilder) -> Result<(), String>,
+    ) -> bool {
+        let mut field = self.builder.child();
+        field.apply_style(StyleClass::FormField, StateFlags::NORMAL);
+
+        let mut label_node = field.named_child("label");
+        label_node.apply_style(StyleClass::Label, StateFlags::NORMAL);
+        label_node.text(label, None);
+
+        field.context.focus_candidate.set(None);
+        let result = build(&mut field);
+        let input = field.context.focus_candidate.take();
+
+        let Err(message) = result else {
+            return true;
+        };
+
+        self.num_invalid += 1;
+
+        if self.first_invalid.is_none() {
+            self.first_invalid = input;
+        }
+
+        if self.show_errors {
+            let mut error = field.named_child("error");
+            error.apply_style(StyleClass::FormError, StateFlags::INVALID);
+            error.text(&message, None);
+        }
+
+        false
+    }
+
+    /// Whether all fields added so far are valid.
+    pub fn is_valid(&self) -> bool {
+        self.num_invalid == 0
+    }
+
+    /// The number of fields added so far that are invalid.
+    pub fn num_invalid(&self) -> usize {
+        self.num_invalid
+    }
+
+    /// Adds the submit button. Returns `true` if the form was submitted this
+    /// frame, which only happens when all fields are valid.
+    pub fn finish(mut self, submit_label: &str) -> bool {
+        let is_valid = self.is_valid();
+
+        let mut button = self.builder.named_child("submit");
+
+        let (interaction, mut state) = Interaction::compute(
+            &button,
+            ClickBehavior::OnPress,
+            StateFlags::HOVERED | StateFlags::PRESSED,
+        );
+
+        button.set_active(state.contains(StateFlags::PRESSED));
+
+        // Still pressable so that it can point the user at the invalid fields.
+        if !is_valid {
+            state = StateFlags::DISABLED;
+        }
+
+        button.apply_style(StyleClass::Button, state);
+        button.text(submit_label, None);
+
+        let submitted = interaction.is_activated && is_valid;
+
+        let show_errors = if interaction.is_activated && !is_valid {
+            if let Some(first_invalid) = self.first_invalid {
+                self.builder.context.focused_widget.set(Some(first_invalid));
+            }
+
+            // The error messages are only added on the next frame.
+            self.builder.context.needs_redraw = true;
+            true
+        } else {
+            self.show_errors && !submitted
+        };
+
+        let id = self.builder.id;
+        self.builder
+            .context
+            .state_mut(id)
+            .set_custom_data(FormState {
+                show_errors: show_errors as u32,
+            });
+
+        submitted
+    }
+}
+
+impl_container!(Form<'a>);
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct FormState {
+    /// Set once the user tries to submit an invalid form, and cleared when
+    /// it is submitted.
+    show_errors: u32,
+}
+
+unsafe impl bytemuck::Pod for FormState {}
+unsafe impl bytemuck::Zeroable for FormState {}
@@ -1,7 +1,9 @@
 use crate::graphics::Color;
 use crate::graphics::GradientPaint;
+use crate::graphics::ImageHandle;
 use crate::graphics::Paint;
 use crate::graphics::Texture;
+use crate::ui::AccessRole;
 use crate::ui::Size;
 use crate::ui::StyleClass;
 use crate::ui::UiBuilder;
@@ -12,7 +14,9 @@ use crate::ui::style::StateFlags;
 pub struct Image<'a> {
     builder: UiBuilder<'a>,
 
-    texture: Texture,
+    /// `None` while an image is loading or if it failed to load.
+    texture: Option<Texture>,
+    placeholder: Option<Texture>,
     mask: Option<Texture>,
 
     border: Option<GradientPaint>,
@@ -25,12 +29,14 @@ impl<'a> Image<'a> {
         let mut builder = builder.child();
 
         builder.apply_style(StyleClass::Image, StateFlags::NORMAL);
+        builder.accessible(AccessRole::Image, "");
 
         builder.size(texture.size()[0] as f32, texture.size()[1] as f32);
 
         Self {
             builder,
-            texture: texture.clone(),
+            texture: Some(texture.clone()),
+            placeholder: None,
             mask: None,
             border: None,
             border_widths: None,
@@ -38,10 +44,40 @@ impl<'a> Image<'a> {
         }
     }
 
+    /// Displays an image that may still be loading.
+    ///
+    /// Until the image has loaded, the widget has no size of its own and
+    /// draws its placeholder, if any. Give it a size to keep the layout from
+    /// shifting when the image arrives.
+    pub fn from_handle(builder: &'a mut UiBuilder<'_>, image: &ImageHandle) -> Self {
+        match image.texture() {
+            Some(texture) => Self::new(builder, &texture),
+            None => {
+                let mut builder = builder.child();
+                builder.apply_style(StyleClass::Image, StateFlags::NORMAL);
+                builder.accessible(AccessRole::Image, "");
+
+                Self {
+                    builder,
+                    texture: None,
+                    placeholder: None,
+                    mask: None,
+                    border: None,
+                    border_widths: None,
+                    corner_radii: None,
+                }
+            }
+        }
+    }
+
+    /// Scales the image's natural size. Has no effect while the image is
+    /// loading.
     pub fn scale(&mut self, scale: f32) -> &mut Self {
-        let size = self.texture.size();
-        self.builder
-            .size(size[0] as f32 * scale, size[1] as f32 * scale);
+        if let Some(texture) = &self.texture {
+            let size = texture.size();
+            self.builder
+                .size(size[0] as f32 * scale, size[1] as f32 * scale);
+        }
         self
     }
 
@@ -80,6 +116,17 @@ impl<'a> Image<'a> {
         self
     }
 
+    /// Drawn in place of an image that is still loading or failed to load.
+    pub fn placeholder(&mut self, placeholder: Texture) -> &mut Self {
+        self.placeholder = Some(placeholder);
+        self
+    }
+
+    pub fn with_placeholder(mut self, placeholder: Texture) -> Self {
+        self.placeholder = Some(placeholder);
+        self
+    }
+
     pub fn mask(&mut self, mask: Texture) -> &mut Self {
         self.mask = Some(mask);
         self
@@ -113,10 +160,19 @@ impl<'a> Image<'a> {
     }
 
     pub fn finish(mut self) {
+        let color_texture = self.texture.take().or(self.placeholder.take());
+
+        // Without a texture the paint would be a solid rectangle.
+        let color_tint = if color_texture.is_some() {
+            Color::WHITE
+        } else {
+            Color::TRANSPARENT
+        };
+
         self.builder.paint(
             Paint::Sampled {
-                color_tint: Color::WHITE,
-                color_texture: Some(self.texture),
+                color_tint,
+                color_texture,
                 alpha_texture: self.mask.take(),
             },
             self.border
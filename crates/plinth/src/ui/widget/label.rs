@@ -1,23 +1,99 @@
 use crate::graphics::GradientPaint;
 use crate::graphics::Paint;
+use crate::graphics::TextSpan;
+use crate::shell::CursorIcon;
+use crate::ui::AccessRole;
 use crate::ui::StyleClass;
 use crate::ui::UiBuilder;
 use crate::ui::style::BorderWidths;
 use crate::ui::style::CornerRadii;
 use crate::ui::style::StateFlags;
 
+use super::ClickBehavior;
+use super::Interaction;
 use super::macros::forward_properties;
 
 pub struct Label<'a> {
     builder: UiBuilder<'a>,
+    link_clicked: Option<usize>,
 }
 
 impl<'a> Label<'a> {
     pub fn new(builder: &'a mut UiBuilder<'_>, text: &str) -> Self {
-        let mut builder = builder.child();
-        builder.apply_style(StyleClass::Label, StateFlags::NORMAL);
+        let mut builder = Self::styled_child(builder);
+        builder.accessible(AccessRole::Label, text);
         builder.text(text, None);
-        Self { builder }
+        Self {
+            builder,
+            link_clicked: None,
+        }
+    }
+
+    /// Creates a label whose text mixes styles, one per span. Spans that are
+    /// links are underlined while hovered, and report when they are clicked
+    /// through [`Self::link_clicked`].
+    pub fn rich(builder: &'a mut UiBuilder<'_>, spans: &[TextSpan]) -> Self {
+        let text = spans.iter().map(|span| span.text).collect::<String>();
+
+        if !spans.iter().any(|span| span.is_link) {
+            let mut builder = Self::styled_child(builder);
+            builder.accessible(AccessRole::Label, &text);
+            builder.rich_text(spans, None);
+            return Self {
+                builder,
+                link_clicked: None,
+            };
+        }
+
+        let mut builder = builder.child();
+        let (interaction, state) = Interaction::compute(
+            &builder,
+            ClickBehavior::OnRelease,
+            StateFlags::HOVERED | StateFlags::PRESSED,
+        );
+        builder.apply_style(StyleClass::Label, state);
+        builder.set_active(state.contains(StateFlags::PRESSED));
+        builder.accessible(AccessRole::Label, &text);
+
+        let hovered_link = interaction
+            .is_hovered
+            .then(|| builder.text_index_at_pointer())
+            .flatten()
+            .and_then(|index| {
+                let mut end = 0;
+                spans.iter().position(|span| {
+                    end += span.text.len();
+                    index < end
+                })
+            })
+            .filter(|&span| spans[span].is_link);
+
+        let mut spans = spans.to_vec();
+        if let Some(span) = hovered_link {
+            spans[span].style.underline = Some(true);
+            builder.set_cursor(CursorIcon::Pointer);
+        }
+        builder.rich_text(&spans, None);
+
+        Self {
+            builder,
+            link_clicked: hovered_link.filter(|_| interaction.is_activated),
+        }
+    }
+
+    fn styled_child(builder: &'a mut UiBuilder<'_>) -> UiBuilder<'a> {
+        let mut builder = builder.child();
+        let state = match builder.is_disabled {
+            true => StateFlags::DISABLED,
+            false => StateFlags::NORMAL,
+        };
+        builder.apply_style(StyleClass::Label, state);
+        builder
+    }
+
+    /// The index of the link span that was clicked this frame, if any.
+    pub fn link_clicked(&self) -> Option<usize> {
+        self.link_clicked
     }
 
     pub fn paint(
@@ -0,0 +1,340 @@
+use glamour::Contains;
+
+use crate::shell::Input;
+use crate::ui::Overflow;
+use crate::ui::StyleClass;
+use crate::ui::UiBuilder;
+use crate::ui::UiElementId;
+use crate::ui::WidgetId;
+use crate::ui::context::UiContext;
+use crate::ui::style::StateFlags;
+
+use super::macros::forward_properties;
+use super::macros::impl_container;
+
+/// The axes along which a scroll area scrolls, or along which linked scroll
+/// areas follow each other.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScrollAxes {
+    Horizontal,
+    #[default]
+    Vertical,
+    Both,
+}
+
+impl ScrollAxes {
+    fn has_x(self) -> bool {
+        matches!(self, ScrollAxes::Horizontal | ScrollAxes::Both)
+    }
+
+    fn has_y(self) -> bool {
+        matches!(self, ScrollAxes::Vertical | ScrollAxes::Both)
+    }
+}
+
+/// How a linked scroll area follows the other areas in its group.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScrollSync {
+    /// Scrolls by the same number of pixels, e.g. for a line-number gutter
+    /// next to the text it numbers.
+    #[default]
+    Offset,
+    /// Scrolls by the same fraction of its scrollable range, e.g. for a
+    /// minimap or for diff panes with content of different lengths.
+    Proportional,
+}
+
+/// An invisible container that clips its children and scrolls them with the
+/// mouse wheel.
+///
+/// A scroll area that fits its content has nothing to scroll, so give it a
+/// size or a maximum size.
+pub struct ScrollArea<'a> {
+    builder: UiBuilder<'a>,
+}
+
+impl<'a> ScrollArea<'a> {
+    pub fn new(builder: &'a mut UiBuilder<'_>) -> Self {
+        let style = builder.theme().get(StyleClass::Surface);
+
+        let major_alignment = style.child_major_alignment.get(StateFlags::NORMAL);
+        let minor_alignment = style.child_minor_alignment.get(StateFlags::NORMAL);
+        let spacing = style.child_spacing.get(StateFlags::NORMAL);
+        let direction = style.child_direction.get(StateFlags::NORMAL);
+
+        let mut child = builder.child();
+        child.child_alignment(major_alignment, minor_alignment);
+        child.child_spacing(spacing);
+        child.child_direction(direction);
+        child.overflow(Overflow::Scroll);
+
+        Self { builder: child }
+    }
+
+    forward_properties!(width, height, size, padding);
+
+    pub fn axes(&mut self, axes: ScrollAxes) -> &mut Self {
+        self.state().axes = axes;
+        self.update_offset();
+        self
+    }
+
+    pub fn with_axes(mut self, axes: ScrollAxes) -> Self {
+        self.axes(axes);
+        self
+    }
+
+    /// Links this scroll area to the other areas in `group`, so that scrolling
+    /// any of them scrolls the others along `axes`.
+    pub fn link(&mut self, group: &str, axes: ScrollAxes, sync: ScrollSync) -> &mut Self {
+        let group_id = WidgetId::new(group);
+
+        let context = &mut *self.builder.context;
+        let area = context.scroll_areas.get_mut(&self.builder.id).unwrap();
+        area.link = Some(ScrollLink { group: group_id });
+
+        // Until its first layout, an area doesn't know how far it can scroll.
+        let group = context.scroll_groups.entry(group_id).or_default();
+        if group.revision > area.seen_revision && area.is_measured {
+            area.follow(group, axes, sync);
+            area.seen_revision = group.revision;
+        }
+
+        self.update_offset();
+        self
+    }
+
+    pub fn with_link(mut self, group: &str, axes: ScrollAxes, sync: ScrollSync) -> Self {
+        self.link(group, axes, sync);
+        self
+    }
+
+    /// How far the content is scrolled, in pixels from its start.
+    pub fn offset(&self) -> (f32, f32) {
+        self.builder.context.scroll_areas[&self.builder.id].offset
+    }
+
+    fn state(&mut self) -> &mut ScrollAreaState {
+        self.builder
+            .context
+            .scroll_areas
+            .get_mut(&self.builder.id)
+            .unwrap()
+    }
+
+    fn update_offset(&mut self) {
+        let offset = self.state().visible_offset();
+        let index = self.builder.index;
+        self.builder.context.ui_tree.atom_mut(index).scroll_offset = offset;
+    }
+}
+
+/// Makes the builder's node scroll its children, see [`Overflow::Scroll`].
+pub(crate) fn scroll_overflow(builder: &mut UiBuilder) {
+    let frame = builder.context.frame_counter;
+    let area = builder.context.scroll_areas.entry(builder.id).or_default();
+    area.frame_last_used = frame;
+    area.node = builder.index;
+
+    let offset = area.visible_offset();
+    builder
+        .context
+        .ui_tree
+        .atom_mut(builder.index)
+        .scroll_offset = offset;
+}
+
+impl_container!(ScrollArea<'a>);
+
+#[derive(Default)]
+pub(crate) struct ScrollAreaState {
+    offset: (f32, f32),
+    /// The largest offset along each axis, as of the last layout.
+    max_offset: (f32, f32),
+    /// The size of the area, as of the last layout.
+    viewport: (f32, f32),
+    axes: ScrollAxes,
+    link: Option<ScrollLink>,
+    /// The revision of the linked group that this area last followed.
+    seen_revision: u64,
+    is_measured: bool,
+
+    node: UiElementId,
+    frame_last_used: u64,
+}
+
+impl ScrollAreaState {
+    /// The offset along the axes that the area scrolls.
+    fn visible_offset(&self) -> (f32, f32) {
+        let (x, y) = self.offset;
+        (
+            if self.axes.has_x() { x } else { 0.0 },
+            if self.axes.has_y() { y } else { 0.0 },
+        )
+    }
+
+    fn can_scroll(&self, dx: f32, dy: f32) -> bool {
+        (self.axes.has_x() && dx != 0.0 && self.max_offset.0 > 0.0)
+            || (self.axes.has_y() && dy != 0.0 && self.max_offset.1 > 0.0)
+    }
+
+    fn clamp(&mut self) {
+        self.offset.0 = self.offset.0.clamp(0.0, self.max_offset.0);
+        self.offset.1 = self.offset.1.clamp(0.0, self.max_offset.1);
+    }
+
+    fn follow(&mut self, group: &ScrollGroup, axes: ScrollAxes, sync: ScrollSync) {
+        let (x, y) = match sync {
+            ScrollSync::Offset => group.offset,
+            ScrollSync::Proportional => (
+                group.fraction.0 * self.max_offset.0,
+                group.fraction.1 * self.max_offset.1,
+            ),
+        };
+
+        if axes.has_x() {
+            self.offset.0 = x;
+        }
+        if axes.has_y() {
+            self.offset.1 = y;
+        }
+        self.clamp();
+    }
+}
+
+#[derive(Clone, Copy)]
+struct ScrollLink {
+    group: WidgetId,
+}
+
+/// The position most recently scrolled to by a member of a group of linked
+/// scroll areas.
+#[derive(Default)]
+pub(crate) struct ScrollGroup {
+    offset: (f32, f32),
+    /// The offset as a fraction of the scrolling member's range.
+    fraction: (f32, f32),
+    revision: u64,
+}
+
+/// Scrolls the innermost scroll area under the pointer that can scroll in
+/// the direction of the wheel. Called at the start of a frame, so that all
+/// areas linked to it follow within the same frame.
+pub(crate) fn apply_scroll_input(context: &mut UiContext, input: &Input) {
+    let (dx, dy) = (input.scroll_delta.x, input.scroll_delta.y);
+    if dx == 0.0 && dy == 0.0 {
+        return;
+    }
+
+    let target = context
+        .scroll_areas
+        .iter()
+        .filter(|(_, area)| area.can_scroll(dx, dy))
+        .filter_map(|(id, _)| {
+            let state = &context.widget_states.get(id)?.state;
+            let is_blocked = state.layer < context.active_pointer_layer
+                || context
+                    .input_block_layer
+                    .is_some_and(|layer| state.layer < layer);
+
+            (!is_blocked && state.visible_area.contains(&input.pointer))
+                .then_some((*id, (state.layer, state.draw_order)))
+        })
+        .max_by_key(|(_, order)| *order)
+        .map(|(id, _)| id);
+
+    let Some(area) = target.and_then(|id| context.scroll_areas.get_mut(&id)) else {
+        return;
+    };
+
+    let previous = area.offset;
+    if area.axes.has_x() {
+        area.offset.0 -= dx;
+    }
+    if area.axes.has_y() {
+        area.offset.1 -= dy;
+    }
+    area.clamp();
+
+    if area.offset == previous {
+        return;
+    }
+
+    if let Some(link) = area.link {
+        let group = context.scroll_groups.entry(link.group).or_default();
+        group.offset = area.offset;
+        group.fraction = (
+            fraction(area.offset.0, area.max_offset.0),
+            fraction(area.offset.1, area.max_offset.1),
+        );
+        group.revision += 1;
+        area.seen_revision = group.revision;
+    }
+}
+
+/// Measures the content of the scroll areas built this frame once layout is
+/// known, and drops the state of areas that weren't.
+pub(crate) fn update_scroll_areas(context: &mut UiContext) {
+    let frame = context.frame_counter;
+    context
+        .scroll_areas
+        .retain(|_, area| area.frame_last_used == frame);
+
+    for area in context.scroll_areas.values_mut() {
+        let (width, height) = context.ui_tree.content_size(area.node);
+        let viewport = context.ui_tree.result(area.node);
+
+        area.max_offset = (
+            (width - viewport.width).max(0.0),
+            (height - viewport.height).max(0.0),
+        );
+        area.viewport = (viewport.width, viewport.height);
+        area.is_measured = true;
+
+        // Content that shrank leaves the area scrolled past its end, which
+        // only shows after another frame.
+        let previous = area.offset;
+        area.clamp();
+        if area.offset != previous {
+            context.needs_redraw = true;
+        }
+    }
+
+    let areas = &context.scroll_areas;
+    context.scroll_groups.retain(|id, _| {
+        areas
+            .values()
+            .any(|area| area.link.is_some_and(|link| link.group == *id))
+    });
+}
+
+/// Scrolls the builder's scroll area vertically by as little as shows the
+/// content from `top` to `bottom`, measured from the start of its content.
+///
+/// The offset isn't limited by the area's last layout, since the content may
+/// have grown since, and is clamped after layout instead.
+pub(crate) fn scroll_y_into_view(builder: &mut UiBuilder, top: f32, bottom: f32) {
+    let Some(area) = builder.context.scroll_areas.get_mut(&builder.id) else {
+        return;
+    };
+    if !area.is_measured {
+        return;
+    }
+
+    let y = &mut area.offset.1;
+    if bottom > *y + area.viewport.1 {
+        *y = bottom - area.viewport.1;
+    }
+    if top < *y {
+        *y = top;
+    }
+    *y = y.max(0.0);
+
+    let offset = area.visible_offset();
+    let index = builder.index;
+    builder.context.ui_tree.atom_mut(index).scroll_offset = offset;
+}
+
+fn fraction(offset: f32, max: f32) -> f32 {
+    if max > 0.0 { offset / max } else { 0.0 }
+}
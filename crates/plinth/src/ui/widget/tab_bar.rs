@@ -0,0 +1,170 @@
+use crate::ui::AccessRole;
+use crate::ui::LayoutDirection;
+use crate::ui::StyleClass;
+use crate::ui::UiBuilder;
+use crate::ui::style::StateFlags;
+
+use super::ClickBehavior;
+use super::Interaction;
+use super::macros::forward_properties;
+
+/// How far, in pixels, the pointer must be dragged above or below the tab
+/// bar before the dragged tab is torn off.
+const TEAR_OFF_DISTANCE: f32 = 24.0;
+
+/// What happened to a [`TabBar`] this frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TabBarResponse {
+    /// The index of the tab that was clicked, if any.
+    pub selected: Option<usize>,
+    /// A tab was dragged from the first index to the second. Remove the tab
+    /// at the first index and insert it at the second to match.
+    pub moved: Option<(usize, usize)>,
+    /// A tab was dragged out of the bar. It is up to the caller to remove it
+    /// and show it somewhere else, e.g. in a new window.
+    pub torn_off: Option<usize>,
+}
+
+/// A row of tabs that can be reordered by dragging them, and torn off by
+/// dragging them away from the row.
+///
+/// The bar doesn't own its tabs: it reports clicks, moves and tear-offs, and
+/// the caller updates its list of tabs to match before the next frame.
+pub struct TabBar<'a> {
+    builder: UiBuilder<'a>,
+    selected: usize,
+    num_tabs: usize,
+    drag: DragState,
+    response: TabBarResponse,
+}
+
+impl<'a> TabBar<'a> {
+    pub fn new(builder: &'a mut UiBuilder<'_>, id: &str, selected: usize) -> Self {
+        let mut builder = builder.named_child(id);
+        builder.child_direction(LayoutDirection::Horizontal);
+        builder.child_spacing(0.0);
+
+        let mut drag = builder
+            .prev_state()
+            .and_then(|s| s.custom_data::<DragState>())
+            .unwrap_or_default();
+
+        if !builder.input().mouse_state.is_left_down() || !builder.is_enabled() {
+            drag = DragState::default();
+        }
+
+        let mut response = TabBarResponse::default();
+
+        // Tearing off ends the drag, so that the tab isn't also moved once the
+        // pointer comes back to the bar.
+        if let Some(tab) = drag.tab()
+            && let Some(bar) = builder.prev_state()
+        {
+            let y = builder.input().pointer.y;
+            let placement = bar.placement;
+
+            if y < placement.origin.y - TEAR_OFF_DISTANCE
+                || y > placement.origin.y + placement.size.height + TEAR_OFF_DISTANCE
+            {
+                response.torn_off = Some(tab);
+                drag = DragState::default();
+            }
+        }
+
+        Self {
+            builder,
+            selected,
+            num_tabs: 0,
+            drag,
+            response,
+        }
+    }
+
+    forward_properties!(width, height, size, padding);
+
+    pub fn tab(&mut self, label: &str) -> &mut Self {
+        let index = self.num_tabs;
+        self.num_tabs += 1;
+
+        // Tabs are named by position rather than by label, so that a tab
+        // moved by dragging takes over the placement of the one it replaced.
+        let mut tab = self.builder.named_child(index);
+
+        let (interaction, mut state) = Interaction::compute(
+            &tab,
+            ClickBehavior::OnPress,
+            StateFlags::HOVERED | StateFlags::PRESSED,
+        );
+
+        if interaction.is_activated {
+            self.response.selected = Some(index);
+            self.drag = DragState { tab: index as u32 };
+        }
+
+        if let Some(dragged) = self.drag.tab()
+            && dragged != index
+            && self.response.moved.is_none()
+        {
+            let pointer_x = tab.input().pointer.x;
+            let is_over = tab.prev_state().is_some_and(|s| {
+                let placement = s.placement;
+                pointer_x >= placement.origin.x
+                    && pointer_x < placement.origin.x + placement.size.width
+            });
+
+            if is_over {
+                self.response.moved = Some((dragged, index));
+                self.drag = DragState { tab: index as u32 };
+            }
+        }
+
+        if self.response.selected.unwrap_or(self.selected) == index {
+            state |= StateFlags::SELECTED;
+        }
+
+        tab.apply_style(StyleClass::Button, state);
+        tab.set_active(state.contains(StateFlags::PRESSED));
+        tab.accessible(AccessRole::Tab, label);
+        tab.text(label, None);
+
+        self
+    }
+
+    pub fn with_tab(mut self, label: &str) -> Self {
+        self.tab(label);
+        self
+    }
+
+    pub fn finish(self) -> TabBarResponse {
+        self.builder
+            .context
+            .state_mut(self.builder.id)
+            .set_custom_data(self.drag);
+
+        self.response
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct DragState {
+    /// The index of the tab being dragged, or `NO_TAB`.
+    tab: u32,
+}
+
+impl DragState {
+    const NO_TAB: u32 = u32::MAX;
+
+    fn tab(self) -> Option<usize> {
+        (self.tab != Self::NO_TAB).then_some(self.tab as usize)
+    }
+}
+
+impl Default for DragState {
+    fn default() -> Self {
+        Self { tab: Self::NO_TAB }
+    }
+}
+
+unsafe impl bytemuck::Pod for DragState {}
+unsafe impl bytemuck::Zeroable for DragState {}
@@ -1,26 +1,35 @@
 use std::cell::Cell;
 use std::cell::RefCell;
+use std::ops::Range;
 use std::rc::Rc;
 
 use glamour::Point2;
 use glamour::Rect;
 use glamour::Size2;
 use parley::PlainEditor;
+use parley::PlainEditorDriver;
 use winit::keyboard::KeyCode;
 use winit::keyboard::PhysicalKey;
 
 use crate::graphics::Canvas;
 use crate::graphics::ClipRect;
 use crate::graphics::Color;
-use crate::graphics::FontStack;
+use crate::graphics::FontFamily;
 use crate::graphics::GradientPaint;
 use crate::graphics::Paint;
 use crate::graphics::Primitive;
 use crate::graphics::TextAlignment;
 use crate::graphics::TextLayoutContext;
+use crate::graphics::Texture;
+use crate::shell::CursorIcon;
+use crate::shell::ImeEvent;
 use crate::shell::Input;
+use crate::ui::AccessRole;
 use crate::ui::Atom;
+use crate::ui::Length;
 use crate::ui::NodeLayout;
+use crate::ui::Overflow;
+use crate::ui::PixelSize;
 use crate::ui::Pixels;
 use crate::ui::Size;
 use crate::ui::builder::UiBuilder;
@@ -31,12 +40,16 @@ use crate::ui::style::BorderWidths;
 use crate::ui::style::CornerRadii;
 use crate::ui::style::StateFlags;
 use crate::ui::style::Style;
+use crate::ui::style::StyleProperty;
+use crate::ui::style::TextDecoration;
 use crate::ui::theme::StyleClass;
+use crate::ui::theme::decoration_offset;
 use crate::ui::theme::default_font_features;
 use crate::ui::widget::ClickBehavior;
 use crate::ui::widget::Interaction;
 
 use super::macros::forward_properties;
+use super::scroll_area::scroll_y_into_view;
 
 pub trait EditableTextBuffer {
     type Layout<'a>: EditableTextLayout
@@ -45,9 +58,51 @@ pub trait EditableTextBuffer {
 
     fn is_empty(&self) -> bool;
 
+    /// The number of characters in the buffer, for character counters.
+    fn char_count(&self) -> usize {
+        self.text().chars().count()
+    }
+
+    /// The buffer's text, including any text being composed.
+    fn text(&self) -> &str;
+
     fn selected_text(&self) -> Option<&str>;
 
-    fn apply_style(&mut self, style: &Style, state: StateFlags);
+    /// The selected range of bytes, which is empty at the cursor when nothing
+    /// is selected.
+    fn selection_range(&self) -> Range<usize>;
+
+    /// Whether an input method is composing text into the buffer.
+    fn is_composing(&self) -> bool {
+        false
+    }
+
+    /// Replaces the text being composed by an input method, or ends
+    /// composition without entering anything if `text` is empty. `cursor` is
+    /// a byte range in `text`.
+    fn set_composition(
+        &mut self,
+        context: &mut TextLayoutContext,
+        text: &str,
+        cursor: Option<Range<usize>>,
+    );
+
+    /// Whether the text is displayed as bullets, see [`TextEdit::masked`].
+    fn is_masked(&self) -> bool;
+
+    fn set_masked(&mut self, context: &mut TextLayoutContext, masked: bool);
+
+    /// Applies the text properties of `style`, except for its font size,
+    /// which is given in pixels. `fallbacks`
+    /// follow the style's font stack, see
+    /// [`Theme::font_fallbacks`](crate::ui::Theme::font_fallbacks).
+    fn apply_style(
+        &mut self,
+        style: &Style,
+        state: StateFlags,
+        font_size: f32,
+        fallbacks: &[FontFamily],
+    );
 
     fn enter_text(&mut self, context: &mut TextLayoutContext, text: &str);
 
@@ -100,7 +155,10 @@ pub enum TextEditMotion {
     SelectDown,
     SelectLeft,
     SelectWordLeft,
+    SelectLine,
     SelectLineAtPoint(Point2<Pixels>),
+    SelectParagraph,
+    SelectParagraphAtPoint(Point2<Pixels>),
     SelectRight,
     SelectWordRight,
     SelectToLineEnd,
@@ -108,9 +166,138 @@ pub enum TextEditMotion {
     SelectToTextEnd,
     SelectToTextStart,
     SelectUp,
+    SelectWord,
     SelectWordAtPoint(Point2<Pixels>),
 }
 
+/// An amount of text that can be selected at once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectionGranularity {
+    Word,
+    /// A line as laid out, which ends where the text wraps.
+    Line,
+    /// Text between line breaks.
+    Paragraph,
+    All,
+}
+
+impl SelectionGranularity {
+    fn select_at_point(self, point: Point2<Pixels>) -> TextEditMotion {
+        match self {
+            Self::Word => TextEditMotion::SelectWordAtPoint(point),
+            Self::Line => TextEditMotion::SelectLineAtPoint(point),
+            Self::Paragraph => TextEditMotion::SelectParagraphAtPoint(point),
+            Self::All => TextEditMotion::SelectAll,
+        }
+    }
+
+    fn select_at_cursor(self) -> TextEditMotion {
+        match self {
+            Self::Word => TextEditMotion::SelectWord,
+            Self::Line => TextEditMotion::SelectLine,
+            Self::Paragraph => TextEditMotion::SelectParagraph,
+            Self::All => TextEditMotion::SelectAll,
+        }
+    }
+}
+
+/// What a [`TextEdit`] selects on repeated clicks and on Ctrl+A.
+#[derive(Clone, Copy, Debug)]
+pub struct SelectionBehavior {
+    pub double_click: SelectionGranularity,
+    pub triple_click: SelectionGranularity,
+    /// Pressing Ctrl+A again once this is selected selects all of the text.
+    pub select_all: SelectionGranularity,
+}
+
+impl Default for SelectionBehavior {
+    fn default() -> Self {
+        Self {
+            double_click: SelectionGranularity::Word,
+            triple_click: SelectionGranularity::Line,
+            select_all: SelectionGranularity::All,
+        }
+    }
+}
+
+/// The separators of numbers in a locale, for numeric text edits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub decimal_separator: char,
+    /// Grouping characters are ignored wherever they are typed. Spaces are
+    /// always ignored, since many locales group digits with them.
+    pub grouping_separator: char,
+}
+
+impl NumberFormat {
+    pub fn new(decimal_separator: char, grouping_separator: char) -> Self {
+        Self {
+            decimal_separator,
+            grouping_separator,
+        }
+    }
+
+    /// Whether `c` may be typed into a numeric text edit.
+    pub fn accepts(&self, c: char) -> bool {
+        normalize_digit(c).is_ascii_digit()
+            || matches!(c, '-' | '+' | '\u{2212}')
+            || c == self.decimal_separator
+            || self.is_grouping(c)
+    }
+
+    /// Parses `text` as a number, ignoring grouping characters. Returns `None`
+    /// if the text is empty or isn't a number.
+    pub fn parse(&self, text: &str) -> Option<f64> {
+        let mut normalized = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                c if self.is_grouping(c) => {}
+                c if c == self.decimal_separator => normalized.push('.'),
+                '-' | '\u{2212}' if normalized.is_empty() => normalized.push('-'),
+                '+' if normalized.is_empty() => {}
+                c if normalize_digit(c).is_ascii_digit() => normalized.push(normalize_digit(c)),
+                _ => return None,
+            }
+        }
+
+        // Rust accepts "1." and ".5", but not a lone separator or sign.
+        if !normalized.contains(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+
+        normalized.parse().ok()
+    }
+
+    fn is_grouping(&self, c: char) -> bool {
+        c == self.grouping_separator || c.is_whitespace()
+    }
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self::new('.', ',')
+    }
+}
+
+/// Maps full-width digits, which input methods for CJK languages produce,
+/// to ASCII.
+fn normalize_digit(c: char) -> char {
+    match c {
+        '\u{FF10}'..='\u{FF19}' => char::from(b'0' + (c as u32 - 0xFF10) as u8),
+        c => c,
+    }
+}
+
+/// The contents of a numeric [`TextEdit`] after this frame's input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NumericEntry {
+    /// The text as typed, including grouping characters.
+    pub text: String,
+    /// The parsed value, or `None` if the text isn't a number or an input
+    /// method is still composing it.
+    pub value: Option<f64>,
+}
+
 pub struct TextEditorState<T: EditableTextBuffer> {
     content: Rc<TextEditorContent<T>>,
 }
@@ -121,6 +308,8 @@ impl<T: EditableTextBuffer> TextEditorState<T> {
             content: Rc::new(TextEditorContent {
                 buffer: RefCell::new(buffer),
                 applied_style: Cell::new(None),
+                placeholder: RefCell::new(None),
+                is_valid: Cell::new(true),
                 #[cfg(debug_assertions)]
                 frame_last_used: Cell::new(None),
             }),
@@ -136,6 +325,13 @@ impl<T: EditableTextBuffer> TextEditorState<T> {
         let mut buffer = self.content.buffer.borrow_mut();
         callback(&mut buffer)
     }
+
+    /// Whether the text passed the validator given to
+    /// [`TextEdit::validate`] when the text edit was last finished. Always
+    /// `true` without a validator.
+    pub fn is_valid(&self) -> bool {
+        self.content.is_valid.get()
+    }
 }
 
 pub type PlainTextEditorState = TextEditorState<PlainTextBuffer>;
@@ -146,11 +342,11 @@ impl TextEditorState<PlainTextBuffer> {
     }
 
     pub fn set_text(&self, text: &str) {
-        self.with_buffer_mut(|buffer| buffer.editor.set_text(text));
+        self.with_buffer_mut(|buffer| buffer.set_text(text));
     }
 
     pub fn with_raw_text<R>(&self, callback: impl FnOnce(&str) -> R) -> R {
-        self.with_buffer(|buffer| callback(buffer.editor.raw_text()))
+        self.with_buffer(|buffer| callback(buffer.text()))
     }
 
     pub fn is_composing(&self) -> bool {
@@ -160,14 +356,26 @@ impl TextEditorState<PlainTextBuffer> {
 
 struct TextEditorContent<T: EditableTextBuffer> {
     buffer: RefCell<T>,
-    // The (theme revision, state flags) the buffer styles were last resolved
-    // from. Reapplying styles marks the text layout dirty, so it must be
-    // skipped when nothing changed.
-    applied_style: Cell<Option<(u64, StateFlags)>>,
+    // The (theme revision, state flags, scale factor bits) the buffer styles
+    // were last resolved from. Reapplying styles marks the text layout dirty,
+    // so it must be skipped when nothing changed.
+    applied_style: Cell<Option<StyleKey>>,
+    placeholder: RefCell<Option<Placeholder>>,
+    is_valid: Cell<bool>,
     #[cfg(debug_assertions)]
     frame_last_used: Cell<Option<u64>>,
 }
 
+/// Text drawn in place of the buffer's contents while it is empty.
+struct Placeholder {
+    text: String,
+    // The key the layout was built with, or `None` if the text changed since.
+    style_key: Option<StyleKey>,
+    layout: parley::Layout<Color>,
+}
+
+type StyleKey = (u64, StateFlags);
+
 impl<T: EditableTextBuffer> TextEditorContent<T> {
     fn check_frame_use(&self, frame_counter: u64) {
         #[cfg(debug_assertions)]
@@ -195,9 +403,25 @@ impl<T: EditableTextBuffer> EditableTextContent for TextEditorContent<T> {
         max_width: f32,
         alignment: TextAlignment,
     ) -> Option<f32> {
-        self.buffer
+        let height = self
+            .buffer
             .borrow_mut()
-            .measure(text_context, max_width, alignment)
+            .measure(text_context, max_width, alignment);
+
+        // The placeholder only needs to fit while it's shown, but sizing for
+        // it regardless keeps the box from jumping when the first character
+        // is typed.
+        match self.placeholder.borrow_mut().as_mut() {
+            Some(placeholder) => {
+                placeholder.layout.break_all_lines(Some(max_width));
+                placeholder
+                    .layout
+                    .align(alignment.into(), Default::default());
+                let placeholder_height = placeholder.layout.height();
+                Some(height.map_or(placeholder_height, |h| h.max(placeholder_height)))
+            }
+            None => height,
+        }
     }
 
     fn draw(
@@ -210,6 +434,15 @@ impl<T: EditableTextBuffer> EditableTextContent for TextEditorContent<T> {
         let mut buffer = self.buffer.borrow_mut();
         let clip = layout.effective_clip;
 
+        if buffer.is_empty()
+            && visuals.show_placeholder
+            && let Some(placeholder) = self.placeholder.borrow().as_ref()
+        {
+            canvas.draw_text_layout(&placeholder.layout, [layout.x, layout.y], clip);
+        }
+
+        let has_overline = visuals.has_overline;
+
         buffer.with_layouts(text_context, |text_layout| {
             let offset = text_layout.offset();
             let x = layout.x + offset.x;
@@ -220,22 +453,68 @@ impl<T: EditableTextBuffer> EditableTextContent for TextEditorContent<T> {
             });
 
             if let Some(mut rect) = text_layout.cursor_geometry(visuals.cursor_size) {
-                // Draw the caret as a 2px-wide bar regardless of the width
-                // reported by the layout.
-                rect.size.width = 2.0;
+                // Draw the caret with the style's width regardless of the
+                // width reported by the layout.
+                rect.size.width = visuals.cursor_width;
                 fill_snapped_rect(canvas, &rect, visuals.cursor_color, x, y, clip);
             }
 
             canvas.draw_text_layout(text_layout.layout(), [x, y], clip);
+            if has_overline {
+                canvas.draw_text_overlines(text_layout.layout(), [x, y], clip);
+            }
         });
     }
 }
 
+type TextFilter<'a> = Box<dyn Fn(&str) -> String + 'a>;
+type TextValidator<'a> = Box<dyn Fn(&str) -> bool + 'a>;
+
 pub struct TextEdit<'a, T: EditableTextBuffer> {
     builder: UiBuilder<'a>,
     interaction: Interaction,
     state_flags: StateFlags,
     state: &'a TextEditorState<T>,
+
+    leading_icon: Option<Texture>,
+    trailing_icon: Option<Texture>,
+    show_clear_button: bool,
+    counter: Option<CharacterCounter>,
+    selection_behavior: SelectionBehavior,
+    number_format: Option<NumberFormat>,
+    max_length: Option<usize>,
+    filter: Option<TextFilter<'a>>,
+    validator: Option<TextValidator<'a>>,
+    multi_line: Option<MultiLine>,
+}
+
+/// The key that starts a new line in a multi-line [`TextEdit`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NewlineKey {
+    /// Enter starts a new line, with or without Shift.
+    #[default]
+    Enter,
+    /// Only Shift+Enter starts a new line, leaving Enter to the app, e.g. to
+    /// send a chat message.
+    ShiftEnter,
+}
+
+#[derive(Clone, Copy)]
+struct MultiLine {
+    max_height: Length,
+    newline_key: NewlineKey,
+}
+
+#[derive(Clone, Copy)]
+struct CharacterCounter {
+    limit: Option<usize>,
+}
+
+#[derive(Clone, Copy)]
+enum ClipboardCommand {
+    Cut,
+    Copy,
+    Paste,
 }
 
 impl<'a, T: EditableTextBuffer + 'static> TextEdit<'a, T> {
@@ -249,33 +528,44 @@ impl<'a, T: EditableTextBuffer + 'static> TextEdit<'a, T> {
         );
 
         builder.set_active(state_flags.contains(StateFlags::PRESSED));
+        if interaction.is_hovered {
+            builder.set_cursor(CursorIcon::Text);
+        }
+        builder.accessible(AccessRole::TextInput, "");
 
         // Apply styles early as defaults, so that users have opportunity to
         // override them before calling `finish()`.
         builder.apply_style(StyleClass::TextEdit, state_flags);
 
-        let min_height = {
-            let style = builder.theme.get(StyleClass::TextEdit);
-
-            let text_height = style.font_size.get(state_flags) as f32;
-            let padding = style.padding.get(state_flags);
-
-            text_height + padding.top + padding.bottom
-        };
+        // Edits fill the width of their parent unless the theme sizes them.
+        if !builder.theme.sets_property(builder.style_id, |property| {
+            matches!(property, StyleProperty::Width(_))
+        }) {
+            builder.width(Size::Grow);
+        }
 
-        builder.size(
-            Size::Grow,
-            Size::Fit {
-                min: min_height,
-                max: f32::MAX,
-            },
-        );
+        // An empty edit still measures as tall as a line of text, unless the
+        // style asks for more.
+        let font_size = builder.theme.font_size(builder.style_id, state_flags);
+        let atom = builder.context.ui_tree.atom_mut(builder.index);
+        let min_height = font_size + atom.inner_padding.top + atom.inner_padding.bottom;
+        atom.height = atom.height.constrain(min_height, f32::MAX);
 
         Self {
             builder,
             interaction,
             state_flags,
             state,
+            leading_icon: None,
+            trailing_icon: None,
+            show_clear_button: false,
+            counter: None,
+            selection_behavior: SelectionBehavior::default(),
+            number_format: None,
+            max_length: None,
+            filter: None,
+            validator: None,
+            multi_line: None,
         }
     }
 
@@ -305,6 +595,131 @@ impl<'a, T: EditableTextBuffer + 'static> TextEdit<'a, T> {
         self
     }
 
+    /// Shows dimmed text while the buffer is empty and the text edit isn't
+    /// focused, styled by [`StyleClass::TextEditPlaceholder`].
+    pub fn placeholder(self, text: &str) -> Self {
+        let mut placeholder = self.state.content.placeholder.borrow_mut();
+
+        match placeholder.as_mut() {
+            Some(placeholder) if placeholder.text == text => {}
+            Some(placeholder) => {
+                placeholder.text.clear();
+                placeholder.text.push_str(text);
+                placeholder.style_key = None;
+            }
+            None => {
+                *placeholder = Some(Placeholder {
+                    text: text.to_string(),
+                    style_key: None,
+                    layout: parley::Layout::new(),
+                });
+            }
+        }
+
+        drop(placeholder);
+        self
+    }
+
+    /// Shows an icon before the text, sized by [`StyleClass::TextEditIcon`].
+    pub fn leading_icon(mut self, icon: &Texture) -> Self {
+        self.leading_icon = Some(icon.clone());
+        self
+    }
+
+    /// Shows an icon after the text, sized by [`StyleClass::TextEditIcon`].
+    pub fn trailing_icon(mut self, icon: &Texture) -> Self {
+        self.trailing_icon = Some(icon.clone());
+        self
+    }
+
+    /// Shows a button that clears the text while there is any.
+    pub fn clear_button(mut self) -> Self {
+        self.show_clear_button = true;
+        self
+    }
+
+    /// Shows the number of characters entered, and the limit if there is one.
+    /// The limit is only displayed, not enforced.
+    pub fn character_counter(mut self, limit: Option<usize>) -> Self {
+        self.counter = Some(CharacterCounter { limit });
+        self
+    }
+
+    pub fn selection_behavior(mut self, behavior: SelectionBehavior) -> Self {
+        self.selection_behavior = behavior;
+        self
+    }
+
+    /// Accepts line breaks, and grows with its text up to `max_height`, after
+    /// which the text scrolls. Otherwise, Enter is left to the app and pasted
+    /// line breaks are replaced with spaces.
+    pub fn multi_line(mut self, max_height: impl Into<Length>) -> Self {
+        self.multi_line = Some(MultiLine {
+            max_height: max_height.into(),
+            newline_key: NewlineKey::default(),
+        });
+        self
+    }
+
+    /// Sets the key that starts a new line in a multi-line text edit.
+    pub fn newline_key(mut self, key: NewlineKey) -> Self {
+        if let Some(multi_line) = &mut self.multi_line {
+            multi_line.newline_key = key;
+        }
+        self
+    }
+
+    /// Displays each character as a bullet, e.g. for passwords. The text is
+    /// kept intact, but can't be copied or cut while masked.
+    pub fn masked(self, masked: bool) -> Self {
+        self.state
+            .content
+            .buffer
+            .borrow_mut()
+            .set_masked(self.builder.text_context, masked);
+        self
+    }
+
+    /// Only accepts characters that can be part of a number in `format`.
+    /// Text composed by an input method is filtered once it is committed.
+    pub fn numeric(mut self, format: NumberFormat) -> Self {
+        self.number_format = Some(format);
+        self
+    }
+
+    /// Limits the text to `max` characters, truncating entered text that
+    /// would exceed it.
+    pub fn max_length(mut self, max: usize) -> Self {
+        self.max_length = Some(max);
+        self
+    }
+
+    /// Transforms entered text before it is inserted, e.g. to uppercase it.
+    /// Returning an empty string rejects the text. Text composed by an input
+    /// method is filtered once it is committed.
+    pub fn filter(mut self, filter: impl Fn(&str) -> String + 'a) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Checks the whole text after each edit, styling the text edit with
+    /// [`StateFlags::INVALID`] while `validator` returns `false`. The result
+    /// is available from [`TextEditorState::is_valid`].
+    pub fn validate(mut self, validator: impl Fn(&str) -> bool + 'a) -> Self {
+        let is_valid = self.state.with_buffer(|buffer| validator(buffer.text()));
+        self.validator = Some(Box::new(validator));
+
+        // Restyled here rather than in `finish()` so that overrides made
+        // after this call aren't replaced.
+        if !is_valid {
+            self.state_flags |= StateFlags::INVALID;
+            self.builder
+                .apply_style(StyleClass::TextEdit, self.state_flags);
+        }
+
+        self
+    }
+
     pub fn paint(
         &mut self,
         paint: Paint,
@@ -317,7 +732,9 @@ impl<'a, T: EditableTextBuffer + 'static> TextEdit<'a, T> {
         self
     }
 
-    pub fn finish(mut self) -> Interaction {
+    /// Returns the selected range of bytes along with the interaction, see
+    /// [`EditableTextBuffer::selection_range`].
+    pub fn finish(mut self) -> (Range<usize>, Interaction) {
         self.state
             .content
             .check_frame_use(self.builder.context.frame_counter);
@@ -333,17 +750,54 @@ impl<'a, T: EditableTextBuffer + 'static> TextEdit<'a, T> {
         let theme = self.builder.theme;
 
         let input = self.builder.input().clone();
-        let placement = self.builder.prev_state().map(|s| s.placement);
+
+        // Pointer positions are mapped into the text node rather than the
+        // whole box, so that padding and icons don't offset them.
+        let text_id = self.builder.id.then("text");
+        let placement = self
+            .builder
+            .context
+            .widget_states
+            .get(&text_id)
+            .map(|container| container.state.placement);
 
         let mut buffer = self.state.content.buffer.borrow_mut();
         let style = theme.get(StyleClass::TextEdit);
+        let font_size = theme.font_size(theme.get_id(StyleClass::TextEdit), self.state_flags);
 
         let style_key = (theme.revision(), self.state_flags);
-        if self.state.content.applied_style.get() != Some(style_key) {
+        let is_style_applied = self.state.content.applied_style.get() == Some(style_key);
+        self.builder.context.style_cache.record(is_style_applied);
+        if !is_style_applied {
             self.state.content.applied_style.set(Some(style_key));
-            buffer.apply_style(style, self.state_flags);
+            buffer.apply_style(style, self.state_flags, font_size, theme.font_fallbacks());
+        }
+
+        if let Some(placeholder) = self.state.content.placeholder.borrow_mut().as_mut() {
+            if placeholder.style_key != Some(style_key) {
+                placeholder.style_key = Some(style_key);
+
+                let text_context = &mut *self.builder.text_context;
+                let mut builder = text_context.layouts.ranged_builder(
+                    &mut text_context.fonts,
+                    &placeholder.text,
+                    1.0,
+                    false,
+                );
+                theme.push_text_defaults(
+                    theme.get_id(StyleClass::TextEditPlaceholder),
+                    self.state_flags,
+                    &mut builder,
+                );
+                builder.build_into(&mut placeholder.layout, &placeholder.text);
+                self.builder.context.text_layouts_built += 1;
+            } else {
+                self.builder.context.text_layouts_reused += 1;
+            }
         }
 
+        let previous_edit = (buffer.selection_range(), buffer.char_count());
+
         if let Some(placement) = placement {
             self.handle_mouse_events(
                 &mut buffer,
@@ -355,10 +809,51 @@ impl<'a, T: EditableTextBuffer + 'static> TextEdit<'a, T> {
         }
 
         if is_focused {
+            self.builder.context.is_text_focused = true;
+            self.handle_ime_events(&mut buffer, &input);
             self.handle_keyboard_events(&mut buffer, &input);
         }
 
-        let cursor_size = style.font_size.get(self.state_flags) as f32;
+        let cursor_size = font_size;
+
+        // Scrolled text follows the cursor as it moves, but is left where the
+        // wheel put it otherwise.
+        let is_edited = previous_edit != (buffer.selection_range(), buffer.char_count());
+
+        // Masked text doesn't go through the input method, since candidates
+        // would show what was typed.
+        if is_focused
+            && !buffer.is_masked()
+            && let Some(placement) = placement
+        {
+            let mut cursor_area = None;
+            buffer.with_layouts(self.builder.text_context, |layout| {
+                let offset = placement.origin + layout.offset().to_vector();
+                cursor_area = layout
+                    .cursor_geometry(cursor_size)
+                    .map(|rect| Rect::new(offset + rect.origin.to_vector(), rect.size));
+            });
+            self.builder.context.ime_cursor_area = cursor_area;
+        }
+
+        if let Some(validator) = &self.validator {
+            // The input method's preedit isn't part of the text yet.
+            let is_valid = buffer.is_composing() || validator(buffer.text());
+            self.state.content.is_valid.set(is_valid);
+
+            if is_valid == self.state_flags.contains(StateFlags::INVALID) {
+                // The box was styled from the text before this frame's edits.
+                self.builder.context.needs_redraw = true;
+            }
+        }
+        let mut cursor_span = None;
+        if is_edited && self.multi_line.is_some() {
+            buffer.with_layouts(self.builder.text_context, |layout| {
+                cursor_span = layout
+                    .cursor_geometry(cursor_size)
+                    .map(|rect| (rect.origin.y, rect.origin.y + rect.size.height));
+            });
+        }
 
         let (selection_color, cursor_color) = if is_focused {
             let sel_color = style.selection_color.get(self.state_flags);
@@ -371,43 +866,190 @@ impl<'a, T: EditableTextBuffer + 'static> TextEdit<'a, T> {
         let visuals = EditableTextVisuals {
             alignment: style.text_align.get(self.state_flags),
             cursor_size,
+            cursor_width: style.cursor_width.get(self.state_flags),
             selection_color,
             cursor_color,
+            // The caret shows where typing goes once focused, so the
+            // placeholder only stands in for the text until then.
+            show_placeholder: !is_focused,
+            has_overline: style
+                .text_decoration
+                .get(self.state_flags)
+                .contains(TextDecoration::OVERLINE),
         };
 
+        let char_count = buffer.char_count();
+        let is_empty = buffer.is_empty();
+        let mut selection = buffer.selection_range();
         drop(buffer);
 
+        if let Some(icon) = self.leading_icon.take() {
+            self.icon("leading_icon", icon);
+        }
+
         let content: Rc<dyn EditableTextContent> = self.state.content.clone();
 
+        let text_parent = match self.multi_line {
+            Some(MultiLine { max_height, .. }) => {
+                let mut area = self.builder.named_child("scroll_area");
+                area.size(
+                    Size::Grow,
+                    Size::Fit {
+                        min: Length::ZERO,
+                        max: max_height,
+                    },
+                );
+                area.overflow(Overflow::Scroll);
+                if let Some((top, bottom)) = cursor_span {
+                    scroll_y_into_view(&mut area, top, bottom);
+                }
+                area.index
+            }
+            None => self.builder.index,
+        };
+
         self.builder.context.ui_tree.add(
-            Some(self.builder.index),
+            Some(text_parent),
             Atom {
-                width: Size::Grow,
-                height: Size::Fit {
+                width: PixelSize::Grow,
+                height: PixelSize::Fit {
                     min: cursor_size,
                     max: f32::MAX,
                 },
+                z_layer: self.builder.layer,
+                is_modal: self.builder.is_modal,
                 ..Default::default()
             },
-            (LayoutContent::EditableText { content, visuals }, None),
+            (
+                LayoutContent::EditableText { content, visuals },
+                Some(text_id),
+            ),
         );
 
-        self.interaction
+        if let Some(CharacterCounter { limit }) = self.counter {
+            let text = match limit {
+                Some(limit) => format!("{char_count}/{limit}"),
+                None => char_count.to_string(),
+            };
+
+            let mut counter = self.builder.named_child("counter");
+            counter.apply_style(StyleClass::TextEditCounter, self.state_flags);
+            counter.text(&text, None);
+        }
+
+        if self.show_clear_button && !is_empty {
+            let mut button = self.builder.named_child("clear_button");
+
+            let (interaction, state) = Interaction::compute(
+                &button,
+                ClickBehavior::OnPress,
+                StateFlags::HOVERED | StateFlags::PRESSED,
+            );
+
+            button.apply_style(StyleClass::TextEditClearButton, state);
+            button.text("\u{00D7}", None);
+            if interaction.is_hovered {
+                button.set_cursor(CursorIcon::Pointer);
+            }
+
+            if interaction.is_activated {
+                let mut buffer = self.state.content.buffer.borrow_mut();
+                buffer.move_cursor(self.builder.text_context, TextEditMotion::SelectAll);
+                buffer.move_cursor(self.builder.text_context, TextEditMotion::Backdelete);
+                selection = buffer.selection_range();
+
+                // The button and counter were built from the old contents.
+                self.builder.context.needs_redraw = true;
+            }
+        }
+
+        if let Some(icon) = self.trailing_icon.take() {
+            self.icon("trailing_icon", icon);
+        }
+
+        (selection, self.interaction)
+    }
+
+    fn icon(&mut self, name: &str, texture: Texture) {
+        let style = self.builder.theme.get(StyleClass::TextEditIcon);
+
+        let mut icon = self.builder.named_child(name);
+        icon.apply_style(StyleClass::TextEditIcon, self.state_flags);
+
+        let border = style.border.get(self.state_flags);
+        let border_widths = style.border_widths.get(self.state_flags);
+        let corner_radii = style.corner_radii.get(self.state_flags);
+
+        icon.paint(
+            Paint::textured(texture, Color::WHITE),
+            border,
+            border_widths,
+            corner_radii,
+        );
     }
 
     fn handle_keyboard_events(&mut self, buffer: &mut T, input: &Input) {
-        for event in input.keyboard_events.iter() {
-            if !event.state.is_pressed() {
+        for (index, event) in input.keyboard_events.iter().enumerate() {
+            if !event.state.is_pressed() || self.builder.context.is_key_event_consumed(index) {
                 continue;
             }
 
             let ctrl_held = input.modifiers.control_key();
             let shift_held = input.modifiers.shift_key();
 
+            let clipboard_command = match event.key {
+                PhysicalKey::Code(KeyCode::KeyX) if ctrl_held => Some(ClipboardCommand::Cut),
+                PhysicalKey::Code(KeyCode::Delete) if shift_held => Some(ClipboardCommand::Cut),
+                PhysicalKey::Code(KeyCode::Cut) => Some(ClipboardCommand::Cut),
+                PhysicalKey::Code(KeyCode::KeyC) if ctrl_held => Some(ClipboardCommand::Copy),
+                PhysicalKey::Code(KeyCode::Insert) if ctrl_held => Some(ClipboardCommand::Copy),
+                PhysicalKey::Code(KeyCode::Copy) => Some(ClipboardCommand::Copy),
+                PhysicalKey::Code(KeyCode::KeyV) if ctrl_held => Some(ClipboardCommand::Paste),
+                PhysicalKey::Code(KeyCode::Insert) if shift_held => Some(ClipboardCommand::Paste),
+                PhysicalKey::Code(KeyCode::Paste) => Some(ClipboardCommand::Paste),
+                _ => None,
+            };
+
+            if let Some(command) = clipboard_command {
+                // The input method owns the text being composed until it is
+                // committed.
+                if !buffer.is_composing() {
+                    self.run_clipboard_command(buffer, command);
+                    self.builder.context.consume_key_event(index);
+                }
+                continue;
+            }
+
             let motion = match event.key {
                 PhysicalKey::Code(KeyCode::ControlLeft | KeyCode::ControlRight) => continue,
+                PhysicalKey::Code(KeyCode::Enter | KeyCode::NumpadEnter) => {
+                    let starts_line = match self.multi_line {
+                        Some(multi_line) => match multi_line.newline_key {
+                            NewlineKey::Enter => true,
+                            NewlineKey::ShiftEnter => shift_held,
+                        },
+                        None => false,
+                    };
+
+                    if starts_line {
+                        self.enter_text(buffer, "\n");
+                        self.builder.context.consume_key_event(index);
+                    }
+                    continue;
+                }
                 PhysicalKey::Code(KeyCode::ShiftLeft | KeyCode::ShiftRight) => continue,
-                PhysicalKey::Code(KeyCode::KeyA) if ctrl_held => TextEditMotion::SelectAll,
+                PhysicalKey::Code(KeyCode::KeyA) if ctrl_held => {
+                    let previous = buffer.selection_range();
+                    let scope = self.selection_behavior.select_all;
+                    buffer.move_cursor(self.builder.text_context, scope.select_at_cursor());
+
+                    if buffer.selection_range() == previous {
+                        buffer.move_cursor(self.builder.text_context, TextEditMotion::SelectAll);
+                    }
+
+                    self.builder.context.consume_key_event(index);
+                    continue;
+                }
                 PhysicalKey::Code(KeyCode::ArrowLeft) => match (ctrl_held, shift_held) {
                     (true, true) => TextEditMotion::SelectWordLeft,
                     (true, false) => TextEditMotion::MoveWordLeft,
@@ -456,23 +1098,10 @@ impl<'a, T: EditableTextBuffer + 'static> TextEdit<'a, T> {
                     (false, true) => TextEditMotion::SelectToLineEnd,
                     (false, false) => TextEditMotion::MoveToLineEnd,
                 },
-                PhysicalKey::Code(KeyCode::KeyC) if ctrl_held => {
-                    if let Some(text) = buffer.selected_text() {
-                        self.builder.clipboard.set_text(text);
-                    }
-
-                    continue;
-                }
-                PhysicalKey::Code(KeyCode::KeyV) if ctrl_held => {
-                    if let Some(text) = self.builder.clipboard.get_text() {
-                        buffer.enter_text(self.builder.text_context, &text);
-                    }
-
-                    continue;
-                }
                 _ => {
                     if let Some(text) = &event.text {
-                        buffer.enter_text(self.builder.text_context, text);
+                        self.enter_text(buffer, text);
+                        self.builder.context.consume_key_event(index);
                     }
 
                     continue;
@@ -480,6 +1109,84 @@ impl<'a, T: EditableTextBuffer + 'static> TextEdit<'a, T> {
             };
 
             buffer.move_cursor(self.builder.text_context, motion);
+            self.builder.context.consume_key_event(index);
+        }
+    }
+
+    fn handle_ime_events(&mut self, buffer: &mut T, input: &Input) {
+        for event in &input.ime_events {
+            match event {
+                ImeEvent::Preedit { text, cursor } => {
+                    buffer.set_composition(self.builder.text_context, text, cursor.clone());
+                }
+                ImeEvent::Commit(text) => {
+                    buffer.set_composition(self.builder.text_context, "", None);
+                    self.enter_text(buffer, text);
+                }
+            }
+        }
+    }
+
+    fn run_clipboard_command(&mut self, buffer: &mut T, command: ClipboardCommand) {
+        match command {
+            // Masked text is hidden from the clipboard as well as the screen.
+            ClipboardCommand::Cut | ClipboardCommand::Copy if buffer.is_masked() => {}
+            ClipboardCommand::Cut => {
+                if let Some(text) = buffer.selected_text() {
+                    self.builder.clipboard.set_text(text);
+                    buffer.move_cursor(self.builder.text_context, TextEditMotion::Delete);
+                }
+            }
+            ClipboardCommand::Copy => {
+                if let Some(text) = buffer.selected_text() {
+                    self.builder.clipboard.set_text(text);
+                }
+            }
+            ClipboardCommand::Paste => {
+                // Entered text replaces the selection.
+                match self.builder.clipboard.get_text() {
+                    Some(text) if self.multi_line.is_none() => {
+                        let text = text.lines().collect::<Vec<_>>().join(" ");
+                        self.enter_text(buffer, &text);
+                    }
+                    Some(text) => self.enter_text(buffer, &text),
+                    None => {}
+                }
+            }
+        }
+    }
+
+    fn enter_text(&mut self, buffer: &mut T, text: &str) {
+        // Filtering a preedit would desynchronize the buffer from the input
+        // method's idea of it.
+        if buffer.is_composing() {
+            buffer.enter_text(self.builder.text_context, text);
+            return;
+        }
+
+        let mut text = match self.number_format {
+            Some(format) => text.chars().filter(|&c| format.accepts(c)).collect(),
+            None => text.to_string(),
+        };
+
+        if let Some(filter) = &self.filter
+            && !text.is_empty()
+        {
+            text = filter(&text);
+        }
+
+        if let Some(max) = self.max_length {
+            // Entered text replaces the selection, freeing up its characters.
+            let selected = buffer.selected_text().map_or(0, |s| s.chars().count());
+            let available = max.saturating_sub(buffer.char_count() - selected);
+
+            if let Some((end, _)) = text.char_indices().nth(available) {
+                text.truncate(end);
+            }
+        }
+
+        if !text.is_empty() {
+            buffer.enter_text(self.builder.text_context, &text);
         }
     }
 
@@ -507,19 +1214,21 @@ impl<'a, T: EditableTextBuffer + 'static> TextEdit<'a, T> {
         let is_left_down = input.mouse_state.is_left_down();
         let local = local.to_point();
 
+        let behavior = self.selection_behavior;
+
         let motion = if interaction.is_activated && is_hovered {
             match left_click_count {
                 4.. => Some(TextEditMotion::SelectAll),
-                3 => Some(TextEditMotion::SelectLineAtPoint(local)),
-                2 => Some(TextEditMotion::SelectWordAtPoint(local)),
+                3 => Some(behavior.triple_click.select_at_point(local)),
+                2 => Some(behavior.double_click.select_at_point(local)),
                 1 if is_shift_held => Some(TextEditMotion::ExtendSelectionToPoint(local)),
                 1 => Some(TextEditMotion::MoveToPoint(local)),
                 0 => None,
             }
         } else if is_left_down && is_focused {
             Some(match left_click_count {
-                3 => TextEditMotion::SelectLineAtPoint(local),
-                2 => TextEditMotion::SelectWordAtPoint(local),
+                3 => behavior.triple_click.select_at_point(local),
+                2 => behavior.double_click.select_at_point(local),
                 _ => TextEditMotion::ExtendSelectionToPoint(local),
             })
         } else {
@@ -532,6 +1241,29 @@ impl<'a, T: EditableTextBuffer + 'static> TextEdit<'a, T> {
     }
 }
 
+impl TextEdit<'_, PlainTextBuffer> {
+    /// Like [`finish`](Self::finish), but also returns the text and its value
+    /// as a number in the format given to [`numeric`](Self::numeric), or the
+    /// default format.
+    pub fn finish_numeric(self) -> (NumericEntry, Interaction) {
+        let format = self.number_format.unwrap_or_default();
+        let state = self.state;
+        let (_, interaction) = self.finish();
+
+        let entry = state.with_buffer(|buffer| {
+            let text = buffer.text();
+            NumericEntry {
+                text: text.to_string(),
+                value: (!buffer.is_composing())
+                    .then(|| format.parse(text))
+                    .flatten(),
+            }
+        });
+
+        (entry, interaction)
+    }
+}
+
 /// Fill a rect from a text layout, snapping its vertical extent to whole
 /// pixels.
 fn fill_snapped_rect(
@@ -542,8 +1274,10 @@ fn fill_snapped_rect(
     y: f32,
     clip: ClipRect,
 ) {
-    let y0 = (y + rect.origin.y).round();
-    let y1 = (y + rect.origin.y + rect.size.height).round();
+    // Snapped to the pixels of the display rather than to logical pixels.
+    let scale = canvas.scale();
+    let y0 = ((y + rect.origin.y) * scale).round() / scale;
+    let y1 = ((y + rect.origin.y + rect.size.height) * scale).round() / scale;
 
     canvas.draw(Primitive {
         point: [x + rect.origin.x, y0],
@@ -551,9 +1285,11 @@ fn fill_snapped_rect(
         clip,
         paint: Paint::solid(color),
         border: GradientPaint::default(),
+        border_colors: None,
         border_width: [0.0; 4],
         corner_radii: [0.0; 4],
         use_nearest_sampling: false,
+        rotation: 0.0,
     });
 }
 
@@ -578,9 +1314,15 @@ impl EditableTextLayout for PlainEditorTextLayout<'_> {
     }
 }
 
+/// Stands in for each character of masked text.
+const MASK_CHAR: char = '\u{2022}';
+
 /// An [`EditableTextBuffer`] backed by parley's single-style [`PlainEditor`].
 pub struct PlainTextBuffer {
     editor: PlainEditor<Color>,
+    // The real text while masked, when the editor holds one `MASK_CHAR` per
+    // character instead.
+    masked_text: Option<String>,
     // The width/alignment last pushed to the editor. Setting either marks the
     // parley layout dirty even when the value is unchanged, so only real
     // changes are forwarded.
@@ -592,12 +1334,34 @@ impl Default for PlainTextBuffer {
     fn default() -> Self {
         Self {
             editor: PlainEditor::new(14.0),
+            masked_text: None,
             prev_width: None,
             prev_alignment: None,
         }
     }
 }
 
+impl PlainTextBuffer {
+    fn set_text(&mut self, text: &str) {
+        match &mut self.masked_text {
+            Some(masked_text) => {
+                masked_text.clear();
+                masked_text.push_str(text);
+                self.editor.set_text(&mask(text));
+            }
+            None => self.editor.set_text(text),
+        }
+    }
+
+    /// The editor's selection in characters, which is the same for the real
+    /// text and its mask.
+    fn selected_chars(&self) -> Range<usize> {
+        let range = self.editor.raw_selection().text_range();
+        let text = self.editor.raw_text();
+        text[..range.start].chars().count()..text[..range.end].chars().count()
+    }
+}
+
 impl EditableTextBuffer for PlainTextBuffer {
     type Layout<'a> = PlainEditorTextLayout<'a>;
 
@@ -605,56 +1369,131 @@ impl EditableTextBuffer for PlainTextBuffer {
         self.editor.raw_text().is_empty()
     }
 
+    fn text(&self) -> &str {
+        self.masked_text
+            .as_deref()
+            .unwrap_or(self.editor.raw_text())
+    }
+
     fn selected_text(&self) -> Option<&str> {
-        self.editor.selected_text()
+        let Some(masked_text) = &self.masked_text else {
+            return self.editor.selected_text();
+        };
+
+        let range = byte_range(masked_text, self.selected_chars());
+        (!range.is_empty()).then(|| &masked_text[range])
     }
 
-    fn apply_style(&mut self, style: &Style, state: StateFlags) {
+    fn selection_range(&self) -> Range<usize> {
+        match &self.masked_text {
+            Some(masked_text) => byte_range(masked_text, self.selected_chars()),
+            None => self.editor.raw_selection().text_range(),
+        }
+    }
+
+    fn is_composing(&self) -> bool {
+        self.editor.is_composing()
+    }
+
+    fn set_composition(
+        &mut self,
+        context: &mut TextLayoutContext,
+        text: &str,
+        cursor: Option<Range<usize>>,
+    ) {
+        // The editor underlines the composed text while laying it out.
+        let mut driver = context.drive(&mut self.editor);
+        if text.is_empty() {
+            driver.clear_compose();
+        } else {
+            driver.set_compose(text, cursor.map(|cursor| (cursor.start, cursor.end)));
+        }
+    }
+
+    fn is_masked(&self) -> bool {
+        self.masked_text.is_some()
+    }
+
+    fn set_masked(&mut self, context: &mut TextLayoutContext, masked: bool) {
+        if masked == self.is_masked() {
+            return;
+        }
+
+        let selection = self.selected_chars();
+
+        match self.masked_text.take() {
+            Some(text) => self.editor.set_text(&text),
+            None => {
+                let text = self.editor.raw_text().to_string();
+                self.editor.set_text(&mask(&text));
+                self.masked_text = Some(text);
+            }
+        }
+
+        let range = byte_range(self.editor.raw_text(), selection);
+        context
+            .drive(&mut self.editor)
+            .select_byte_range(range.start, range.end);
+    }
+
+    fn apply_style(
+        &mut self,
+        style: &Style,
+        state: StateFlags,
+        font_size: f32,
+        fallbacks: &[FontFamily],
+    ) {
         use parley::StyleProperty as Prop;
 
         let styles = self.editor.edit_styles();
 
         styles.insert(Prop::FontFeatures(default_font_features()));
         styles.insert(Prop::Brush(style.text_color.get(state)));
-        styles.insert(Prop::FontSize(style.font_size.get(state) as f32));
+        styles.insert(Prop::FontSize(font_size));
         styles.insert(Prop::FontStyle(style.font_style.get(state).into()));
         styles.insert(Prop::FontWeight(parley::FontWeight::new(
             style.font_weight.get(state) as f32,
         )));
+        let decoration = style.text_decoration.get(state);
+        styles.insert(Prop::Strikethrough(
+            decoration.contains(TextDecoration::STRIKETHROUGH),
+        ));
         styles.insert(Prop::StrikethroughBrush(Some(
             style.strikethrough_color.get(state),
         )));
-        styles.insert(Prop::StrikethroughOffset(Some(
+        styles.insert(Prop::StrikethroughOffset(decoration_offset(
             style.strikethrough_offset.get(state),
         )));
+        styles.insert(Prop::Underline(
+            decoration.contains(TextDecoration::UNDERLINE),
+        ));
         styles.insert(Prop::UnderlineBrush(Some(style.underline_color.get(state))));
-        styles.insert(Prop::UnderlineOffset(Some(
+        styles.insert(Prop::UnderlineOffset(decoration_offset(
             style.underline_offset.get(state),
         )));
 
-        match &style.font.get(state).family {
-            FontStack::Source(cow) => {
-                styles.insert(Prop::FontFamily(parley::FontFamily::Source(cow.clone())));
-            }
-            FontStack::Single(font_family) => {
-                styles.insert(Prop::FontFamily(parley::FontFamily::Single(
-                    font_family.clone().into(),
-                )));
-            }
-            FontStack::List(cow) => {
-                let families = cow.iter().cloned().map(|f| f.into()).collect::<Vec<_>>();
-                styles.insert(Prop::FontFamily(parley::FontFamily::List(families.into())));
-            }
-        }
+        let family = style.font.get(state).family.to_parley_family(fallbacks);
+        styles.insert(Prop::FontFamily(family));
     }
 
     fn enter_text(&mut self, context: &mut TextLayoutContext, text: &str) {
+        let selection = self.selected_chars();
+
+        let Some(masked_text) = &mut self.masked_text else {
+            context
+                .drive(&mut self.editor)
+                .insert_or_replace_selection(text);
+            return;
+        };
+
+        masked_text.replace_range(byte_range(masked_text, selection), text);
         context
             .drive(&mut self.editor)
-            .insert_or_replace_selection(text);
+            .insert_or_replace_selection(&mask(text));
     }
 
     fn move_cursor(&mut self, context: &mut TextLayoutContext, motion: TextEditMotion) {
+        let char_count = self.char_count();
         let mut driver = context.drive(&mut self.editor);
 
         match motion {
@@ -679,11 +1518,26 @@ impl EditableTextBuffer for PlainTextBuffer {
             TextEditMotion::SelectAll => driver.select_all(),
             TextEditMotion::SelectDown => driver.select_down(),
             TextEditMotion::SelectLeft => driver.select_left(),
+            TextEditMotion::SelectLine => {
+                if let Some((x, y)) = cursor_point(&mut driver) {
+                    driver.select_line_at_point(x, y);
+                }
+            }
             TextEditMotion::SelectLineAtPoint(p) => {
                 driver.select_line_at_point(p.x, p.y);
             }
+            TextEditMotion::SelectParagraph => select_paragraph(&mut driver),
+            TextEditMotion::SelectParagraphAtPoint(p) => {
+                driver.move_to_point(p.x, p.y);
+                select_paragraph(&mut driver);
+            }
             TextEditMotion::SelectRight => driver.select_right(),
             TextEditMotion::SelectUp => driver.select_up(),
+            TextEditMotion::SelectWord => {
+                if let Some((x, y)) = cursor_point(&mut driver) {
+                    driver.select_word_at_point(x, y);
+                }
+            }
             TextEditMotion::SelectWordAtPoint(p) => {
                 driver.select_word_at_point(p.x, p.y);
             }
@@ -694,6 +1548,16 @@ impl EditableTextBuffer for PlainTextBuffer {
             TextEditMotion::SelectToTextEnd => driver.select_to_text_end(),
             TextEditMotion::SelectToTextStart => driver.select_to_text_start(),
         }
+
+        // Every deletion leaves the cursor where the removed characters
+        // started, so the same characters can be removed from the real text.
+        let removed = char_count - self.char_count();
+        if removed > 0 && self.masked_text.is_some() {
+            let start = self.selected_chars().start;
+            if let Some(masked_text) = &mut self.masked_text {
+                masked_text.replace_range(byte_range(masked_text, start..start + removed), "");
+            }
+        }
     }
 
     fn measure(
@@ -732,6 +1596,42 @@ impl EditableTextBuffer for PlainTextBuffer {
     }
 }
 
+/// The middle of the caret at the focus end of the selection.
+fn cursor_point(driver: &mut PlainEditorDriver<'_, Color>) -> Option<(f32, f32)> {
+    driver.refresh_layout();
+    let bbox = driver.editor.cursor_geometry(1.0)?;
+    Some((bbox.x0 as f32, ((bbox.y0 + bbox.y1) / 2.0) as f32))
+}
+
+/// Extends the selection to the line breaks on either side of it.
+fn select_paragraph(driver: &mut PlainEditorDriver<'_, Color>) {
+    let is_break = |c: char| matches!(c, '\n' | '\r' | '\u{2029}');
+
+    let text = driver.editor.raw_text();
+    let selection = driver.editor.raw_selection().text_range();
+    let start = text[..selection.start].rfind(is_break).map_or(0, |i| i + 1);
+    let end = text[selection.end..]
+        .find(is_break)
+        .map_or(text.len(), |i| selection.end + i);
+
+    driver.select_byte_range(start, end);
+}
+
+fn mask(text: &str) -> String {
+    text.chars().map(|_| MASK_CHAR).collect()
+}
+
+/// The bytes spanned by a range of characters in `text`.
+fn byte_range(text: &str, chars: Range<usize>) -> Range<usize> {
+    let mut offsets = text.char_indices().map(|(i, _)| i).chain([text.len()]);
+    let start = offsets.nth(chars.start).unwrap_or(text.len());
+    let end = match chars.len() {
+        0 => start,
+        len => offsets.nth(len - 1).unwrap_or(text.len()),
+    };
+    start..end
+}
+
 fn bounding_box_rect(bbox: parley::BoundingBox) -> Rect<Pixels> {
     Rect {
         origin: Point2 {
@@ -744,3 +1644,127 @@ fn bounding_box_rect(bbox: parley::BoundingBox) -> Rect<Pixels> {
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use keyboard_types::Location;
+
+    use super::*;
+    use crate::shell::Clipboard;
+    use crate::shell::ElementState;
+    use crate::shell::KeyboardEvent;
+    use crate::shell::ModifiersState;
+    use crate::shell::Shortcut;
+    use crate::shell::Shortcuts;
+    use crate::shell::WindowShape;
+    use crate::ui::Theme;
+    use crate::ui::context::UiContext;
+    use crate::ui::text::TextLayoutStorage;
+
+    #[test]
+    fn number_format_strips_grouping_and_maps_decimal_separator() {
+        let german = NumberFormat::new(',', '.');
+        assert_eq!(german.parse("1.234,5"), Some(1234.5));
+        assert_eq!(german.parse("-0,25"), Some(-0.25));
+
+        let french = NumberFormat::new(',', '\u{202F}');
+        assert_eq!(french.parse("1\u{202F}000,5"), Some(1000.5));
+
+        let english = NumberFormat::default();
+        assert_eq!(english.parse("1,000,000"), Some(1_000_000.0));
+        assert_eq!(english.parse("\u{FF11}\u{FF12}.5"), Some(12.5));
+    }
+
+    #[test]
+    fn number_format_rejects_non_numbers() {
+        let format = NumberFormat::default();
+        assert_eq!(format.parse(""), None);
+        assert_eq!(format.parse("-"), None);
+        assert_eq!(format.parse("."), None);
+        assert_eq!(format.parse("1.2.3"), None);
+        assert_eq!(format.parse("1-2"), None);
+        assert_eq!(format.parse("inf"), None);
+        assert!(!format.accepts('e'));
+    }
+
+    #[test]
+    fn byte_range_maps_characters_to_bytes() {
+        let text = "a\u{00E9}\u{2022}b";
+        assert_eq!(byte_range(text, 0..0), 0..0);
+        assert_eq!(byte_range(text, 1..3), 1..6);
+        assert_eq!(byte_range(text, 3..4), 6..7);
+        assert_eq!(byte_range(text, 4..4), 7..7);
+        assert_eq!(mask(text), "\u{2022}".repeat(4));
+    }
+
+    #[test]
+    fn focused_text_edit_keeps_typed_keys_from_shortcuts() {
+        let clipboard = Clipboard::new();
+        let mut text_context = TextLayoutContext::default();
+        let mut text_layouts = TextLayoutStorage::default();
+        let mut format_buffer = String::new();
+        let theme = Theme::default();
+        let state = TextEditorState::plain();
+
+        let mut shortcuts = Shortcuts::default();
+        shortcuts.register(
+            Shortcut::new(ModifiersState::empty(), KeyCode::KeyA),
+            "command",
+        );
+
+        let mut typed = Input::default();
+        typed.keyboard_events.push(KeyboardEvent {
+            key: PhysicalKey::Code(KeyCode::KeyA),
+            text: Some("a".into()),
+            location: Location::Standard,
+            is_repeat: false,
+            state: ElementState::Pressed,
+        });
+
+        // Builds a frame with only the edit, returning the edit's ID, whether
+        // the shortcut was pressed, and how many keyboard events are left for
+        // widgets built after the edit.
+        let mut frame = |context: &mut UiContext, input: &Input| {
+            // Counted when the frame finishes, which needs a canvas.
+            context.frame_counter += 1;
+            context.match_shortcuts(input, &shortcuts);
+            let mut builder = context.begin_frame(
+                &clipboard,
+                &mut text_context,
+                &mut text_layouts,
+                &mut format_buffer,
+                &theme,
+                input,
+                Duration::ZERO,
+                Duration::ZERO,
+                None,
+                WindowShape::default(),
+            );
+
+            builder.context.focus_candidate.set(None);
+            TextEdit::new(&mut builder, &state).finish();
+
+            (
+                builder.context.focus_candidate.get(),
+                builder.shortcut_pressed("command"),
+                builder.keyboard_events().count(),
+            )
+        };
+
+        let mut context = UiContext::default();
+        let (edit, is_shortcut_pressed, _) = frame(&mut context, &typed);
+        assert!(is_shortcut_pressed);
+        assert_eq!(state.with_raw_text(str::to_owned), "");
+
+        // Shortcuts learn that a text edit is focused a frame later.
+        context.focused_widget.set(edit);
+        frame(&mut context, &Input::default());
+
+        let (_, is_shortcut_pressed, unconsumed) = frame(&mut context, &typed);
+        assert!(!is_shortcut_pressed);
+        assert_eq!(unconsumed, 0);
+        assert_eq!(state.with_raw_text(str::to_owned), "a");
+    }
+}
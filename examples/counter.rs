@@ -29,6 +29,7 @@ impl AppLifecycleHandler for Demo {
                 title: "Counter".into(),
                 width: 400,
                 height: 300,
+                ..Default::default()
             },
             AppWindow::default().into_handler(),
         );
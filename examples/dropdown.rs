@@ -30,6 +30,7 @@ impl AppLifecycleHandler for DropdownDemo {
                 title: "Dropdown Example".into(),
                 width: 600,
                 height: 500,
+                ..Default::default()
             },
             AppWindow::default().into_handler(),
         );
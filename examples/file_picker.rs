@@ -27,6 +27,7 @@ impl AppLifecycleHandler for Demo {
                 title: "File Picker".into(),
                 width: 400,
                 height: 300,
+                ..Default::default()
             },
             AppWindow::default().into_handler(),
         );
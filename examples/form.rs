@@ -0,0 +1,99 @@
+#![allow(unused_crate_dependencies)]
+
+use plinth::shell::AppContext;
+use plinth::shell::AppContextBuilder;
+use plinth::shell::AppLifecycleHandler;
+use plinth::shell::Context;
+use plinth::shell::WindowConfig;
+use plinth::ui::Alignment;
+use plinth::ui::CommonWidgetsExt;
+use plinth::ui::LayoutDirection;
+use plinth::ui::Padding;
+use plinth::ui::UiBuilder;
+use plinth::ui::widget::PlainTextEditorState;
+
+fn main() {
+    tracing_subscriber::fmt().pretty().init();
+
+    AppContextBuilder::default().run(FormDemo {});
+}
+
+struct FormDemo {}
+
+impl AppLifecycleHandler for FormDemo {
+    fn resume(&mut self, runtime: &mut AppContext) {
+        runtime.create_window(
+            WindowConfig {
+                title: "Form Example".into(),
+                width: 800,
+                height: 600,
+                ..Default::default()
+            },
+            AppWindow::default().into_handler(),
+        );
+    }
+}
+
+struct AppWindow {
+    name: PlainTextEditorState,
+    email: PlainTextEditorState,
+    submitted: Option<String>,
+}
+
+impl Default for AppWindow {
+    fn default() -> Self {
+        Self {
+            name: PlainTextEditorState::plain(),
+            email: PlainTextEditorState::plain(),
+            submitted: None,
+        }
+    }
+}
+
+impl AppWindow {
+    fn into_handler(mut self) -> impl FnMut(Context, UiBuilder) {
+        move |context, ui| self.update(context, ui)
+    }
+
+    fn update(&mut self, _context: Context, mut ui: UiBuilder) {
+        ui.child_alignment(Alignment::Center, Alignment::Center);
+
+        let mut panel = ui.surface();
+        panel
+            .width(400.0)
+            .child_alignment(Alignment::Start, Alignment::Start)
+            .child_direction(LayoutDirection::Vertical)
+            .padding(Padding::equal(20.0));
+
+        let mut form = panel.form("signup");
+
+        form.field("Name", |field| {
+            field.text_edit(&self.name).placeholder("Jane Doe").finish();
+
+            match self.name.with_raw_text(|text| text.trim().is_empty()) {
+                true => Err("A name is required".into()),
+                false => Ok(()),
+            }
+        });
+
+        form.field("Email", |field| {
+            field
+                .text_edit(&self.email)
+                .placeholder("jane@example.com")
+                .finish();
+
+            match self.email.with_raw_text(|text| text.contains('@')) {
+                true => Ok(()),
+                false => Err("Enter a valid email address".into()),
+            }
+        });
+
+        if form.finish("Sign up") {
+            self.submitted = Some(self.name.with_raw_text(str::to_owned));
+        }
+
+        if let Some(name) = &self.submitted {
+            panel.label(&format!("Signed up as {name}"));
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! A gallery of every built-in widget, with controls to switch between light
+//! and dark themes, compact and comfortable spacing, and several text scales.
+//!
+//! The gallery doubles as living documentation and as a fixed corpus of
+//! widgets to compare when checking a change for visual regressions.
+
+#![allow(unused_crate_dependencies)]
+
+use plinth::graphics::Color;
+use plinth::graphics::GradientPaint;
+use plinth::graphics::ImageHandle;
+use plinth::graphics::Paint;
+use plinth::shell::AppContext;
+use plinth::shell::AppContextBuilder;
+use plinth::shell::AppLifecycleHandler;
+use plinth::shell::Appearance;
+use plinth::shell::Context;
+use plinth::shell::ModifiersState;
+use plinth::shell::WindowConfig;
+use plinth::ui::Alignment;
+use plinth::ui::CommonWidgetsExt;
+use plinth::ui::LayoutDirection;
+use plinth::ui::Length;
+use plinth::ui::Padding;
+use plinth::ui::StyleClass;
+use plinth::ui::Theme;
+use plinth::ui::UiBuilder;
+use plinth::ui::style::StateFlags;
+use plinth::ui::style::StyleProperty;
+use plinth::ui::widget::Container;
+use plinth::ui::widget::Image;
+use plinth::ui::widget::PlainTextEditorState;
+
+const CHECKERBOARD_PNG: &[u8] = &[
+    0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x10, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1f, 0xf3, 0xff,
+    0x61, 0x00, 0x00, 0x00, 0x28, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0xb0, 0xa9, 0x38, 0xf1,
+    0x1f, 0x19, 0x3f, 0x7b, 0xf6, 0x0c, 0x05, 0x13, 0x92, 0x67, 0x18, 0x06, 0x06, 0x90, 0xaa, 0x01,
+    0x5d, 0x7e, 0x38, 0x18, 0x30, 0x9a, 0x0e, 0x46, 0xd3, 0x01, 0x10, 0x03, 0x00, 0x59, 0x0c, 0x16,
+    0x2e, 0xad, 0x33, 0xe5, 0x87, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60,
+    0x82,
+];
+
+const APPEARANCES: [&str; 3] = ["System", "Light", "Dark"];
+const DENSITIES: [&str; 2] = ["Compact", "Comfortable"];
+const SCALES: [(&str, f32); 3] = [("100%", 1.0), ("125%", 1.25), ("150%", 1.5)];
+
+fn main() {
+    tracing_subscriber::fmt().pretty().init();
+
+    AppContextBuilder::default().run(GalleryDemo {});
+}
+
+struct GalleryDemo {}
+
+impl AppLifecycleHandler for GalleryDemo {
+    fn resume(&mut self, runtime: &mut AppContext) {
+        runtime.create_window(
+            WindowConfig {
+                title: "Widget Gallery".into(),
+                width: 900,
+                height: 700,
+                ..Default::default()
+            },
+            AppWindow::default().into_handler(),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct ThemeSettings {
+    appearance: Appearance,
+    density: usize,
+    scale: usize,
+}
+
+impl ThemeSettings {
+    fn build(&self) -> Theme {
+        let mut theme = Theme::default();
+        let scale = SCALES[self.scale].1;
+        let spacing = if self.density == 0 { 2.0 } else { 8.0 } * scale;
+
+        theme.set_base_style([
+            (
+                StateFlags::empty(),
+                StyleProperty::FontSize(Length::Px((14.0 * scale).round())),
+            ),
+            (
+                StateFlags::empty(),
+                StyleProperty::Padding(Padding::equal(spacing)),
+            ),
+            (
+                StateFlags::empty(),
+                StyleProperty::ChildSpacing(Length::Px(spacing)),
+            ),
+        ]);
+
+        if self.appearance == Appearance::Dark {
+            let background = Color::srgb_nonlinear(0.12, 0.12, 0.13, 1.0);
+            let raised = Color::srgb_nonlinear(0.2, 0.2, 0.22, 1.0);
+            let text = Color::srgb_nonlinear(0.92, 0.92, 0.92, 1.0);
+
+            theme.set_base_style([
+                (
+                    StateFlags::empty(),
+                    StyleProperty::Background(Paint::solid(background)),
+                ),
+                (StateFlags::empty(), StyleProperty::TextColor(text)),
+                (StateFlags::empty(), StyleProperty::CursorColor(text)),
+                (
+                    StateFlags::empty(),
+                    StyleProperty::Border(GradientPaint::solid(Color::DARK_GRAY)),
+                ),
+            ]);
+
+            for class in [StyleClass::Button, StyleClass::DropdownMenu] {
+                theme.update_style(
+                    theme.get_id(class),
+                    [(
+                        StateFlags::empty(),
+                        StyleProperty::Background(Paint::solid(raised)),
+                    )],
+                );
+            }
+
+            for class in [
+                StyleClass::HorizontalSeparator,
+                StyleClass::VerticalSeparator,
+            ] {
+                theme.update_style(
+                    theme.get_id(class),
+                    [(
+                        StateFlags::empty(),
+                        StyleProperty::Background(Paint::solid(Color::DARK_GRAY)),
+                    )],
+                );
+            }
+        }
+
+        theme
+    }
+}
+
+struct AppWindow {
+    appearance: usize,
+    density: usize,
+    scale: usize,
+    applied: Option<ThemeSettings>,
+
+    clicks: u32,
+    text: PlainTextEditorState,
+    limited: PlainTextEditorState,
+    fruit: Option<usize>,
+    menu_choice: Option<&'static str>,
+    name: PlainTextEditorState,
+    submitted: bool,
+    image: Option<ImageHandle>,
+}
+
+impl Default for AppWindow {
+    fn default() -> Self {
+        Self {
+            appearance: 0,
+            density: 1,
+            scale: 0,
+            applied: None,
+            clicks: 0,
+            text: PlainTextEditorState::plain(),
+            limited: PlainTextEditorState::plain(),
+            fruit: None,
+            menu_choice: None,
+            name: PlainTextEditorState::plain(),
+            submitted: false,
+            image: None,
+        }
+    }
+}
+
+impl AppWindow {
+    fn into_handler(mut self) -> impl FnMut(Context, UiBuilder) {
+        move |context, ui| self.update(context, ui)
+    }
+
+    fn update(&mut self, mut context: Context, mut ui: UiBuilder) {
+        let settings = ThemeSettings {
+            appearance: match self.appearance {
+                0 => context.system_appearance(),
+                1 => Appearance::Light,
+                _ => Appearance::Dark,
+            },
+            density: self.density,
+            scale: self.scale,
+        };
+
+        if self.applied != Some(settings) {
+            context.set_theme(settings.build());
+            self.applied = Some(settings);
+        }
+
+        let image = self
+            .image
+            .get_or_insert_with(|| context.load_image_source(CHECKERBOARD_PNG.to_vec()));
+
+        ui.child_alignment(Alignment::Start, Alignment::Start)
+            .style_inspector(ModifiersState::CONTROL | ModifiersState::SHIFT);
+
+        // The root node must lay out its children horizontally.
+        let mut page = ui.surface();
+        page.child_direction(LayoutDirection::Vertical)
+            .child_alignment(Alignment::Start, Alignment::Start);
+
+        {
+            let mut controls = page.surface();
+            controls.child_direction(LayoutDirection::Horizontal);
+
+            controls.label("Theme:");
+            self.appearance = controls
+                .dropdown(
+                    "appearance",
+                    APPEARANCES[self.appearance],
+                    Some(self.appearance),
+                    APPEARANCES,
+                )
+                .unwrap_or(self.appearance);
+
+            controls.label("Density:");
+            self.density = controls
+                .dropdown(
+                    "density",
+                    DENSITIES[self.density],
+                    Some(self.density),
+                    DENSITIES,
+                )
+                .unwrap_or(self.density);
+
+            controls.label("Scale:");
+            self.scale = controls
+                .dropdown(
+                    "scale",
+                    SCALES[self.scale].0,
+                    Some(self.scale),
+                    SCALES.map(|(label, _)| label),
+                )
+                .unwrap_or(self.scale);
+        }
+
+        page.horizontal_separator();
+
+        let mut columns = page.surface();
+        columns.child_direction(LayoutDirection::Horizontal);
+
+        {
+            let mut column = columns.surface();
+            column
+                .child_direction(LayoutDirection::Vertical)
+                .child_alignment(Alignment::Start, Alignment::Start);
+
+            column.label("Labels and buttons");
+            column.label(&format!("Clicked {} times", self.clicks));
+            if column.text_button("Click me").is_activated {
+                self.clicks += 1;
+            }
+            column.enabled(false);
+            column.text_button("Disabled");
+            column.enabled(true);
+
+            column.horizontal_separator();
+
+            column.label("Text edits");
+            column
+                .text_edit(&self.text)
+                .placeholder("Type here")
+                .clear_button()
+                .finish();
+            column
+                .text_edit(&self.limited)
+                .placeholder("At most 20 characters")
+                .character_counter(Some(20))
+                .finish();
+
+            column.horizontal_separator();
+
+            column.label("Dropdown");
+            let fruits = ["Apple", "Banana", "Cherry"];
+            let label = self.fruit.map_or("Pick a fruit", |i| fruits[i]);
+            self.fruit = column.dropdown("fruit", label, self.fruit, fruits);
+        }
+
+        columns.vertical_separator();
+
+        {
+            let mut column = columns.surface();
+            column
+                .child_direction(LayoutDirection::Vertical)
+                .child_alignment(Alignment::Start, Alignment::Start);
+
+            column.label("Context menu");
+            {
+                let mut target = column.surface();
+                target.label(self.menu_choice.unwrap_or("Right-click here"));
+
+                let items = ["Cut", "Copy", "Paste"];
+                if let Some(index) = target.context_menu("menu", items) {
+                    self.menu_choice = Some(items[index]);
+                }
+            }
+
+            column.horizontal_separator();
+
+            column.label("Form");
+            let mut form = column.form("form");
+            form.field("Name", |field| {
+                field.text_edit(&self.name).placeholder("Required").finish();
+
+                match self.name.with_raw_text(|text| text.trim().is_empty()) {
+                    true => Err("A name is required".into()),
+                    false => Ok(()),
+                }
+            });
+            if form.finish("Submit") {
+                self.submitted = true;
+            }
+            if self.submitted {
+                column.label("Submitted");
+            }
+
+            column.horizontal_separator();
+
+            column.label("Image");
+            Image::from_handle(column.builder_mut(), image)
+                .with_size(64.0, 64.0)
+                .finish();
+        }
+    }
+}
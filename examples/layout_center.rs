@@ -6,6 +6,7 @@ use plinth::shell::Context;
 use plinth::shell::WindowConfig;
 use plinth::ui::Alignment;
 use plinth::ui::LayoutDirection;
+use plinth::ui::Length;
 use plinth::ui::Padding;
 use plinth::ui::UiBuilder;
 use tracing::Level;
@@ -39,6 +40,7 @@ impl AppLifecycleHandler for App {
                 title: "Sabre App".into(),
                 width: 800,
                 height: 600,
+                ..Default::default()
             },
             AppWindow::default().into_handler(),
         );
@@ -61,10 +63,10 @@ impl AppWindow {
                     .with_child(|ui| {
                         ui.child_spacing(10.0)
                             .padding(Padding {
-                                left: 15.0,
-                                right: 15.0,
-                                top: 15.0,
-                                bottom: 15.0,
+                                left: Length::Px(15.0),
+                                right: Length::Px(15.0),
+                                top: Length::Px(15.0),
+                                bottom: Length::Px(15.0),
                             })
                             .color(Color::BLUE)
                             .rect(100.0, 100.0, Color::WHITE)
@@ -75,10 +77,10 @@ impl AppWindow {
                         ui.child_spacing(10.0)
                             .color(Color::GREEN)
                             .padding(Padding {
-                                left: 15.0,
-                                right: 15.0,
-                                top: 15.0,
-                                bottom: 15.0,
+                                left: Length::Px(15.0),
+                                right: Length::Px(15.0),
+                                top: Length::Px(15.0),
+                                bottom: Length::Px(15.0),
                             })
                             .rect(100.0, 91.0, Color::WHITE)
                             .rect(100.0, 15.0, Color::WHITE)
@@ -0,0 +1,72 @@
+//! A log viewer whose lines are only rebuilt when a line is added.
+
+#![allow(unused_crate_dependencies)]
+
+use plinth::shell::AppContext;
+use plinth::shell::AppContextBuilder;
+use plinth::shell::AppLifecycleHandler;
+use plinth::shell::Context;
+use plinth::shell::WindowConfig;
+use plinth::ui::Alignment;
+use plinth::ui::CommonWidgetsExt;
+use plinth::ui::LayoutDirection;
+use plinth::ui::RetainedUi;
+use plinth::ui::UiBuilder;
+
+fn main() {
+    tracing_subscriber::fmt().pretty().init();
+
+    AppContextBuilder::default().run(LogViewerDemo {});
+}
+
+struct LogViewerDemo {}
+
+impl AppLifecycleHandler for LogViewerDemo {
+    fn resume(&mut self, runtime: &mut AppContext) {
+        runtime.create_window(
+            WindowConfig {
+                title: "Log Viewer Example".into(),
+                width: 600,
+                height: 400,
+                ..Default::default()
+            },
+            AppWindow::default().into_handler(),
+        );
+    }
+}
+
+#[derive(Default)]
+struct AppWindow {
+    lines: Vec<String>,
+    log_view: RetainedUi,
+}
+
+impl AppWindow {
+    fn into_handler(mut self) -> impl FnMut(Context, UiBuilder) {
+        move |context, ui| self.update(context, ui)
+    }
+
+    fn update(&mut self, _context: Context, mut ui: UiBuilder) {
+        let mut page = ui.surface();
+        page.child_direction(LayoutDirection::Vertical)
+            .child_alignment(Alignment::Start, Alignment::Start);
+
+        if page.text_button("Add 1000 lines").is_activated {
+            let start = self.lines.len();
+            self.lines
+                .extend((start..start + 1000).map(|i| format!("[{i:>6}] request handled")));
+        }
+
+        let lines = &self.lines;
+        self.log_view.show(&mut page, lines.len(), |ui| {
+            let mut column = ui.child();
+            column
+                .child_direction(LayoutDirection::Vertical)
+                .child_alignment(Alignment::Start, Alignment::Start);
+
+            for line in lines.iter().rev().take(100) {
+                column.label(line);
+            }
+        });
+    }
+}
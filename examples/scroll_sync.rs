@@ -0,0 +1,107 @@
+//! Linked scroll areas: a line-number gutter that follows the text next to
+//! it, and an overview that follows the text proportionally.
+
+#![allow(unused_crate_dependencies)]
+
+use plinth::shell::AppContext;
+use plinth::shell::AppContextBuilder;
+use plinth::shell::AppLifecycleHandler;
+use plinth::shell::Context;
+use plinth::shell::WindowConfig;
+use plinth::ui::Alignment;
+use plinth::ui::CommonWidgetsExt;
+use plinth::ui::LayoutDirection;
+use plinth::ui::UiBuilder;
+use plinth::ui::widget::ScrollAxes;
+use plinth::ui::widget::ScrollSync;
+
+const NUM_LINES: usize = 200;
+
+fn main() {
+    tracing_subscriber::fmt().pretty().init();
+
+    AppContextBuilder::default().run(ScrollSyncDemo {});
+}
+
+struct ScrollSyncDemo {}
+
+impl AppLifecycleHandler for ScrollSyncDemo {
+    fn resume(&mut self, runtime: &mut AppContext) {
+        runtime.create_window(
+            WindowConfig {
+                title: "Scroll Sync Example".into(),
+                width: 600,
+                height: 400,
+                ..Default::default()
+            },
+            AppWindow::default().into_handler(),
+        );
+    }
+}
+
+struct AppWindow {
+    lines: Vec<String>,
+    numbers: Vec<String>,
+}
+
+impl Default for AppWindow {
+    fn default() -> Self {
+        Self {
+            lines: (0..NUM_LINES)
+                .map(|i| format!("let value_{i} = compute({i});"))
+                .collect(),
+            numbers: (1..=NUM_LINES).map(|i| i.to_string()).collect(),
+        }
+    }
+}
+
+impl AppWindow {
+    fn into_handler(mut self) -> impl FnMut(Context, UiBuilder) {
+        move |context, ui| self.update(context, ui)
+    }
+
+    fn update(&mut self, _context: Context, mut ui: UiBuilder) {
+        ui.child_alignment(Alignment::Start, Alignment::Start);
+
+        {
+            let mut gutter = ui
+                .scroll_area()
+                .with_height(360.0)
+                .with_link("editor", ScrollAxes::Vertical, ScrollSync::Offset)
+                .with_child_direction(LayoutDirection::Vertical);
+
+            for number in &self.numbers {
+                gutter.label(number);
+            }
+        }
+
+        ui.vertical_separator();
+
+        {
+            let mut text = ui
+                .scroll_area()
+                .with_size(400.0, 360.0)
+                .with_link("editor", ScrollAxes::Vertical, ScrollSync::Offset)
+                .with_child_direction(LayoutDirection::Vertical);
+
+            for line in &self.lines {
+                text.label(line);
+            }
+        }
+
+        ui.vertical_separator();
+
+        {
+            let mut overview = ui
+                .scroll_area()
+                .with_height(360.0)
+                .with_link("editor", ScrollAxes::Vertical, ScrollSync::Proportional)
+                .with_child_direction(LayoutDirection::Vertical)
+                .with_child_alignment(Alignment::Start, Alignment::Start);
+
+            for line in self.lines.iter().step_by(10) {
+                overview.label(line);
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! A round, borderless utility window. Presses outside of the circle are
+//! ignored.
+
+#![allow(unused_crate_dependencies)]
+
+use plinth::shell::AppContext;
+use plinth::shell::AppContextBuilder;
+use plinth::shell::AppLifecycleHandler;
+use plinth::shell::Context;
+use plinth::shell::WindowConfig;
+use plinth::shell::WindowShape;
+use plinth::ui::Alignment;
+use plinth::ui::CommonWidgetsExt;
+use plinth::ui::LayoutDirection;
+use plinth::ui::UiBuilder;
+
+fn main() {
+    tracing_subscriber::fmt().pretty().init();
+    AppContextBuilder::default().run(Demo {});
+}
+
+struct Demo {}
+
+impl AppLifecycleHandler for Demo {
+    fn resume(&mut self, runtime: &mut AppContext) {
+        runtime.create_window(
+            WindowConfig {
+                title: "Shaped Window".into(),
+                width: 240,
+                height: 240,
+                shape: WindowShape::Circle,
+                ..Default::default()
+            },
+            AppWindow::default().into_handler(),
+        );
+    }
+}
+
+#[derive(Default)]
+struct AppWindow {
+    clicks: u32,
+}
+
+impl AppWindow {
+    fn into_handler(mut self) -> impl FnMut(Context, UiBuilder) {
+        move |context, ui| self.update(context, ui)
+    }
+
+    fn update(&mut self, _context: Context, mut ui: UiBuilder) {
+        ui.child_alignment(Alignment::Center, Alignment::Center);
+
+        let mut panel = ui.surface();
+        panel
+            .child_direction(LayoutDirection::Vertical)
+            .child_alignment(Alignment::Center, Alignment::Center);
+
+        panel.label(&format!("Clicked {} times", self.clicks));
+
+        if panel.text_button("Click me").is_activated {
+            self.clicks += 1;
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! A background import whose progress is shown while it runs.
+
+#![allow(unused_crate_dependencies)]
+
+use std::time::Duration;
+
+use plinth::graphics::Color;
+use plinth::shell::AppContext;
+use plinth::shell::AppContextBuilder;
+use plinth::shell::AppLifecycleHandler;
+use plinth::shell::Context;
+use plinth::shell::TaskHandle;
+use plinth::shell::WindowConfig;
+use plinth::ui::Alignment;
+use plinth::ui::CommonWidgetsExt;
+use plinth::ui::LayoutDirection;
+use plinth::ui::Padding;
+use plinth::ui::UiBuilder;
+use plinth::ui::widget::Container;
+
+const NUM_FILES: u32 = 40;
+const BAR_WIDTH: f32 = 300.0;
+
+fn main() {
+    tracing_subscriber::fmt().pretty().init();
+
+    AppContextBuilder::default().run(TaskProgressDemo {});
+}
+
+struct TaskProgressDemo {}
+
+impl AppLifecycleHandler for TaskProgressDemo {
+    fn resume(&mut self, runtime: &mut AppContext) {
+        runtime.create_window(
+            WindowConfig {
+                title: "Task Progress Example".into(),
+                width: 400,
+                height: 200,
+                ..Default::default()
+            },
+            AppWindow::default().into_handler(),
+        );
+    }
+}
+
+#[derive(Default)]
+struct AppWindow {
+    import: Option<TaskHandle<u32>>,
+}
+
+impl AppWindow {
+    fn into_handler(mut self) -> impl FnMut(Context, UiBuilder) {
+        move |context, ui| self.update(context, ui)
+    }
+
+    fn update(&mut self, mut context: Context, mut ui: UiBuilder) {
+        let mut page = ui.surface();
+        page.child_direction(LayoutDirection::Vertical)
+            .child_alignment(Alignment::Start, Alignment::Start);
+
+        let is_running = self.import.as_ref().is_some_and(|task| !task.is_finished());
+
+        let mut button = page.child();
+        button.enabled(!is_running);
+        if button.text_button("Import files").is_activated {
+            self.import = Some(context.spawn(|progress| {
+                for imported in 1..=NUM_FILES {
+                    std::thread::sleep(Duration::from_millis(100));
+                    progress.send(imported);
+                }
+            }));
+        }
+
+        let imported = self
+            .import
+            .as_ref()
+            .and_then(TaskHandle::progress)
+            .unwrap_or(0);
+
+        page.label(&format!("Imported {imported} of {NUM_FILES} files"));
+
+        let fraction = imported as f32 / NUM_FILES as f32;
+        page.builder_mut().with_child(|bar| {
+            bar.width(BAR_WIDTH)
+                .color(Color::LIGHT_GRAY)
+                .padding(Padding::equal(0.0))
+                .child_alignment(Alignment::Start, Alignment::Center)
+                .rect(BAR_WIDTH * fraction, 12.0, Color::BLUE);
+        });
+    }
+}
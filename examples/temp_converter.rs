@@ -55,6 +55,7 @@ impl AppLifecycleHandler for Demo {
                 title: "Temperature Converter".into(),
                 width: 400,
                 height: 300,
+                ..Default::default()
             },
             AppWindow::default().into_handler(),
         );
@@ -94,7 +95,7 @@ impl AppWindow {
             edit_c.set_text(&format!("{:.2}", temp_c));
         }
 
-        let temp_c = parse_temp(&self.edit_c, edit_c.finish());
+        let temp_c = parse_temp(&self.edit_c, edit_c.finish().1);
 
         panel.label("°C =");
 
@@ -108,7 +109,7 @@ impl AppWindow {
             }
         }
 
-        if let Some(temp) = parse_temp(&self.edit_f, edit_f.finish()) {
+        if let Some(temp) = parse_temp(&self.edit_f, edit_f.finish().1) {
             let temp_c = (temp - 32.0) * 5.0 / 9.0;
 
             if self.temp_c != Some(temp_c) {
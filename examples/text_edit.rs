@@ -11,6 +11,8 @@ use plinth::ui::LayoutDirection;
 use plinth::ui::Padding;
 use plinth::ui::UiBuilder;
 use plinth::ui::widget::PlainTextEditorState;
+use plinth::ui::widget::SelectionBehavior;
+use plinth::ui::widget::SelectionGranularity;
 
 fn main() {
     tracing_subscriber::fmt().pretty().init();
@@ -27,6 +29,7 @@ impl AppLifecycleHandler for TextEditDemo {
                 title: "TextEdit Example".into(),
                 width: 800,
                 height: 600,
+                ..Default::default()
             },
             AppWindow::default().into_handler(),
         );
@@ -63,10 +66,18 @@ impl AppWindow {
 
         panel.label("TextEdit Widget Demo:");
 
-        let interaction = panel
+        let (selection, interaction) = panel
             .text_edit(&self.text_editor)
             .with_width(200.0)
             .with_height(100.0)
+            .placeholder("Type something...")
+            .clear_button()
+            .character_counter(Some(140))
+            .selection_behavior(SelectionBehavior {
+                triple_click: SelectionGranularity::Paragraph,
+                select_all: SelectionGranularity::Paragraph,
+                ..Default::default()
+            })
             .finish();
 
         let text_content = self.text_editor.with_raw_text(str::to_owned);
@@ -78,6 +89,7 @@ impl AppWindow {
 
         info_panel.label(&format!("Current text: {text_content}"));
         info_panel.label(&format!("Text length: {} characters", text_content.len()));
+        info_panel.label(&format!("Selected: {}", &text_content[selection]));
         info_panel.label(&format!("Hovered: {}", interaction.is_hovered));
         info_panel.label(&format!("Clicked: {}", interaction.is_activated));
 
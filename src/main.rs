@@ -8,6 +8,7 @@ use plinth::shell::WindowConfig;
 use plinth::ui::Alignment;
 use plinth::ui::CommonWidgetsExt;
 use plinth::ui::LayoutDirection;
+use plinth::ui::Length;
 use plinth::ui::Padding;
 use plinth::ui::Size;
 use plinth::ui::Size::Flex;
@@ -67,7 +68,10 @@ fn main() {
             StateFlags::NORMAL,
             StyleProperty::Background(Paint::solid(Color::srgb_nonlinear(0.1, 0.2, 0.3, 1.0))),
         ),
-        (StateFlags::NORMAL, StyleProperty::FontSize(32)),
+        (
+            StateFlags::NORMAL,
+            StyleProperty::FontSize(Length::Px(32.0)),
+        ),
     ]);
 
     theme
@@ -89,24 +93,24 @@ fn main() {
                 (
                     StateFlags::NORMAL,
                     StyleProperty::Padding(Padding {
-                        left: 10.0,
-                        right: 10.0,
-                        top: 5.0,
-                        bottom: 5.0,
+                        left: Length::Px(10.0),
+                        right: Length::Px(10.0),
+                        top: Length::Px(5.0),
+                        bottom: Length::Px(5.0),
                     }),
                 ),
                 (
                     StateFlags::NORMAL,
                     StyleProperty::Width(Size::Fit {
-                        min: 20.0,
-                        max: f32::MAX,
+                        min: Length::Px(20.0),
+                        max: Length::MAX,
                     }),
                 ),
                 (
                     StateFlags::NORMAL,
                     StyleProperty::Height(Size::Fit {
-                        min: 10.0,
-                        max: f32::MAX,
+                        min: Length::Px(10.0),
+                        max: Length::MAX,
                     }),
                 ),
                 (
@@ -134,6 +138,7 @@ impl AppLifecycleHandler for SabreApp {
                 title: "Sabre App".into(),
                 width: 800,
                 height: 600,
+                ..Default::default()
             },
             ViewportState::new().into_handler(),
         );
@@ -158,8 +163,8 @@ impl ViewportState {
         let mut menu = ui.child();
 
         menu.width(Flex {
-            min: 200.0,
-            max: 600.0,
+            min: Length::Px(200.0),
+            max: Length::Px(600.0),
         })
         .child_direction(LayoutDirection::Vertical);
 
@@ -172,10 +177,10 @@ impl ViewportState {
                 .width(Grow)
                 .color(Color::WHITE)
                 .padding(Padding {
-                    left: 15.0,
-                    right: 15.0,
-                    top: 15.0,
-                    bottom: 15.0,
+                    left: Length::Px(15.0),
+                    right: Length::Px(15.0),
+                    top: Length::Px(15.0),
+                    bottom: Length::Px(15.0),
                 })
                 .text("Menu Item 1", None)
                 .rect(Grow, None, None)
@@ -187,10 +192,10 @@ impl ViewportState {
                 .width(Grow)
                 .color(Color::WHITE)
                 .padding(Padding {
-                    left: 15.0,
-                    right: 15.0,
-                    top: 15.0,
-                    bottom: 15.0,
+                    left: Length::Px(15.0),
+                    right: Length::Px(15.0),
+                    top: Length::Px(15.0),
+                    bottom: Length::Px(15.0),
                 })
                 .text("modern morning merman even longer", None)
                 .rect(Grow, None, None)
@@ -202,10 +207,10 @@ impl ViewportState {
                 .width(Grow)
                 .color(Color::WHITE)
                 .padding(Padding {
-                    left: 15.0,
-                    right: 15.0,
-                    top: 15.0,
-                    bottom: 15.0,
+                    left: Length::Px(15.0),
+                    right: Length::Px(15.0),
+                    top: Length::Px(15.0),
+                    bottom: Length::Px(15.0),
                 })
                 .text("VA To ff ti it tt ft", None)
                 .rect(Grow, None, None)